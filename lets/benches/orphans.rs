@@ -0,0 +1,124 @@
+//! Compares the two ways of handling an orphan once the message it is linked to arrives: keeping
+//! the raw [`TransportMessage`] and parsing its header again, or keeping the [`PreparsedMessage`]
+//! and resuming the unwrapping right after the header.
+//!
+//! The memory kept per orphan by each approach is printed before the benchmarks run.
+
+// Rust
+use std::mem::size_of;
+
+// 3rd-party
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// IOTA
+
+// Streams
+use lets::{
+    address::{AppAddr, MsgId},
+    id::Identifier,
+    message::{
+        ContentSizeof, ContentUnwrap, ContentWrap, Message, PreparsedMessage, Topic, TransportMessage, HDF, PCF,
+    },
+};
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Mask},
+        io,
+        types::Bytes,
+    },
+    error::Result as SpongosResult,
+    KeccakF1600, Spongos, PRP,
+};
+
+/// Masked payload standing for the content of an orphaned packet
+#[derive(Default)]
+struct Payload(Vec<u8>);
+
+#[async_trait(?Send)]
+impl ContentSizeof<Payload> for sizeof::Context {
+    async fn sizeof(&mut self, payload: &Payload) -> SpongosResult<&mut Self> {
+        self.mask(Bytes::new(&payload.0))
+    }
+}
+
+#[async_trait(?Send)]
+impl<OS, F> ContentWrap<Payload> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+{
+    async fn wrap(&mut self, payload: &mut Payload) -> SpongosResult<&mut Self> {
+        self.mask(Bytes::new(&payload.0))
+    }
+}
+
+#[async_trait(?Send)]
+impl<IS, F> ContentUnwrap<Payload> for unwrap::Context<IS, F>
+where
+    F: PRP,
+    IS: io::IStream,
+{
+    async fn unwrap(&mut self, payload: &mut Payload) -> SpongosResult<&mut Self> {
+        self.mask(Bytes::new(&mut payload.0))
+    }
+}
+
+async fn orphan_message(payload_size: usize) -> TransportMessage {
+    let topic = Topic::from("BASE_BRANCH");
+    let publisher = Identifier::default();
+    let linked_msg = MsgId::gen(AppAddr::default(), &publisher, &topic, 1);
+    let header = HDF::new(0, 2, publisher, &topic).with_linked_msg_address(linked_msg);
+    let payload = PCF::new_final_frame().with_content(Payload(vec![7; payload_size]));
+    let (transport_msg, _): (TransportMessage, Spongos) = Message::new(header, payload).wrap().await.unwrap();
+    transport_msg
+}
+
+async fn resume(preparsed: PreparsedMessage) -> Vec<u8> {
+    let (message, _) = preparsed.unwrap(Payload::default()).await.unwrap();
+    message.into_payload().into_content().0
+}
+
+fn bench_orphans(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let sizes = [32, 256, 1024, 8192, 32768];
+
+    for size in sizes {
+        let transport_msg = runtime.block_on(orphan_message(size));
+        println!(
+            "Orphan with a {size} bytes payload: {} bytes kept as raw message, {} bytes kept preparsed",
+            size_of::<TransportMessage>() + transport_msg.as_ref().len(),
+            size_of::<PreparsedMessage<KeccakF1600>>() + transport_msg.as_ref().len(),
+        );
+    }
+
+    let mut group = c.benchmark_group("Handle Orphan by Payload Size");
+    for size in sizes {
+        let transport_msg = runtime.block_on(orphan_message(size));
+        let preparsed: PreparsedMessage = runtime.block_on(transport_msg.clone().parse_header()).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("reparse", size), &transport_msg, |b, transport_msg| {
+            b.iter_batched(
+                || transport_msg.clone(),
+                |transport_msg| {
+                    runtime.block_on(async {
+                        let preparsed: PreparsedMessage = transport_msg.parse_header().await.unwrap();
+                        resume(preparsed).await
+                    })
+                },
+                criterion::BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("resume", size), &preparsed, |b, preparsed| {
+            b.iter_batched(
+                || preparsed.clone(),
+                |preparsed| runtime.block_on(resume(preparsed)),
+                criterion::BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_orphans);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+//! Compares the two ways of wrapping a message: computing its size with a `sizeof` pass to wrap it
+//! into a fixed buffer, or wrapping it in a single pass into a growable buffer.
+
+// Rust
+
+// 3rd-party
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// IOTA
+
+// Streams
+use lets::{
+    address::{AppAddr, MsgId},
+    id::Identifier,
+    message::{ContentSizeof, ContentWrap, Message, Topic, HDF, PCF},
+};
+use spongos::{
+    ddml::{
+        commands::{sizeof, wrap, Absorb, Mask},
+        io,
+        types::{Bytes, Size},
+    },
+    error::Result as SpongosResult,
+    KeccakF1600, Spongos, PRP,
+};
+
+/// Content made of several fields, so that each pass has some traversal to do
+struct Payload {
+    readings: Vec<usize>,
+    data: Vec<u8>,
+}
+
+#[async_trait(?Send)]
+impl ContentSizeof<Payload> for sizeof::Context {
+    async fn sizeof(&mut self, payload: &Payload) -> SpongosResult<&mut Self> {
+        self.absorb(Size::new(payload.readings.len()))?;
+        for reading in &payload.readings {
+            self.mask(Size::new(*reading))?;
+        }
+        self.mask(Bytes::new(&payload.data))
+    }
+}
+
+#[async_trait(?Send)]
+impl<OS, F> ContentWrap<Payload> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+{
+    async fn wrap(&mut self, payload: &mut Payload) -> SpongosResult<&mut Self> {
+        self.absorb(Size::new(payload.readings.len()))?;
+        for reading in &payload.readings {
+            self.mask(Size::new(*reading))?;
+        }
+        self.mask(Bytes::new(&payload.data))
+    }
+}
+
+fn sample_message(payload_size: usize) -> Message<Payload> {
+    let topic = Topic::from("BASE_BRANCH");
+    let publisher = Identifier::default();
+    let linked_msg = MsgId::gen(AppAddr::default(), &publisher, &topic, 1);
+    let header = HDF::new(0, 2, publisher, &topic).with_linked_msg_address(linked_msg);
+    let payload = PCF::new_final_frame().with_content(Payload {
+        readings: (0..payload_size / 8).collect(),
+        data: vec![7; payload_size],
+    });
+    Message::new(header, payload)
+}
+
+async fn two_pass(message: &mut Message<Payload>) -> Vec<u8> {
+    let mut buf = vec![0; message.sizeof().await.unwrap()];
+    let _: Spongos<KeccakF1600> = message.wrap_into(&mut buf).await.unwrap();
+    buf
+}
+
+async fn single_pass(message: &mut Message<Payload>) -> Vec<u8> {
+    let (transport_msg, _): (_, Spongos<KeccakF1600>) = message.wrap().await.unwrap();
+    transport_msg.into()
+}
+
+fn bench_wrapping(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("Wrap Message by Payload Size");
+    for size in [32, 256, 1024, 8192, 32768] {
+        let mut message = sample_message(size);
+        assert_eq!(
+            runtime.block_on(two_pass(&mut message)),
+            runtime.block_on(single_pass(&mut message))
+        );
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_function(BenchmarkId::new("two-pass", size), |b| {
+            b.iter(|| runtime.block_on(two_pass(&mut message)))
+        });
+        group.bench_function(BenchmarkId::new("single-pass", size), |b| {
+            b.iter(|| runtime.block_on(single_pass(&mut message)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_wrapping);
+criterion_main!(benches);
@@ -100,12 +100,18 @@ pub enum Error {
     #[error("Malformed {0}: missing '{1}' for {2}")]
     Malformed(&'static str, &'static str, String),
 
+    #[error("Invalid topic filter '{0}': {1}")]
+    TopicFilter(String, &'static str),
+
     #[error("There was an issue with {0} the signature, cannot {1}")]
     Signature(&'static str, &'static str),
 
     #[error("Internal Spongos error: {0}")]
     Spongos(SpongosError),
 
+    #[error("Message size mismatch: expected {0} bytes by sizeof, but {1} bytes were wrapped")]
+    SizeMismatch(usize, usize),
+
     /// Transport
 
     #[error("Transport error for address {1}: {0}")]
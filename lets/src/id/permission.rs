@@ -123,6 +123,15 @@ impl<Identifier> Permissioned<Identifier> {
         }
     }
 
+    /// Returns the [`PermissionDuration`] of the [`Permissioned`] if it is
+    /// [`Permissioned::ReadWrite`].
+    pub fn duration(&self) -> Option<PermissionDuration> {
+        match self {
+            Permissioned::ReadWrite(_, duration) => Some(*duration),
+            _ => None,
+        }
+    }
+
     /// Returns if the [`Permissioned`] is [`Permissioned::Read`].
     pub fn is_readonly(&self) -> bool {
         matches!(self, Permissioned::Read(..))
@@ -259,3 +268,20 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PermissionDuration, Permissioned};
+
+    #[test]
+    fn only_write_permissions_have_a_duration() {
+        let timed = PermissionDuration::Unix(1_000);
+        assert_eq!(Permissioned::ReadWrite("id", timed).duration(), Some(timed));
+        assert_eq!(
+            Permissioned::ReadWrite("id", PermissionDuration::Perpetual).duration(),
+            Some(PermissionDuration::Perpetual)
+        );
+        assert_eq!(Permissioned::Read("id").duration(), None);
+        assert_eq!(Permissioned::Admin("id").duration(), None);
+    }
+}
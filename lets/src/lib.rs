@@ -34,3 +34,11 @@ pub mod id;
 
 /// Errors specific for LETS
 pub mod error;
+
+/// Items used by the code generated by the [`Ddml`](message::Ddml) derive macro
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+    pub use async_trait::async_trait;
+    pub use spongos;
+}
@@ -9,7 +9,7 @@ use spongos::{
         types::{Mac, Maybe, NBytes, Size, Uint8},
     },
     error::{Error as SpongosError, Result as SpongosResult},
-    PRP,
+    KeccakF1600, PRP,
 };
 
 use crate::{
@@ -19,7 +19,7 @@ use crate::{
     message::{
         content::{ContentSizeof, ContentUnwrap, ContentWrap},
         topic::{Topic, TopicHash},
-        version::{HDF_ID, STREAMS_VER, UTF8},
+        version::{CIPHER_SUITE_VER, HDF_ID, STREAMS_VER, UTF8},
     },
 };
 
@@ -35,6 +35,9 @@ pub struct HDF {
     encoding: u8,
     /// Streams version
     pub version: u8,
+    /// Identifier of the cipher suite the message is wrapped with, only encoded in headers of
+    /// version [`CIPHER_SUITE_VER`]
+    cipher_suite: u8,
     /// Message type identifier
     // content type is 4 bits
     pub message_type: u8,
@@ -61,6 +64,7 @@ impl Default for HDF {
         Self {
             encoding: UTF8,
             version: STREAMS_VER,
+            cipher_suite: 0,
             message_type: 0,
             payload_length: 0,
             frame_type: HDF_ID,
@@ -90,6 +94,7 @@ impl HDF {
         Self {
             encoding: UTF8,
             version: STREAMS_VER,
+            cipher_suite: 0,
             message_type,
             payload_length: 0,
             frame_type: HDF_ID,
@@ -110,6 +115,40 @@ impl HDF {
         self
     }
 
+    /// Overrides the [`TopicHash`] of the [`HDF`], e.g. with a keyed hash of the branch [`Topic`]
+    ///
+    /// # Arguments
+    /// * `topic_hash`: The [`TopicHash`] of the branch
+    pub fn with_topic_hash(mut self, topic_hash: TopicHash) -> Self {
+        self.topic_hash = topic_hash;
+        self
+    }
+
+    /// Overrides the cipher suite of the [`HDF`]. The default cipher suite `0` is implicit in the
+    /// headers of version 2, any other is declared in the cleartext prefix of a header of version
+    /// 3, so that readers can tell it before unwrapping the rest of the message.
+    ///
+    /// # Arguments
+    /// * `cipher_suite`: The identifier of the cipher suite
+    pub fn with_cipher_suite(mut self, cipher_suite: u8) -> Self {
+        self.cipher_suite = cipher_suite;
+        self.version = match cipher_suite {
+            0 => STREAMS_VER,
+            _ => CIPHER_SUITE_VER,
+        };
+        self
+    }
+
+    /// Overrides the payload encoding of the [`HDF`], tagging the content type of the payload so
+    /// readers know how to decode it
+    ///
+    /// # Arguments
+    /// * `encoding`: The payload encoding identifier
+    pub fn with_encoding(mut self, encoding: u8) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Injects a payload length into the [`HDF`]. Can be a maximum of 10 bits in size
     ///
     /// # Arguments
@@ -124,6 +163,16 @@ impl HDF {
         }
     }
 
+    /// Returns the payload encoding identifier, tagging the content type of the payload
+    pub fn encoding(&self) -> u8 {
+        self.encoding
+    }
+
+    /// Returns the identifier of the cipher suite the message is wrapped with
+    pub fn cipher_suite(&self) -> u8 {
+        self.cipher_suite
+    }
+
     /// Returns the message type for the associated payload
     pub fn message_type(&self) -> u8 {
         self.message_type
@@ -166,8 +215,11 @@ impl ContentSizeof<HDF> for sizeof::Context {
         let message_type_and_payload_length = NBytes::<[u8; 2]>::default();
         let payload_frame_count = NBytes::<[u8; 3]>::default();
         self.absorb(Uint8::new(hdf.encoding))?
-            .absorb(Uint8::new(hdf.version))?
-            .skip(message_type_and_payload_length)?
+            .absorb(Uint8::new(hdf.version))?;
+        if hdf.version == CIPHER_SUITE_VER {
+            self.absorb(Uint8::new(hdf.cipher_suite))?;
+        }
+        self.skip(message_type_and_payload_length)?
             .absorb(External::new(Uint8::new(hdf.message_type << 4)))?
             .absorb(Uint8::new(hdf.frame_type))?
             .skip(payload_frame_count)?
@@ -205,8 +257,11 @@ where
         };
 
         self.absorb(Uint8::new(hdf.encoding))?
-            .absorb(Uint8::new(hdf.version))?
-            .skip(message_type_and_payload_length)?
+            .absorb(Uint8::new(hdf.version))?;
+        if hdf.version == CIPHER_SUITE_VER {
+            self.absorb(Uint8::new(hdf.cipher_suite))?;
+        }
+        self.skip(message_type_and_payload_length)?
             .absorb(External::new(Uint8::new(hdf.message_type << 4)))?
             .absorb(Uint8::new(hdf.frame_type))?
             .skip(payload_frame_count)?
@@ -230,6 +285,7 @@ where
     async fn unwrap(&mut self, mut hdf: &mut HDF) -> SpongosResult<&mut Self> {
         let mut encoding = Uint8::default();
         let mut version = Uint8::default();
+        let mut cipher_suite = Uint8::default();
         // [message_type x 4][reserved x 2][payload_length x 2]
         // [payload_length x 8 -------------------------------]
         let mut message_type_and_payload_length = NBytes::<[u8; 2]>::default();
@@ -240,10 +296,13 @@ where
         self.absorb(&mut encoding)?
             .absorb(&mut version)?
             .guard(
-                version.inner() == STREAMS_VER,
+                version.inner() == STREAMS_VER || version.inner() == CIPHER_SUITE_VER,
                 SpongosError::Version("Msg", version.inner()),
-            )?
-            .skip(message_type_and_payload_length.as_mut())?
+            )?;
+        if version.inner() == CIPHER_SUITE_VER {
+            self.absorb(&mut cipher_suite)?;
+        }
+        self.skip(message_type_and_payload_length.as_mut())?
             .guard(
                 0 == message_type_and_payload_length[0] & 0b1100,
                 SpongosError::Reserved("bits 5 and 6 between content-type and payload-length"),
@@ -271,6 +330,7 @@ where
 
         hdf.encoding = encoding.inner();
         hdf.version = version.inner();
+        hdf.cipher_suite = cipher_suite.inner();
         hdf.message_type = message_type_and_payload_length[0] >> 4;
         hdf.payload_length =
             (((message_type_and_payload_length[0] & 0b0011) as u16) << 8) | (message_type_and_payload_length[1] as u16);
@@ -286,3 +346,24 @@ where
         Ok(self)
     }
 }
+
+/// Decodes the identifier of the cipher suite a message is wrapped with from the cleartext prefix
+/// of its [`HDF`]. The prefix is only absorbed, so it reads the same whatever the permutation the
+/// rest of the message is wrapped with.
+///
+/// # Arguments
+/// * `body`: The bytes of the message
+pub(crate) fn cipher_suite(body: &[u8]) -> SpongosResult<u8> {
+    let mut ctx = unwrap::Context::<_, KeccakF1600>::new(body);
+    let mut encoding = Uint8::default();
+    let mut version = Uint8::default();
+    let mut cipher_suite = Uint8::default();
+    ctx.absorb(&mut encoding)?.absorb(&mut version)?.guard(
+        version.inner() == STREAMS_VER || version.inner() == CIPHER_SUITE_VER,
+        SpongosError::Version("Msg", version.inner()),
+    )?;
+    if version.inner() == CIPHER_SUITE_VER {
+        ctx.absorb(&mut cipher_suite)?;
+    }
+    Ok(cipher_suite.inner())
+}
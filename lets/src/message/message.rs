@@ -1,4 +1,5 @@
 // Rust
+use alloc::vec::Vec;
 
 // IOTA
 
@@ -10,7 +11,7 @@ use spongos::{
 
 // Local
 use crate::{
-    error::Result,
+    error::{Error, Result},
     message::{
         content::{ContentSizeof, ContentWrap},
         hdf::HDF,
@@ -79,32 +80,69 @@ impl<Payload> Message<Payload> {
 
     /// Encodes the message for transport, wrapping the [`HDF`] and [`PCF`] into one binary message,
     /// returning that [`TransportMessage`] and the context [`Spongos`] state.
+    ///
+    /// The message is wrapped in a single pass into a growable buffer. Use [`Message::sizeof()`]
+    /// and [`Message::wrap_into()`] to wrap it into a fixed buffer instead. Debug builds check that
+    /// the bytes written match the [size](`Message::sizeof()`) of the message, and fail with
+    /// [`Error::SizeMismatch`] otherwise.
     pub async fn wrap<F>(&mut self) -> Result<(TransportMessage, Spongos<F>)>
     where
         F: PRP + Default,
-        for<'b> wrap::Context<&'b mut [u8], F>: ContentWrap<HDF> + ContentWrap<PCF<Payload>>,
+        for<'b> wrap::Context<&'b mut Vec<u8>, F>: ContentWrap<HDF> + ContentWrap<PCF<Payload>>,
         sizeof::Context: ContentSizeof<HDF> + ContentSizeof<PCF<Payload>>,
     {
-        let mut ctx = sizeof::Context::new();
-        ctx.sizeof(&self.header).await?.commit()?.sizeof(&self.payload).await?;
-        let buf_size = ctx.finalize();
-
-        let mut buf = vec![0; buf_size];
-
-        let mut ctx = wrap::Context::new(&mut buf[..]);
+        let mut buf = Vec::new();
+        let mut ctx = wrap::Context::new(&mut buf);
         ctx.wrap(&mut self.header)
             .await?
             .commit()?
             .wrap(&mut self.payload)
             .await?;
-        // If buffer is not empty, it's an implementation error, panic
-        assert!(
-            ctx.stream().is_empty(),
-            "Missmatch between buffer size expected by SizeOf ({buf_size}) and actual size of Wrap ({})",
-            ctx.stream().len()
-        );
         let spongos = ctx.finalize();
 
+        if cfg!(debug_assertions) {
+            let buf_size = self.sizeof().await?;
+            if buf.len() != buf_size {
+                return Err(Error::SizeMismatch(buf_size, buf.len()));
+            }
+        }
+
         Ok((TransportMessage::new(buf), spongos))
     }
+
+    /// Returns the size of the binary message the [`Message`] is wrapped into, to allocate the
+    /// fixed buffer passed to [`Message::wrap_into()`]
+    pub async fn sizeof(&self) -> Result<usize>
+    where
+        sizeof::Context: ContentSizeof<HDF> + ContentSizeof<PCF<Payload>>,
+    {
+        let mut ctx = sizeof::Context::new();
+        ctx.sizeof(&self.header).await?.commit()?.sizeof(&self.payload).await?;
+        Ok(ctx.finalize())
+    }
+
+    /// Encodes the message into a fixed buffer, wrapping the [`HDF`] and [`PCF`] into it and
+    /// returning the context [`Spongos`] state. The buffer must be exactly the
+    /// [size](`Message::sizeof()`) of the message: if the message does not fill it, wrapping fails
+    /// with [`Error::SizeMismatch`].
+    ///
+    /// # Arguments
+    /// * `buf`: The buffer to wrap the message into
+    pub async fn wrap_into<F>(&mut self, buf: &mut [u8]) -> Result<Spongos<F>>
+    where
+        F: PRP + Default,
+        for<'b> wrap::Context<&'b mut [u8], F>: ContentWrap<HDF> + ContentWrap<PCF<Payload>>,
+    {
+        let buf_size = buf.len();
+        let mut ctx = wrap::Context::new(buf);
+        ctx.wrap(&mut self.header)
+            .await?
+            .commit()?
+            .wrap(&mut self.payload)
+            .await?;
+        if !ctx.stream().is_empty() {
+            return Err(Error::SizeMismatch(buf_size, buf_size - ctx.stream().len()));
+        }
+        Ok(ctx.finalize())
+    }
 }
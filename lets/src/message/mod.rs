@@ -24,5 +24,6 @@ pub use hdf::HDF;
 pub use message::Message;
 pub use pcf::PCF;
 pub use preparsed::PreparsedMessage;
-pub use topic::{Topic, TopicHash};
-pub use transport::TransportMessage;
+pub use spongos_derive::Ddml;
+pub use topic::{Topic, TopicFilter, TopicHash, TopicKey};
+pub use transport::{BytesView, TransportMessage};
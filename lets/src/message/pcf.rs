@@ -1,11 +1,8 @@
-use alloc::boxed::Box;
 use core::convert::{TryFrom, TryInto};
 
-use async_trait::async_trait;
-
 use spongos::{
     ddml::{
-        commands::{sizeof, unwrap, wrap, Absorb, Skip},
+        commands::{sizeof, unwrap, wrap, Skip},
         io,
         types::{NBytes, Uint8},
     },
@@ -16,21 +13,33 @@ use spongos::{
 use crate::{
     error::{Error, Result},
     message::{
-        content::{ContentSizeof, ContentUnwrap, ContentWrap},
         version::{FINAL_PCF_ID, INIT_PCF_ID, INTER_PCF_ID},
+        Ddml,
     },
 };
 
 /// Payload Carrying Frame. Contains the body of a Streams message
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+///
+/// ```ddml
+/// message PCF {
+///     absorb                      u8  frame_type;
+///     skip                        u8  payload_frame_num[3];
+///     content;
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Ddml)]
+#[ddml(crate = "crate")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct PCF<Content> {
     /// Identifies the type of message
+    #[ddml(absorb, as = "Uint8")]
     frame_type: u8,
     /// Designates the position of this frame in the total message
     // 22-bit field
+    #[ddml(skip)]
     payload_frame_num: PayloadFrameNum,
     /// Payload of the message
+    #[ddml(content)]
     content: Content,
 }
 
@@ -133,57 +142,6 @@ impl<Content> PCF<Content> {
     }
 }
 
-#[async_trait(?Send)]
-impl<Content> ContentSizeof<PCF<Content>> for sizeof::Context
-where
-    sizeof::Context: ContentSizeof<Content>,
-{
-    async fn sizeof(&mut self, pcf: &PCF<Content>) -> SpongosResult<&mut Self> {
-        self.absorb(Uint8::new(pcf.frame_type))?
-            .skip(pcf.payload_frame_num)?
-            .sizeof(&pcf.content)
-            .await?;
-        Ok(self)
-    }
-}
-
-#[async_trait(?Send)]
-impl<F, OS, Content> ContentWrap<PCF<Content>> for wrap::Context<OS, F>
-where
-    F: PRP,
-    OS: io::OStream,
-    Self: ContentWrap<Content>,
-{
-    async fn wrap(&mut self, pcf: &mut PCF<Content>) -> SpongosResult<&mut Self>
-    where
-        Content: 'async_trait,
-    {
-        self.absorb(Uint8::new(pcf.frame_type))?
-            .skip(pcf.payload_frame_num)?
-            .wrap(&mut pcf.content)
-            .await?;
-        Ok(self)
-    }
-}
-
-#[async_trait(?Send)]
-impl<F, IS, Content> ContentUnwrap<PCF<Content>> for unwrap::Context<IS, F>
-where
-    F: PRP,
-    IS: io::IStream,
-    unwrap::Context<IS, F>: ContentUnwrap<Content>,
-{
-    async fn unwrap(&mut self, pcf: &mut PCF<Content>) -> SpongosResult<&mut Self> {
-        let mut frame_type = Uint8::default();
-        self.absorb(&mut frame_type)?
-            .skip(&mut pcf.payload_frame_num)?
-            .unwrap(&mut pcf.content)
-            .await?;
-        pcf.frame_type = frame_type.into();
-        Ok(self)
-    }
-}
-
 /// Frame number for sharded messages
 #[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct PayloadFrameNum(u32);
@@ -4,7 +4,10 @@ use core::fmt;
 // IOTA
 
 // Streams
-use spongos::{ddml::commands::unwrap, KeccakF1600, Spongos, PRP};
+use spongos::{
+    ddml::commands::unwrap::{self, Limits},
+    KeccakF1600, Spongos, PRP,
+};
 
 // Local
 use crate::{
@@ -24,6 +27,8 @@ pub struct PreparsedMessage<F = KeccakF1600> {
     /// Streaming position within `Context`, marking the end of the `HDF` and beginning of the
     /// `PCF`. Used in partial processing.
     cursor: usize,
+    /// [`Limits`] on the resources spent unwrapping the `PCF`
+    limits: Limits,
 }
 
 impl<F> PreparsedMessage<F> {
@@ -41,9 +46,25 @@ impl<F> PreparsedMessage<F> {
             header,
             spongos,
             cursor,
+            limits: Limits::default(),
         }
     }
 
+    /// Sets the [`Limits`] on the resources spent unwrapping the content of the message, which is
+    /// untrusted until authenticated.
+    ///
+    /// # Arguments
+    /// * `limits`: The limits to apply when unwrapping the `PCF`
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the [`Limits`] applied when unwrapping the content of the message
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
     /// Returns a reference to the message [`HDF`]
     pub fn header(&self) -> &HDF {
         &self.header
@@ -84,13 +105,45 @@ impl<F> PreparsedMessage<F> {
         let mut pcf = PCF::<()>::default().with_content(content);
         let spongos = self.spongos;
         let transport_msg = self.transport_msg;
-        // Cannot use Self::remaining_message() due to partial move of spongos
-        let mut ctx = unwrap::Context::new_with_spongos(&transport_msg.body()[self.cursor..], spongos);
-        ctx.unwrap(&mut pcf).await?;
+        // Skip the header undecoded, so that errors are located within the whole body
+        let mut ctx = unwrap::Context::new_with_spongos(&transport_msg.body()[..], spongos).with_limits(self.limits);
+        ctx.drop(self.cursor)?;
+        let result = ctx.unwrap(&mut pcf).await.map(|_| ());
+        result.map_err(|e| ctx.locate(e))?;
         // discard `self.ctx.stream` that should be empty
         let (spongos, _) = ctx.finalize();
         Ok((Message::new(self.header, pcf), spongos))
     }
+
+    /// Decode the `PCF` in place within the message bytes, starting from the cursor position, so
+    /// that content can decrypt its masked payloads without copying them out of the message.
+    /// Returns a new [`Message`] wrapper around the [`HDF`] and [`PCF`], the spongos state following
+    /// the unwrapping operations, and the [`TransportMessage`] holding the decrypted bytes.
+    ///
+    /// Positions recorded by the content during unwrapping are relative to the start of the
+    /// [`TransportMessage`], so that they can be resolved into [`BytesView`](super::BytesView)s of
+    /// its body.
+    ///
+    /// # Arguments
+    /// * `content` - An implementation of a [`PCF`] [`unwrap::Context`] over a mutable stream
+    pub async fn unwrap_in_place<Content>(
+        self,
+        content: Content,
+    ) -> Result<(Message<Content>, Spongos<F>, TransportMessage)>
+    where
+        for<'a> unwrap::Context<&'a mut [u8], F>: ContentUnwrap<PCF<Content>>,
+        F: PRP,
+    {
+        let mut pcf = PCF::<()>::default().with_content(content);
+        let mut body = self.transport_msg.into_body();
+        // Skip the header undecoded, so that content records positions within the whole body
+        let mut ctx = unwrap::Context::new_with_spongos(&mut body[..], self.spongos).with_limits(self.limits);
+        ctx.drop(self.cursor)?;
+        let result = ctx.unwrap(&mut pcf).await.map(|_| ());
+        result.map_err(|e| ctx.locate(e))?;
+        let (spongos, _) = ctx.finalize();
+        Ok((Message::new(self.header, pcf), spongos, TransportMessage::new(body)))
+    }
 }
 
 impl<F> fmt::Debug for PreparsedMessage<F> {
@@ -99,7 +152,7 @@ impl<F> fmt::Debug for PreparsedMessage<F> {
             f,
             "{{header: {:?}, ctx: {:?}}}",
             self.header,
-            &self.remaining_message()[..10]
+            &self.remaining_message()[..self.remaining_message().len().min(10)]
         )
     }
 }
@@ -17,9 +17,19 @@ use spongos::{
     KeccakF1600, Spongos, PRP,
 };
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+
+/// Separator between the levels of a hierarchical [`Topic`]
+pub const TOPIC_SEPARATOR: char = '/';
+/// [`TopicFilter`] level matching exactly one [`Topic`] level
+pub const SINGLE_LEVEL_WILDCARD: &str = "+";
+/// [`TopicFilter`] level matching any number of trailing [`Topic`] levels
+pub const MULTI_LEVEL_WILDCARD: &str = "#";
 
 /// A wrapper around a `String` used for identifying a branch within a `Stream`
+///
+/// Topics can be hierarchical, with levels separated by a `/` (e.g. "plant1/line3/temperature").
+/// The branch of a [`Topic`] is considered a child of the branch named after its [parent](`Topic::parent`).
 #[derive(Clone, PartialEq, Eq, Debug, Default, Hash, serde::Serialize)]
 pub struct Topic(String);
 
@@ -36,6 +46,28 @@ impl Topic {
     pub fn str(&self) -> &str {
         &self.0
     }
+
+    /// Returns an iterator over the levels of the [`Topic`]
+    pub fn levels(&self) -> impl Iterator<Item = &str> {
+        self.0.split(TOPIC_SEPARATOR)
+    }
+
+    /// Returns the parent [`Topic`], or `None` if the [`Topic`] has a single level
+    pub fn parent(&self) -> Option<Topic> {
+        self.0
+            .rsplit_once(TOPIC_SEPARATOR)
+            .map(|(parent, _)| Topic::from(parent))
+    }
+
+    /// Returns true if the [`Topic`] is a direct or indirect parent of the provided [`Topic`]
+    ///
+    /// # Arguments
+    /// * `other`: The potential descendant [`Topic`]
+    pub fn is_ancestor_of(&self, other: &Topic) -> bool {
+        other.0.len() > self.0.len()
+            && other.0.starts_with(self.0.as_str())
+            && other.0[self.0.len()..].starts_with(TOPIC_SEPARATOR)
+    }
 }
 
 impl From<&str> for Topic {
@@ -121,6 +153,114 @@ where
     }
 }
 
+/// A pattern matching hierarchical [topics](`Topic`)
+///
+/// Levels of the filter are separated by a `/`, as in [`Topic`]s. A `+` level matches exactly one
+/// level of a [`Topic`] ("plant1/+/temperature" matches "plant1/line3/temperature"), and a
+/// trailing `#` level matches any number of remaining levels, including none ("plant1/#" matches
+/// "plant1" and "plant1/line3/temperature").
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct TopicFilter(String);
+
+impl TopicFilter {
+    /// Creates a new [`TopicFilter`], checking that wildcards occupy whole levels and that `#` is
+    /// only used as the last level.
+    ///
+    /// # Arguments
+    /// * `filter`: The filter pattern
+    pub fn new<F: Into<String>>(filter: F) -> Result<Self> {
+        let filter = filter.into();
+        if filter.is_empty() {
+            return Err(Error::TopicFilter(filter, "filter cannot be empty"));
+        }
+        let mut levels = filter.split(TOPIC_SEPARATOR).peekable();
+        while let Some(level) = levels.next() {
+            if level == MULTI_LEVEL_WILDCARD && levels.peek().is_some() {
+                return Err(Error::TopicFilter(filter, "'#' can only be used as the last level"));
+            }
+            if level != MULTI_LEVEL_WILDCARD
+                && level != SINGLE_LEVEL_WILDCARD
+                && (level.contains(MULTI_LEVEL_WILDCARD) || level.contains(SINGLE_LEVEL_WILDCARD))
+            {
+                return Err(Error::TopicFilter(filter, "wildcards must occupy a whole level"));
+            }
+        }
+        Ok(Self(filter))
+    }
+
+    /// Returns a reference to the inner filter `String`
+    pub fn str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if the filter contains no wildcards, and thus matches a single [`Topic`]
+    pub fn is_exact(&self) -> bool {
+        !self
+            .0
+            .split(TOPIC_SEPARATOR)
+            .any(|level| level == MULTI_LEVEL_WILDCARD || level == SINGLE_LEVEL_WILDCARD)
+    }
+
+    /// Returns true if the provided [`Topic`] matches the filter
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] to match
+    pub fn matches(&self, topic: &Topic) -> bool {
+        let mut topic_levels = topic.levels();
+        for level in self.0.split(TOPIC_SEPARATOR) {
+            match level {
+                MULTI_LEVEL_WILDCARD => return true,
+                SINGLE_LEVEL_WILDCARD => {
+                    if topic_levels.next().is_none() {
+                        return false;
+                    }
+                }
+                exact => {
+                    if topic_levels.next() != Some(exact) {
+                        return false;
+                    }
+                }
+            }
+        }
+        topic_levels.next().is_none()
+    }
+}
+
+impl TryFrom<&str> for TopicFilter {
+    type Error = crate::error::Error;
+    fn try_from(filter: &str) -> Result<Self> {
+        Self::new(filter)
+    }
+}
+
+impl TryFrom<String> for TopicFilter {
+    type Error = crate::error::Error;
+    fn try_from(filter: String) -> Result<Self> {
+        Self::new(filter)
+    }
+}
+
+impl TryFrom<&Topic> for TopicFilter {
+    type Error = crate::error::Error;
+    /// Creates a [`TopicFilter`] matching exactly the provided [`Topic`]. Topics containing `+` or
+    /// `#` are refused, as the filter would read them as wildcards and match other topics.
+    fn try_from(topic: &Topic) -> Result<Self> {
+        if topic.0.contains(SINGLE_LEVEL_WILDCARD) || topic.0.contains(MULTI_LEVEL_WILDCARD) {
+            return Err(Error::TopicFilter(
+                topic.0.clone(),
+                "topics containing wildcards cannot be matched exactly",
+            ));
+        }
+        Ok(Self(topic.0.clone()))
+    }
+}
+
+impl core::fmt::Display for TopicFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", &self.0)
+    }
+}
+
 /// A 16 byte fixed size hash representation of a [`Topic`]
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Debug, Default, Hash, serde::Serialize)]
 pub struct TopicHash([u8; 16]);
@@ -132,6 +272,23 @@ impl From<&Topic> for TopicHash {
     }
 }
 
+impl TopicHash {
+    /// Derives a keyed [`TopicHash`] for the provided [`Topic`]. Unlike the plain hash, a keyed
+    /// hash cannot be mapped back to a low-entropy topic name without knowledge of the
+    /// [`TopicKey`].
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] to be hashed
+    /// * `key`: The [`TopicKey`] shared among the members of the stream
+    pub fn keyed(topic: &Topic, key: &TopicKey) -> Self {
+        let mut spongos = Spongos::<KeccakF1600>::init();
+        spongos.absorb(key);
+        spongos.commit();
+        let topic_hash: [u8; 16] = spongos.sponge(topic.as_ref());
+        Self(topic_hash)
+    }
+}
+
 impl From<&str> for TopicHash {
     fn from(t: &str) -> Self {
         TopicHash::from(&Topic::from(t))
@@ -176,3 +333,140 @@ where
         Ok(self)
     }
 }
+
+/// A 32 byte secret shared among the members of a stream, used to derive keyed [`TopicHash`]es
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TopicKey([u8; 32]);
+
+impl TopicKey {
+    /// Creates a new [`TopicKey`] wrapper around the provided bytes
+    ///
+    /// # Arguments
+    /// * `array`: Fixed-size 32 byte array
+    pub fn new(array: [u8; 32]) -> Self {
+        Self(array)
+    }
+
+    /// Generates a new [`TopicKey`] by using [`Spongos`] to sponge the provided seed bytes into a
+    /// fixed 32 byte array, and wrapping it.
+    ///
+    /// # Arguments
+    /// * `seed`: A unique variable sized seed slice
+    pub fn from_seed<T>(seed: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut spongos = Spongos::<KeccakF1600>::init();
+        spongos.absorb("TOPICKEY");
+        Self(spongos.sponge(seed))
+    }
+}
+
+impl AsRef<[u8]> for TopicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for TopicKey {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl core::fmt::Debug for TopicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        // The key is a secret, never print its contents
+        write!(f, "TopicKey(<redacted>)")
+    }
+}
+
+impl Mask<&TopicKey> for sizeof::Context {
+    fn mask(&mut self, key: &TopicKey) -> SpongosResult<&mut Self> {
+        self.mask(NBytes::new(key))
+    }
+}
+
+impl<OS, F> Mask<&TopicKey> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+{
+    fn mask(&mut self, key: &TopicKey) -> SpongosResult<&mut Self> {
+        self.mask(NBytes::new(key))
+    }
+}
+
+impl<IS, F> Mask<&mut TopicKey> for unwrap::Context<IS, F>
+where
+    F: PRP,
+    IS: io::IStream,
+{
+    fn mask(&mut self, key: &mut TopicKey) -> SpongosResult<&mut Self> {
+        self.mask(NBytes::new(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::{Topic, TopicFilter, TopicHash, TopicKey};
+
+    #[test]
+    fn topic_hierarchy() {
+        let topic = Topic::from("plant1/line3/temperature");
+        assert_eq!(topic.parent(), Some(Topic::from("plant1/line3")));
+        assert_eq!(Topic::from("plant1").parent(), None);
+        assert!(Topic::from("plant1").is_ancestor_of(&topic));
+        assert!(Topic::from("plant1/line3").is_ancestor_of(&topic));
+        assert!(!Topic::from("plant").is_ancestor_of(&topic));
+        assert!(!topic.is_ancestor_of(&topic));
+    }
+
+    #[test]
+    fn topic_filter_matching() {
+        let temperature = Topic::from("plant1/line3/temperature");
+        let single = TopicFilter::new("plant1/+/temperature").unwrap();
+        let multi = TopicFilter::new("plant1/#").unwrap();
+        let exact = TopicFilter::new("plant1/line3").unwrap();
+
+        assert!(single.matches(&temperature));
+        assert!(!single.matches(&Topic::from("plant1/line3/pressure")));
+        assert!(!single.matches(&Topic::from("plant1/temperature")));
+        assert!(multi.matches(&temperature));
+        assert!(multi.matches(&Topic::from("plant1")));
+        assert!(!multi.matches(&Topic::from("plant2/line3")));
+        assert!(exact.is_exact());
+        assert!(!exact.matches(&temperature));
+        assert!(exact.matches(&Topic::from("plant1/line3")));
+    }
+
+    #[test]
+    fn keyed_topic_hashes_depend_on_the_topic_and_the_key() {
+        let topic = Topic::from("plant1/line3");
+        let key = TopicKey::from_seed("topic key");
+        let keyed = TopicHash::keyed(&topic, &key);
+
+        assert_eq!(keyed, TopicHash::keyed(&Topic::from("plant1/line3"), &TopicKey::from_seed("topic key")));
+        assert_ne!(keyed, TopicHash::from(&topic));
+        assert_ne!(keyed, TopicHash::keyed(&topic, &TopicKey::from_seed("another topic key")));
+        assert_ne!(keyed, TopicHash::keyed(&Topic::from("plant1/line4"), &key));
+    }
+
+    #[test]
+    fn topic_filter_validation() {
+        assert!(TopicFilter::new("").is_err());
+        assert!(TopicFilter::new("plant1/#/temperature").is_err());
+        assert!(TopicFilter::new("plant1/line+").is_err());
+        assert!(TopicFilter::new("#").is_ok());
+        assert!(TopicFilter::new("+/+").is_ok());
+
+        let exact = TopicFilter::try_from(&Topic::from("plant1/line3")).unwrap();
+        assert!(exact.is_exact());
+        assert!(exact.matches(&Topic::from("plant1/line3")));
+        assert!(TopicFilter::try_from(&Topic::from("plant1/+")).is_err());
+        assert!(TopicFilter::try_from(&Topic::from("plant1/#")).is_err());
+        assert!(TopicFilter::try_from(&Topic::from("plant1/line+")).is_err());
+    }
+}
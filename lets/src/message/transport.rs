@@ -1,15 +1,27 @@
 // Rust
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, Range},
+};
 
 // IOTA
 
 // Streams
-use spongos::{ddml::commands::unwrap, PRP};
+use spongos::{
+    ddml::commands::unwrap::{self, Limits},
+    PRP,
+};
 
 // Local
 use crate::{
     error::Result,
-    message::{content::ContentUnwrap, hdf::HDF, preparsed::PreparsedMessage},
+    message::{
+        content::ContentUnwrap,
+        hdf::{self, HDF},
+        preparsed::PreparsedMessage,
+    },
 };
 
 /// Binary network Message representation.
@@ -37,6 +49,13 @@ impl TransportMessage {
 }
 
 impl TransportMessage {
+    /// Returns the identifier of the cipher suite the message is wrapped with. It is read from the
+    /// cleartext prefix of the [`HDF`], so that it can be known before choosing the permutation to
+    /// [parse the header](`TransportMessage::parse_header`) with.
+    pub fn cipher_suite(&self) -> Result<u8> {
+        Ok(hdf::cipher_suite(self.body())?)
+    }
+
     /// Creates a new [`unwrap::Context`] for the message body and decodes the [`HDF`].
     /// The remaining context [`spongos::Spongos`] and cursor position are then wrapped with the
     /// [`HDF`] into a [`PreparsedMessage`] for content processing and returned.
@@ -44,14 +63,27 @@ impl TransportMessage {
     where
         F: PRP + Default,
     {
-        let mut ctx = unwrap::Context::new(self.body().as_ref());
+        self.parse_header_with_limits(Limits::default()).await
+    }
+
+    /// Decodes the [`HDF`] like [`TransportMessage::parse_header`], within the provided [`Limits`].
+    /// The limits are kept in the [`PreparsedMessage`] to be applied to the content as well.
+    ///
+    /// # Arguments
+    /// * `limits`: The limits on the resources spent unwrapping the message
+    pub async fn parse_header_with_limits<F>(self, limits: Limits) -> Result<PreparsedMessage<F>>
+    where
+        F: PRP + Default,
+    {
+        let mut ctx = unwrap::Context::new(self.body().as_ref()).with_limits(limits);
         let mut header = HDF::default();
 
-        ctx.unwrap(&mut header).await?;
+        let result = ctx.unwrap(&mut header).await.map(|_| ());
+        result.map_err(|e| ctx.locate(e))?;
 
         let (spongos, cursor) = ctx.finalize();
 
-        Ok(PreparsedMessage::new(self, header, spongos, cursor))
+        Ok(PreparsedMessage::new(self, header, spongos, cursor).with_limits(limits))
     }
 }
 
@@ -66,3 +98,114 @@ impl AsRef<[u8]> for TransportMessage {
         self.body().as_ref()
     }
 }
+
+/// A shared view over a range of the body of a [`TransportMessage`]. Messages unwrapped in place hand
+/// out their payloads as views into their body, so that the payloads are not copied out of it.
+#[derive(Clone, Default)]
+pub struct BytesView {
+    /// The shared body of the message
+    body: Arc<Vec<u8>>,
+    /// Position of the viewed bytes within the body
+    range: Range<usize>,
+}
+
+impl BytesView {
+    /// Returns a new [`BytesView`] over a range of the bytes viewed by this one, sharing the same
+    /// body.
+    ///
+    /// # Arguments
+    /// * `range`: The range to view, relative to the bytes viewed by this [`BytesView`]
+    ///
+    /// # Panics
+    /// Panics if the range is out of the bounds of the bytes viewed by this [`BytesView`]
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {:?} out of the bounds of a view of {} bytes",
+            range,
+            self.len()
+        );
+        Self {
+            body: self.body.clone(),
+            range: self.range.start + range.start..self.range.start + range.end,
+        }
+    }
+
+    /// Returns the viewed bytes as a slice
+    pub fn as_slice(&self) -> &[u8] {
+        &self.body[self.range.clone()]
+    }
+}
+
+impl From<TransportMessage> for BytesView {
+    fn from(message: TransportMessage) -> Self {
+        Self::from(message.into_body())
+    }
+}
+
+impl From<Vec<u8>> for BytesView {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            range: 0..bytes.len(),
+            body: Arc::new(bytes),
+        }
+    }
+}
+
+impl From<BytesView> for Vec<u8> {
+    fn from(view: BytesView) -> Self {
+        view.as_slice().to_vec()
+    }
+}
+
+impl Deref for BytesView {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for BytesView {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl PartialEq for BytesView {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for BytesView {}
+
+impl PartialEq<[u8]> for BytesView {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl PartialEq<&[u8]> for BytesView {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl PartialEq<Vec<u8>> for BytesView {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Hash for BytesView {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl fmt::Debug for BytesView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
@@ -14,6 +14,11 @@
 /// Streams version number.
 pub(crate) const STREAMS_VER: u8 = 2;
 
+/// Streams version number of the headers declaring a cipher suite other than the default one.
+/// Their layout adds the cipher suite identifier right after the version, so that headers of the
+/// default cipher suite keep the layout of [`STREAMS_VER`].
+pub(crate) const CIPHER_SUITE_VER: u8 = 3;
+
 /// Encoding Constants
 pub(crate) const UTF8: u8 = 0;
 
@@ -1,6 +1,11 @@
 // Rust
 use alloc::{boxed::Box, rc::Rc, vec::Vec};
-use core::cell::RefCell;
+use core::{
+    cell::{RefCell, RefMut},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 // 3rd-party
 use async_trait::async_trait;
@@ -51,17 +56,50 @@ impl<'a, Tsp: Transport<'a>> Transport<'a> for Rc<RefCell<Tsp>> {
     type Msg = Tsp::Msg;
     type SendResponse = Tsp::SendResponse;
 
-    /// Send a message.
+    /// Send a message, once the operations in flight through the other clones of the transport are
+    /// done.
     async fn send_message(&mut self, address: Address, msg: Tsp::Msg) -> Result<Tsp::SendResponse>
     where
         Self::Msg: 'async_trait,
     {
-        self.borrow_mut().send_message(address, msg).await
+        borrow_when_released(self).await.send_message(address, msg).await
     }
 
-    /// Receive messages with default options.
+    /// Receive messages with default options, once the operations in flight through the other
+    /// clones of the transport are done.
     async fn recv_messages(&mut self, address: Address) -> Result<Vec<Tsp::Msg>> {
-        self.borrow_mut().recv_messages(address).await
+        borrow_when_released(self).await.recv_messages(address).await
+    }
+}
+
+/// Mutably borrows a transport shared through a [`RefCell`], yielding until the operation in flight
+/// through another clone of it releases it. Operations driven concurrently on clones of the same
+/// transport thus take turns instead of panicking.
+///
+/// # Arguments
+/// * `transport`: The shared transport
+async fn borrow_when_released<Tsp>(transport: &RefCell<Tsp>) -> RefMut<'_, Tsp> {
+    loop {
+        match transport.try_borrow_mut() {
+            Ok(transport) => return transport,
+            Err(_) => YieldNow(false).await,
+        }
+    }
+}
+
+/// A future yielding once to the executor, which is woken up right away to poll it again
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
     }
 }
 
@@ -0,0 +1,567 @@
+//! # Spongos Derive
+//! Derive macros generating the `DDML` encoding of message contents.
+//!
+//! `#[derive(Ddml)]` implements [`ContentSizeof`], [`ContentWrap`] and [`ContentUnwrap`] for a
+//! struct with named fields, processing its fields in declaration order with the commands given in
+//! their `#[ddml(...)]` attribute. The three implementations are generated from the same list of
+//! commands, so the size computed by `sizeof` is always the size written by `wrap`.
+//!
+//! ```ignore
+//! use lets::{id::Identifier, message::{Ddml, Topic}};
+//! use spongos::ddml::types::{Bytes, Mac, Size, Uint8};
+//!
+//! /// message Reading {
+//! ///     absorb          u8  version;
+//! ///     mask            u8  publisher[];
+//! ///     mask            u8  topic[];
+//! ///     skip            u8  size(sequence);
+//! ///     commit;
+//! ///     mask            u8  payload[];
+//! ///     commit;
+//! ///     squeeze         u8  mac[32];
+//! /// }
+//! #[derive(Ddml, Default)]
+//! struct Reading {
+//!     #[ddml(absorb, as = "Uint8", guard = "supported_version")]
+//!     version: u8,
+//!     #[ddml(mask)]
+//!     publisher: Identifier,
+//!     #[ddml(mask)]
+//!     topic: Topic,
+//!     #[ddml(skip, as = "Size", commit)]
+//!     sequence: usize,
+//!     #[ddml(mask, with = "Bytes", commit)]
+//!     payload: Vec<u8>,
+//!     #[ddml(squeeze)]
+//!     mac: Mac,
+//!     #[ddml(ignore)]
+//!     received_at: Option<u64>,
+//! }
+//!
+//! fn supported_version(version: &u8) -> Result<(), spongos::error::Error> {
+//!     match *version {
+//!         1 => Ok(()),
+//!         version => Err(spongos::error::Error::Version("Reading", version)),
+//!     }
+//! }
+//! ```
+//!
+//! Each field takes exactly one command:
+//! * `mask`: The field is masked
+//! * `absorb`: The field is absorbed
+//! * `skip`: The field is encoded in the stream without being processed with the [`Spongos`]
+//!   state. Like the other spongos commands taking values, it is handed by value when wrapping, and
+//!   must therefore be `Copy` unless it is converted with `with`
+//! * `squeeze`: The field, such as a `Mac`, is squeezed from the [`Spongos`] state. It is handed by
+//!   reference in every context
+//! * `join`: The field is a [`Spongos`] state joined into the state of the context. It takes no
+//!   space in the stream, and its `PRP` must be the one of the context (see `prp` below)
+//! * `content`: The field is itself a content, processed with its own [`ContentSizeof`],
+//!   [`ContentWrap`] and [`ContentUnwrap`] implementations
+//! * `ignore`: The field is not encoded, and keeps its value when unwrapping
+//!
+//! And any of these modifiers:
+//! * `as = "Type"`: The field is converted into the spongos type `Type` (such as `Uint8` or `Size`)
+//!   with `Type::new(field)`, and back with `Type::inner()`. The field must be `Copy`
+//! * `with = "Type"`: The field is processed wrapped by reference in the spongos type `Type` (such
+//!   as `Bytes` or `NBytes`) with `Type::new(&field)` and `Type::new(&mut field)`
+//! * `repeated`: The field is a `Vec`, whose length is processed as a `Size` with the command of the
+//!   field (absorbed for `content` fields), followed by each of its items. Items must implement
+//!   `Default`
+//! * `fork`: The field is processed in a fork of the [`Spongos`] state, which is discarded afterwards
+//! * `commit`: The [`Spongos`] state is committed after processing the field
+//! * `guard = "path"`: Once the field is processed when wrapping or unwrapping, the function at
+//!   `path` is called with a reference to it. An error returned by the function, which must convert
+//!   into a spongos error, fails the command with the `guard` command of the context
+//!
+//! The implementations of [`ContentWrap`] and [`ContentUnwrap`] are generic over the `PRP` of the
+//! context unless the struct is annotated with `#[ddml(prp = "Type")]`, for contents whose fields
+//! are only implemented for a specific `PRP`, or that join a [`Spongos`] state. `Type` may be a
+//! type parameter of the struct. The implementations of the `content` fields are required in the
+//! bounds of the generated implementations, so contents can be generic over the contents they hold.
+//!
+//! The generated code refers to the content traits and to `spongos` through the `lets` crate, at
+//! `::lets` by default. Crates that depend on `lets` under another name, or that re-export it, give
+//! its path with `#[ddml(crate = "path")]` (`lets` itself derives its contents with
+//! `#[ddml(crate = "crate")]`).
+//!
+//! ## Scope
+//! The derive covers contents whose fields map one-to-one, in order, to spongos commands. Contents
+//! whose encoding depends on their values are still implemented by hand:
+//! * `HDF`, which packs several fields in the bits of a single skipped value, and only encodes its
+//!   cipher suite in some versions
+//! * `Identifier` and `Permissioned`, which are enums encoding their variant as an oracle
+//! * the messages of `streams`, which sign or verify with identities, exchange keys with each
+//!   subscriber, or look up the key of a recipient while unwrapping
+//!
+//! [`ContentSizeof`]: https://docs.rs/lets/latest/lets/message/trait.ContentSizeof.html
+//! [`ContentWrap`]: https://docs.rs/lets/latest/lets/message/trait.ContentWrap.html
+//! [`ContentUnwrap`]: https://docs.rs/lets/latest/lets/message/trait.ContentUnwrap.html
+//! [`Spongos`]: https://docs.rs/spongos/latest/spongos/struct.Spongos.html
+
+// Rust
+
+// 3rd-party
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, GenericArgument, Lit, Meta, NestedMeta,
+    Path, PathArguments, Result, Type,
+};
+
+/// Derives the `ContentSizeof`, `ContentWrap` and `ContentUnwrap` implementations of a struct from
+/// the `#[ddml(...)]` attributes of its fields. See the [crate documentation](crate) for the
+/// supported attributes.
+#[proc_macro_derive(Ddml, attributes(ddml))]
+pub fn derive_ddml(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// The command a field is processed with
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Mask,
+    Absorb,
+    Skip,
+    Squeeze,
+    Join,
+    Content,
+    Ignore,
+}
+
+/// The way the value of a field is handed to its command
+enum Conversion {
+    /// The field is handed by reference
+    None,
+    /// The field is converted into a spongos type by value
+    As(Path),
+    /// The field is wrapped by reference in a spongos type
+    With(Path),
+}
+
+/// The attributes of the struct
+struct Container {
+    /// The `PRP` the implementations are specific to, if any
+    prp: Option<Path>,
+    /// The path of the `lets` crate, which provides the content traits and the spongos commands
+    krate: Path,
+}
+
+/// A field of the struct, along with the way it is processed
+struct Field {
+    ident: Ident,
+    ty: Type,
+    command: Command,
+    conversion: Conversion,
+    /// The type of the items of the field, if it is repeated
+    repeated: Option<Type>,
+    fork: bool,
+    commit: bool,
+    /// The function checking the field once processed, if any
+    guard: Option<Path>,
+}
+
+/// The context an implementation is generated for
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Sizeof,
+    Wrap,
+    Unwrap,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(parse_field).collect::<Result<Vec<_>>>()?,
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                return Err(Error::new(
+                    input.span(),
+                    "Ddml can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => return Err(Error::new(input.span(), "Ddml can only be derived for structs")),
+    };
+    let Container { prp, krate } = parse_container(&input)?;
+
+    let name = &input.ident;
+    let (sizeof_generics, ty_generics, _) = input.generics.split_for_impl();
+    let sizeof_body = body(&fields, Mode::Sizeof);
+    let wrap_body = body(&fields, Mode::Wrap);
+    let unwrap_body = body(&fields, Mode::Unwrap);
+
+    let forks = fields.iter().any(|field| field.fork);
+    let (prp_param, prp_type) = match &prp {
+        Some(prp) => (None, quote!(#prp)),
+        None => (Some(quote!(__F)), quote!(__F)),
+    };
+    let prp_bound = match forks {
+        false => quote!(#prp_type: spongos::PRP,),
+        true => quote!(#prp_type: spongos::PRP + ::core::clone::Clone,),
+    };
+
+    // The contents held by the struct must themselves be contents
+    let contents: Vec<&Type> = fields
+        .iter()
+        .filter(|field| field.command == Command::Content)
+        .map(|field| field.repeated.as_ref().unwrap_or(&field.ty))
+        .collect();
+    let struct_where = where_predicates(&input.generics);
+    let sizeof_where = quote!(#struct_where #(sizeof::Context: ContentSizeof<#contents>,)*);
+    let wrap_contents = quote!(#(wrap::Context<__OS, #prp_type>: ContentWrap<#contents>,)*);
+    let unwrap_contents = quote!(#(unwrap::Context<__IS, #prp_type>: ContentUnwrap<#contents>,)*);
+    // The futures of the methods borrow the content, and so its type parameters
+    let type_params: Vec<&Ident> = input.generics.type_params().map(|param| &param.ident).collect();
+    let method_where = match type_params.is_empty() {
+        true => quote!(),
+        false => quote!(where #(#type_params: 'async_trait,)*),
+    };
+
+    let mut wrap_generics = input.generics.clone();
+    wrap_generics.params.push(syn::parse_quote!(__OS));
+    if let Some(param) = &prp_param {
+        wrap_generics.params.push(syn::parse_quote!(#param));
+    }
+    let (wrap_generics, _, _) = wrap_generics.split_for_impl();
+
+    let mut unwrap_generics = input.generics.clone();
+    unwrap_generics.params.push(syn::parse_quote!(__IS));
+    if let Some(param) = &prp_param {
+        unwrap_generics.params.push(syn::parse_quote!(#param));
+    }
+    let (unwrap_generics, _, _) = unwrap_generics.split_for_impl();
+
+    Ok(quote! {
+        const _: () = {
+            use #krate::__private::{async_trait, spongos, Box};
+            #[allow(unused_imports)]
+            use #krate::message::{ContentSizeof, ContentUnwrap, ContentWrap};
+            #[allow(unused_imports)]
+            use spongos::ddml::commands::{sizeof, unwrap, wrap, Absorb, Commit, Fork, Guard, Join, Mask, Skip, Squeeze};
+
+            #[async_trait(?Send)]
+            impl #sizeof_generics ContentSizeof<#name #ty_generics> for sizeof::Context
+            where
+                #sizeof_where
+            {
+                #[allow(unused_variables)]
+                async fn sizeof(&mut self, content: &#name #ty_generics) -> spongos::error::Result<&mut Self>
+                #method_where
+                {
+                    #sizeof_body
+                    Ok(self)
+                }
+            }
+
+            #[async_trait(?Send)]
+            impl #wrap_generics ContentWrap<#name #ty_generics> for wrap::Context<__OS, #prp_type>
+            where
+                #struct_where
+                #wrap_contents
+                __OS: spongos::ddml::io::OStream,
+                #prp_bound
+            {
+                #[allow(unused_variables)]
+                async fn wrap(&mut self, content: &mut #name #ty_generics) -> spongos::error::Result<&mut Self>
+                #method_where
+                {
+                    #wrap_body
+                    Ok(self)
+                }
+            }
+
+            #[async_trait(?Send)]
+            impl #unwrap_generics ContentUnwrap<#name #ty_generics> for unwrap::Context<__IS, #prp_type>
+            where
+                #struct_where
+                #unwrap_contents
+                __IS: spongos::ddml::io::IStream,
+                #prp_bound
+            {
+                #[allow(unused_variables)]
+                async fn unwrap(&mut self, content: &mut #name #ty_generics) -> spongos::error::Result<&mut Self>
+                #method_where
+                {
+                    #unwrap_body
+                    Ok(self)
+                }
+            }
+        };
+    })
+}
+
+/// Returns the predicates of the where clause of the struct, if any
+fn where_predicates(generics: &syn::Generics) -> TokenStream2 {
+    match &generics.where_clause {
+        Some(where_clause) => {
+            let predicates = where_clause.predicates.iter();
+            quote!(#(#predicates,)*)
+        }
+        None => quote!(),
+    }
+}
+
+/// Parses the `#[ddml(prp = "Type", crate = "path")]` attributes of the struct
+fn parse_container(input: &DeriveInput) -> Result<Container> {
+    let mut prp = None;
+    let mut krate = syn::parse_quote!(::lets);
+    for meta in ddml_metas(&input.attrs)? {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("prp") => {
+                prp = Some(parse_path(&name_value.lit)?);
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("crate") => {
+                krate = parse_path(&name_value.lit)?;
+            }
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    "unknown ddml attribute, expected `prp = \"Type\"` or `crate = \"path\"`",
+                ))
+            }
+        }
+    }
+    Ok(Container { prp, krate })
+}
+
+/// Parses the `#[ddml(...)]` attribute of a field
+fn parse_field(field: &syn::Field) -> Result<Field> {
+    let ident = field.ident.clone().expect("fields of structs with named fields are named");
+    let mut command = None;
+    let mut conversion = Conversion::None;
+    let mut repeated = false;
+    let mut fork = false;
+    let mut commit = false;
+    let mut guard = None;
+
+    for meta in ddml_metas(&field.attrs)? {
+        let span = meta.span();
+        let mut set_command = |new_command| match command.replace(new_command) {
+            None => Ok(()),
+            Some(_) => Err(Error::new(span, "a field can only be processed with one ddml command")),
+        };
+        match meta {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("mask") => set_command(Command::Mask)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("absorb") => set_command(Command::Absorb)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => set_command(Command::Skip)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("squeeze") => set_command(Command::Squeeze)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("join") => set_command(Command::Join)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("content") => set_command(Command::Content)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("ignore") => set_command(Command::Ignore)?,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("repeated") => repeated = true,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("fork") => fork = true,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("commit") => commit = true,
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("as") => {
+                conversion = Conversion::As(parse_path(&name_value.lit)?)
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("with") => {
+                conversion = Conversion::With(parse_path(&name_value.lit)?)
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("guard") => {
+                guard = Some(parse_path(&name_value.lit)?)
+            }
+            meta => return Err(Error::new(meta.span(), "unknown ddml attribute")),
+        }
+    }
+
+    let command = command.ok_or_else(|| {
+        Error::new(
+            field.span(),
+            "missing ddml command, expected one of `mask`, `absorb`, `skip`, `squeeze`, `join`, `content` or \
+             `ignore`",
+        )
+    })?;
+    let converted = !matches!(conversion, Conversion::None);
+    if command == Command::Ignore && (repeated || fork || commit || converted || guard.is_some()) {
+        return Err(Error::new(field.span(), "ignored fields take no other ddml attribute"));
+    }
+    if matches!(command, Command::Content | Command::Squeeze | Command::Join) && converted {
+        return Err(Error::new(
+            field.span(),
+            "content, squeezed and joined fields cannot be converted with `as` or `with`",
+        ));
+    }
+    if matches!(command, Command::Skip | Command::Squeeze | Command::Join) && repeated {
+        return Err(Error::new(
+            field.span(),
+            "skipped, squeezed and joined fields cannot be repeated",
+        ));
+    }
+    if repeated && (fork || converted) {
+        return Err(Error::new(
+            field.span(),
+            "repeated fields cannot be forked nor converted with `as` or `with`",
+        ));
+    }
+    let repeated = if repeated { Some(item_type(&field.ty)?) } else { None };
+
+    Ok(Field {
+        ident,
+        ty: field.ty.clone(),
+        command,
+        conversion,
+        repeated,
+        fork,
+        commit,
+        guard,
+    })
+}
+
+/// Returns the items of the `#[ddml(...)]` attributes
+fn ddml_metas(attrs: &[syn::Attribute]) -> Result<Vec<NestedMeta>> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("ddml")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => metas.extend(list.nested),
+            meta => return Err(Error::new(meta.span(), "expected `#[ddml(...)]`")),
+        }
+    }
+    Ok(metas)
+}
+
+/// Parses the path of a type given as a string literal
+fn parse_path(lit: &Lit) -> Result<Path> {
+    match lit {
+        Lit::Str(lit) => lit.parse(),
+        lit => Err(Error::new(lit.span(), "expected the path of a type as a string")),
+    }
+}
+
+/// Returns the type of the items of a repeated field, that is the single type argument of its type
+fn item_type(ty: &Type) -> Result<Type> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                if let Some(GenericArgument::Type(item)) = arguments.args.first() {
+                    return Ok(item.clone());
+                }
+            }
+        }
+    }
+    Err(Error::new(ty.span(), "repeated fields must be of type `Vec<Item>`"))
+}
+
+/// Generates the statements processing all the fields of the struct in a context
+fn body(fields: &[Field], mode: Mode) -> TokenStream2 {
+    let statements = fields.iter().map(|field| statement(field, mode));
+    quote!(#(#statements)*)
+}
+
+/// Generates the statements processing a field in a context
+fn statement(field: &Field, mode: Mode) -> TokenStream2 {
+    let ident = &field.ident;
+    let ctx = if field.fork { quote!(self.fork()) } else { quote!(self) };
+    let method = match field.command {
+        Command::Mask => format_ident!("mask"),
+        Command::Absorb => format_ident!("absorb"),
+        Command::Skip => format_ident!("skip"),
+        Command::Squeeze => format_ident!("squeeze"),
+        // Joining takes no space in the stream, and the content is not mutable when computing its size
+        Command::Join if mode == Mode::Sizeof => return quote!(),
+        Command::Join => format_ident!("join"),
+        Command::Content => match mode {
+            Mode::Sizeof => format_ident!("sizeof"),
+            Mode::Wrap => format_ident!("wrap"),
+            Mode::Unwrap => format_ident!("unwrap"),
+        },
+        Command::Ignore => return quote!(),
+    };
+    let call = |argument: TokenStream2| match field.command {
+        Command::Content => quote!(#ctx.#method(#argument).await?;),
+        _ => quote!(#ctx.#method(#argument)?;),
+    };
+
+    let process = match (&field.repeated, &field.conversion) {
+        (Some(item), _) => {
+            // The amount of items of repeated contents is absorbed
+            let amount_method = match field.command {
+                Command::Content => format_ident!("absorb"),
+                _ => method.clone(),
+            };
+            let size = quote!(spongos::ddml::types::Size);
+            let item_call = call(quote!(item));
+            match mode {
+                Mode::Sizeof => quote! {
+                    self.#amount_method(#size::new(content.#ident.len()))?;
+                    for item in content.#ident.iter() {
+                        #item_call
+                    }
+                },
+                Mode::Wrap => {
+                    let iter = match field.command {
+                        Command::Content => quote!(iter_mut),
+                        _ => quote!(iter),
+                    };
+                    quote! {
+                        self.#amount_method(#size::new(content.#ident.len()))?;
+                        for item in content.#ident.#iter() {
+                            #item_call
+                        }
+                    }
+                }
+                Mode::Unwrap => {
+                    let item_call = call(quote!(&mut item));
+                    quote! {
+                        let mut amount = #size::default();
+                        self.#amount_method(&mut amount)?;
+                        content.#ident.clear();
+                        for _ in 0..amount.inner() {
+                            let mut item = <#item as ::core::default::Default>::default();
+                            #item_call
+                            content.#ident.push(item);
+                        }
+                    }
+                }
+            }
+        }
+        (None, Conversion::None) => match (mode, field.command) {
+            (_, Command::Squeeze) => call(quote!(&content.#ident)),
+            (Mode::Sizeof, Command::Skip) | (Mode::Wrap, Command::Skip) => call(quote!(content.#ident)),
+            (Mode::Sizeof, _) => call(quote!(&content.#ident)),
+            (Mode::Wrap, Command::Content) | (Mode::Wrap, Command::Join) | (Mode::Unwrap, _) => {
+                call(quote!(&mut content.#ident))
+            }
+            (Mode::Wrap, _) => call(quote!(&content.#ident)),
+        },
+        (None, Conversion::As(ty)) => match mode {
+            Mode::Sizeof | Mode::Wrap => call(quote!(#ty::new(content.#ident))),
+            Mode::Unwrap => {
+                let value_call = call(quote!(&mut value));
+                quote! {
+                    let mut value = <#ty as ::core::default::Default>::default();
+                    #value_call
+                    content.#ident = value.inner();
+                }
+            }
+        },
+        (None, Conversion::With(ty)) => match mode {
+            Mode::Sizeof | Mode::Wrap => call(quote!(#ty::new(&content.#ident))),
+            Mode::Unwrap => call(quote!(#ty::new(&mut content.#ident))),
+        },
+    };
+    let guard = match (&field.guard, mode) {
+        (Some(guard), Mode::Wrap) | (Some(guard), Mode::Unwrap) => quote! {
+            if let ::core::result::Result::Err(error) = #guard(&content.#ident) {
+                self.guard(false, error)?;
+            }
+        },
+        _ => quote!(),
+    };
+    let commit = if field.commit {
+        quote!(self.commit()?;)
+    } else {
+        quote!()
+    };
+
+    // Scoped so that the temporary values of a field do not clash with those of the next ones
+    quote! {
+        {
+            #process
+            #guard
+            #commit
+        }
+    }
+}
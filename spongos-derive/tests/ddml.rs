@@ -0,0 +1,235 @@
+// Rust
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use lets::{
+    id::Identifier,
+    message::{ContentSizeof, ContentUnwrap, ContentWrap, Ddml, Topic, PCF},
+};
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Absorb, Skip},
+        types::{Bytes, Mac, NBytes, Size, Uint16, Uint8},
+    },
+    error::Error,
+    KeccakF1600, Spongos, PRP,
+};
+
+#[derive(Ddml, Default, Debug, PartialEq)]
+struct Nested {
+    #[ddml(absorb, as = "Uint16")]
+    code: u16,
+    #[ddml(mask)]
+    topic: Topic,
+}
+
+#[derive(Ddml, Default, Debug, PartialEq)]
+struct Reading {
+    #[ddml(absorb, as = "Uint8", guard = "supported_version")]
+    version: u8,
+    #[ddml(mask)]
+    publisher: Identifier,
+    #[ddml(mask, as = "Size", commit)]
+    sequence: usize,
+    #[ddml(mask, with = "Bytes")]
+    payload: Vec<u8>,
+    #[ddml(mask, repeated)]
+    topics: Vec<Topic>,
+    #[ddml(fork, mask, with = "NBytes")]
+    nonce: [u8; 16],
+    #[ddml(content, repeated, commit)]
+    nested: Vec<Nested>,
+    #[ddml(skip, with = "NBytes")]
+    tag: [u8; 4],
+    #[ddml(skip, as = "Size")]
+    hops: usize,
+    #[ddml(ignore)]
+    cache: usize,
+}
+
+fn supported_version(version: &u8) -> Result<(), Error> {
+    match *version {
+        1 | 2 => Ok(()),
+        version => Err(Error::Version("Reading", version)),
+    }
+}
+
+/// `lets` as re-exported by a crate depending on it
+mod reexported {
+    pub use lets::*;
+}
+
+#[derive(Ddml, Default, Debug, PartialEq)]
+#[ddml(crate = "crate::reexported")]
+struct Relocated {
+    #[ddml(mask, with = "Bytes")]
+    payload: Vec<u8>,
+}
+
+/// message Authenticated {
+///     join(spongos);
+///     mask            u8  payload[];
+///     commit;
+///     squeeze         u8  mac[32];
+/// }
+#[derive(Ddml)]
+#[ddml(prp = "F")]
+struct Authenticated<F> {
+    #[ddml(join)]
+    base: Spongos<F>,
+    #[ddml(mask, with = "Bytes", commit)]
+    payload: Vec<u8>,
+    #[ddml(squeeze)]
+    mac: Mac,
+}
+
+impl<F: PRP + Default> Authenticated<F> {
+    fn new(base: &[u8], payload: &[u8]) -> Self {
+        let mut spongos = Spongos::init();
+        spongos.absorb(base);
+        spongos.commit();
+        Self {
+            base: spongos,
+            payload: payload.to_vec(),
+            mac: Mac::new(32),
+        }
+    }
+}
+
+#[tokio::test]
+async fn derived_contents_wrap_into_their_size_and_unwrap_back() {
+    let mut reading = Reading {
+        version: 2,
+        publisher: Identifier::default(),
+        sequence: 1234,
+        payload: b"temperature: 21.5".to_vec(),
+        topics: vec![Topic::from("SENSORS"), Topic::from("SENSORS/KITCHEN")],
+        nonce: [7; 16],
+        nested: vec![
+            Nested {
+                code: 404,
+                topic: Topic::from("ALERTS"),
+            },
+            Nested::default(),
+        ],
+        tag: [1, 2, 3, 4],
+        hops: 3,
+        cache: 42,
+    };
+
+    let mut ctx = sizeof::Context::new();
+    ctx.sizeof(&reading).await.unwrap();
+    let size = ctx.finalize();
+
+    let mut buf = vec![0; size];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+    ctx.wrap(&mut reading).await.unwrap();
+    // Whatever the attributes, wrap writes exactly the size computed by sizeof
+    assert!(ctx.stream().is_empty());
+    let wrap_spongos: Spongos = ctx.finalize();
+
+    let mut unwrapped = Reading::default();
+    let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..]);
+    ctx.unwrap(&mut unwrapped).await.unwrap();
+    let (unwrap_spongos, read) = ctx.finalize();
+
+    assert_eq!(read, size);
+    assert_eq!(wrap_spongos, unwrap_spongos);
+    assert_eq!(unwrapped, Reading { cache: 0, ..reading });
+}
+
+#[tokio::test]
+async fn guards_reject_the_fields_they_check() {
+    let mut reading = Reading {
+        version: 3,
+        ..Reading::default()
+    };
+    let mut buf = vec![0; 1024];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+    let error = ctx.wrap(&mut reading).await.unwrap_err();
+    assert!(matches!(error, Error::Version("Reading", 3)));
+}
+
+#[tokio::test]
+async fn derived_contents_can_refer_to_lets_through_another_path() {
+    let mut relocated = Relocated {
+        payload: b"payload".to_vec(),
+    };
+    let mut ctx = sizeof::Context::new();
+    ctx.sizeof(&relocated).await.unwrap();
+    let mut buf = vec![0; ctx.finalize()];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+    ctx.wrap(&mut relocated).await.unwrap();
+    assert!(ctx.stream().is_empty());
+
+    let mut unwrapped = Relocated::default();
+    let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..]);
+    ctx.unwrap(&mut unwrapped).await.unwrap();
+    assert_eq!(unwrapped, relocated);
+}
+
+#[tokio::test]
+async fn joined_states_and_squeezed_macs_authenticate_the_content() {
+    let mut authenticated = Authenticated::<KeccakF1600>::new(b"linked message", b"payload");
+    let mut ctx = sizeof::Context::new();
+    ctx.sizeof(&authenticated).await.unwrap();
+    let mut buf = vec![0; ctx.finalize()];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+    ctx.wrap(&mut authenticated).await.unwrap();
+    assert!(ctx.stream().is_empty());
+
+    let mut unwrapped = Authenticated::<KeccakF1600>::new(b"linked message", b"");
+    let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..]);
+    ctx.unwrap(&mut unwrapped).await.unwrap();
+    assert_eq!(unwrapped.payload, b"payload");
+
+    // The MAC only matches when unwrapping from the state the content was wrapped from
+    let mut unwrapped = Authenticated::<KeccakF1600>::new(b"another message", b"");
+    let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..]);
+    assert!(ctx.unwrap(&mut unwrapped).await.is_err());
+}
+
+#[tokio::test]
+async fn derived_pcf_wraps_into_the_bytes_of_its_hand_written_implementation() {
+    let nested = Nested {
+        code: 404,
+        topic: Topic::from("ALERTS"),
+    };
+    let mut pcf = PCF::new(2, 1234, nested).unwrap();
+
+    let mut ctx = sizeof::Context::new();
+    ctx.sizeof(&pcf).await.unwrap();
+    let size = ctx.finalize();
+    let mut buf = vec![0; size];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+    ctx.wrap(&mut pcf).await.unwrap();
+    let wrap_spongos: Spongos = ctx.finalize();
+
+    // The commands of the implementation of PCF written before it was derived
+    let mut expected = vec![0; size];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut expected[..]);
+    let frame_num = 1234u32.to_be_bytes();
+    let mut content = Nested {
+        code: 404,
+        topic: Topic::from("ALERTS"),
+    };
+    ctx.absorb(Uint8::new(2))
+        .unwrap()
+        .skip(NBytes::new(&frame_num[1..]))
+        .unwrap()
+        .wrap(&mut content)
+        .await
+        .unwrap();
+    assert!(ctx.stream().is_empty());
+    assert_eq!(buf, expected);
+    assert_eq!(wrap_spongos, ctx.finalize());
+
+    let mut unwrapped = PCF::<Nested>::default();
+    let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..]);
+    ctx.unwrap(&mut unwrapped).await.unwrap();
+    assert_eq!(unwrapped, pcf);
+    assert_eq!(unwrapped.payload_frame_num(), 1234);
+}
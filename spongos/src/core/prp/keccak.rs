@@ -44,3 +44,84 @@ impl PRP for KeccakF1600 {
         unsafe { &mut *(self.state.as_mut_ptr().add(21) as *mut GenericArray<u8, Self::CapacitySize>) }
     }
 }
+
+/// A psuedo-random permutation implementing `Keccak-p[1600,12]`, the last 12 of the 24 rounds of
+/// `Keccak-F[1600]`, as used by `KangarooTwelve`. Twice as fast as [`KeccakF1600`] with the same
+/// state size, rate and capacity, at the cost of a reduced security margin.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeccakP1600R12 {
+    /// Inner state for transformation, as the little-endian bytes of 25 lanes
+    state: [u8; 200],
+}
+
+impl Default for KeccakP1600R12 {
+    fn default() -> Self {
+        Self { state: [0; 200] }
+    }
+}
+
+impl KeccakP1600R12 {
+    /// Number of rounds of the permutation
+    const ROUNDS: usize = 12;
+
+    /// Use `Keccak-p[1600,12]` sponge function on inner state
+    fn permutation(&mut self) {
+        let mut lanes = [0; 25];
+        for (lane, bytes) in lanes.iter_mut().zip(self.state.chunks_exact(8)) {
+            let mut lane_bytes = [0; 8];
+            lane_bytes.copy_from_slice(bytes);
+            *lane = u64::from_le_bytes(lane_bytes);
+        }
+        keccak::p1600(&mut lanes, Self::ROUNDS);
+        for (bytes, lane) in self.state.chunks_exact_mut(8).zip(lanes.iter()) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+    }
+}
+
+impl PRP for KeccakP1600R12 {
+    type RateSize = U168; // (1600 - 256) / 8
+
+    type CapacitySize = U32; // 256
+
+    fn transform(&mut self) {
+        self.permutation();
+    }
+
+    fn outer(&self) -> &GenericArray<u8, Self::RateSize> {
+        GenericArray::from_slice(&self.state[..168])
+    }
+
+    fn outer_mut(&mut self) -> &mut GenericArray<u8, Self::RateSize> {
+        GenericArray::from_mut_slice(&mut self.state[..168])
+    }
+
+    fn inner(&self) -> &GenericArray<u8, Self::CapacitySize> {
+        GenericArray::from_slice(&self.state[168..])
+    }
+
+    fn inner_mut(&mut self) -> &mut GenericArray<u8, Self::CapacitySize> {
+        GenericArray::from_mut_slice(&mut self.state[168..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeccakP1600R12, PRP};
+
+    /// `KangarooTwelve` of the empty message with an empty customization string, from the known
+    /// answers of RFC 9861. The message fits a single block, hashed with `Keccak-p[1600,12]`.
+    #[test]
+    fn kangaroo_twelve_of_empty_message() {
+        let mut keccak = KeccakP1600R12::default();
+        // The empty customization string is encoded as a zero byte, followed by the domain
+        // separation byte and the final bit of the padding
+        keccak.outer_mut()[1] ^= 0x07;
+        keccak.outer_mut()[167] ^= 0x80;
+        keccak.transform();
+        assert_eq!(
+            hex::encode(&keccak.outer()[..32]),
+            "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use generic_array::{ArrayLength, GenericArray};
 
 pub(crate) mod keccak;
+pub(crate) mod xoodoo;
 
 /// Pseudo-random permutation.
 ///
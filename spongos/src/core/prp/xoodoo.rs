@@ -0,0 +1,157 @@
+use generic_array::{
+    typenum::{U16, U32},
+    GenericArray,
+};
+
+use super::PRP;
+
+/// Round constants of the 12 rounds of `Xoodoo`
+const ROUND_CONSTANTS: [u32; 12] = [
+    0x00000058, 0x00000038, 0x000003c0, 0x000000d0, 0x00000120, 0x00000014, 0x00000060, 0x0000002c, 0x00000380,
+    0x000000f0, 0x000001a0, 0x00000012,
+];
+
+/// A psuedo-random permutation implementing `Xoodoo[12]`, the 384-bit permutation of `Xoodyak`.
+/// Its small state and 32-bit lanes suit constrained devices, with a rate of 16 bytes and a
+/// capacity of 32 bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Xoodoo {
+    /// Inner state for transformation, as the little-endian bytes of 3 planes of 4 lanes
+    state: [u8; 48],
+}
+
+impl Default for Xoodoo {
+    fn default() -> Self {
+        Self { state: [0; 48] }
+    }
+}
+
+impl Xoodoo {
+    /// Use `Xoodoo[12]` sponge function on inner state
+    fn permutation(&mut self) {
+        let mut lanes = self.lanes();
+        for &round_constant in ROUND_CONSTANTS.iter() {
+            Self::round(&mut lanes, round_constant);
+        }
+        for (bytes, lane) in self.state.chunks_exact_mut(4).zip(lanes.iter()) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+    }
+
+    /// Returns the lanes of the state, read from their little-endian bytes
+    fn lanes(&self) -> [u32; 12] {
+        let mut lanes = [0; 12];
+        for (lane, bytes) in lanes.iter_mut().zip(self.state.chunks_exact(4)) {
+            let mut lane_bytes = [0; 4];
+            lane_bytes.copy_from_slice(bytes);
+            *lane = u32::from_le_bytes(lane_bytes);
+        }
+        lanes
+    }
+
+    /// Apply a single round of `Xoodoo` to the lanes of the state. Lane `x` of plane `y` is
+    /// `a[x + 4 * y]`.
+    ///
+    /// # Arguments
+    /// * `a`: The lanes of the state
+    /// * `round_constant`: The constant of the round, added to the first lane
+    fn round(a: &mut [u32; 12], round_constant: u32) {
+        // θ: add to each lane the column parity of the previous column, rotated
+        let mut p = [0u32; 4];
+        for (x, parity) in p.iter_mut().enumerate() {
+            *parity = a[x] ^ a[x + 4] ^ a[x + 8];
+        }
+        for x in 0..4 {
+            let q = p[(x + 3) % 4];
+            let e = q.rotate_left(5) ^ q.rotate_left(14);
+            a[x] ^= e;
+            a[x + 4] ^= e;
+            a[x + 8] ^= e;
+        }
+        // ρ-west: shift plane 1 by one lane, rotate the lanes of plane 2 by 11 bits
+        a[4..8].rotate_right(1);
+        for lane in &mut a[8..12] {
+            *lane = lane.rotate_left(11);
+        }
+        // ι
+        a[0] ^= round_constant;
+        // χ
+        for x in 0..4 {
+            let (a0, a1, a2) = (a[x], a[x + 4], a[x + 8]);
+            a[x] = a0 ^ (!a1 & a2);
+            a[x + 4] = a1 ^ (!a2 & a0);
+            a[x + 8] = a2 ^ (!a0 & a1);
+        }
+        // ρ-east: rotate the lanes of plane 1 by 1 bit, shift plane 2 by two lanes and rotate its
+        // lanes by 8 bits
+        for lane in &mut a[4..8] {
+            *lane = lane.rotate_left(1);
+        }
+        a[8..12].rotate_right(2);
+        for lane in &mut a[8..12] {
+            *lane = lane.rotate_left(8);
+        }
+    }
+}
+
+impl PRP for Xoodoo {
+    type RateSize = U16; // (384 - 256) / 8
+
+    type CapacitySize = U32; // 256
+
+    fn transform(&mut self) {
+        self.permutation();
+    }
+
+    fn outer(&self) -> &GenericArray<u8, Self::RateSize> {
+        GenericArray::from_slice(&self.state[..16])
+    }
+
+    fn outer_mut(&mut self) -> &mut GenericArray<u8, Self::RateSize> {
+        GenericArray::from_mut_slice(&mut self.state[..16])
+    }
+
+    fn inner(&self) -> &GenericArray<u8, Self::CapacitySize> {
+        GenericArray::from_slice(&self.state[16..])
+    }
+
+    fn inner_mut(&mut self) -> &mut GenericArray<u8, Self::CapacitySize> {
+        GenericArray::from_mut_slice(&mut self.state[16..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Xoodoo, PRP};
+
+    #[test]
+    fn permutation_of_zero_state() {
+        let mut xoodoo = Xoodoo::default();
+        xoodoo.permutation();
+        let expected = [
+            0x89d5d88d, 0xa963fcbf, 0x1b232d19, 0xffa5a014, 0x36b18106, 0xafc7c1fe, 0xaee57cbe, 0xa77540bd, 0x2e86e870,
+            0xfef5b7c9, 0x8b4fadf2, 0x5e4f4062,
+        ];
+        assert_eq!(xoodoo.lanes(), expected);
+    }
+
+    /// `Xoodyak` hash of the empty message, from the known answers of its submission to the NIST
+    /// lightweight cryptography standardization
+    #[test]
+    fn xoodyak_hash_of_empty_message() {
+        let mut xoodoo = Xoodoo::default();
+        let mut digest = [0; 32];
+        // Absorbing the empty message pads the first block and sets the first block flag
+        xoodoo.outer_mut()[0] ^= 0x01;
+        xoodoo.inner_mut()[31] ^= 0x01;
+        for block in digest.chunks_exact_mut(16) {
+            xoodoo.transform();
+            block.copy_from_slice(xoodoo.outer());
+            xoodoo.outer_mut()[0] ^= 0x01;
+        }
+        assert_eq!(
+            hex::encode(digest),
+            "ea152f2b47bce24efb66c479d4adf17bd324d806e85ff75ee369ee50dc8f8bd1"
+        );
+    }
+}
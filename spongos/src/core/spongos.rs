@@ -31,6 +31,14 @@ fn decrypt_xor(s: &mut [u8], y: &[u8], x: &mut [u8]) {
     }
 }
 
+fn decrypt_xor_in_place(s: &mut [u8], yx: &mut [u8]) {
+    for (si, yxi) in s.iter_mut().zip(yx.iter_mut()) {
+        let yi = *yxi;
+        *yxi = *si ^ yi;
+        *si = yi;
+    }
+}
+
 fn copy(s: &[u8], y: &mut [u8]) {
     for (si, yi) in s.iter().zip(y.iter_mut()) {
         *yi = *si;
@@ -231,6 +239,18 @@ impl<F: PRP> Spongos<F> {
         Ok(())
     }
 
+    /// Decrypt a byte slice in place with mutable [`Spongos`] object, replacing the ciphertext with
+    /// its plaintext.
+    pub(crate) fn decrypt_in_place(&mut self, mut buf: &mut [u8]) {
+        while !buf.is_empty() {
+            let spongos = self.outer_min_mut(buf.len());
+            let n = spongos.len();
+            decrypt_xor_in_place(spongos, &mut buf[..n]);
+            buf = &mut buf[n..];
+            self.update(n);
+        }
+    }
+
     /// Decrypt a byte slice with [`Spongos`] object.
     /// Input and output slices must be non-overlapping.
     pub fn decrypt<T>(&mut self, ciphertext: &T) -> Result<T>
@@ -254,6 +274,20 @@ impl<F: PRP> Spongos<F> {
         }
     }
 
+    /// Digest of the whole [`Spongos`] state, outer position included. Used to compare states
+    /// without exposing them.
+    pub(crate) fn state_digest<R>(&self) -> R
+    where
+        R: AsMut<[u8]> + Default,
+    {
+        let mut digest = Spongos::<KeccakF1600>::init();
+        digest.absorb(self.s.outer());
+        digest.absorb(self.s.inner());
+        digest.absorb((self.pos as u64).to_le_bytes());
+        digest.commit();
+        digest.squeeze()
+    }
+
     /// Check whether [`Spongos`] state is committed.
     pub fn is_committed(&self) -> bool {
         0 == self.pos
@@ -106,6 +106,7 @@ pub trait Guard {
 
 /// Dump context info into stdout.
 /// Use it like this: `ctx.dump(format_args!("checkpoint"))`
+#[deprecated(note = "attach a `Trace` to the context with `with_trace` and inspect or export its events instead")]
 pub trait Dump {
     fn dump<'a>(&mut self, args: core::fmt::Arguments<'a>) -> Result<&mut Self>;
 }
@@ -3,7 +3,7 @@ use crypto::{keys::x25519, signatures::ed25519};
 use crate::{
     ddml::{
         commands::{sizeof::Context, Absorb},
-        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
@@ -53,6 +53,15 @@ impl Absorb<Size> for Context {
     }
 }
 
+/// Increases [`Context`] size by the number of bytes needed to encode the provided [`Varint`]
+/// wrapper. `Varint` has LEB128 var-size encoding.
+impl Absorb<Varint> for Context {
+    fn absorb(&mut self, varint: Varint) -> Result<&mut Self> {
+        self.size += varint.num_bytes();
+        Ok(self)
+    }
+}
+
 /// Increases [`Context`] size by the number of bytes present in the provided [`Bytes`] wrapper.
 /// `Bytes<bytes[n]>` has variable size thus the size `n` is encoded before the content bytes.
 impl<T: AsRef<[u8]>> Absorb<Bytes<T>> for Context {
@@ -5,7 +5,7 @@ use crate::{
     core::{prp::PRP, spongos::Spongos},
     ddml::{
         commands::{sizeof::Context, Mask},
-        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
@@ -55,6 +55,15 @@ impl Mask<Size> for Context {
     }
 }
 
+/// Increases [`Context`] size by the number of bytes needed to encode the provided [`Varint`]
+/// wrapper. `Varint` has LEB128 var-size encoding.
+impl Mask<Varint> for Context {
+    fn mask(&mut self, varint: Varint) -> Result<&mut Self> {
+        self.size += varint.num_bytes();
+        Ok(self)
+    }
+}
+
 /// Increases [`Context`] size by the number of bytes present in the provided [`NBytes`] wrapper.
 /// `NByte<bytes[n]>` is fixed-size and is masked with `n` bytes.
 impl<T: AsRef<[u8]>> Mask<NBytes<T>> for Context {
@@ -1,4 +1,7 @@
 //! Implementation of command traits for calculating the size for output buffer in Wrap operation.
+//!
+//! The [`Context`] is not traced: it keeps no spongos state, and the commands it counts are the
+//! ones recorded, with their lengths and offsets, by a traced `wrap` context.
 
 /// Message size counting context.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
@@ -22,7 +25,9 @@ impl Context {
 mod absorb;
 mod absorb_external;
 mod commit;
+// The deprecated `Dump` command is kept until it is removed
 #[cfg(feature = "std")]
+#[allow(deprecated)]
 mod dump;
 mod fork;
 mod join;
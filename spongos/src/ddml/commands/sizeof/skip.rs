@@ -1,7 +1,7 @@
 use crate::{
     ddml::{
         commands::{sizeof::Context, Skip},
-        types::{Bytes, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        types::{Bytes, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
@@ -51,6 +51,15 @@ impl Skip<Size> for Context {
     }
 }
 
+/// Increases [`Context`] size by the number of bytes needed to encode the provided [`Varint`]
+/// wrapper. `Varint` has LEB128 var-size encoding.
+impl Skip<Varint> for Context {
+    fn skip(&mut self, varint: Varint) -> Result<&mut Self> {
+        self.size += varint.num_bytes();
+        Ok(self)
+    }
+}
+
 /// Increases [`Context`] size by the number of bytes present in the provided [`Bytes`] wrapper.
 /// `Bytes<bytes[n]>` has variable size thus the size `n` is encoded before the content bytes.
 impl<T: AsRef<[u8]>> Skip<Bytes<T>> for Context {
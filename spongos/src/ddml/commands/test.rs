@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use core::borrow::BorrowMut;
+use core::{borrow::BorrowMut, ops::Range};
 
 use crypto::{keys::x25519, signatures::ed25519};
 use generic_array::{typenum::U64, GenericArray};
@@ -11,11 +11,12 @@ use crate::{
         prp::{keccak::KeccakF1600, PRP},
     },
     ddml::{
-        commands::{sizeof, unwrap, wrap, Absorb, Commit, Ed25519, Mask, Squeeze, X25519},
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Ed25519, Fork, Mask, Repeated, Skip, Squeeze, X25519},
         modifiers::External,
-        types::{Bytes, Mac, NBytes, Size, Uint8},
+        trace::{Command, Trace},
+        types::{Bytes, Mac, NBytes, Size, Uint8, Varint},
     },
-    error::Result,
+    error::{Error, Result},
 };
 
 fn absorb_mask_u8<F>() -> Result<()>
@@ -132,6 +133,56 @@ fn size() {
     assert!(absorb_mask_size::<KeccakF1600>().is_ok());
 }
 
+fn absorb_mask_skip_varint<F>() -> Result<()>
+where
+    F: PRP + Default,
+{
+    let values = [0, 1, 127, 128, 300, 16383, 16384, u64::from(u32::MAX), u64::MAX];
+    for n in values {
+        let varint = Varint::new(n);
+        let buf_size = sizeof::Context::new()
+            .absorb(varint)?
+            .mask(varint)?
+            .skip(varint)?
+            .finalize();
+        assert_eq!(buf_size, 3 * varint.num_bytes());
+
+        let mut buf = vec![0u8; buf_size];
+        let mut ctx = wrap::Context::<_, F>::new(&mut buf[..]);
+        ctx.absorb(varint)?.mask(varint)?.skip(varint)?;
+        assert!(ctx.stream().is_empty(), "Output stream has not been exhausted");
+
+        let (mut absorbed, mut masked, mut skipped) = (Varint::default(), Varint::default(), Varint::default());
+        let mut ctx = unwrap::Context::<_, F>::new(&buf[..]);
+        ctx.absorb(&mut absorbed)?.mask(&mut masked)?.skip(&mut skipped)?;
+        assert!(ctx.stream().is_empty(), "Input stream has not been exhausted");
+        assert_eq!([absorbed, masked, skipped], [varint; 3]);
+    }
+    assert_eq!(Varint::new(u64::MAX).num_bytes(), 10);
+
+    // 300 is split into 7 bit groups, least significant first: 0b0101100 and 0b0000010
+    let mut buf = vec![0u8; 2];
+    wrap::Context::<_, F>::new(&mut buf[..]).skip(Varint::new(300))?;
+    assert_eq!(buf, [0xac, 0x02]);
+
+    // Overlong and overflowing encodings are rejected
+    let malformed: [&[u8]; 2] = [&[0x80, 0x00], &[0xff; 10]];
+    for bytes in malformed {
+        let mut varint = Varint::default();
+        let error = unwrap::Context::<_, F>::new(bytes).skip(&mut varint).err();
+        assert!(matches!(
+            error.as_ref().map(Error::unlocated),
+            Some(Error::MalformedVarint(_))
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn varint() {
+    assert!(absorb_mask_skip_varint::<KeccakF1600>().is_ok());
+}
+
 fn absorb_mask_squeeze_bytes_mac<F>() -> Result<()>
 where
     F: PRP + Default,
@@ -228,6 +279,312 @@ fn bytes() {
     assert!(absorb_mask_squeeze_bytes_mac::<KeccakF1600>().is_ok());
 }
 
+fn wrap_into_growable_buffer<F>() -> Result<()>
+where
+    F: PRP + Default + PartialEq,
+{
+    let mut prng = SpongosRng::<F>::new("Spongos tests");
+    let payload: Vec<u8> = prng.borrow_mut().sample_iter(Standard).take(1002).collect();
+    let nonce: NBytes<[u8; 32]> = prng.gen();
+
+    let buf_size = sizeof::Context::new()
+        .absorb(nonce.as_ref())?
+        .mask(Size::new(payload.len()))?
+        .mask(Bytes::new(&payload))?
+        .commit()?
+        .squeeze(&Mac::new(32))?
+        .finalize();
+    let mut sized = vec![0u8; buf_size];
+    let mut ctx = wrap::Context::<_, F>::new(&mut sized[..]);
+    ctx.absorb(nonce.as_ref())?
+        .mask(Size::new(payload.len()))?
+        .mask(Bytes::new(&payload))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+    let sized_spongos = ctx.finalize();
+
+    // A growable buffer needs no sizing pass
+    let mut growable = Vec::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut growable);
+    ctx.absorb(nonce.as_ref())?
+        .mask(Size::new(payload.len()))?
+        .mask(Bytes::new(&payload))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+    let growable_spongos = ctx.finalize();
+
+    assert_eq!(growable, sized, "Growable buffer does not match the sized buffer");
+    assert_eq!(growable_spongos, sized_spongos);
+    Ok(())
+}
+
+#[test]
+fn growable_buffer() {
+    assert!(wrap_into_growable_buffer::<KeccakF1600>().is_ok());
+}
+
+fn unwrap_in_place<F>() -> Result<()>
+where
+    F: PRP + Default + PartialEq,
+{
+    let mut prng = SpongosRng::<F>::new("Spongos tests");
+    let public: Vec<u8> = prng.borrow_mut().sample_iter(Standard).take(77).collect();
+    let masked: Vec<u8> = prng.borrow_mut().sample_iter(Standard).take(1002).collect();
+
+    let mut buf = Vec::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut buf);
+    ctx.absorb(Bytes::new(&public))?
+        .mask(Bytes::new(&masked))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+    let wrap_spongos = ctx.finalize();
+
+    let (mut public_copy, mut masked_copy) = (Vec::new(), Vec::new());
+    let mut ctx = unwrap::Context::<_, F>::new(&buf[..]);
+    ctx.absorb(Bytes::new(&mut public_copy))?
+        .mask(Bytes::new(&mut masked_copy))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+    let (copy_spongos, _) = ctx.finalize();
+
+    let (mut public_range, mut masked_range) = (Range::default(), Range::default());
+    let mut ctx = unwrap::Context::<_, F>::new(&mut buf[..]);
+    ctx.absorb(Bytes::new(&mut public_range))?
+        .mask(Bytes::new(&mut masked_range))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+    let (in_place_spongos, _) = ctx.finalize();
+
+    assert_eq!(public_copy, public);
+    assert_eq!(masked_copy, masked);
+    assert_eq!(&buf[public_range], &public[..], "Absorbed bytes are not left in place");
+    assert_eq!(&buf[masked_range], &masked[..], "Masked bytes are not decrypted in place");
+    assert!(wrap_spongos == copy_spongos && copy_spongos == in_place_spongos);
+    Ok(())
+}
+
+#[test]
+fn in_place() {
+    assert!(unwrap_in_place::<KeccakF1600>().is_ok());
+}
+
+fn unwrap_within_limits<F>() -> Result<()>
+where
+    F: PRP + Default + Clone,
+{
+    let items = vec![vec![1u8; 100], vec![2u8; 100], vec![3u8; 100]];
+    let mut buf = Vec::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut buf);
+    ctx.absorb(Size::new(items.len()))?;
+    for item in &items {
+        ctx.mask(Bytes::new(item))?;
+    }
+    ctx.commit()?.squeeze(&Mac::new(32))?;
+
+    let unwrap_with = |limits: unwrap::Limits| -> Result<Vec<Vec<u8>>> {
+        let mut unwrapped = Vec::new();
+        let mut ctx = unwrap::Context::<_, F>::new(&buf[..]).with_limits(limits);
+        let mut n = Size::default();
+        ctx.absorb(&mut n)?.repeated(n.inner(), |ctx| {
+            let mut item = Vec::new();
+            ctx.mask(Bytes::new(&mut item))?;
+            unwrapped.push(item);
+            Ok(ctx)
+        })?;
+        ctx.commit()?.squeeze(&Mac::new(32))?;
+        Ok(unwrapped)
+    };
+
+    let limits = unwrap::Limits::new()
+        .with_max_allocation(300)
+        .with_max_bytes_len(100)
+        .with_max_repeated(3)
+        .with_max_depth(1);
+    assert_eq!(unwrap_with(limits)?, items);
+    for (limit, limits) in [
+        ("allocation", limits.with_max_allocation(299)),
+        ("bytes length", limits.with_max_bytes_len(99)),
+        ("repeated count", limits.with_max_repeated(2)),
+        ("nesting depth", limits.with_max_depth(0)),
+    ] {
+        let error = unwrap_with(limits).err();
+        assert!(
+            matches!(error.as_ref().map(Error::unlocated), Some(Error::LimitExceeded(name, ..)) if *name == limit),
+            "{} limit not enforced",
+            limit
+        );
+    }
+
+    // Forks count towards the nesting depth
+    let mut ctx = unwrap::Context::<_, F>::new(&buf[..]).with_limits(limits.with_max_depth(0));
+    let mut n = Size::default();
+    assert!(ctx.fork().absorb(&mut n)?.ensure_repeated(n.inner()).is_err());
+
+    // Allocations made by forks count towards the budget of the context they are forked from
+    let mut buf = Vec::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut buf);
+    for item in &items {
+        ctx.fork().mask(Bytes::new(item))?;
+    }
+    ctx.commit()?.squeeze(&Mac::new(32))?;
+    let unwrap_forks_with = |limits: unwrap::Limits| -> Result<()> {
+        let mut ctx = unwrap::Context::<_, F>::new(&buf[..]).with_limits(limits);
+        for _ in &items {
+            ctx.fork().mask(Bytes::new(&mut Vec::new()))?;
+        }
+        ctx.commit()?.squeeze(&Mac::new(32))?;
+        Ok(())
+    };
+    assert!(unwrap_forks_with(unwrap::Limits::new().with_max_allocation(300)).is_ok());
+    let error = unwrap_forks_with(unwrap::Limits::new().with_max_allocation(299)).err();
+    assert!(
+        matches!(
+            error.as_ref().map(Error::unlocated),
+            Some(Error::LimitExceeded("allocation", 299, 300))
+        ),
+        "allocation limit not enforced across forks"
+    );
+
+    // Skipped bytes are allocated too
+    let mut buf = Vec::new();
+    wrap::Context::<_, F>::new(&mut buf).skip(Bytes::new(&items[0]))?;
+    let skip_with = |limits: unwrap::Limits| -> Result<Vec<u8>> {
+        let mut skipped = Vec::new();
+        unwrap::Context::<_, F>::new(&buf[..])
+            .with_limits(limits)
+            .skip(Bytes::new(&mut skipped))?;
+        Ok(skipped)
+    };
+    assert_eq!(skip_with(unwrap::Limits::new().with_max_allocation(100))?, items[0]);
+    let error = skip_with(unwrap::Limits::new().with_max_allocation(99)).err();
+    assert!(
+        matches!(
+            error.as_ref().map(Error::unlocated),
+            Some(Error::LimitExceeded("allocation", 99, 100))
+        ),
+        "allocation limit not enforced on skipped bytes"
+    );
+    Ok(())
+}
+
+#[test]
+fn limits() {
+    assert!(unwrap_within_limits::<KeccakF1600>().is_ok());
+}
+
+fn locate_unwrap_errors<F>() -> Result<()>
+where
+    F: PRP + Default,
+{
+    let items = vec![vec![1u8; 100], vec![2u8; 100]];
+    let mut buf = Vec::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut buf);
+    ctx.absorb(Size::new(items.len()))?;
+    for item in &items {
+        ctx.mask(Bytes::new(item))?;
+    }
+    ctx.commit()?.squeeze(&Mac::new(32))?;
+
+    let unwrap_with = |buf: &[u8]| -> Result<()> {
+        let mut ctx = unwrap::Context::<_, F>::new(buf);
+        let mut n = Size::default();
+        ctx.absorb(&mut n)?.push_breadcrumb("items");
+        for i in 0..n.inner() {
+            ctx.index_breadcrumb(i).mask(Bytes::new(&mut Vec::new()))?;
+        }
+        ctx.pop_breadcrumb().commit()?.squeeze(&Mac::new(32))?;
+        Ok(())
+    };
+
+    // The second item is cut short, after its size was decoded
+    let error = unwrap_with(&buf[..150]).unwrap_err();
+    assert_eq!(error.location(), Some(("items[1]/mask", 106)));
+    assert!(matches!(error.unlocated(), Error::StreamAllocationExceededIn(100, 44)));
+
+    let last = buf.len() - 1;
+    buf[last] ^= 1;
+    let error = unwrap_with(&buf).unwrap_err();
+    assert_eq!(error.location(), Some(("squeeze", 206)));
+    assert!(matches!(error.unlocated(), Error::BadMac));
+    Ok(())
+}
+
+#[test]
+fn locate_errors() {
+    assert!(locate_unwrap_errors::<KeccakF1600>().is_ok());
+}
+
+fn trace_commands<F>() -> Result<()>
+where
+    F: PRP + Default,
+{
+    let payload = vec![3u8; 20];
+    let mut ctx = sizeof::Context::new();
+    ctx.absorb(Uint8::new(1))?
+        .mask(Bytes::new(&payload))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+
+    let mut buf = vec![0u8; ctx.finalize()];
+    let wrap_trace = Trace::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut buf[..]).with_trace(wrap_trace.clone());
+    ctx.absorb(Uint8::new(1))?
+        .mask(Bytes::new(&payload))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+
+    let (mut u, mut unwrapped) = (Uint8::default(), Vec::new());
+    let unwrap_trace = Trace::new();
+    let mut ctx = unwrap::Context::<_, F>::new(&buf[..]).with_trace(unwrap_trace.clone());
+    ctx.absorb(&mut u)?
+        .mask(Bytes::new(&mut unwrapped))?
+        .commit()?
+        .squeeze(&Mac::new(32))?;
+
+    // The size of the bytes is masked as a command of its own
+    let commands = [
+        Command::Absorb,
+        Command::Mask,
+        Command::Mask,
+        Command::Commit,
+        Command::Squeeze,
+    ];
+    let events = wrap_trace.events();
+    assert!(events.iter().map(|event| event.command()).eq(commands));
+    assert_eq!(events[2].offset(), 2);
+    assert_eq!(wrap_trace, unwrap_trace);
+    // The offset past the last event is the size of the message
+    assert_eq!(events[4].offset() + events[4].length(), buf.len());
+
+    let json = wrap_trace.to_json();
+    assert!(json.starts_with(r#"[{"command":"absorb","length":1,"offset":0,"before":""#));
+    assert!(json.ends_with(r#","failed":false}]"#));
+
+    // Commands failing are recorded as such, without a digest of the spongos state after them
+    let truncated_trace = Trace::new();
+    let mut ctx = unwrap::Context::<_, F>::new(&buf[..1]).with_trace(truncated_trace.clone());
+    assert!(ctx.absorb(&mut u)?.mask(Bytes::new(&mut unwrapped)).is_err());
+    let events = truncated_trace.events();
+    assert!(events.iter().map(|event| event.failed()).eq([false, true]));
+    assert_eq!(events[1].command(), Command::Mask);
+    assert_eq!(events[1].after(), None);
+
+    let last = buf.len() - 1;
+    buf[last] ^= 1;
+    let bad_mac_trace = Trace::new();
+    let mut ctx = unwrap::Context::<_, F>::new(&buf[..]).with_trace(bad_mac_trace.clone());
+    ctx.absorb(&mut u)?.mask(Bytes::new(&mut unwrapped))?.commit()?;
+    assert!(ctx.squeeze(&Mac::new(32)).is_err());
+    let events = bad_mac_trace.events();
+    assert!(events.iter().map(|event| event.failed()).eq([false, false, false, false, true]));
+    Ok(())
+}
+
+#[test]
+fn trace() {
+    assert!(trace_commands::<KeccakF1600>().is_ok());
+}
+
 fn absorb_ed25519<F: PRP + Default>() -> Result<()> {
     let secret = ed25519::SecretKey::from_bytes([7; ed25519::SECRET_KEY_LENGTH]);
 
@@ -321,3 +678,64 @@ fn x25519_transport<F: PRP + Default>() -> Result<()> {
 fn test_x25519() {
     assert!(x25519_transport::<KeccakF1600>().is_ok());
 }
+
+#[cfg(feature = "async-io")]
+fn async_streams<F>() -> Result<()>
+where
+    F: PRP + Default,
+{
+    use futures_util::{future::FutureExt, io::Cursor};
+
+    use crate::ddml::io::{AsyncReader, AsyncWriter};
+
+    let items = vec![vec![1u8; 100], vec![2u8; 50]];
+    let mut buf = Vec::new();
+    let mut ctx = wrap::Context::<_, F>::new(&mut buf);
+    for item in &items {
+        ctx.mask(Bytes::new(item))?;
+    }
+    ctx.commit()?.squeeze(&Mac::new(32))?;
+
+    // Flushing the writer between the commands produces the same bytes
+    let mut written = Vec::new();
+    {
+        let mut ctx = wrap::Context::<_, F>::new(AsyncWriter::new(&mut written));
+        for item in &items {
+            ctx.mask(Bytes::new(item))?;
+            ctx.stream_mut().flush().now_or_never().expect("writing to a vector never blocks")?;
+        }
+        ctx.commit()?.squeeze(&Mac::new(32))?;
+        ctx.stream_mut().finish().now_or_never().expect("writing to a vector never blocks")?;
+        assert_eq!(ctx.stream().position(), buf.len());
+    }
+    assert_eq!(written, buf);
+
+    // The lengths are read from the stream itself, so the reader is filled with the whole message
+    let mut unwrapped = vec![Vec::new(), Vec::new()];
+    let mut ctx = unwrap::Context::<_, F>::new(AsyncReader::new(Cursor::new(&written)));
+    ctx.stream_mut()
+        .fill(written.len())
+        .now_or_never()
+        .expect("reading from a vector never blocks")?;
+    for item in &mut unwrapped {
+        ctx.mask(Bytes::new(item))?;
+    }
+    ctx.commit()?.squeeze(&Mac::new(32))?;
+    assert_eq!(unwrapped, items);
+    assert_eq!(ctx.stream().position(), written.len());
+
+    // Commands advancing past the bytes filled fail as if the stream had ended
+    let mut ctx = unwrap::Context::<_, F>::new(AsyncReader::new(Cursor::new(&written)));
+    ctx.stream_mut()
+        .fill(10)
+        .now_or_never()
+        .expect("reading from a vector never blocks")?;
+    assert!(ctx.mask(Bytes::new(&mut Vec::new())).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "async-io")]
+#[test]
+fn async_io() {
+    assert!(async_streams::<KeccakF1600>().is_ok());
+}
@@ -1,5 +1,6 @@
 // Rust
 use alloc::vec::Vec;
+use core::ops::Range;
 
 // IOTA
 use crypto::{keys::x25519, signatures::ed25519};
@@ -13,7 +14,8 @@ use crate::{
             Absorb,
         },
         io,
-        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::{Command, Digest},
+        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::{Error::PublicKeyGenerationFailure, Result},
 };
@@ -22,12 +24,40 @@ use crate::{
 struct AbsorbContext<'a, F: PRP, IS: io::IStream> {
     /// Internal [`Context`] that [`Absorb`] operations will be conducted on
     ctx: &'a mut Context<IS, F>,
+    /// Number of bytes processed by the command so far
+    length: usize,
+    /// Digest of the spongos state before the command, if the context is traced
+    before: Option<Digest>,
+    /// Whether all the bytes of the command have been processed
+    completed: bool,
 }
 
 /// Create a new [`AbsorbContext`] from the provided [`Context`].
 impl<'a, F: PRP, IS: io::IStream> AbsorbContext<'a, F, IS> {
     fn new(ctx: &'a mut Context<IS, F>) -> Self {
-        Self { ctx }
+        let before = ctx.trace_before();
+        Self {
+            ctx,
+            length: 0,
+            before,
+            completed: false,
+        }
+    }
+
+    /// Marks the command as completed, once all its bytes have been processed
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// Records the command in the [`Context`] trace, as failed unless all its bytes have been processed.
+impl<F: PRP, IS: io::IStream> Drop for AbsorbContext<'_, F, IS> {
+    fn drop(&mut self) {
+        if self.completed {
+            self.ctx.trace_command(Command::Absorb, self.length, self.before);
+        } else {
+            self.ctx.trace_failed(Command::Absorb, self.length, self.before);
+        }
     }
 }
 
@@ -39,10 +69,13 @@ impl<F: PRP, IS: io::IStream> Unwrap for AbsorbContext<'_, F, IS> {
         T: AsMut<[u8]>,
     {
         let bytes = bytes.as_mut();
-        let slice = self.ctx.stream.try_advance(bytes.len())?;
+        match self.ctx.stream.try_advance(bytes.len()) {
+            Ok(slice) => bytes.copy_from_slice(slice),
+            Err(e) => return Err(self.ctx.locate_command("absorb", e)),
+        }
         self.ctx.cursor += bytes.len();
-        bytes.copy_from_slice(slice);
         self.ctx.spongos.absorb(bytes);
+        self.length += bytes.len();
         Ok(self)
     }
 }
@@ -50,7 +83,7 @@ impl<F: PRP, IS: io::IStream> Unwrap for AbsorbContext<'_, F, IS> {
 /// Reads a single byte encoded [`Uint8`] wrapper from [`Context`].
 impl<F: PRP, IS: io::IStream> Absorb<&mut Uint8> for Context<IS, F> {
     fn absorb(&mut self, u: &mut Uint8) -> Result<&mut Self> {
-        AbsorbContext::new(self).unwrap_u8(u)?;
+        AbsorbContext::new(self).unwrap_u8(u)?.complete();
         Ok(self)
     }
 }
@@ -58,7 +91,7 @@ impl<F: PRP, IS: io::IStream> Absorb<&mut Uint8> for Context<IS, F> {
 /// Reads a two byte encoded [`Uint16`] wrapper from [`Context`].
 impl<F: PRP, IS: io::IStream> Absorb<&mut Uint16> for Context<IS, F> {
     fn absorb(&mut self, u: &mut Uint16) -> Result<&mut Self> {
-        AbsorbContext::new(self).unwrap_u16(u)?;
+        AbsorbContext::new(self).unwrap_u16(u)?.complete();
         Ok(self)
     }
 }
@@ -66,7 +99,7 @@ impl<F: PRP, IS: io::IStream> Absorb<&mut Uint16> for Context<IS, F> {
 /// Reads a four byte encoded [`Uint32`] wrapper from [`Context`].
 impl<F: PRP, IS: io::IStream> Absorb<&mut Uint32> for Context<IS, F> {
     fn absorb(&mut self, u: &mut Uint32) -> Result<&mut Self> {
-        AbsorbContext::new(self).unwrap_u32(u)?;
+        AbsorbContext::new(self).unwrap_u32(u)?.complete();
         Ok(self)
     }
 }
@@ -74,7 +107,7 @@ impl<F: PRP, IS: io::IStream> Absorb<&mut Uint32> for Context<IS, F> {
 /// Reads an eight byte encoded [`Uint64`] wrapper from [`Context`].
 impl<F: PRP, IS: io::IStream> Absorb<&mut Uint64> for Context<IS, F> {
     fn absorb(&mut self, u: &mut Uint64) -> Result<&mut Self> {
-        AbsorbContext::new(self).unwrap_u64(u)?;
+        AbsorbContext::new(self).unwrap_u64(u)?.complete();
         Ok(self)
     }
 }
@@ -82,7 +115,16 @@ impl<F: PRP, IS: io::IStream> Absorb<&mut Uint64> for Context<IS, F> {
 /// Reads an `n` byte encoded [`Size`] wrapper from [`Context`].
 impl<F: PRP, IS: io::IStream> Absorb<&mut Size> for Context<IS, F> {
     fn absorb(&mut self, size: &mut Size) -> Result<&mut Self> {
-        AbsorbContext::new(self).unwrap_size(size)?;
+        AbsorbContext::new(self).unwrap_size(size)?.complete();
+        Ok(self)
+    }
+}
+
+/// Reads a LEB128 encoded [`Varint`] wrapper from [`Context`]. Malformed encodings are rejected.
+impl<'a, F: PRP, IS: io::IStream> Absorb<&'a mut Varint> for Context<IS, F> {
+    fn absorb(&mut self, varint: &'a mut Varint) -> Result<&mut Self> {
+        let result = AbsorbContext::new(self).unwrap_varint(varint).map(|ctx| ctx.complete());
+        result.map_err(|e| self.locate_command("absorb", e))?;
         Ok(self)
     }
 }
@@ -91,7 +133,7 @@ impl<F: PRP, IS: io::IStream> Absorb<&mut Size> for Context<IS, F> {
 /// encoded with `n` bytes.
 impl<F: PRP, T: AsMut<[u8]>, IS: io::IStream> Absorb<NBytes<T>> for Context<IS, F> {
     fn absorb(&mut self, nbytes: NBytes<T>) -> Result<&mut Self> {
-        AbsorbContext::new(self).unwrapn(nbytes)?;
+        AbsorbContext::new(self).unwrapn(nbytes)?.complete();
         Ok(self)
     }
 }
@@ -102,9 +144,33 @@ impl<F: PRP, IS: io::IStream> Absorb<Bytes<&mut Vec<u8>>> for Context<IS, F> {
     fn absorb(&mut self, mut bytes: Bytes<&mut Vec<u8>>) -> Result<&mut Self> {
         let mut size = Size::default();
         self.absorb(&mut size)?;
-        self.stream.ensure_size(size.inner())?;
+        self.stream
+            .ensure_size(size.inner())
+            .map_err(|e| self.locate_command("absorb", e))?;
+        self.allocate(size.inner())?;
         bytes.resize(size.inner());
-        AbsorbContext::new(self).unwrapn(bytes)?;
+        AbsorbContext::new(self).unwrapn(bytes)?.complete();
+        Ok(self)
+    }
+}
+
+/// Reads a variable sized [`Bytes`] from [`Context`] without copying them out of the stream. The
+/// provided `Range` is set to the position of the bytes relative to the start of the [`Context`]
+/// stream.
+impl<'a, F: PRP, IS: io::IStream> Absorb<Bytes<&'a mut Range<usize>>> for Context<IS, F> {
+    fn absorb(&mut self, mut bytes: Bytes<&'a mut Range<usize>>) -> Result<&mut Self> {
+        let mut size = Size::default();
+        self.absorb(&mut size)?;
+        self.ensure_bytes_len(size.inner())?;
+        let before = self.trace_before();
+        let start = self.cursor;
+        match self.stream.try_advance(size.inner()) {
+            Ok(x) => self.spongos.absorb(x),
+            Err(e) => return Err(self.locate_command("absorb", e)),
+        }
+        self.cursor += size.inner();
+        self.trace_command(Command::Absorb, size.inner(), before);
+        **bytes.inner_mut() = start..self.cursor;
         Ok(self)
     }
 }
@@ -113,7 +179,7 @@ impl<F: PRP, IS: io::IStream> Absorb<Bytes<&mut Vec<u8>>> for Context<IS, F> {
 impl<'a, F: PRP, IS: io::IStream> Absorb<&'a mut ed25519::PublicKey> for Context<IS, F> {
     fn absorb(&mut self, public_key: &'a mut ed25519::PublicKey) -> Result<&mut Self> {
         let mut bytes = [0u8; ed25519::PUBLIC_KEY_LENGTH];
-        AbsorbContext::new(self).unwrapn(&mut bytes)?;
+        AbsorbContext::new(self).unwrapn(&mut bytes)?.complete();
         match ed25519::PublicKey::try_from_bytes(bytes) {
             Ok(pk) => {
                 *public_key = pk;
@@ -128,7 +194,7 @@ impl<'a, F: PRP, IS: io::IStream> Absorb<&'a mut ed25519::PublicKey> for Context
 impl<'a, F: PRP, IS: io::IStream> Absorb<&'a mut x25519::PublicKey> for Context<IS, F> {
     fn absorb(&mut self, public_key: &'a mut x25519::PublicKey) -> Result<&mut Self> {
         let mut bytes = [0u8; x25519::PUBLIC_KEY_LENGTH];
-        AbsorbContext::new(self).unwrapn(&mut bytes)?;
+        AbsorbContext::new(self).unwrapn(&mut bytes)?.complete();
         *public_key = x25519::PublicKey::from(bytes);
         Ok(self)
     }
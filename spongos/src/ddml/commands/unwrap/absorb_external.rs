@@ -5,7 +5,8 @@ use crate::{
     ddml::{
         commands::{unwrap::Context, Absorb},
         modifiers::External,
-        types::{NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::Command,
+        types::{NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
@@ -14,7 +15,7 @@ use crate::{
 /// does not advance internal stream.
 impl<F: PRP, IS> Absorb<External<Uint8>> for Context<IS, F> {
     fn absorb(&mut self, u: External<Uint8>) -> Result<&mut Self> {
-        self.spongos.absorb(u.into_inner().to_bytes());
+        self.absorb_external(u.into_inner().to_bytes());
         Ok(self)
     }
 }
@@ -23,7 +24,7 @@ impl<F: PRP, IS> Absorb<External<Uint8>> for Context<IS, F> {
 /// does not advance internal stream.
 impl<F: PRP, IS> Absorb<External<Uint16>> for Context<IS, F> {
     fn absorb(&mut self, u: External<Uint16>) -> Result<&mut Self> {
-        self.spongos.absorb(u.into_inner().to_bytes());
+        self.absorb_external(u.into_inner().to_bytes());
         Ok(self)
     }
 }
@@ -32,7 +33,7 @@ impl<F: PRP, IS> Absorb<External<Uint16>> for Context<IS, F> {
 /// does not advance internal stream.
 impl<F: PRP, IS> Absorb<External<Uint32>> for Context<IS, F> {
     fn absorb(&mut self, u: External<Uint32>) -> Result<&mut Self> {
-        self.spongos.absorb(u.into_inner().to_bytes());
+        self.absorb_external(u.into_inner().to_bytes());
         Ok(self)
     }
 }
@@ -41,7 +42,7 @@ impl<F: PRP, IS> Absorb<External<Uint32>> for Context<IS, F> {
 /// does not advance internal stream.
 impl<F: PRP, IS> Absorb<External<Uint64>> for Context<IS, F> {
     fn absorb(&mut self, u: External<Uint64>) -> Result<&mut Self> {
-        self.spongos.absorb(u.into_inner().to_bytes());
+        self.absorb_external(u.into_inner().to_bytes());
         Ok(self)
     }
 }
@@ -50,10 +51,30 @@ impl<F: PRP, IS> Absorb<External<Uint64>> for Context<IS, F> {
 /// does not advance internal stream.
 impl<F: PRP, IS> Absorb<External<Size>> for Context<IS, F> {
     fn absorb(&mut self, size: External<Size>) -> Result<&mut Self> {
+        let before = self.trace_before();
+        let mut length = 0;
         size.into_inner().encode(|byte| {
             self.spongos.absorb(&[byte]);
+            length += 1;
             Ok(())
         })?;
+        self.trace_command(Command::AbsorbExternal, length, before);
+        Ok(self)
+    }
+}
+
+/// Reads a LEB128 encoded [`Varint`] from [Spongos](`crate::core::spongos::Spongos`) state but
+/// does not advance internal stream.
+impl<F: PRP, IS> Absorb<External<Varint>> for Context<IS, F> {
+    fn absorb(&mut self, varint: External<Varint>) -> Result<&mut Self> {
+        let before = self.trace_before();
+        let mut length = 0;
+        varint.into_inner().encode(|byte| {
+            self.spongos.absorb(&[byte]);
+            length += 1;
+            Ok(())
+        })?;
+        self.trace_command(Command::AbsorbExternal, length, before);
         Ok(self)
     }
 }
@@ -62,7 +83,7 @@ impl<F: PRP, IS> Absorb<External<Size>> for Context<IS, F> {
 /// does not advance internal stream.
 impl<'a, F: PRP, T: AsRef<[u8]>, IS> Absorb<External<&'a NBytes<T>>> for Context<IS, F> {
     fn absorb(&mut self, bytes: External<&'a NBytes<T>>) -> Result<&mut Self> {
-        self.spongos.absorb(bytes);
+        self.absorb_external(bytes);
         Ok(self)
     }
 }
@@ -71,7 +92,7 @@ impl<'a, F: PRP, T: AsRef<[u8]>, IS> Absorb<External<&'a NBytes<T>>> for Context
 /// does not advance internal stream.
 impl<'a, F: PRP, IS> Absorb<External<&'a ed25519::PublicKey>> for Context<IS, F> {
     fn absorb(&mut self, public_key: External<&'a ed25519::PublicKey>) -> Result<&mut Self> {
-        self.spongos.absorb(public_key);
+        self.absorb_external(public_key);
         Ok(self)
     }
 }
@@ -80,7 +101,7 @@ impl<'a, F: PRP, IS> Absorb<External<&'a ed25519::PublicKey>> for Context<IS, F>
 /// does not advance internal stream.
 impl<'a, F: PRP, IS> Absorb<External<&'a x25519::PublicKey>> for Context<IS, F> {
     fn absorb(&mut self, public_key: External<&'a x25519::PublicKey>) -> Result<&mut Self> {
-        self.spongos.absorb(public_key);
+        self.absorb_external(public_key);
         Ok(self)
     }
 }
@@ -6,6 +6,7 @@ use crate::{
         commands::{unwrap::Context, Ed25519},
         io,
         modifiers::External,
+        trace::Command,
         types::NBytes,
     },
     error::{Error, Result},
@@ -18,14 +19,16 @@ impl<'a, F, IS: io::IStream> Ed25519<&'a ed25519::PublicKey, External<&'a NBytes
         public_key: &'a ed25519::PublicKey,
         hash: External<&'a NBytes<[u8; 64]>>,
     ) -> Result<&mut Self> {
-        let signature_bytes = self.stream.try_advance(ed25519::SIGNATURE_LENGTH)?;
-        self.cursor += ed25519::SIGNATURE_LENGTH;
+        let signature_bytes = match self.stream.try_advance(ed25519::SIGNATURE_LENGTH) {
+            Ok(signature_bytes) => signature_bytes,
+            Err(e) => return Err(self.locate_command("ed25519", e)),
+        };
         let signature = ed25519::Signature::from_bytes(signature_bytes.try_into()?);
-        let is_valid = public_key.verify(&signature, hash.inner().as_slice());
-
-        match is_valid {
-            true => Ok(self),
-            false => Err(Error::SignatureMismatch),
+        if !public_key.verify(&signature, hash.inner().as_slice()) {
+            return Err(self.locate_command("ed25519", Error::SignatureMismatch));
         }
+        self.cursor += ed25519::SIGNATURE_LENGTH;
+        self.trace_untouched(Command::Ed25519, ed25519::SIGNATURE_LENGTH);
+        Ok(self)
     }
 }
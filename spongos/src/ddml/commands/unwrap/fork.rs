@@ -1,4 +1,7 @@
-use crate::ddml::commands::{unwrap::Context, Fork};
+use crate::ddml::{
+    commands::{unwrap::Context, Fork},
+    trace::Command,
+};
 
 /// Copy context for looped [`Context`] decryption operations
 impl<'a, F, IS> Fork<'a> for Context<IS, F>
@@ -9,6 +12,18 @@ where
     type Forked = Context<&'a mut IS, F>;
     fn fork(&'a mut self) -> Context<&'a mut IS, F> {
         let fork = self.spongos.fork();
-        Context::new_with_spongos(self.stream_mut(), fork)
+        let (limits, allocated, depth) = (self.limits, self.allocated.share(), self.depth + 1);
+        let (cursor, breadcrumbs, trace) = (self.cursor, self.breadcrumbs.clone(), self.trace.clone());
+        self.trace_untouched(Command::Fork, 0);
+        let mut forked = Context::new_with_spongos(self.stream_mut(), fork).with_limits(limits);
+        // The fork shares the allocation budget of the context, and is nested one level deeper
+        forked.allocated = allocated;
+        forked.depth = depth;
+        // The fork continues reading at the same position and path, so that its errors are located
+        forked.cursor = cursor;
+        forked.breadcrumbs = breadcrumbs;
+        // The commands of the fork are recorded along with those of the context
+        forked.trace = trace;
+        forked
     }
 }
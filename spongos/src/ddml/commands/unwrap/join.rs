@@ -1,13 +1,18 @@
 use crate::{
     core::{prp::PRP, spongos::Spongos},
-    ddml::commands::{unwrap::Context, Join},
+    ddml::{
+        commands::{unwrap::Context, Join},
+        trace::Command,
+    },
     error::Result,
 };
 
 /// Absorbs the provided [`Spongos`] into the beginning of the current [`Context`] spongos.
 impl<F: PRP, IS> Join<F> for Context<IS, F> {
     fn join(&mut self, joinee: &mut Spongos<F>) -> Result<&mut Self> {
+        let before = self.trace_before();
         self.spongos.join(joinee);
+        self.trace_command(Command::Join, 0, before);
         Ok(self)
     }
 }
@@ -0,0 +1,144 @@
+use alloc::sync::Arc;
+use core::{
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Limits on the resources an unwrap [`Context`](super::Context) may spend on a message.
+///
+/// Sizes and counts are decoded from untrusted input, and are used before the message is
+/// authenticated. Limiting them keeps a malicious message from requesting huge allocations or very
+/// long loops. All limits are unbounded by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// Maximum number of bytes allocated for the decoded [`Bytes`](crate::ddml::types::Bytes)
+    max_allocation: usize,
+    /// Maximum count of a repeated block
+    max_repeated: usize,
+    /// Maximum length of a single [`Bytes`](crate::ddml::types::Bytes)
+    max_bytes_len: usize,
+    /// Maximum nesting depth of repeated blocks and forks
+    max_depth: usize,
+}
+
+impl Limits {
+    /// Creates a new set of [`Limits`], all unbounded
+    pub fn new() -> Self {
+        Self {
+            max_allocation: usize::MAX,
+            max_repeated: usize::MAX,
+            max_bytes_len: usize::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+
+    /// Limits the total number of bytes allocated while unwrapping a message
+    ///
+    /// # Arguments
+    /// * `max_allocation`: The maximum number of bytes
+    pub fn with_max_allocation(mut self, max_allocation: usize) -> Self {
+        self.max_allocation = max_allocation;
+        self
+    }
+
+    /// Limits the count of a repeated block
+    ///
+    /// # Arguments
+    /// * `max_repeated`: The maximum count
+    pub fn with_max_repeated(mut self, max_repeated: usize) -> Self {
+        self.max_repeated = max_repeated;
+        self
+    }
+
+    /// Limits the length of a single variable sized array of bytes
+    ///
+    /// # Arguments
+    /// * `max_bytes_len`: The maximum length
+    pub fn with_max_bytes_len(mut self, max_bytes_len: usize) -> Self {
+        self.max_bytes_len = max_bytes_len;
+        self
+    }
+
+    /// Limits the nesting depth of repeated blocks and forks
+    ///
+    /// # Arguments
+    /// * `max_depth`: The maximum depth
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the maximum number of bytes allocated while unwrapping a message
+    pub fn max_allocation(&self) -> usize {
+        self.max_allocation
+    }
+
+    /// Returns the maximum count of a repeated block
+    pub fn max_repeated(&self) -> usize {
+        self.max_repeated
+    }
+
+    /// Returns the maximum length of a single variable sized array of bytes
+    pub fn max_bytes_len(&self) -> usize {
+        self.max_bytes_len
+    }
+
+    /// Returns the maximum nesting depth of repeated blocks and forks
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of bytes allocated so far by an unwrap [`Context`](super::Context), shared with its forks.
+///
+/// A fork unwraps part of the same message, so its allocations count towards the budget of the
+/// context it was forked from. Cloning a context instead copies the count, as the clone unwraps
+/// the message on its own.
+#[derive(Default, Debug)]
+pub(crate) struct Allocated(Arc<AtomicUsize>);
+
+impl Allocated {
+    /// Returns a counter shared with this one, for a fork of the context
+    pub(crate) fn share(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    /// Returns the number of bytes allocated so far
+    pub(crate) fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Sets the number of bytes allocated so far
+    ///
+    /// # Arguments
+    /// * `allocated`: The number of bytes
+    pub(crate) fn set(&self, allocated: usize) {
+        self.0.store(allocated, Ordering::Relaxed)
+    }
+}
+
+impl Clone for Allocated {
+    fn clone(&self) -> Self {
+        Self(Arc::new(AtomicUsize::new(self.get())))
+    }
+}
+
+impl PartialEq for Allocated {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for Allocated {}
+
+impl Hash for Allocated {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
@@ -1,5 +1,6 @@
 // Rust
 use alloc::vec::Vec;
+use core::ops::Range;
 
 // IOTA
 use crypto::{keys::x25519, signatures::ed25519};
@@ -13,21 +14,50 @@ use crate::{
             Mask,
         },
         io,
-        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::{Command, Digest},
+        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::{Error::PublicKeyGenerationFailure, Result},
 };
 
 /// A helper struct wrapper for performing [`Mask`] operations with
-struct MaskContext<'a, F, IS> {
+struct MaskContext<'a, F: PRP, IS: io::IStream> {
     /// Internal [`Context`] that [`Mask`] operations will be conducted on
     ctx: &'a mut Context<IS, F>,
+    /// Number of bytes processed by the command so far
+    length: usize,
+    /// Digest of the spongos state before the command, if the context is traced
+    before: Option<Digest>,
+    /// Whether all the bytes of the command have been processed
+    completed: bool,
 }
 
 /// Create a new [`MaskContext`] from the provided [`Context`].
 impl<'a, F: PRP, IS: io::IStream> MaskContext<'a, F, IS> {
     fn new(ctx: &'a mut Context<IS, F>) -> Self {
-        Self { ctx }
+        let before = ctx.trace_before();
+        Self {
+            ctx,
+            length: 0,
+            before,
+            completed: false,
+        }
+    }
+
+    /// Marks the command as completed, once all its bytes have been processed
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// Records the command in the [`Context`] trace, as failed unless all its bytes have been processed.
+impl<F: PRP, IS: io::IStream> Drop for MaskContext<'_, F, IS> {
+    fn drop(&mut self) {
+        if self.completed {
+            self.ctx.trace_command(Command::Mask, self.length, self.before);
+        } else {
+            self.ctx.trace_failed(Command::Mask, self.length, self.before);
+        }
     }
 }
 
@@ -38,9 +68,13 @@ impl<F: PRP, IS: io::IStream> Unwrap for MaskContext<'_, F, IS> {
     where
         T: AsMut<[u8]>,
     {
-        let y = self.ctx.stream.try_advance(bytes.as_mut().len())?;
+        let y = match self.ctx.stream.try_advance(bytes.as_mut().len()) {
+            Ok(y) => y,
+            Err(e) => return Err(self.ctx.locate_command("mask", e)),
+        };
         self.ctx.cursor += bytes.as_mut().len();
         self.ctx.spongos.decrypt_mut(y, &mut bytes)?;
+        self.length += bytes.as_mut().len();
         Ok(self)
     }
 }
@@ -48,7 +82,7 @@ impl<F: PRP, IS: io::IStream> Unwrap for MaskContext<'_, F, IS> {
 /// Decrypts a single byte encoded [`Uint8`] wrapper from [`Context`].
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint8> for Context<IS, F> {
     fn mask(&mut self, u: &'a mut Uint8) -> Result<&mut Self> {
-        MaskContext::new(self).unwrap_u8(u)?;
+        MaskContext::new(self).unwrap_u8(u)?.complete();
         Ok(self)
     }
 }
@@ -56,7 +90,7 @@ impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint8> for Context<IS, F> {
 /// Decrypts a two byte encoded [`Uint16`] wrapper from [`Context`].
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint16> for Context<IS, F> {
     fn mask(&mut self, u: &'a mut Uint16) -> Result<&mut Self> {
-        MaskContext::new(self).unwrap_u16(u)?;
+        MaskContext::new(self).unwrap_u16(u)?.complete();
         Ok(self)
     }
 }
@@ -64,7 +98,7 @@ impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint16> for Context<IS, F> {
 /// Decrypts a four byte encoded [`Uint32`] wrapper from [`Context`].
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint32> for Context<IS, F> {
     fn mask(&mut self, u: &'a mut Uint32) -> Result<&mut Self> {
-        MaskContext::new(self).unwrap_u32(u)?;
+        MaskContext::new(self).unwrap_u32(u)?.complete();
         Ok(self)
     }
 }
@@ -72,7 +106,7 @@ impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint32> for Context<IS, F> {
 /// Decrypts an eight byte encoded [`Uint64`] wrapper from [`Context`].
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint64> for Context<IS, F> {
     fn mask(&mut self, u: &'a mut Uint64) -> Result<&mut Self> {
-        MaskContext::new(self).unwrap_u64(u)?;
+        MaskContext::new(self).unwrap_u64(u)?.complete();
         Ok(self)
     }
 }
@@ -80,7 +114,16 @@ impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Uint64> for Context<IS, F> {
 /// Decrypts an `n` byte encoded [`Size`] wrapper from [`Context`].
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Size> for Context<IS, F> {
     fn mask(&mut self, size: &'a mut Size) -> Result<&mut Self> {
-        MaskContext::new(self).unwrap_size(size)?;
+        MaskContext::new(self).unwrap_size(size)?.complete();
+        Ok(self)
+    }
+}
+
+/// Decrypts a LEB128 encoded [`Varint`] wrapper from [`Context`]. Malformed encodings are rejected.
+impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Varint> for Context<IS, F> {
+    fn mask(&mut self, varint: &'a mut Varint) -> Result<&mut Self> {
+        let result = MaskContext::new(self).unwrap_varint(varint).map(|ctx| ctx.complete());
+        result.map_err(|e| self.locate_command("mask", e))?;
         Ok(self)
     }
 }
@@ -89,7 +132,7 @@ impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut Size> for Context<IS, F> {
 /// is decoded with `n` bytes.
 impl<F: PRP, T: AsMut<[u8]>, IS: io::IStream> Mask<NBytes<T>> for Context<IS, F> {
     fn mask(&mut self, nbytes: NBytes<T>) -> Result<&mut Self> {
-        MaskContext::new(self).unwrapn(nbytes)?;
+        MaskContext::new(self).unwrapn(nbytes)?.complete();
         Ok(self)
     }
 }
@@ -100,9 +143,33 @@ impl<'a, F: PRP, IS: io::IStream> Mask<Bytes<&'a mut Vec<u8>>> for Context<IS, F
     fn mask(&mut self, mut bytes: Bytes<&'a mut Vec<u8>>) -> Result<&mut Self> {
         let mut size = Size::default();
         self.mask(&mut size)?;
-        self.stream.ensure_size(size.inner())?;
+        self.stream
+            .ensure_size(size.inner())
+            .map_err(|e| self.locate_command("mask", e))?;
+        self.allocate(size.inner())?;
         bytes.resize(size.inner());
-        MaskContext::new(self).unwrapn(bytes)?;
+        MaskContext::new(self).unwrapn(bytes)?.complete();
+        Ok(self)
+    }
+}
+
+/// Decrypts a variable sized [`Bytes`] in place within the [`Context`] stream, without copying them
+/// out. The decrypted bytes are left in the stream buffer, and the provided `Range` is set to their
+/// position relative to the start of the [`Context`] stream.
+impl<'a, F: PRP, IS: io::IStreamMut> Mask<Bytes<&'a mut Range<usize>>> for Context<IS, F> {
+    fn mask(&mut self, mut bytes: Bytes<&'a mut Range<usize>>) -> Result<&mut Self> {
+        let mut size = Size::default();
+        self.mask(&mut size)?;
+        self.ensure_bytes_len(size.inner())?;
+        let before = self.trace_before();
+        let start = self.cursor;
+        match self.stream.try_advance_mut(size.inner()) {
+            Ok(y) => self.spongos.decrypt_in_place(y),
+            Err(e) => return Err(self.locate_command("mask", e)),
+        }
+        self.cursor += size.inner();
+        self.trace_command(Command::Mask, size.inner(), before);
+        **bytes.inner_mut() = start..self.cursor;
         Ok(self)
     }
 }
@@ -111,7 +178,7 @@ impl<'a, F: PRP, IS: io::IStream> Mask<Bytes<&'a mut Vec<u8>>> for Context<IS, F
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut x25519::PublicKey> for Context<IS, F> {
     fn mask(&mut self, public_key: &'a mut x25519::PublicKey) -> Result<&mut Self> {
         let mut bytes = [0u8; x25519::PUBLIC_KEY_LENGTH];
-        MaskContext::new(self).unwrapn(&mut bytes)?;
+        MaskContext::new(self).unwrapn(&mut bytes)?.complete();
         *public_key = x25519::PublicKey::from(bytes);
         Ok(self)
     }
@@ -121,7 +188,7 @@ impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut x25519::PublicKey> for Context<IS
 impl<'a, F: PRP, IS: io::IStream> Mask<&'a mut ed25519::PublicKey> for Context<IS, F> {
     fn mask(&mut self, public_key: &'a mut ed25519::PublicKey) -> Result<&mut Self> {
         let mut bytes = [0u8; ed25519::PUBLIC_KEY_LENGTH];
-        MaskContext::new(self).unwrapn(&mut bytes)?;
+        MaskContext::new(self).unwrapn(&mut bytes)?.complete();
         match ed25519::PublicKey::try_from_bytes(bytes) {
             Ok(pk) => {
                 *public_key = pk;
@@ -141,7 +208,8 @@ where
     fn mask(&mut self, spongos: &mut Spongos<F>) -> Result<&mut Self> {
         MaskContext::new(self)
             .unwrapn(spongos.outer_mut())?
-            .unwrapn(spongos.inner_mut())?;
+            .unwrapn(spongos.inner_mut())?
+            .complete();
         Ok(self)
     }
 }
@@ -1,5 +1,6 @@
 //! Implementation of command traits for unwrapping.
-use core::fmt;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::{self, Write};
 
 use crate::{
     core::{
@@ -8,13 +9,17 @@ use crate::{
     },
     ddml::{
         io,
-        types::{Size, Uint16, Uint32, Uint64, Uint8},
+        trace::{self, Command, Digest, Trace},
+        types::{Size, Uint16, Uint32, Uint64, Uint8, Varint},
+    },
+    error::{
+        Error::{self, LimitExceeded, Located},
+        Result,
     },
-    error::Result,
 };
 
 /// Unwrapped state of message. Used to decode `DDML` variables to
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
 pub struct Context<IS, F = KeccakF1600> {
     /// The [`Spongos`] object representing the current state of the unwrap [`Context`]
     spongos: Spongos<F>,
@@ -22,6 +27,17 @@ pub struct Context<IS, F = KeccakF1600> {
     stream: IS,
     /// Position of the current reading state
     cursor: usize,
+    /// The [`Limits`] on the resources spent unwrapping
+    limits: Limits,
+    /// Number of bytes allocated so far for decoded variable sized arrays of bytes, including
+    /// those allocated by the forks of the [`Context`]
+    allocated: Allocated,
+    /// Nesting depth of the current repeated blocks and forks
+    depth: usize,
+    /// Path to the field being unwrapped, reported along with the errors
+    breadcrumbs: Vec<Breadcrumb>,
+    /// The [`Trace`] the commands are recorded into, if any
+    trace: Option<Trace>,
 }
 
 impl<IS, F> Context<IS, F> {
@@ -34,6 +50,11 @@ impl<IS, F> Context<IS, F> {
             spongos: Spongos::<F>::init(),
             stream,
             cursor: 0,
+            limits: Limits::default(),
+            allocated: Allocated::default(),
+            depth: 0,
+            breadcrumbs: Vec::new(),
+            trace: None,
         }
     }
 
@@ -44,16 +65,228 @@ impl<IS, F> Context<IS, F> {
             spongos,
             stream,
             cursor: 0,
+            limits: Limits::default(),
+            allocated: Allocated::default(),
+            depth: 0,
+            breadcrumbs: Vec::new(),
+            trace: None,
+        }
+    }
+
+    /// Sets the [`Limits`] on the resources spent unwrapping, checked as sizes and counts are
+    /// decoded from the stream.
+    ///
+    /// # Arguments
+    /// * `limits`: The limits to apply
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The [`Limits`] applied by the current [`Context`].
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Records the commands processed by the [`Context`], and the forks of it, into a [`Trace`].
+    ///
+    /// # Arguments
+    /// * `trace`: The trace to record the commands into
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// The [`Trace`] the commands processed by the [`Context`] are recorded into, if any.
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Digest of the [`Spongos`] state before a command, if the commands are traced.
+    pub(crate) fn trace_before(&self) -> Option<Digest>
+    where
+        F: PRP,
+    {
+        self.trace.as_ref().map(|_| trace::digest(&self.spongos))
+    }
+
+    /// Records a command processing the [`Spongos`] state, if the commands are traced.
+    pub(crate) fn trace_command(&self, command: Command, length: usize, before: Option<Digest>)
+    where
+        F: PRP,
+    {
+        if let Some(trace) = &self.trace {
+            trace.record(command, length, before, Some(trace::digest(&self.spongos)), false);
+        }
+    }
+
+    /// Records a command leaving the [`Spongos`] state untouched, if the commands are traced.
+    pub(crate) fn trace_untouched(&self, command: Command, length: usize) {
+        if let Some(trace) = &self.trace {
+            trace.record(command, length, None, None, false);
+        }
+    }
+
+    /// Records a command that failed before completing, if the commands are traced.
+    pub(crate) fn trace_failed(&self, command: Command, length: usize, before: Option<Digest>) {
+        if let Some(trace) = &self.trace {
+            trace.record(command, length, before, None, true);
+        }
+    }
+
+    /// Absorbs external bytes into the [`Spongos`] state without advancing the stream.
+    pub(crate) fn absorb_external<T: AsRef<[u8]>>(&mut self, bytes: T)
+    where
+        F: PRP,
+    {
+        let before = self.trace_before();
+        self.spongos.absorb(bytes.as_ref());
+        self.trace_command(Command::AbsorbExternal, bytes.as_ref().len(), before);
+    }
+
+    /// Appends a step to the breadcrumb path of the field being unwrapped. Errors produced while
+    /// unwrapping are located at this path until the step is removed with
+    /// [`Context::pop_breadcrumb`].
+    ///
+    /// # Arguments
+    /// * `name`: The name of the step, usually the message or field being unwrapped
+    pub fn push_breadcrumb(&mut self, name: &'static str) -> &mut Self {
+        self.breadcrumbs.push(Breadcrumb { name, index: None });
+        self
+    }
+
+    /// Sets the index of the last step of the breadcrumb path, for fields repeated in a list.
+    ///
+    /// # Arguments
+    /// * `index`: The index of the item being unwrapped
+    pub fn index_breadcrumb(&mut self, index: usize) -> &mut Self {
+        if let Some(breadcrumb) = self.breadcrumbs.last_mut() {
+            breadcrumb.index = Some(index);
+        }
+        self
+    }
+
+    /// Removes the last step of the breadcrumb path. Should only be called once the field has been
+    /// unwrapped successfully, so that failures keep the path they occurred at.
+    pub fn pop_breadcrumb(&mut self) -> &mut Self {
+        self.breadcrumbs.pop();
+        self
+    }
+
+    /// Returns the breadcrumb path of the field being unwrapped, e.g. `keyload/subscribers[3]`.
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+        for (i, breadcrumb) in self.breadcrumbs.iter().enumerate() {
+            if i > 0 {
+                path.push('/');
+            }
+            // Writing to a `String` cannot fail
+            let _ = write!(path, "{}", breadcrumb);
+        }
+        path
+    }
+
+    /// Attaches the breadcrumb path and the read position of the [`Context`] to an error produced
+    /// while unwrapping. Errors already located deeper in the unwrapping keep their location.
+    ///
+    /// The read position counts the bytes consumed by the [`Context`] and the contexts it was
+    /// forked from. Bytes consumed by forks that have been dropped are not counted.
+    ///
+    /// # Arguments
+    /// * `error`: The error to locate
+    pub fn locate(&self, error: Error) -> Error {
+        match error {
+            Located(..) => error,
+            error => Located(self.path(), self.cursor, Box::new(error)),
+        }
+    }
+
+    /// Attaches the breadcrumb path, followed by the failing `command`, and the read position of
+    /// the [`Context`] to an error produced by a command.
+    pub(crate) fn locate_command(&self, command: &'static str, error: Error) -> Error {
+        let mut path = self.path();
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(command);
+        match error {
+            Located(..) => error,
+            error => Located(path, self.cursor, Box::new(error)),
+        }
+    }
+
+    /// Ensure a block repeated `n` times, with `n` decoded from the stream, stays within the
+    /// [`Limits`] of the [`Context`]. Should be called before looping over untrusted counts.
+    ///
+    /// # Arguments
+    /// * `n`: The number of repetitions
+    pub fn ensure_repeated(&mut self, n: usize) -> Result<&mut Self> {
+        self.ensure_depth()?;
+        if n > self.limits.max_repeated() {
+            return Err(self.locate(LimitExceeded("repeated count", self.limits.max_repeated(), n)));
+        }
+        Ok(self)
+    }
+
+    /// Accounts for the allocation of a variable sized array of `n` bytes, ensuring it stays within
+    /// the [`Limits`] of the [`Context`].
+    pub(crate) fn allocate(&mut self, n: usize) -> Result<()> {
+        self.ensure_depth()?;
+        self.ensure_bytes_len(n)?;
+        let allocated = self.allocated.get().saturating_add(n);
+        if allocated > self.limits.max_allocation() {
+            return Err(self.locate(LimitExceeded("allocation", self.limits.max_allocation(), allocated)));
+        }
+        self.allocated.set(allocated);
+        Ok(())
+    }
+
+    /// Ensure a variable sized array of `n` bytes stays within the [`Limits`] of the [`Context`].
+    pub(crate) fn ensure_bytes_len(&self, n: usize) -> Result<()> {
+        match n <= self.limits.max_bytes_len() {
+            true => Ok(()),
+            false => Err(self.locate(LimitExceeded("bytes length", self.limits.max_bytes_len(), n))),
+        }
+    }
+
+    /// Ensure the nesting of the current repeated blocks and forks stays within the [`Limits`] of
+    /// the [`Context`]. Forks cannot fail, so a fork nested too deep is rejected by the first limited
+    /// operation it performs.
+    fn ensure_depth(&self) -> Result<()> {
+        match self.depth <= self.limits.max_depth() {
+            true => Ok(()),
+            false => Err(self.locate(LimitExceeded("nesting depth", self.limits.max_depth(), self.depth))),
         }
     }
 
+    /// Enters a nested repeated block, ensuring the nesting stays within the [`Limits`] of the
+    /// [`Context`].
+    pub(crate) fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if let Err(e) = self.ensure_depth() {
+            self.depth -= 1;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Leaves a nested repeated block.
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
     /// The read stream of the current [`Context`].
     pub fn stream(&self) -> &IS {
         &self.stream
     }
 
+    /// A mutable reference to the [`Spongos`] state of the current [`Context`].
+    pub(crate) fn spongos_mut(&mut self) -> &mut Spongos<F> {
+        &mut self.spongos
+    }
+
     /// A mutable reference to the read stream of the current [`Context`].
-    pub(crate) fn stream_mut(&mut self) -> &mut IS {
+    pub fn stream_mut(&mut self) -> &mut IS {
         &mut self.stream
     }
 
@@ -62,8 +295,11 @@ impl<IS, F> Context<IS, F> {
     where
         IS: io::IStream,
     {
-        self.stream.try_advance(bytes)?;
+        if let Err(e) = self.stream.try_advance(bytes) {
+            return Err(self.locate_command("drop", e));
+        }
         self.cursor += bytes;
+        self.trace_untouched(Command::Drop, bytes);
         Ok(self)
     }
 
@@ -83,6 +319,24 @@ impl<IS, F> fmt::Debug for Context<IS, F> {
     }
 }
 
+/// A step of the breadcrumb path to the field being unwrapped
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct Breadcrumb {
+    /// Name of the message or field
+    name: &'static str,
+    /// Index of the item, for fields repeated in a list
+    index: Option<usize>,
+}
+
+impl fmt::Display for Breadcrumb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{}[{}]", self.name, index),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
 /// Helper trait for unwrapping (decoding/absorbing) uint8s.
 /// Base trait for decoding binary data from an [IStream](`crate::ddml::io::IStream`)
 ///
@@ -143,16 +397,30 @@ trait Unwrap {
         )?;
         Ok(self)
     }
+
+    /// Decode a [`Varint`] value one byte at a time, until a byte without continuation bit
+    fn unwrap_varint(&mut self, varint: &mut Varint) -> Result<&mut Self> {
+        *varint = Varint::decode(|byte| {
+            let mut typed_byte = Uint8::new(*byte);
+            self.unwrap_u8(&mut typed_byte)?;
+            *byte = typed_byte.inner();
+            Ok(())
+        })?;
+        Ok(self)
+    }
 }
 
 mod absorb;
 mod absorb_external;
 mod commit;
+// The deprecated `Dump` command is kept until it is removed
 #[cfg(feature = "std")]
+#[allow(deprecated)]
 mod dump;
 mod fork;
 mod guard;
 mod join;
+mod limits;
 mod mask;
 mod repeated;
 mod skip;
@@ -160,3 +428,6 @@ mod squeeze;
 
 mod ed25519;
 mod x25519;
+
+use limits::Allocated;
+pub use limits::Limits;
@@ -3,15 +3,16 @@ use crate::{
     error::Result,
 };
 
-/// Repeat a provided function an explicitly dictated number of times.
+/// Repeat a provided function an explicitly dictated number of times, within the
+/// [`Limits`](super::Limits) of the [`Context`].
 impl<C, F, IS> Repeated<usize, C> for Context<IS, F>
 where
     C: for<'b> FnMut(&'b mut Self) -> Result<&'b mut Self>,
 {
     fn repeated(&mut self, n: usize, mut handle: C) -> Result<&mut Self> {
-        for _ in 0..n {
-            handle(self)?;
-        }
-        Ok(self)
+        self.ensure_repeated(n)?.enter()?;
+        let result = (0..n).try_for_each(|_| handle(self).map(|_| ()));
+        self.leave();
+        result.map(|_| self)
     }
 }
@@ -9,7 +9,8 @@ use crate::{
             Skip,
         },
         io,
-        types::{Bytes, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::Command,
+        types::{Bytes, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
@@ -18,12 +19,36 @@ use crate::{
 struct SkipContext<'a, F, IS> {
     /// Internal [`Context`] that [`Skip`] operations will be conducted on
     ctx: &'a mut Context<IS, F>,
+    /// Number of bytes processed by the command so far
+    length: usize,
+    /// Whether all the bytes of the command have been processed
+    completed: bool,
 }
 
 /// Create a new [`SkipContext`] from the provided [`Context`].
 impl<'a, F, IS: io::IStream> SkipContext<'a, F, IS> {
     fn new(ctx: &'a mut Context<IS, F>) -> Self {
-        Self { ctx }
+        Self {
+            ctx,
+            length: 0,
+            completed: false,
+        }
+    }
+
+    /// Marks the command as completed, once all its bytes have been processed
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// Records the command in the [`Context`] trace, as failed unless all its bytes have been processed.
+impl<F, IS> Drop for SkipContext<'_, F, IS> {
+    fn drop(&mut self) {
+        if self.completed {
+            self.ctx.trace_untouched(Command::Skip, self.length);
+        } else {
+            self.ctx.trace_failed(Command::Skip, self.length, None);
+        }
     }
 }
 
@@ -34,44 +59,56 @@ impl<'a, F, IS: io::IStream> Unwrap for SkipContext<'a, F, IS> {
         T: AsMut<[u8]>,
     {
         let bytes = bytes.as_mut();
-        let slice = self.ctx.stream.try_advance(bytes.len())?;
+        match self.ctx.stream.try_advance(bytes.len()) {
+            Ok(slice) => bytes.copy_from_slice(slice),
+            Err(e) => return Err(self.ctx.locate_command("skip", e)),
+        }
         self.ctx.cursor += bytes.len();
-        bytes.copy_from_slice(slice);
+        self.length += bytes.len();
         Ok(self)
     }
 }
 
 impl<'a, F, IS: io::IStream> Skip<&'a mut Uint8> for Context<IS, F> {
     fn skip(&mut self, u: &'a mut Uint8) -> Result<&mut Self> {
-        SkipContext::new(self).unwrap_u8(u)?;
+        SkipContext::new(self).unwrap_u8(u)?.complete();
         Ok(self)
     }
 }
 
 impl<'a, F, IS: io::IStream> Skip<&'a mut Uint16> for Context<IS, F> {
     fn skip(&mut self, u: &'a mut Uint16) -> Result<&mut Self> {
-        SkipContext::new(self).unwrap_u16(u)?;
+        SkipContext::new(self).unwrap_u16(u)?.complete();
         Ok(self)
     }
 }
 
 impl<'a, F, IS: io::IStream> Skip<&'a mut Uint32> for Context<IS, F> {
     fn skip(&mut self, u: &'a mut Uint32) -> Result<&mut Self> {
-        SkipContext::new(self).unwrap_u32(u)?;
+        SkipContext::new(self).unwrap_u32(u)?.complete();
         Ok(self)
     }
 }
 
 impl<'a, F, IS: io::IStream> Skip<&'a mut Uint64> for Context<IS, F> {
     fn skip(&mut self, u: &'a mut Uint64) -> Result<&mut Self> {
-        SkipContext::new(self).unwrap_u64(u)?;
+        SkipContext::new(self).unwrap_u64(u)?.complete();
         Ok(self)
     }
 }
 
 impl<'a, F, IS: io::IStream> Skip<&'a mut Size> for Context<IS, F> {
     fn skip(&mut self, size: &'a mut Size) -> Result<&mut Self> {
-        SkipContext::new(self).unwrap_size(size)?;
+        SkipContext::new(self).unwrap_size(size)?.complete();
+        Ok(self)
+    }
+}
+
+/// Decodes a LEB128 encoded [`Varint`] wrapper from [`Context`]. Malformed encodings are rejected.
+impl<'a, F, IS: io::IStream> Skip<&'a mut Varint> for Context<IS, F> {
+    fn skip(&mut self, varint: &'a mut Varint) -> Result<&mut Self> {
+        let result = SkipContext::new(self).unwrap_varint(varint).map(|ctx| ctx.complete());
+        result.map_err(|e| self.locate_command("skip", e))?;
         Ok(self)
     }
 }
@@ -82,7 +119,7 @@ where
     IS: io::IStream,
 {
     fn skip(&mut self, nbytes: NBytes<T>) -> Result<&mut Self> {
-        SkipContext::new(self).unwrapn(nbytes)?;
+        SkipContext::new(self).unwrapn(nbytes)?.complete();
         Ok(self)
     }
 }
@@ -91,9 +128,12 @@ impl<'a, F, IS: io::IStream> Skip<Bytes<&'a mut Vec<u8>>> for Context<IS, F> {
     fn skip(&mut self, mut bytes: Bytes<&'a mut Vec<u8>>) -> Result<&mut Self> {
         let mut size = Size::default();
         self.skip(&mut size)?;
-        self.stream.ensure_size(size.inner())?;
+        self.stream
+            .ensure_size(size.inner())
+            .map_err(|e| self.locate_command("skip", e))?;
+        self.allocate(size.inner())?;
         bytes.resize(size.inner());
-        SkipContext::new(self).unwrapn(bytes)?;
+        SkipContext::new(self).unwrapn(bytes)?.complete();
         Ok(self)
     }
 }
@@ -4,6 +4,7 @@ use crate::{
         commands::{unwrap::Context, Squeeze},
         io,
         modifiers::External,
+        trace::Command,
         types::{Mac, NBytes},
     },
     error::{Error::BadMac, Result},
@@ -12,10 +13,17 @@ use crate::{
 /// Squeeze [`Context`] into a [`Mac`] length hash and compare it with hash from stream.
 impl<'a, F: PRP, IS: io::IStream> Squeeze<&'a Mac> for Context<IS, F> {
     fn squeeze(&mut self, val: &'a Mac) -> Result<&mut Self> {
-        if !self.spongos.squeeze_eq(self.stream.try_advance(val.length())?) {
-            return Err(BadMac);
+        let before = self.trace_before();
+        let is_valid = match self.stream.try_advance(val.length()) {
+            Ok(mac) => self.spongos.squeeze_eq(mac),
+            Err(e) => return Err(self.locate_command("squeeze", e)),
+        };
+        if !is_valid {
+            self.trace_failed(Command::Squeeze, val.length(), before);
+            return Err(self.locate_command("squeeze", BadMac));
         }
         self.cursor += val.length();
+        self.trace_command(Command::Squeeze, val.length(), before);
         Ok(self)
     }
 }
@@ -29,8 +37,11 @@ impl<F: PRP, IS: io::IStream> Squeeze<Mac> for Context<IS, F> {
 
 /// Squeeze [`Context`] into an [`NBytes`] length hash.
 impl<'a, F: PRP, T: AsMut<[u8]>, IS> Squeeze<External<&'a mut NBytes<T>>> for Context<IS, F> {
-    fn squeeze(&mut self, val: External<&'a mut NBytes<T>>) -> Result<&mut Self> {
+    fn squeeze(&mut self, mut val: External<&'a mut NBytes<T>>) -> Result<&mut Self> {
+        let before = self.trace_before();
+        let length = AsMut::<[u8]>::as_mut(&mut val).len();
         self.spongos.squeeze_mut(val);
+        self.trace_command(Command::SqueezeExternal, length, before);
         Ok(self)
     }
 }
@@ -8,21 +8,50 @@ use crate::{
             Absorb,
         },
         io,
-        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::{Command, Digest},
+        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
 
 /// A helper struct wrapper for performing [`Absorb`] operations with
-struct AbsorbContext<'a, F, OS> {
+struct AbsorbContext<'a, F: PRP, OS: io::OStream> {
     /// Internal [`Context`] that [`Absorb`] operations will be conducted on
     ctx: &'a mut Context<OS, F>,
+    /// Number of bytes processed by the command so far
+    length: usize,
+    /// Digest of the spongos state before the command, if the context is traced
+    before: Option<Digest>,
+    /// Whether all the bytes of the command have been processed
+    completed: bool,
 }
 
 /// Create a new [`AbsorbContext`] from the provided [`Context`].
-impl<'a, F, OS> AbsorbContext<'a, F, OS> {
+impl<'a, F: PRP, OS: io::OStream> AbsorbContext<'a, F, OS> {
     fn new(ctx: &'a mut Context<OS, F>) -> Self {
-        Self { ctx }
+        let before = ctx.trace_before();
+        Self {
+            ctx,
+            length: 0,
+            before,
+            completed: false,
+        }
+    }
+
+    /// Marks the command as completed, once all its bytes have been processed
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// Records the command in the [`Context`] trace, as failed unless all its bytes have been processed.
+impl<F: PRP, OS: io::OStream> Drop for AbsorbContext<'_, F, OS> {
+    fn drop(&mut self) {
+        if self.completed {
+            self.ctx.trace_command(Command::Absorb, self.length, self.before);
+        } else {
+            self.ctx.trace_failed(Command::Absorb, self.length, self.before);
+        }
     }
 }
 
@@ -36,6 +65,7 @@ impl<'a, F: PRP, OS: io::OStream> Wrap for AbsorbContext<'a, F, OS> {
         let bytes = bytes.as_ref();
         self.ctx.spongos.absorb(bytes);
         self.ctx.stream.try_advance(bytes.len())?.copy_from_slice(bytes);
+        self.length += bytes.len();
         Ok(self)
     }
 }
@@ -43,7 +73,7 @@ impl<'a, F: PRP, OS: io::OStream> Wrap for AbsorbContext<'a, F, OS> {
 /// Absorbs a single byte encoded [`Uint8`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Absorb<Uint8> for Context<OS, F> {
     fn absorb(&mut self, u: Uint8) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrap_u8(u)?;
+        AbsorbContext::new(self).wrap_u8(u)?.complete();
         Ok(self)
     }
 }
@@ -51,7 +81,7 @@ impl<F: PRP, OS: io::OStream> Absorb<Uint8> for Context<OS, F> {
 /// Absorbs a two byte encoded [`Uint16`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Absorb<Uint16> for Context<OS, F> {
     fn absorb(&mut self, u: Uint16) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrap_u16(u)?;
+        AbsorbContext::new(self).wrap_u16(u)?.complete();
         Ok(self)
     }
 }
@@ -59,7 +89,7 @@ impl<F: PRP, OS: io::OStream> Absorb<Uint16> for Context<OS, F> {
 /// Absorbs a four byte encoded [`Uint32`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Absorb<Uint32> for Context<OS, F> {
     fn absorb(&mut self, u: Uint32) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrap_u32(u)?;
+        AbsorbContext::new(self).wrap_u32(u)?.complete();
         Ok(self)
     }
 }
@@ -67,7 +97,7 @@ impl<F: PRP, OS: io::OStream> Absorb<Uint32> for Context<OS, F> {
 /// Absorbs an eight byte encoded [`Uint64`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Absorb<Uint64> for Context<OS, F> {
     fn absorb(&mut self, u: Uint64) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrap_u64(u)?;
+        AbsorbContext::new(self).wrap_u64(u)?.complete();
         Ok(self)
     }
 }
@@ -75,7 +105,15 @@ impl<F: PRP, OS: io::OStream> Absorb<Uint64> for Context<OS, F> {
 /// Absorbs an `n` byte encoded [`Size`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Absorb<Size> for Context<OS, F> {
     fn absorb(&mut self, size: Size) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrap_size(size)?;
+        AbsorbContext::new(self).wrap_size(size)?.complete();
+        Ok(self)
+    }
+}
+
+/// Absorbs a LEB128 encoded [`Varint`] wrapper into [`Context`].
+impl<F: PRP, OS: io::OStream> Absorb<Varint> for Context<OS, F> {
+    fn absorb(&mut self, varint: Varint) -> Result<&mut Self> {
+        AbsorbContext::new(self).wrap_varint(varint)?.complete();
         Ok(self)
     }
 }
@@ -89,7 +127,7 @@ where
     OS: io::OStream,
 {
     fn absorb(&mut self, nbytes: NBytes<T>) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrapn(nbytes)?;
+        AbsorbContext::new(self).wrapn(nbytes)?.complete();
         Ok(self)
     }
 }
@@ -102,7 +140,7 @@ where
 {
     fn absorb(&mut self, bytes: Bytes<T>) -> Result<&mut Self> {
         self.absorb(Size::new(bytes.len()))?;
-        AbsorbContext::new(self).wrapn(bytes)?;
+        AbsorbContext::new(self).wrapn(bytes)?.complete();
         Ok(self)
     }
 }
@@ -110,7 +148,7 @@ where
 /// Absorbs an Ed25519 public key into [`Context`].
 impl<'a, F: PRP, OS: io::OStream> Absorb<&'a ed25519::PublicKey> for Context<OS, F> {
     fn absorb(&mut self, public_key: &'a ed25519::PublicKey) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrapn(public_key)?;
+        AbsorbContext::new(self).wrapn(public_key)?.complete();
         Ok(self)
     }
 }
@@ -118,7 +156,7 @@ impl<'a, F: PRP, OS: io::OStream> Absorb<&'a ed25519::PublicKey> for Context<OS,
 /// Absorbs an X25519 public key into [`Context`].
 impl<'a, F: PRP, OS: io::OStream> Absorb<&'a x25519::PublicKey> for Context<OS, F> {
     fn absorb(&mut self, public_key: &'a x25519::PublicKey) -> Result<&mut Self> {
-        AbsorbContext::new(self).wrapn(public_key)?;
+        AbsorbContext::new(self).wrapn(public_key)?.complete();
         Ok(self)
     }
 }
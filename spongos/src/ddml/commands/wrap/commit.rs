@@ -1,13 +1,18 @@
 use crate::{
     core::prp::PRP,
-    ddml::commands::{wrap::Context, Commit},
+    ddml::{
+        commands::{wrap::Context, Commit},
+        trace::Command,
+    },
     error::Result,
 };
 
 /// Commit [`Spongos`](`crate::core::spongos::Spongos`) state.
 impl<F: PRP, OS> Commit for Context<OS, F> {
     fn commit(&mut self) -> Result<&mut Self> {
+        let before = self.trace_before();
         self.spongos.commit();
+        self.trace_command(Command::Commit, 0, before);
         Ok(self)
     }
 }
@@ -5,6 +5,7 @@ use crate::{
         commands::{wrap::Context, Ed25519},
         io,
         modifiers::External,
+        trace::Command,
         types::NBytes,
     },
     error::Result,
@@ -18,6 +19,7 @@ impl<F, OS: io::OStream> Ed25519<&ed25519::SecretKey, External<&NBytes<[u8; 64]>
         self.stream
             .try_advance(ed25519::SIGNATURE_LENGTH)?
             .copy_from_slice(&signature.to_bytes());
+        self.trace_untouched(Command::Ed25519, ed25519::SIGNATURE_LENGTH);
         Ok(self)
     }
 }
@@ -1,4 +1,7 @@
-use crate::ddml::commands::{wrap::Context, Fork};
+use crate::ddml::{
+    commands::{wrap::Context, Fork},
+    trace::Command,
+};
 
 /// Copy context for looped [`Context`] encryption operations
 impl<'a, F, OS> Fork<'a> for Context<OS, F>
@@ -9,6 +12,11 @@ where
     type Forked = Context<&'a mut OS, F>;
     fn fork(&'a mut self) -> Context<&'a mut OS, F> {
         let fork = self.spongos.fork();
-        Context::new_with_spongos(self.stream_mut(), fork)
+        let trace = self.trace.clone();
+        self.trace_untouched(Command::Fork, 0);
+        let mut forked = Context::new_with_spongos(self.stream_mut(), fork);
+        // The commands of the fork are recorded along with those of the context
+        forked.trace = trace;
+        forked
     }
 }
@@ -8,21 +8,50 @@ use crate::{
             Mask,
         },
         io,
-        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::{Command, Digest},
+        types::{Bytes, Maybe, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
 
 /// A helper struct wrapper for performing [`Mask`] operations with
-struct MaskContext<'a, F, OS> {
+struct MaskContext<'a, F: PRP, OS: io::OStream> {
     /// Internal [`Context`] that [`Mask`] operations will be conducted on
     ctx: &'a mut Context<OS, F>,
+    /// Number of bytes processed by the command so far
+    length: usize,
+    /// Digest of the spongos state before the command, if the context is traced
+    before: Option<Digest>,
+    /// Whether all the bytes of the command have been processed
+    completed: bool,
 }
 
 /// Create a new [`MaskContext`] from the provided [`Context`].
-impl<'a, F, OS> MaskContext<'a, F, OS> {
+impl<'a, F: PRP, OS: io::OStream> MaskContext<'a, F, OS> {
     fn new(ctx: &'a mut Context<OS, F>) -> Self {
-        Self { ctx }
+        let before = ctx.trace_before();
+        Self {
+            ctx,
+            length: 0,
+            before,
+            completed: false,
+        }
+    }
+
+    /// Marks the command as completed, once all its bytes have been processed
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// Records the command in the [`Context`] trace, as failed unless all its bytes have been processed.
+impl<F: PRP, OS: io::OStream> Drop for MaskContext<'_, F, OS> {
+    fn drop(&mut self) {
+        if self.completed {
+            self.ctx.trace_command(Command::Mask, self.length, self.before);
+        } else {
+            self.ctx.trace_failed(Command::Mask, self.length, self.before);
+        }
     }
 }
 
@@ -36,6 +65,7 @@ impl<'a, F: PRP, OS: io::OStream> Wrap for MaskContext<'a, F, OS> {
         let bytes = bytes.as_ref();
         let mut slice = self.ctx.stream.try_advance(bytes.len())?;
         self.ctx.spongos.encrypt_mut(bytes, &mut slice)?;
+        self.length += bytes.len();
         Ok(self)
     }
 }
@@ -43,7 +73,7 @@ impl<'a, F: PRP, OS: io::OStream> Wrap for MaskContext<'a, F, OS> {
 /// Encrypts a single byte encoded [`Uint8`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Mask<Uint8> for Context<OS, F> {
     fn mask(&mut self, u: Uint8) -> Result<&mut Self> {
-        MaskContext::new(self).wrap_u8(u)?;
+        MaskContext::new(self).wrap_u8(u)?.complete();
         Ok(self)
     }
 }
@@ -51,7 +81,7 @@ impl<F: PRP, OS: io::OStream> Mask<Uint8> for Context<OS, F> {
 /// Encrypts a two byte encoded [`Uint16`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Mask<Uint16> for Context<OS, F> {
     fn mask(&mut self, u: Uint16) -> Result<&mut Self> {
-        MaskContext::new(self).wrap_u16(u)?;
+        MaskContext::new(self).wrap_u16(u)?.complete();
         Ok(self)
     }
 }
@@ -59,7 +89,7 @@ impl<F: PRP, OS: io::OStream> Mask<Uint16> for Context<OS, F> {
 /// Encrypts a four byte encoded [`Uint32`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Mask<Uint32> for Context<OS, F> {
     fn mask(&mut self, u: Uint32) -> Result<&mut Self> {
-        MaskContext::new(self).wrap_u32(u)?;
+        MaskContext::new(self).wrap_u32(u)?.complete();
         Ok(self)
     }
 }
@@ -67,7 +97,7 @@ impl<F: PRP, OS: io::OStream> Mask<Uint32> for Context<OS, F> {
 /// Encrypts an eight byte encoded [`Uint64`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Mask<Uint64> for Context<OS, F> {
     fn mask(&mut self, u: Uint64) -> Result<&mut Self> {
-        MaskContext::new(self).wrap_u64(u)?;
+        MaskContext::new(self).wrap_u64(u)?.complete();
         Ok(self)
     }
 }
@@ -75,7 +105,15 @@ impl<F: PRP, OS: io::OStream> Mask<Uint64> for Context<OS, F> {
 /// Encrypts an `n` byte encoded [`Size`] wrapper into [`Context`].
 impl<F: PRP, OS: io::OStream> Mask<Size> for Context<OS, F> {
     fn mask(&mut self, size: Size) -> Result<&mut Self> {
-        MaskContext::new(self).wrap_size(size)?;
+        MaskContext::new(self).wrap_size(size)?.complete();
+        Ok(self)
+    }
+}
+
+/// Encrypts a LEB128 encoded [`Varint`] wrapper into [`Context`].
+impl<F: PRP, OS: io::OStream> Mask<Varint> for Context<OS, F> {
+    fn mask(&mut self, varint: Varint) -> Result<&mut Self> {
+        MaskContext::new(self).wrap_varint(varint)?.complete();
         Ok(self)
     }
 }
@@ -84,7 +122,7 @@ impl<F: PRP, OS: io::OStream> Mask<Size> for Context<OS, F> {
 /// `NByte<bytes[n]>` is fixed-size and is encoded with `n` bytes.
 impl<F: PRP, T: AsRef<[u8]>, OS: io::OStream> Mask<NBytes<T>> for Context<OS, F> {
     fn mask(&mut self, bytes: NBytes<T>) -> Result<&mut Self> {
-        MaskContext::new(self).wrapn(bytes)?;
+        MaskContext::new(self).wrapn(bytes)?.complete();
         Ok(self)
     }
 }
@@ -97,7 +135,7 @@ where
 {
     fn mask(&mut self, bytes: Bytes<T>) -> Result<&mut Self> {
         self.mask(Size::new(bytes.len()))?;
-        MaskContext::new(self).wrapn(bytes)?;
+        MaskContext::new(self).wrapn(bytes)?.complete();
         Ok(self)
     }
 }
@@ -105,7 +143,7 @@ where
 /// Encrypts an Ed25519 public key into [`Context`].
 impl<'a, F: PRP, OS: io::OStream> Mask<&'a x25519::PublicKey> for Context<OS, F> {
     fn mask(&mut self, public_key: &'a x25519::PublicKey) -> Result<&mut Self> {
-        MaskContext::new(self).wrapn(public_key)?;
+        MaskContext::new(self).wrapn(public_key)?.complete();
         Ok(self)
     }
 }
@@ -113,7 +151,7 @@ impl<'a, F: PRP, OS: io::OStream> Mask<&'a x25519::PublicKey> for Context<OS, F>
 /// Encrypts an X25519 public key into [`Context`].
 impl<'a, F: PRP, OS: io::OStream> Mask<&'a ed25519::PublicKey> for Context<OS, F> {
     fn mask(&mut self, public_key: &'a ed25519::PublicKey) -> Result<&mut Self> {
-        MaskContext::new(self).wrapn(public_key)?;
+        MaskContext::new(self).wrapn(public_key)?.complete();
         Ok(self)
     }
 }
@@ -125,7 +163,10 @@ where
     OS: io::OStream,
 {
     fn mask(&mut self, spongos: &Spongos<F>) -> Result<&mut Self> {
-        MaskContext::new(self).wrapn(spongos.outer())?.wrapn(spongos.inner())?;
+        MaskContext::new(self)
+            .wrapn(spongos.outer())?
+            .wrapn(spongos.inner())?
+            .complete();
         Ok(self)
     }
 }
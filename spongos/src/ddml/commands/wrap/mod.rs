@@ -5,7 +5,10 @@ use crate::{
         prp::{keccak::KeccakF1600, PRP},
         spongos::Spongos,
     },
-    ddml::types::{Size, Uint16, Uint32, Uint64, Uint8},
+    ddml::{
+        trace::{self, Command, Digest, Trace},
+        types::{Size, Uint16, Uint32, Uint64, Uint8, Varint},
+    },
     error::Result,
 };
 
@@ -15,6 +18,8 @@ pub struct Context<OS, F = KeccakF1600> {
     spongos: Spongos<F>,
     /// The writing stream bytes will be wrapped into
     stream: OS,
+    /// The [`Trace`] the commands are recorded into, if any
+    trace: Option<Trace>,
 }
 
 /// Context for wrapping bytes.
@@ -27,13 +32,74 @@ impl<OS, F> Context<OS, F> {
         Self {
             spongos: Spongos::<F>::init(),
             stream,
+            trace: None,
         }
     }
 
     /// Creates a new [`Context`] from a provided write stream and [`Spongos`] state. Used for
     /// forking `DDML` operations. This allows for a copy of an existing [`Context`] to be used.
     pub(crate) fn new_with_spongos(stream: OS, spongos: Spongos<F>) -> Self {
-        Self { spongos, stream }
+        Self {
+            spongos,
+            stream,
+            trace: None,
+        }
+    }
+
+    /// Records the commands processed by the [`Context`], and the forks of it, into a [`Trace`].
+    ///
+    /// # Arguments
+    /// * `trace`: The trace to record the commands into
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// The [`Trace`] the commands processed by the [`Context`] are recorded into, if any.
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Digest of the [`Spongos`] state before a command, if the commands are traced.
+    pub(crate) fn trace_before(&self) -> Option<Digest>
+    where
+        F: PRP,
+    {
+        self.trace.as_ref().map(|_| trace::digest(&self.spongos))
+    }
+
+    /// Records a command processing the [`Spongos`] state, if the commands are traced.
+    pub(crate) fn trace_command(&self, command: Command, length: usize, before: Option<Digest>)
+    where
+        F: PRP,
+    {
+        if let Some(trace) = &self.trace {
+            trace.record(command, length, before, Some(trace::digest(&self.spongos)), false);
+        }
+    }
+
+    /// Records a command leaving the [`Spongos`] state untouched, if the commands are traced.
+    pub(crate) fn trace_untouched(&self, command: Command, length: usize) {
+        if let Some(trace) = &self.trace {
+            trace.record(command, length, None, None, false);
+        }
+    }
+
+    /// Records a command that failed before completing, if the commands are traced.
+    pub(crate) fn trace_failed(&self, command: Command, length: usize, before: Option<Digest>) {
+        if let Some(trace) = &self.trace {
+            trace.record(command, length, before, None, true);
+        }
+    }
+
+    /// Absorbs external bytes into the [`Spongos`] state without advancing the stream.
+    pub(crate) fn absorb_external<T: AsRef<[u8]>>(&mut self, bytes: T)
+    where
+        F: PRP,
+    {
+        let before = self.trace_before();
+        self.spongos.absorb(bytes.as_ref());
+        self.trace_command(Command::AbsorbExternal, bytes.as_ref().len(), before);
     }
 
     /// The write stream of the current [`Context`].
@@ -41,8 +107,13 @@ impl<OS, F> Context<OS, F> {
         &self.stream
     }
 
+    /// A mutable reference to the [`Spongos`] state of the current [`Context`].
+    pub(crate) fn spongos_mut(&mut self) -> &mut Spongos<F> {
+        &mut self.spongos
+    }
+
     /// A mutable reference to the write stream of the current [`Context`].
-    pub(crate) fn stream_mut(&mut self) -> &mut OS {
+    pub fn stream_mut(&mut self) -> &mut OS {
         &mut self.stream
     }
 
@@ -93,12 +164,22 @@ trait Wrap {
         })?;
         Ok(self)
     }
+    /// Encode a [`Varint`] value one byte at a time, 7 bits per byte
+    fn wrap_varint(&mut self, varint: Varint) -> Result<&mut Self> {
+        varint.encode(|byte| {
+            self.wrap_u8(Uint8::new(byte))?;
+            Ok(())
+        })?;
+        Ok(self)
+    }
 }
 
 mod absorb;
 mod absorb_external;
 mod commit;
+// The deprecated `Dump` command is kept until it is removed
 #[cfg(feature = "std")]
+#[allow(deprecated)]
 mod dump;
 mod fork;
 mod guard;
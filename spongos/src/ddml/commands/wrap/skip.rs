@@ -5,7 +5,8 @@ use crate::{
             Skip,
         },
         io,
-        types::{Bytes, NBytes, Size, Uint16, Uint32, Uint64, Uint8},
+        trace::Command,
+        types::{Bytes, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
     },
     error::Result,
 };
@@ -14,12 +15,36 @@ use crate::{
 struct SkipContext<'a, F, OS> {
     /// Internal [`Context`] that [`Skip`] operations will be conducted on
     ctx: &'a mut Context<OS, F>,
+    /// Number of bytes processed by the command so far
+    length: usize,
+    /// Whether all the bytes of the command have been processed
+    completed: bool,
 }
 
 /// Create a new [`SkipContext`] from the provided [`Context`].
 impl<'a, F, OS> SkipContext<'a, F, OS> {
     fn new(ctx: &'a mut Context<OS, F>) -> Self {
-        Self { ctx }
+        Self {
+            ctx,
+            length: 0,
+            completed: false,
+        }
+    }
+
+    /// Marks the command as completed, once all its bytes have been processed
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+/// Records the command in the [`Context`] trace, as failed unless all its bytes have been processed.
+impl<F, OS> Drop for SkipContext<'_, F, OS> {
+    fn drop(&mut self) {
+        if self.completed {
+            self.ctx.trace_untouched(Command::Skip, self.length);
+        } else {
+            self.ctx.trace_failed(Command::Skip, self.length, None);
+        }
     }
 }
 
@@ -31,6 +56,7 @@ impl<'a, F, OS: io::OStream> Wrap for SkipContext<'a, F, OS> {
     {
         let bytes = bytes.as_ref();
         self.ctx.stream.try_advance(bytes.len())?.copy_from_slice(bytes);
+        self.length += bytes.len();
         Ok(self)
     }
 }
@@ -39,7 +65,7 @@ impl<'a, F, OS: io::OStream> Wrap for SkipContext<'a, F, OS> {
 /// All Uint8 values are encoded with 1 byte
 impl<F, OS: io::OStream> Skip<Uint8> for Context<OS, F> {
     fn skip(&mut self, u: Uint8) -> Result<&mut Self> {
-        SkipContext::new(self).wrap_u8(u)?;
+        SkipContext::new(self).wrap_u8(u)?.complete();
         Ok(self)
     }
 }
@@ -48,7 +74,7 @@ impl<F, OS: io::OStream> Skip<Uint8> for Context<OS, F> {
 /// All Uint16 values are encoded with 2 bytes
 impl<F, OS: io::OStream> Skip<Uint16> for Context<OS, F> {
     fn skip(&mut self, u: Uint16) -> Result<&mut Self> {
-        SkipContext::new(self).wrap_u16(u)?;
+        SkipContext::new(self).wrap_u16(u)?.complete();
         Ok(self)
     }
 }
@@ -57,7 +83,7 @@ impl<F, OS: io::OStream> Skip<Uint16> for Context<OS, F> {
 /// All Uint32 values are encoded with 4 bytes
 impl<F, OS: io::OStream> Skip<Uint32> for Context<OS, F> {
     fn skip(&mut self, u: Uint32) -> Result<&mut Self> {
-        SkipContext::new(self).wrap_u32(u)?;
+        SkipContext::new(self).wrap_u32(u)?.complete();
         Ok(self)
     }
 }
@@ -66,7 +92,7 @@ impl<F, OS: io::OStream> Skip<Uint32> for Context<OS, F> {
 /// All Uint64 values are encoded with 8 bytes
 impl<F, OS: io::OStream> Skip<Uint64> for Context<OS, F> {
     fn skip(&mut self, u: Uint64) -> Result<&mut Self> {
-        SkipContext::new(self).wrap_u64(u)?;
+        SkipContext::new(self).wrap_u64(u)?.complete();
         Ok(self)
     }
 }
@@ -74,7 +100,15 @@ impl<F, OS: io::OStream> Skip<Uint64> for Context<OS, F> {
 /// Encodes an `n` byte encoded [`Size`] wrapper into [`Context`].
 impl<F, OS: io::OStream> Skip<Size> for Context<OS, F> {
     fn skip(&mut self, size: Size) -> Result<&mut Self> {
-        SkipContext::new(self).wrap_size(size)?;
+        SkipContext::new(self).wrap_size(size)?.complete();
+        Ok(self)
+    }
+}
+
+/// Encodes a LEB128 encoded [`Varint`] wrapper into [`Context`].
+impl<F, OS: io::OStream> Skip<Varint> for Context<OS, F> {
+    fn skip(&mut self, varint: Varint) -> Result<&mut Self> {
+        SkipContext::new(self).wrap_varint(varint)?.complete();
         Ok(self)
     }
 }
@@ -83,7 +117,7 @@ impl<F, OS: io::OStream> Skip<Size> for Context<OS, F> {
 /// `NByte<bytes[n]>` is fixed-size and is encoded with `n` bytes.
 impl<F, T: AsRef<[u8]>, OS: io::OStream> Skip<NBytes<T>> for Context<OS, F> {
     fn skip(&mut self, bytes: NBytes<T>) -> Result<&mut Self> {
-        SkipContext::new(self).wrapn(bytes)?;
+        SkipContext::new(self).wrapn(bytes)?.complete();
         Ok(self)
     }
 }
@@ -96,7 +130,7 @@ where
 {
     fn skip(&mut self, bytes: Bytes<T>) -> Result<&mut Self> {
         self.skip(Size::new(bytes.len()))?;
-        SkipContext::new(self).wrapn(bytes)?;
+        SkipContext::new(self).wrapn(bytes)?.complete();
         Ok(self)
     }
 }
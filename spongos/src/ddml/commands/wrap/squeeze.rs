@@ -4,6 +4,7 @@ use crate::{
         commands::{wrap::Context, Squeeze},
         io,
         modifiers::External,
+        trace::Command,
         types::{Mac, NBytes},
     },
     error::Result,
@@ -12,7 +13,9 @@ use crate::{
 /// Squeeze [`Context`] into a [`Mac`] length hash, using allocated space in context byte stream.
 impl<'a, F: PRP, OS: io::OStream> Squeeze<&'a Mac> for Context<OS, F> {
     fn squeeze(&mut self, mac: &'a Mac) -> Result<&mut Self> {
+        let before = self.trace_before();
         self.spongos.squeeze_mut(&mut self.stream.try_advance(mac.length())?);
+        self.trace_command(Command::Squeeze, mac.length(), before);
         Ok(self)
     }
 }
@@ -26,8 +29,11 @@ impl<F: PRP, OS: io::OStream> Squeeze<Mac> for Context<OS, F> {
 
 /// Squeeze [`Context`] into an [`NBytes`] length hash.
 impl<'a, F: PRP, T: AsMut<[u8]>, OS> Squeeze<External<&'a mut NBytes<T>>> for Context<OS, F> {
-    fn squeeze(&mut self, external_nbytes: External<&'a mut NBytes<T>>) -> Result<&mut Self> {
+    fn squeeze(&mut self, mut external_nbytes: External<&'a mut NBytes<T>>) -> Result<&mut Self> {
+        let before = self.trace_before();
+        let length = AsMut::<[u8]>::as_mut(&mut external_nbytes).len();
         self.spongos.squeeze_mut(external_nbytes);
+        self.trace_command(Command::SqueezeExternal, length, before);
         Ok(self)
     }
 }
@@ -1,4 +1,4 @@
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use core::ops::{Deref, DerefMut};
 
 use crate::error::{
@@ -6,6 +6,18 @@ use crate::error::{
     Result,
 };
 
+/// Streams over `std::io` readers and writers
+#[cfg(feature = "std")]
+mod std_io;
+/// Streams over asynchronous readers and writers
+#[cfg(feature = "async-io")]
+mod async_io;
+
+#[cfg(feature = "async-io")]
+pub use async_io::{AsyncReader, AsyncWriter};
+#[cfg(feature = "std")]
+pub use std_io::{Reader, Writer};
+
 /// Write
 pub trait OStream {
     /// Try put n bytes into the stream, returning a slice to the buffer.
@@ -27,6 +39,12 @@ pub trait IStream {
     fn dump(&self) -> String;
 }
 
+/// Read, giving mutable access to the bytes read so that they can be decoded in place
+pub trait IStreamMut: IStream {
+    /// Try get n bytes from the stream, returning a mutable slice to the buffer.
+    fn try_advance_mut(&mut self, n: usize) -> Result<&mut [u8]>;
+}
+
 /// Implementing the OStream trait for a mutable slice of bytes.
 impl OStream for &mut [u8] {
     /// `try_advance` takes a mutable reference to a `StreamAllocator` and tries to advance the
@@ -58,6 +76,31 @@ impl OStream for &mut [u8] {
     }
 }
 
+/// Implementing the OStream trait for a growable vector of bytes, so that messages can be wrapped
+/// without computing their size beforehand.
+impl OStream for Vec<u8> {
+    /// `try_advance` grows the vector by a given number of zeroed bytes, which never fails.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the stream by.
+    ///
+    /// Returns:
+    /// A mutable slice of the appended bytes.
+    fn try_advance(&mut self, n: usize) -> Result<&mut [u8]> {
+        let start = self.len();
+        self.resize(start + n, 0);
+        Ok(&mut self[start..])
+    }
+
+    /// Returns a hexadecimal string representation of the bytes written so far.
+    ///
+    /// Returns:
+    /// A String
+    fn dump(&self) -> String {
+        hex::encode(self)
+    }
+}
+
 /// Implementation of `OStream` for a mutable reference implementing `OStream`.
 impl<T> OStream for &mut T
 where
@@ -167,3 +210,77 @@ where
         self.deref().dump()
     }
 }
+
+/// Implementing the IStream trait for a mutable slice of bytes, so that the bytes can be decoded in
+/// place.
+impl IStream for &mut [u8] {
+    /// Ensure the input stream size is equal to or less than the number of bytes intended for
+    /// allocation;
+    ///
+    /// # Arguments
+    /// * `n`: the number of bytes we want to allocate
+    ///
+    /// Returns:
+    /// Ok if size does not exceed allocation, Error if it does
+    fn ensure_size(&self, n: usize) -> Result<()> {
+        match n <= self.len() {
+            true => Ok(()),
+            false => Err(StreamAllocationExceededIn(n, self.len())),
+        }
+    }
+
+    /// Advances the stream by `n` bytes, returning the head of the split as an immutable slice.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the cursor by.
+    ///
+    /// Returns:
+    /// A slice of the bytes in the buffer.
+    fn try_advance(&mut self, n: usize) -> Result<&[u8]> {
+        self.try_advance_mut(n).map(|head| &*head)
+    }
+
+    /// Returns a hexadecimal string representation of the bytes in the slice.
+    ///
+    /// Returns:
+    /// A String
+    fn dump(&self) -> String {
+        hex::encode(self)
+    }
+}
+
+/// Implementing the IStreamMut trait for a mutable slice of bytes.
+impl IStreamMut for &mut [u8] {
+    /// `try_advance_mut` calls `ensure_size` to make sure there are enough bytes in the buffer, then
+    /// splits the stream at the advancement value, returning the head of that split and replacing
+    /// self with the remainder of the slice.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the cursor by.
+    ///
+    /// Returns:
+    /// A mutable slice of the bytes in the buffer.
+    fn try_advance_mut(&mut self, n: usize) -> Result<&mut [u8]> {
+        self.ensure_size(n)?;
+        let (head, tail) = core::mem::take(self).split_at_mut(n);
+        *self = tail;
+        Ok(head)
+    }
+}
+
+/// Implementation of `IStreamMut` for a mutable reference implementing `IStreamMut`.
+impl<T> IStreamMut for &mut T
+where
+    T: IStreamMut,
+{
+    /// Advances the referenced stream by `n` bytes.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the cursor by.
+    ///
+    /// Returns:
+    /// A mutable slice of the bytes in the buffer.
+    fn try_advance_mut(&mut self, n: usize) -> Result<&mut [u8]> {
+        self.deref_mut().try_advance_mut(n)
+    }
+}
@@ -0,0 +1,195 @@
+// Rust
+use alloc::{string::String, vec::Vec};
+
+// 3rd-party
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+// Local
+use crate::{
+    ddml::io::{IStream, OStream},
+    error::{Error::StreamAllocationExceededIn, Result},
+};
+
+/// Output stream over an [`AsyncWrite`] implementation.
+///
+/// The writer is buffered rather than incremental: `DDML` commands are synchronous, so the bytes
+/// they produce are kept in memory until [`AsyncWriter::flush`] writes them to the inner writer.
+/// Flushing is only possible between the commands or contents wrapped by the caller, so a single
+/// content wrapped at once, such as a whole message or a user backup, is buffered entirely.
+/// [`AsyncWriter::finish`] writes what remains once the wrapping is complete.
+#[derive(Debug)]
+pub struct AsyncWriter<W> {
+    /// The writer the stream bytes are written to
+    inner: W,
+    /// The bytes produced since the last flush
+    buffer: Vec<u8>,
+    /// The number of bytes written so far
+    written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWriter<W> {
+    /// Creates a new [`AsyncWriter`] stream over the provided [`AsyncWrite`] implementation.
+    ///
+    /// # Arguments
+    /// * `inner`: The writer the stream bytes will be written to
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Returns the number of bytes written to the stream so far, including those not yet flushed
+    pub fn position(&self) -> usize {
+        self.written + self.buffer.len()
+    }
+
+    /// Writes the bytes produced since the last flush to the inner writer.
+    pub async fn flush(&mut self) -> Result<()> {
+        self.inner
+            .write_all(&self.buffer)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to write to the output stream"))?;
+        self.written += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Writes the remaining bytes and flushes the inner writer. Must be called once the wrapping is
+    /// complete.
+    pub async fn finish(&mut self) -> Result<()> {
+        self.flush().await?;
+        self.inner
+            .flush()
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to flush the output stream"))?;
+        Ok(())
+    }
+
+    /// Consumes the [`AsyncWriter`], returning the inner writer. Bytes not flushed are lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> OStream for AsyncWriter<W> {
+    /// Appends `n` zeroed bytes to the buffer to be written.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the stream by.
+    ///
+    /// Returns:
+    /// A mutable slice of the appended bytes.
+    fn try_advance(&mut self, n: usize) -> Result<&mut [u8]> {
+        self.buffer.try_advance(n)
+    }
+
+    /// Returns a hexadecimal string representation of the bytes not yet written.
+    ///
+    /// Returns:
+    /// A String
+    fn dump(&self) -> String {
+        hex::encode(&self.buffer)
+    }
+}
+
+/// Input stream over an [`AsyncRead`] implementation.
+///
+/// The reader is buffered rather than incremental: `DDML` commands are synchronous, so the bytes
+/// they consume must be read beforehand with [`AsyncReader::fill`]. The lengths of the variable
+/// sized fields are encoded within the stream itself, so the number of bytes to fill is in practice
+/// only known for a whole message, which ends up held in memory. Commands advancing past the bytes
+/// filled fail as if the stream had ended.
+#[derive(Debug)]
+pub struct AsyncReader<R> {
+    /// The reader the stream bytes are read from
+    inner: R,
+    /// The bytes read and not yet consumed, starting at `pos`
+    buffer: Vec<u8>,
+    /// The read position within `buffer`
+    pos: usize,
+    /// The number of bytes consumed so far
+    read: usize,
+}
+
+impl<R: AsyncRead + Unpin> AsyncReader<R> {
+    /// Creates a new [`AsyncReader`] stream over the provided [`AsyncRead`] implementation.
+    ///
+    /// # Arguments
+    /// * `inner`: The reader the stream bytes will be read from
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            pos: 0,
+            read: 0,
+        }
+    }
+
+    /// Returns the number of bytes consumed from the stream so far
+    pub fn position(&self) -> usize {
+        self.read
+    }
+
+    /// Reads `n` more bytes from the inner reader for the next commands to consume, producing an
+    /// error if the reader ends before.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to read
+    pub async fn fill(&mut self, n: usize) -> Result<()> {
+        self.buffer.drain(..self.pos);
+        self.pos = 0;
+        let expected = self.buffer.len() + n;
+        (&mut self.inner)
+            .take(n as u64)
+            .read_to_end(&mut self.buffer)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("failed to read from the input stream"))?;
+        match self.buffer.len() == expected {
+            true => Ok(()),
+            false => Err(StreamAllocationExceededIn(expected, self.buffer.len())),
+        }
+    }
+
+    /// Consumes the [`AsyncReader`], returning the inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> IStream for AsyncReader<R> {
+    /// Ensure the bytes filled and not yet consumed are enough for the advancement.
+    ///
+    /// # Arguments
+    /// * `n`: the number of bytes we want to allocate
+    ///
+    /// Returns:
+    /// Ok if size does not exceed the bytes filled, Error if it does
+    fn ensure_size(&self, n: usize) -> Result<()> {
+        (&self.buffer[self.pos..]).ensure_size(n)
+    }
+
+    /// Consumes `n` of the bytes filled, producing an error if not enough were filled.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the cursor by.
+    ///
+    /// Returns:
+    /// A slice of the bytes consumed.
+    fn try_advance(&mut self, n: usize) -> Result<&[u8]> {
+        self.ensure_size(n)?;
+        let start = self.pos;
+        self.pos += n;
+        self.read += n;
+        Ok(&self.buffer[start..self.pos])
+    }
+
+    /// Returns a hexadecimal string representation of the bytes filled and not yet consumed.
+    ///
+    /// Returns:
+    /// A String
+    fn dump(&self) -> String {
+        hex::encode(&self.buffer[self.pos..])
+    }
+}
@@ -0,0 +1,252 @@
+// Rust
+use alloc::{string::String, vec::Vec};
+use std::io::{Read, Write};
+
+// Local
+use crate::{
+    ddml::io::{IStream, OStream},
+    error::{Error::StreamAllocationExceededIn, Result},
+};
+
+/// Output stream over a [`Write`] implementation, so that `DDML` commands are written as they are
+/// processed instead of wrapping the whole message in memory.
+///
+/// The bytes handed out to a command are kept until the next command advances the stream, and only
+/// then written. [`Writer::finish`] must therefore be called once the wrapping is complete, to
+/// write the bytes of the last command.
+#[derive(Debug)]
+pub struct Writer<W> {
+    /// The writer the stream bytes are written to
+    inner: W,
+    /// The bytes handed out to the last command, not yet written
+    pending: Vec<u8>,
+    /// The number of bytes written so far
+    written: usize,
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new [`Writer`] stream over the provided [`Write`] implementation.
+    ///
+    /// # Arguments
+    /// * `inner`: The writer the stream bytes will be written to
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Returns the number of bytes written to the stream so far, including those not yet flushed
+    pub fn position(&self) -> usize {
+        self.written + self.pending.len()
+    }
+
+    /// Writes the bytes of the last command to the inner writer, without flushing it.
+    fn write_pending(&mut self) -> Result<()> {
+        self.inner
+            .write_all(&self.pending)
+            .map_err(|e| anyhow::Error::new(e).context("failed to write to the output stream"))?;
+        self.written += self.pending.len();
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Writes the bytes of the last command and flushes the inner writer. Must be called once the
+    /// wrapping is complete.
+    pub fn finish(&mut self) -> Result<()> {
+        self.write_pending()?;
+        self.inner
+            .flush()
+            .map_err(|e| anyhow::Error::new(e).context("failed to flush the output stream"))?;
+        Ok(())
+    }
+
+    /// Consumes the [`Writer`], returning the inner writer. Bytes not written with
+    /// [`Writer::finish`] are lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> OStream for Writer<W> {
+    /// Writes the bytes handed out to the previous command, and hands out a zeroed chunk of `n`
+    /// bytes for the current one.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the stream by.
+    ///
+    /// Returns:
+    /// A mutable slice of `n` bytes to be written.
+    fn try_advance(&mut self, n: usize) -> Result<&mut [u8]> {
+        self.write_pending()?;
+        self.pending.resize(n, 0);
+        Ok(&mut self.pending)
+    }
+
+    /// Returns a hexadecimal string representation of the bytes not yet written.
+    ///
+    /// Returns:
+    /// A String
+    fn dump(&self) -> String {
+        hex::encode(&self.pending)
+    }
+}
+
+/// Input stream over a [`Read`] implementation, so that `DDML` commands are read as they are
+/// processed instead of loading the whole message in memory.
+///
+/// The remaining length of a reader is not known in advance, so [`IStream::ensure_size`] cannot
+/// reject a stream that is too short. It is only detected when the missing bytes are read.
+///
+/// A corrupt size is therefore not caught before the bytes it covers are allocated: the
+/// [`unwrap::Context`](crate::ddml::commands::unwrap::Context) reading from a [`Reader`] must be given
+/// [`Limits`](crate::ddml::commands::unwrap::Limits) bounding its allocations, as its default limits
+/// are unbounded.
+#[derive(Debug)]
+pub struct Reader<R> {
+    /// The reader the stream bytes are read from
+    inner: R,
+    /// The bytes read for the last command
+    chunk: Vec<u8>,
+    /// The number of bytes read so far
+    read: usize,
+}
+
+impl<R: Read> Reader<R> {
+    /// Creates a new [`Reader`] stream over the provided [`Read`] implementation.
+    ///
+    /// # Arguments
+    /// * `inner`: The reader the stream bytes will be read from
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            chunk: Vec::new(),
+            read: 0,
+        }
+    }
+
+    /// Returns the number of bytes read from the stream so far
+    pub fn position(&self) -> usize {
+        self.read
+    }
+
+    /// Consumes the [`Reader`], returning the inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> IStream for Reader<R> {
+    /// The size of a reader is not known in advance, so no check can be performed. The allocations
+    /// must be bounded by the limits of the context instead.
+    ///
+    /// # Arguments
+    /// * `n`: the number of bytes we want to allocate
+    ///
+    /// Returns:
+    /// Ok
+    fn ensure_size(&self, _n: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Reads the next `n` bytes from the inner reader, producing an error if it ends before.
+    ///
+    /// # Arguments
+    /// * `n`: The number of bytes to advance the cursor by.
+    ///
+    /// Returns:
+    /// A slice of the bytes read.
+    fn try_advance(&mut self, n: usize) -> Result<&[u8]> {
+        self.chunk.clear();
+        // Read through `take` so that a corrupt length does not allocate more than what is available
+        let available = (&mut self.inner)
+            .take(n as u64)
+            .read_to_end(&mut self.chunk)
+            .map_err(|e| anyhow::Error::new(e).context("failed to read from the input stream"))?;
+        self.read += available;
+        match available == n {
+            true => Ok(&self.chunk),
+            false => Err(StreamAllocationExceededIn(n, available)),
+        }
+    }
+
+    /// Returns a hexadecimal string representation of the bytes read for the last command.
+    ///
+    /// Returns:
+    /// A String
+    fn dump(&self) -> String {
+        hex::encode(&self.chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use std::io::Cursor;
+
+    use crate::{
+        ddml::{
+            commands::{
+                unwrap::{self, Limits},
+                wrap, Absorb, Commit, Mask, Squeeze,
+            },
+            types::{Bytes, Mac, Size},
+        },
+        error::{Error, Result},
+        KeccakF1600,
+    };
+
+    use super::{Reader, Writer};
+
+    #[test]
+    fn streams_match_in_memory_buffers() -> Result<()> {
+        let payload = vec![7; 10_000];
+
+        let mut buf = Vec::new();
+        let mut ctx = wrap::Context::new(&mut buf);
+        ctx.absorb(Size::new(3))?
+            .mask(Bytes::new(&payload))?
+            .commit()?
+            .squeeze(&Mac::new(32))?;
+        let spongos = ctx.finalize();
+
+        let mut written = Vec::new();
+        let mut ctx = wrap::Context::new(Writer::new(&mut written));
+        ctx.absorb(Size::new(3))?
+            .mask(Bytes::new(&payload))?
+            .commit()?
+            .squeeze(&Mac::new(32))?;
+        assert_eq!(ctx.stream().position(), buf.len());
+        ctx.stream_mut().finish()?;
+        assert_eq!(ctx.finalize(), spongos);
+        assert_eq!(written, buf);
+
+        let (mut size, mut unwrapped) = (Size::default(), Vec::new());
+        let mut ctx = unwrap::Context::new(Reader::new(Cursor::new(&buf)));
+        ctx.absorb(&mut size)?
+            .mask(Bytes::new(&mut unwrapped))?
+            .commit()?
+            .squeeze(&Mac::new(32))?;
+        assert_eq!((size, unwrapped), (Size::new(3), payload));
+        assert_eq!(ctx.finalize().0, spongos);
+
+        let mut ctx = unwrap::Context::new(Reader::new(Cursor::new(&buf[..100])));
+        assert!(ctx.absorb(&mut size)?.mask(Bytes::new(&mut unwrapped)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn readers_allocate_within_the_limits_of_their_context() -> Result<()> {
+        let mut buf = Vec::new();
+        wrap::Context::<_, KeccakF1600>::new(&mut buf).absorb(Size::new(1 << 40))?;
+
+        let mut unwrapped = Vec::new();
+        let mut ctx = unwrap::Context::<_, KeccakF1600>::new(Reader::new(Cursor::new(&buf)))
+            .with_limits(Limits::new().with_max_allocation(1024));
+        let error = ctx.absorb(Bytes::new(&mut unwrapped)).unwrap_err();
+        assert!(matches!(error.unlocated(), Error::LimitExceeded("allocation", ..)));
+        assert!(unwrapped.is_empty());
+        Ok(())
+    }
+}
@@ -12,3 +12,9 @@ pub mod io;
 
 /// DDML specific types.
 pub mod types;
+
+/// Structured trace of the DDML commands processed by the contexts.
+pub mod trace;
+
+/// Interpreter of DDML schemas, processing messages described in DDML text at runtime.
+pub mod schema;
@@ -0,0 +1,1278 @@
+use alloc::{borrow::ToOwned, collections::BTreeMap, format, string::String, vec, vec::Vec};
+use core::{convert::TryInto, fmt, str::SplitWhitespace};
+
+use crypto::{keys::x25519, signatures::ed25519};
+
+use crate::{
+    core::{
+        prp::{keccak::KeccakF1600, PRP},
+        spongos::Spongos,
+    },
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Ed25519, Join, Mask, Skip, Squeeze, X25519},
+        io,
+        modifiers::External,
+        types::{Bytes, Mac, NBytes, Size, Uint16, Uint32, Uint64, Uint8, Varint},
+    },
+    error::{Error, Result},
+};
+
+/// A message schema written in DDML, interpreted at runtime to size, wrap and unwrap the messages
+/// it describes, with the values of their fields held in a [`Record`].
+///
+/// The schema is a `message <Name> { ... }` block with one statement per line, `//` comments
+/// being ignored. The following statements are supported:
+/// * `absorb`, `mask` and `skip` of a field of type `u8` (or `byte`), `u16`, `u32`, `u64`, `uint`
+///   (a [`Size`]), `varint` (a LEB128 [`Varint`]) or `bytes` (size-prefixed [`Bytes`]), declared
+///   as `<type> <name>`. `u8 size(<name>)` declares a [`Size`], and `u8 <name>[<length>]` a byte
+///   array whose length is either a number or the name of a previous field.
+/// * `absorb external` of a field of fixed size, absorbed without being encoded.
+/// * `squeeze u8 <name>[<length>]`, encoding a [`Mac`], and `squeeze external u8
+///   <name>[<length>]`, storing the squeezed bytes in the [`Record`].
+/// * `commit;`, `join(<name>);` with a [`Value::Spongos`], and `fork;`, processing the rest of
+///   its block on a fork of the spongos state.
+/// * `repeated(<count>):` followed by an indented block, processed once for each [`Record`] of the
+///   [`Value::List`] held under the name of the count.
+///
+/// * `ed25519(<hash>) u8 <name>[64];`, signing the 64 bytes previously squeezed into the external
+///   field `<hash>`. The [`Record`] holds the key of the signer under `<name>_key`: the 32 bytes of
+///   its ed25519 secret key when sizing and wrapping, and of its public key when unwrapping, in
+///   which case the signature is verified.
+/// * `x25519(<key>) u8 <name>[32];`, encrypting the 32 bytes of `<name>` with a key exchanged
+///   with the x25519 key held under `<key>`: the public key of the recipient when sizing and
+///   wrapping, and its secret key when unwrapping. The ephemeral key of the exchange is random, so
+///   messages wrapped with it differ from one wrap to another, and wrapping it needs the `osrng`
+///   feature.
+///
+/// ```
+/// use spongos::{
+///     ddml::{
+///         commands::{sizeof, unwrap, wrap},
+///         schema::{Record, Schema, Value},
+///     },
+///     KeccakF1600,
+/// };
+///
+/// # fn main() -> spongos::error::Result<()> {
+/// let schema = Schema::parse(
+///     "message Reading {
+///         absorb      u8      version;
+///         mask        bytes   payload;
+///         commit;
+///         squeeze     u8      mac[32];
+///     }",
+/// )?;
+/// let mut reading = Record::new()
+///     .with("version", Value::Uint8(1))
+///     .with("payload", Value::Bytes(b"21.5".to_vec()));
+///
+/// let mut ctx = sizeof::Context::new();
+/// schema.sizeof(&mut ctx, &reading)?;
+/// let mut buf = vec![0; ctx.finalize()];
+/// schema.wrap(&mut wrap::Context::<_, KeccakF1600>::new(&mut buf[..]), &mut reading)?;
+///
+/// let mut unwrapped = Record::new();
+/// schema.unwrap(&mut unwrap::Context::<_, KeccakF1600>::new(&buf[..]), &mut unwrapped)?;
+/// assert_eq!(unwrapped, reading);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Schema {
+    /// The name of the message
+    name: String,
+    /// The statements of the message
+    statements: Vec<Statement>,
+}
+
+impl Schema {
+    /// Parses the DDML schema of a message
+    ///
+    /// # Arguments
+    /// * `text`: The `message <Name> { ... }` block describing the message
+    pub fn parse(text: &str) -> Result<Self> {
+        let lines: Vec<Line> = text
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| Line::new(i + 1, line))
+            .collect();
+        let (header, lines) = lines
+            .split_first()
+            .ok_or_else(|| Error::Schema(1, "the schema is empty".to_owned()))?;
+        let name = header
+            .text
+            .strip_prefix("message ")
+            .and_then(|name| name.strip_suffix('{'))
+            .ok_or_else(|| header.error("expected `message <Name> {`"))?;
+        let name = header.identifier(name.trim())?;
+        let body = match lines.split_last() {
+            Some((footer, body)) if footer.text == "}" => body,
+            _ => return Err(header.error(format!("message `{}` is not closed", name))),
+        };
+        let statements = parse_block(body, &mut 0, 0)?;
+        Ok(Self { name, statements })
+    }
+
+    /// Returns the name of the message
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds the size of a message to a [`sizeof::Context`]
+    ///
+    /// # Arguments
+    /// * `ctx`: The [`sizeof::Context`] measuring the message
+    /// * `record`: The values of the fields of the message
+    pub fn sizeof<'c, F: Clone>(
+        &self,
+        ctx: &'c mut sizeof::Context,
+        record: &Record<F>,
+    ) -> Result<&'c mut sizeof::Context> {
+        // Placeholders of the squeezed bytes are stored in a copy of the record, left as it was
+        run(ctx, &self.statements, &mut record.clone(), None)?;
+        Ok(ctx)
+    }
+
+    /// Wraps a message into a [`wrap::Context`]. The bytes squeezed into external fields are
+    /// stored in the record.
+    ///
+    /// # Arguments
+    /// * `ctx`: The [`wrap::Context`] the message is wrapped into
+    /// * `record`: The values of the fields of the message
+    pub fn wrap<'c, OS, F>(
+        &self,
+        ctx: &'c mut wrap::Context<OS, F>,
+        record: &mut Record<F>,
+    ) -> Result<&'c mut wrap::Context<OS, F>>
+    where
+        F: PRP + Clone,
+        OS: io::OStream,
+    {
+        run(ctx, &self.statements, record, None)?;
+        Ok(ctx)
+    }
+
+    /// Unwraps a message from an [`unwrap::Context`], storing the values of its fields in the
+    /// record. The record must already hold the external fields and the spongos states to join.
+    /// External fields of repeated blocks are held by the records of the [`Value::List`] under
+    /// the name of the count, which must match the unwrapped count.
+    ///
+    /// # Arguments
+    /// * `ctx`: The [`unwrap::Context`] the message is unwrapped from
+    /// * `record`: The values of the fields of the message
+    pub fn unwrap<'c, IS, F>(
+        &self,
+        ctx: &'c mut unwrap::Context<IS, F>,
+        record: &mut Record<F>,
+    ) -> Result<&'c mut unwrap::Context<IS, F>>
+    where
+        F: PRP + Clone,
+        IS: io::IStream,
+    {
+        run(ctx, &self.statements, record, None)?;
+        Ok(ctx)
+    }
+}
+
+/// The values of the fields of a message processed with a [`Schema`], by name
+#[derive(Clone, PartialEq)]
+pub struct Record<F = KeccakF1600> {
+    fields: BTreeMap<String, Value<F>>,
+}
+
+impl<F> Record<F> {
+    /// Creates an empty [`Record`]
+    pub fn new() -> Self {
+        Self {
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the value of a field, returning the [`Record`]
+    ///
+    /// # Arguments
+    /// * `name`: The name of the field
+    /// * `value`: The [`Value`] of the field
+    pub fn with(mut self, name: impl Into<String>, value: Value<F>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets the value of a field, returning its previous value if any
+    ///
+    /// # Arguments
+    /// * `name`: The name of the field
+    /// * `value`: The [`Value`] of the field
+    pub fn insert(&mut self, name: impl Into<String>, value: Value<F>) -> Option<Value<F>> {
+        self.fields.insert(name.into(), value)
+    }
+
+    /// Returns the value of a field
+    ///
+    /// # Arguments
+    /// * `name`: The name of the field
+    pub fn get(&self, name: &str) -> Option<&Value<F>> {
+        self.fields.get(name)
+    }
+
+    /// Returns the names and values of the fields, ordered by name
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &Value<F>)> {
+        self.fields.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Removes a field, returning its value if any
+    ///
+    /// # Arguments
+    /// * `name`: The name of the field
+    pub fn remove(&mut self, name: &str) -> Option<Value<F>> {
+        self.fields.remove(name)
+    }
+
+    /// Stores an unwrapped value, keeping the items of a repeated block already held under the
+    /// name of its count
+    fn fill(&mut self, name: &str, value: Value<F>) -> Result<()> {
+        if let (Some(Value::List(items)), Value::Size(count)) = (self.fields.get(name), &value) {
+            return if items.len() == *count {
+                Ok(())
+            } else {
+                Err(Error::LengthMismatch(items.len(), *count))
+            };
+        }
+        self.fields.insert(name.to_owned(), value);
+        Ok(())
+    }
+}
+
+impl<F> Default for Record<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PRP> fmt::Debug for Record<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(&self.fields).finish()
+    }
+}
+
+/// The value of a field of a [`Record`]
+#[derive(Clone, PartialEq)]
+pub enum Value<F = KeccakF1600> {
+    /// Value of `u8` fields
+    Uint8(u8),
+    /// Value of `u16` fields
+    Uint16(u16),
+    /// Value of `u32` fields
+    Uint32(u32),
+    /// Value of `u64` fields
+    Uint64(u64),
+    /// Value of `uint` and `size()` fields
+    Size(usize),
+    /// Value of `varint` fields
+    Varint(u64),
+    /// Value of `bytes` fields and byte arrays
+    Bytes(Vec<u8>),
+    /// The items of a `repeated` block, held under the name of its count
+    List(Vec<Record<F>>),
+    /// A spongos state to `join`
+    Spongos(Spongos<F>),
+}
+
+impl<F: PRP> fmt::Debug for Value<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Uint8(value) => f.debug_tuple("Uint8").field(value).finish(),
+            Value::Uint16(value) => f.debug_tuple("Uint16").field(value).finish(),
+            Value::Uint32(value) => f.debug_tuple("Uint32").field(value).finish(),
+            Value::Uint64(value) => f.debug_tuple("Uint64").field(value).finish(),
+            Value::Size(value) => f.debug_tuple("Size").field(value).finish(),
+            Value::Varint(value) => f.debug_tuple("Varint").field(value).finish(),
+            Value::Bytes(bytes) => f.debug_tuple("Bytes").field(&hex::encode(bytes)).finish(),
+            Value::List(items) => f.debug_tuple("List").field(items).finish(),
+            Value::Spongos(spongos) => f.debug_tuple("Spongos").field(spongos).finish(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Statement {
+    Field(Field),
+    Squeeze {
+        external: bool,
+        name: String,
+        length: Length,
+    },
+    Commit,
+    Fork,
+    Join(String),
+    Ed25519 {
+        hash: String,
+        signature: String,
+    },
+    X25519 {
+        key: String,
+        name: String,
+    },
+    Repeated {
+        count: String,
+        body: Vec<Statement>,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Field {
+    command: Command,
+    external: bool,
+    ty: Type,
+    name: String,
+    /// Number of the line of the schema declaring the field
+    line: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Command {
+    Absorb,
+    Mask,
+    Skip,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Type {
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    Size,
+    Varint,
+    Bytes,
+    NBytes(Length),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Uint8 => write!(f, "u8"),
+            Type::Uint16 => write!(f, "u16"),
+            Type::Uint32 => write!(f, "u32"),
+            Type::Uint64 => write!(f, "u64"),
+            Type::Size => write!(f, "uint"),
+            Type::Varint => write!(f, "varint"),
+            Type::Bytes => write!(f, "bytes"),
+            Type::NBytes(Length::Fixed(length)) => write!(f, "u8[{}]", length),
+            Type::NBytes(Length::Field(length)) => write!(f, "u8[{}]", length),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Length {
+    Fixed(usize),
+    Field(String),
+}
+
+/// A non-empty line of a schema, stripped of its comment
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn new(number: usize, line: &'a str) -> Option<Self> {
+        let code = line.split("//").next().unwrap_or_default().trim_end();
+        let text = code.trim_start();
+        if text.is_empty() {
+            return None;
+        }
+        Some(Line {
+            number,
+            indent: code.len() - text.len(),
+            text,
+        })
+    }
+
+    fn error(&self, reason: impl Into<String>) -> Error {
+        Error::Schema(self.number, reason.into())
+    }
+
+    fn identifier(&self, name: &str) -> Result<String> {
+        let mut chars = name.chars();
+        let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if valid {
+            Ok(name.to_owned())
+        } else {
+            Err(self.error(format!("`{}` is not a valid name", name)))
+        }
+    }
+
+    fn statement(&self) -> Result<Statement> {
+        let text = self
+            .text
+            .strip_suffix(';')
+            .ok_or_else(|| self.error("expected `;` at the end of the statement"))?;
+        let mut tokens = text.split_whitespace();
+        let command = tokens.next().unwrap_or_default();
+        let statement = match command {
+            "absorb" => return self.field(Command::Absorb, tokens),
+            "mask" => return self.field(Command::Mask, tokens),
+            "skip" => return self.field(Command::Skip, tokens),
+            "squeeze" => return self.squeeze(tokens),
+            "commit" => Statement::Commit,
+            "fork" => Statement::Fork,
+            _ if command.starts_with("ed25519(") => {
+                let (hash, signature) = self.keyed(command, "ed25519(", tokens, 64)?;
+                return Ok(Statement::Ed25519 { hash, signature });
+            }
+            _ if command.starts_with("x25519(") => {
+                let (key, name) = self.keyed(command, "x25519(", tokens, 32)?;
+                return Ok(Statement::X25519 { key, name });
+            }
+            _ => match command.strip_prefix("join(").and_then(|name| name.strip_suffix(')')) {
+                Some(name) => Statement::Join(self.identifier(name)?),
+                None => return Err(self.error(format!("unknown command `{}`", command))),
+            },
+        };
+        match tokens.next() {
+            None => Ok(statement),
+            Some(token) => Err(self.error(format!("unexpected `{}` after `{}`", token, command))),
+        }
+    }
+
+    fn field(&self, command: Command, tokens: SplitWhitespace) -> Result<Statement> {
+        let (external, name, ty) = self.declaration(tokens)?;
+        if external && command != Command::Absorb {
+            return Err(self.error("only `absorb` and `squeeze` can be external"));
+        }
+        if external && ty == Type::Bytes {
+            return Err(self.error("external fields must have a fixed size"));
+        }
+        Ok(Statement::Field(Field {
+            command,
+            external,
+            ty,
+            name,
+            line: self.number,
+        }))
+    }
+
+    fn squeeze(&self, tokens: SplitWhitespace) -> Result<Statement> {
+        match self.declaration(tokens)? {
+            (external, name, Type::NBytes(length)) => Ok(Statement::Squeeze { external, name, length }),
+            _ => Err(self.error("squeezed fields must be byte arrays")),
+        }
+    }
+
+    /// Parses the `<command>(<argument>) u8 <name>[<length>]` statements using keys, returning the
+    /// argument and the name of the field
+    fn keyed(&self, command: &str, prefix: &str, tokens: SplitWhitespace, length: usize) -> Result<(String, String)> {
+        let argument = command
+            .strip_prefix(prefix)
+            .and_then(|argument| argument.strip_suffix(')'))
+            .ok_or_else(|| self.error(format!("expected `{}<name>)`", prefix)))?;
+        match self.declaration(tokens)? {
+            (false, name, Type::NBytes(Length::Fixed(n))) if n == length => Ok((self.identifier(argument)?, name)),
+            _ => Err(self.error(format!("expected `u8 <name>[{}]` after `{}`", length, command))),
+        }
+    }
+
+    /// Parses a `[external] <type> <declarator>` field declaration
+    fn declaration(&self, tokens: SplitWhitespace) -> Result<(bool, String, Type)> {
+        let tokens: Vec<&str> = tokens.collect();
+        let (external, tokens) = match tokens.split_first() {
+            Some((&"external", tokens)) => (true, tokens),
+            _ => (false, &tokens[..]),
+        };
+        let (ty, declarator) = match tokens {
+            [ty, declarator] => (*ty, *declarator),
+            _ => return Err(self.error("expected `[external] <type> <name>`")),
+        };
+        let byte = matches!(ty, "u8" | "byte");
+        let size = declarator.strip_prefix("size(").and_then(|name| name.strip_suffix(')'));
+        let array = declarator
+            .strip_suffix(']')
+            .and_then(|declarator| declarator.split_once('['));
+        let (name, ty) = match (size, array) {
+            (Some(name), _) if byte => (name, Type::Size),
+            (_, Some((name, length))) if byte => (name, Type::NBytes(self.length(length)?)),
+            (None, None) => (declarator, self.ty(ty)?),
+            _ => return Err(self.error(format!("`{}` cannot be declared as `{}`", ty, declarator))),
+        };
+        Ok((external, self.identifier(name)?, ty))
+    }
+
+    fn ty(&self, ty: &str) -> Result<Type> {
+        match ty {
+            "u8" | "byte" => Ok(Type::Uint8),
+            "u16" => Ok(Type::Uint16),
+            "u32" => Ok(Type::Uint32),
+            "u64" => Ok(Type::Uint64),
+            "uint" => Ok(Type::Size),
+            "varint" => Ok(Type::Varint),
+            "bytes" => Ok(Type::Bytes),
+            _ => Err(self.error(format!("unknown type `{}`", ty))),
+        }
+    }
+
+    fn length(&self, length: &str) -> Result<Length> {
+        match length.parse() {
+            Ok(length) => Ok(Length::Fixed(length)),
+            Err(_) => self.identifier(length).map(Length::Field),
+        }
+    }
+}
+
+/// Parses the statements of a block, made of the lines indented at least by `indent`
+fn parse_block(lines: &[Line], position: &mut usize, indent: usize) -> Result<Vec<Statement>> {
+    let mut statements = Vec::new();
+    while let Some(line) = lines.get(*position).filter(|line| line.indent >= indent) {
+        *position += 1;
+        match line
+            .text
+            .strip_prefix("repeated(")
+            .and_then(|count| count.strip_suffix("):"))
+        {
+            Some(count) => {
+                let count = line.identifier(count)?;
+                let body = parse_block(lines, position, line.indent + 1)?;
+                if body.is_empty() {
+                    return Err(line.error("expected an indented block after `repeated`"));
+                }
+                statements.push(Statement::Repeated { count, body });
+            }
+            None => statements.push(line.statement()?),
+        }
+    }
+    Ok(statements)
+}
+
+/// The value of a field as taken by the commands
+enum Scalar<'a> {
+    Uint8(Uint8),
+    Uint16(Uint16),
+    Uint32(Uint32),
+    Uint64(Uint64),
+    Size(Size),
+    Varint(Varint),
+    Bytes(&'a [u8]),
+    NBytes(&'a [u8]),
+}
+
+/// The record of the block being processed, along with the records of its enclosing blocks
+struct Scope<'a, F> {
+    record: &'a Record<F>,
+    parent: Option<&'a Scope<'a, F>>,
+}
+
+impl<'a, F> Scope<'a, F> {
+    fn lookup(&self, name: &str) -> Option<&'a Value<F>> {
+        self.record
+            .get(name)
+            .or_else(|| self.parent.and_then(|parent| parent.lookup(name)))
+    }
+
+    fn length(&self, ty: &Type) -> Result<Option<usize>> {
+        match ty {
+            Type::NBytes(length) => self.resolve(length).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn resolve(&self, length: &Length) -> Result<usize> {
+        match length {
+            Length::Fixed(length) => Ok(*length),
+            Length::Field(name) => match self.lookup(name) {
+                Some(Value::Uint8(length)) => Ok(usize::from(*length)),
+                Some(Value::Uint16(length)) => Ok(usize::from(*length)),
+                Some(Value::Uint32(length)) => Ok(*length as usize),
+                Some(Value::Uint64(length)) => Ok(*length as usize),
+                Some(Value::Size(length)) => Ok(*length),
+                Some(Value::Varint(length)) => Ok(*length as usize),
+                Some(Value::List(items)) => Ok(items.len()),
+                _ => Err(Error::SchemaField(name.clone(), "expected a length".to_owned())),
+            },
+        }
+    }
+
+    fn scalar(&self, field: &Field, length: Option<usize>) -> Result<Scalar<'a>> {
+        let value = self
+            .lookup(&field.name)
+            .ok_or_else(|| Error::SchemaField(field.name.clone(), "the value is missing".to_owned()))?;
+        match (&field.ty, value) {
+            (Type::Uint8, Value::Uint8(value)) => Ok(Scalar::Uint8(Uint8::new(*value))),
+            (Type::Uint16, Value::Uint16(value)) => Ok(Scalar::Uint16(Uint16::new(*value))),
+            (Type::Uint32, Value::Uint32(value)) => Ok(Scalar::Uint32(Uint32::new(*value))),
+            (Type::Uint64, Value::Uint64(value)) => Ok(Scalar::Uint64(Uint64::new(*value))),
+            (Type::Size, Value::Size(value)) => Ok(Scalar::Size(Size::new(*value))),
+            (Type::Size, Value::List(items)) => Ok(Scalar::Size(Size::new(items.len()))),
+            (Type::Varint, Value::Varint(value)) => Ok(Scalar::Varint(Varint::new(*value))),
+            (Type::Bytes, Value::Bytes(bytes)) => Ok(Scalar::Bytes(bytes)),
+            (Type::NBytes(_), Value::Bytes(bytes)) if length == Some(bytes.len()) => Ok(Scalar::NBytes(bytes)),
+            (Type::NBytes(_), Value::Bytes(bytes)) => {
+                Err(Error::LengthMismatch(length.unwrap_or_default(), bytes.len()))
+            }
+            (ty, _) => Err(Error::SchemaField(
+                field.name.clone(),
+                format!("expected a value of type {}", ty),
+            )),
+        }
+    }
+}
+
+fn spongos_to_join<F: Clone>(name: &str, value: Option<&Value<F>>) -> Result<Spongos<F>> {
+    match value {
+        Some(Value::Spongos(spongos)) => Ok(spongos.clone()),
+        _ => Err(Error::SchemaField(
+            name.to_owned(),
+            "expected a spongos state to join".to_owned(),
+        )),
+    }
+}
+
+/// Takes the bytes of a field holding a key or a squeezed hash
+///
+/// # Arguments
+/// * `name`: The name of the field
+/// * `value`: The value of the field, if any
+fn key_bytes<F, const N: usize>(name: &str, value: Option<&Value<F>>) -> Result<[u8; N]> {
+    match value {
+        Some(Value::Bytes(bytes)) => bytes.as_slice().try_into().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| Error::SchemaField(name.to_owned(), format!("expected {} bytes", N)))
+}
+
+/// Processing of the statements of a [`Schema`] by a context
+trait Driver<F> {
+    /// Whether the values of the fields are taken from the record, rather than from the stream
+    const READS: bool;
+
+    /// Processes a field whose value is known
+    fn input(&mut self, field: &Field, value: Scalar) -> Result<()>;
+
+    /// Processes a field whose value is read from the stream, returning it
+    fn output(&mut self, field: &Field, length: Option<usize>) -> Result<Value<F>>;
+
+    /// Squeezes bytes from the spongos state, returning them if they are external
+    fn squeeze_bytes(&mut self, external: bool, length: usize) -> Result<Option<Vec<u8>>>;
+
+    fn commit_state(&mut self) -> Result<()>;
+
+    fn join_state(&mut self, name: &str, joinee: Option<&Value<F>>) -> Result<()>;
+
+    /// Signs a hash with the secret key of the signer, or verifies its signature with the public
+    /// key of the signer when unwrapping
+    fn sign_hash(&mut self, name: &str, key: [u8; 32], hash: &NBytes<[u8; 64]>) -> Result<()>;
+
+    /// Encrypts a key with a key exchanged with the public key of the recipient, or decrypts it
+    /// into `encryption_key` with the secret key of the recipient when unwrapping
+    fn exchange_key(&mut self, name: &str, key: [u8; 32], encryption_key: &mut [u8; 32]) -> Result<()>;
+
+    /// The spongos state of the context, if it keeps one
+    fn state(&mut self) -> Option<&mut Spongos<F>>;
+
+    /// Ensures a repeated block whose count was read from the stream can be processed `n` times
+    fn repeat(&mut self, _n: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! command {
+    ($ctx:expr, $command:expr, $value:expr) => {
+        match $command {
+            Command::Absorb => {
+                $ctx.absorb($value)?;
+            }
+            Command::Mask => {
+                $ctx.mask($value)?;
+            }
+            Command::Skip => {
+                $ctx.skip($value)?;
+            }
+        }
+    };
+}
+
+macro_rules! encode {
+    ($ctx:expr, $command:expr, $value:expr) => {
+        match $value {
+            Scalar::Uint8(value) => command!($ctx, $command, value),
+            Scalar::Uint16(value) => command!($ctx, $command, value),
+            Scalar::Uint32(value) => command!($ctx, $command, value),
+            Scalar::Uint64(value) => command!($ctx, $command, value),
+            Scalar::Size(value) => command!($ctx, $command, value),
+            Scalar::Varint(value) => command!($ctx, $command, value),
+            Scalar::Bytes(bytes) => command!($ctx, $command, Bytes::new(bytes)),
+            Scalar::NBytes(bytes) => command!($ctx, $command, NBytes::new(bytes)),
+        }
+    };
+}
+
+macro_rules! absorb_external {
+    ($ctx:expr, $value:expr) => {
+        match $value {
+            Scalar::Uint8(value) => $ctx.absorb(External::new(value))?,
+            Scalar::Uint16(value) => $ctx.absorb(External::new(value))?,
+            Scalar::Uint32(value) => $ctx.absorb(External::new(value))?,
+            Scalar::Uint64(value) => $ctx.absorb(External::new(value))?,
+            Scalar::Size(value) => $ctx.absorb(External::new(value))?,
+            Scalar::Varint(value) => $ctx.absorb(External::new(value))?,
+            Scalar::NBytes(bytes) => $ctx.absorb(External::new(&NBytes::new(bytes)))?,
+            Scalar::Bytes(_) => unreachable!("external fields of variable size are rejected by the parser"),
+        }
+    };
+}
+
+macro_rules! unwrap_output {
+    ($ctx:expr, $command:expr, $ty:ty, $variant:ident) => {{
+        let mut value = <$ty>::default();
+        command!($ctx, $command, &mut value);
+        Value::$variant(value.inner())
+    }};
+}
+
+impl<F> Driver<F> for sizeof::Context {
+    const READS: bool = true;
+
+    fn input(&mut self, field: &Field, value: Scalar) -> Result<()> {
+        // External fields take no space in the message
+        if !field.external {
+            encode!(self, field.command, value);
+        }
+        Ok(())
+    }
+
+    fn output(&mut self, field: &Field, _length: Option<usize>) -> Result<Value<F>> {
+        // The values of all the fields are taken from the record when sizing
+        Err(Error::Schema(
+            field.line,
+            format!("`{}` cannot be read from the stream when sizing", field.name),
+        ))
+    }
+
+    fn squeeze_bytes(&mut self, external: bool, length: usize) -> Result<Option<Vec<u8>>> {
+        if external {
+            // Placeholder of the squeezed bytes, for the signatures of the message
+            Ok(Some(vec![0; length]))
+        } else {
+            self.squeeze(Mac::new(length))?;
+            Ok(None)
+        }
+    }
+
+    fn commit_state(&mut self) -> Result<()> {
+        self.commit()?;
+        Ok(())
+    }
+
+    fn join_state(&mut self, _name: &str, _joinee: Option<&Value<F>>) -> Result<()> {
+        // Joining does not change the size of the message
+        Ok(())
+    }
+
+    fn sign_hash(&mut self, _name: &str, key: [u8; 32], hash: &NBytes<[u8; 64]>) -> Result<()> {
+        self.ed25519(&ed25519::SecretKey::from_bytes(key), External::new(hash))?;
+        Ok(())
+    }
+
+    fn exchange_key(&mut self, _name: &str, key: [u8; 32], encryption_key: &mut [u8; 32]) -> Result<()> {
+        self.x25519(&x25519::PublicKey::from(key), NBytes::new(encryption_key))?;
+        Ok(())
+    }
+
+    fn state(&mut self) -> Option<&mut Spongos<F>> {
+        None
+    }
+}
+
+impl<F, OS> Driver<F> for wrap::Context<OS, F>
+where
+    F: PRP + Clone,
+    OS: io::OStream,
+{
+    const READS: bool = true;
+
+    fn input(&mut self, field: &Field, value: Scalar) -> Result<()> {
+        if field.external {
+            absorb_external!(self, value);
+        } else {
+            encode!(self, field.command, value);
+        }
+        Ok(())
+    }
+
+    fn output(&mut self, field: &Field, _length: Option<usize>) -> Result<Value<F>> {
+        // The values of all the fields are taken from the record when wrapping
+        Err(Error::Schema(
+            field.line,
+            format!("`{}` cannot be read from the stream when wrapping", field.name),
+        ))
+    }
+
+    fn squeeze_bytes(&mut self, external: bool, length: usize) -> Result<Option<Vec<u8>>> {
+        if external {
+            let mut bytes = vec![0; length];
+            self.squeeze(External::new(&mut NBytes::new(&mut bytes)))?;
+            Ok(Some(bytes))
+        } else {
+            self.squeeze(Mac::new(length))?;
+            Ok(None)
+        }
+    }
+
+    fn commit_state(&mut self) -> Result<()> {
+        self.commit()?;
+        Ok(())
+    }
+
+    fn join_state(&mut self, name: &str, joinee: Option<&Value<F>>) -> Result<()> {
+        self.join(&mut spongos_to_join(name, joinee)?)?;
+        Ok(())
+    }
+
+    fn sign_hash(&mut self, _name: &str, key: [u8; 32], hash: &NBytes<[u8; 64]>) -> Result<()> {
+        self.ed25519(&ed25519::SecretKey::from_bytes(key), External::new(hash))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "osrng")]
+    fn exchange_key(&mut self, _name: &str, key: [u8; 32], encryption_key: &mut [u8; 32]) -> Result<()> {
+        self.x25519(&x25519::PublicKey::from(key), NBytes::new(encryption_key))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "osrng"))]
+    fn exchange_key(&mut self, name: &str, _key: [u8; 32], _encryption_key: &mut [u8; 32]) -> Result<()> {
+        Err(Error::SchemaField(
+            name.to_owned(),
+            "wrapping an x25519 key exchange needs the `osrng` feature".to_owned(),
+        ))
+    }
+
+    fn state(&mut self) -> Option<&mut Spongos<F>> {
+        Some(self.spongos_mut())
+    }
+}
+
+impl<F, IS> Driver<F> for unwrap::Context<IS, F>
+where
+    F: PRP + Clone,
+    IS: io::IStream,
+{
+    const READS: bool = false;
+
+    fn input(&mut self, _field: &Field, value: Scalar) -> Result<()> {
+        // Only the values of external fields are taken from the record when unwrapping
+        absorb_external!(self, value);
+        Ok(())
+    }
+
+    fn output(&mut self, field: &Field, length: Option<usize>) -> Result<Value<F>> {
+        let value = match &field.ty {
+            Type::Uint8 => unwrap_output!(self, field.command, Uint8, Uint8),
+            Type::Uint16 => unwrap_output!(self, field.command, Uint16, Uint16),
+            Type::Uint32 => unwrap_output!(self, field.command, Uint32, Uint32),
+            Type::Uint64 => unwrap_output!(self, field.command, Uint64, Uint64),
+            Type::Size => unwrap_output!(self, field.command, Size, Size),
+            Type::Varint => unwrap_output!(self, field.command, Varint, Varint),
+            Type::Bytes => {
+                let mut bytes = Vec::new();
+                command!(self, field.command, Bytes::new(&mut bytes));
+                Value::Bytes(bytes)
+            }
+            Type::NBytes(_) => {
+                let length = length.unwrap_or_default();
+                self.allocate(length)?;
+                let mut bytes = vec![0; length];
+                command!(self, field.command, NBytes::new(&mut bytes));
+                Value::Bytes(bytes)
+            }
+        };
+        Ok(value)
+    }
+
+    fn squeeze_bytes(&mut self, external: bool, length: usize) -> Result<Option<Vec<u8>>> {
+        if external {
+            let mut bytes = vec![0; length];
+            self.squeeze(External::new(&mut NBytes::new(&mut bytes)))?;
+            Ok(Some(bytes))
+        } else {
+            self.squeeze(Mac::new(length))?;
+            Ok(None)
+        }
+    }
+
+    fn commit_state(&mut self) -> Result<()> {
+        self.commit()?;
+        Ok(())
+    }
+
+    fn join_state(&mut self, name: &str, joinee: Option<&Value<F>>) -> Result<()> {
+        self.join(&mut spongos_to_join(name, joinee)?)?;
+        Ok(())
+    }
+
+    fn sign_hash(&mut self, name: &str, key: [u8; 32], hash: &NBytes<[u8; 64]>) -> Result<()> {
+        let public_key = ed25519::PublicKey::try_from_bytes(key)
+            .map_err(|_| Error::SchemaField(name.to_owned(), "expected an ed25519 public key".to_owned()))?;
+        self.ed25519(&public_key, External::new(hash))?;
+        Ok(())
+    }
+
+    fn exchange_key(&mut self, _name: &str, key: [u8; 32], encryption_key: &mut [u8; 32]) -> Result<()> {
+        self.x25519(&x25519::SecretKey::from_bytes(key), NBytes::new(encryption_key))?;
+        Ok(())
+    }
+
+    fn state(&mut self) -> Option<&mut Spongos<F>> {
+        Some(self.spongos_mut())
+    }
+
+    fn repeat(&mut self, n: usize) -> Result<()> {
+        self.ensure_repeated(n)?;
+        Ok(())
+    }
+}
+
+/// Processes the statements of a block with a [`Driver`]
+///
+/// # Arguments
+/// * `driver`: The context processing the statements
+/// * `statements`: The statements of the block
+/// * `record`: The values of the fields of the block
+/// * `parent`: The records of the enclosing blocks
+fn run<F, D>(driver: &mut D, statements: &[Statement], record: &mut Record<F>, parent: Option<&Scope<F>>) -> Result<()>
+where
+    F: Clone,
+    D: Driver<F>,
+{
+    for (i, statement) in statements.iter().enumerate() {
+        match statement {
+            Statement::Field(field) => {
+                let scope = Scope {
+                    record: &*record,
+                    parent,
+                };
+                let length = scope.length(&field.ty)?;
+                if D::READS || field.external {
+                    driver.input(field, scope.scalar(field, length)?)?;
+                } else {
+                    let value = driver.output(field, length)?;
+                    record.fill(&field.name, value)?;
+                }
+            }
+            Statement::Squeeze { external, name, length } => {
+                let length = Scope {
+                    record: &*record,
+                    parent,
+                }
+                .resolve(length)?;
+                if let Some(bytes) = driver.squeeze_bytes(*external, length)? {
+                    record.insert(name.as_str(), Value::Bytes(bytes));
+                }
+            }
+            Statement::Commit => driver.commit_state()?,
+            Statement::Join(name) => {
+                let joinee = Scope {
+                    record: &*record,
+                    parent,
+                }
+                .lookup(name);
+                driver.join_state(name, joinee)?;
+            }
+            Statement::Ed25519 { hash, signature } => {
+                let scope = Scope {
+                    record: &*record,
+                    parent,
+                };
+                let key_name = format!("{}_key", signature);
+                let key = key_bytes(&key_name, scope.lookup(&key_name))?;
+                let hash = key_bytes(hash, scope.lookup(hash))?;
+                driver.sign_hash(&key_name, key, &NBytes::new(hash))?;
+            }
+            Statement::X25519 { key, name } => {
+                let scope = Scope {
+                    record: &*record,
+                    parent,
+                };
+                let exchange_key = key_bytes(key, scope.lookup(key))?;
+                let mut encryption_key = if D::READS {
+                    key_bytes(name, scope.lookup(name))?
+                } else {
+                    [0; 32]
+                };
+                driver.exchange_key(key, exchange_key, &mut encryption_key)?;
+                if !D::READS {
+                    record.fill(name, Value::Bytes(encryption_key.to_vec()))?;
+                }
+            }
+            Statement::Fork => {
+                // The rest of the block is processed on a copy of the spongos state, which is
+                // discarded afterwards
+                let original = driver.state().cloned();
+                run(driver, &statements[i + 1..], record, parent)?;
+                if let (Some(spongos), Some(original)) = (driver.state(), original) {
+                    *spongos = original;
+                }
+                return Ok(());
+            }
+            Statement::Repeated { count, body } => {
+                let mut items = match record.remove(count) {
+                    Some(Value::List(items)) => items,
+                    Some(Value::Size(n)) if !D::READS => {
+                        driver.repeat(n)?;
+                        (0..n).map(|_| Record::new()).collect()
+                    }
+                    _ => {
+                        return Err(Error::SchemaField(
+                            count.clone(),
+                            "expected the list of the items of the repeated block".to_owned(),
+                        ));
+                    }
+                };
+                let scope = Scope {
+                    record: &*record,
+                    parent,
+                };
+                for item in &mut items {
+                    run(driver, body, item, Some(&scope))?;
+                }
+                record.insert(count.as_str(), Value::List(items));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        core::{prp::keccak::KeccakF1600, spongos::Spongos},
+        ddml::{
+            commands::{sizeof, unwrap, wrap, Absorb, Commit, Fork, Join, Mask, Skip, Squeeze},
+            modifiers::External,
+            types::{Bytes, Mac, NBytes, Size, Uint16, Varint},
+        },
+        error::{Error, Result},
+    };
+
+    use super::{Record, Schema, Value};
+
+    const SAMPLE: &str = "
+        // A sample message exercising every supported statement
+        message Sample {
+            join(spongos);
+            absorb              u8      nonce[4];
+            absorb              varint  sequence;
+            absorb              u8      size(n_members);
+            repeated(n_members):
+              fork;
+              mask              u8      id[2];
+              absorb external   u8      psk[2];     // one per member
+              commit;
+              mask              u16     permission;
+            mask                uint    payload_size;
+            mask                u8      payload[payload_size];
+            skip                bytes   note;
+            commit;
+            squeeze external    u8      hash[8];
+            squeeze             u8      mac[8];
+        }
+    ";
+
+    fn member(id: [u8; 2], psk: [u8; 2], permission: u16) -> Record {
+        Record::new()
+            .with("id", Value::Bytes(id.to_vec()))
+            .with("psk", Value::Bytes(psk.to_vec()))
+            .with("permission", Value::Uint16(permission))
+    }
+
+    #[test]
+    fn schemas_wrap_like_the_commands_they_describe() -> Result<()> {
+        let schema = Schema::parse(SAMPLE)?;
+        assert_eq!(schema.name(), "Sample");
+        let mut linked = Spongos::<KeccakF1600>::init();
+        linked.absorb(b"linked message");
+        let members = [([1, 2], [3, 4], 5), ([6, 7], [8, 9], 10)];
+        let mut record = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("nonce", Value::Bytes(vec![11; 4]))
+            .with("sequence", Value::Varint(300))
+            .with(
+                "n_members",
+                Value::List(members.iter().map(|&(id, psk, p)| member(id, psk, p)).collect()),
+            )
+            .with("payload_size", Value::Size(3))
+            .with("payload", Value::Bytes(b"abc".to_vec()))
+            .with("note", Value::Bytes(b"skipped".to_vec()));
+
+        let mut ctx = sizeof::Context::new();
+        schema.sizeof(&mut ctx, &record)?;
+        let size = ctx.finalize();
+
+        let mut buf = vec![0; size];
+        let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+        schema.wrap(&mut ctx, &mut record)?;
+        assert!(ctx.stream().is_empty());
+        let spongos = ctx.finalize();
+
+        let mut expected = vec![0; size];
+        let mut hash = NBytes::new([0; 8]);
+        let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut expected[..]);
+        ctx.join(&mut linked.clone())?
+            .absorb(NBytes::new([11; 4]))?
+            .absorb(Varint::new(300))?
+            .absorb(Size::new(members.len()))?;
+        for (id, psk, permission) in members {
+            ctx.fork()
+                .mask(NBytes::new(id))?
+                .absorb(External::new(&NBytes::new(psk)))?
+                .commit()?
+                .mask(Uint16::new(permission))?;
+        }
+        ctx.mask(Size::new(3))?
+            .mask(NBytes::new(b"abc"))?
+            .skip(Bytes::new(b"skipped"))?
+            .commit()?
+            .squeeze(External::new(&mut hash))?
+            .squeeze(Mac::new(8))?;
+        assert_eq!(buf, expected);
+        assert_eq!(spongos, ctx.finalize());
+        assert_eq!(record.get("hash"), Some(&Value::Bytes(hash.as_slice().to_vec())));
+
+        // External fields and joined spongos states must be provided to unwrap the message
+        let members: Vec<Record> = members
+            .iter()
+            .map(|&(_, psk, _)| Record::new().with("psk", Value::Bytes(psk.to_vec())))
+            .collect();
+        let mut unwrapped = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("n_members", Value::List(members));
+        let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..]);
+        schema.unwrap(&mut ctx, &mut unwrapped)?;
+        let (unwrap_spongos, read) = ctx.finalize();
+        assert_eq!(read, size);
+        assert_eq!(unwrap_spongos, spongos);
+        assert_eq!(unwrapped, record);
+        Ok(())
+    }
+
+    const SIGNED: &str = "
+        message Signed {
+            x25519(exchange_key)    u8      session_key[32];
+            mask                    bytes   payload;
+            commit;
+            squeeze external        u8      hash[64];
+            ed25519(hash)           u8      signature[64];
+        }
+    ";
+
+    #[cfg(feature = "osrng")]
+    #[test]
+    fn schemas_sign_and_exchange_keys_with_the_keys_of_their_records() -> Result<()> {
+        use crypto::{keys::x25519, signatures::ed25519};
+
+        use crate::ddml::commands::{Ed25519, X25519};
+
+        let schema = Schema::parse(SIGNED)?;
+        let signing_key = ed25519::SecretKey::from_bytes([7; ed25519::SECRET_KEY_LENGTH]);
+        let exchange_key = x25519::SecretKey::from_bytes([9; x25519::SECRET_KEY_LENGTH]);
+        let mut record = Record::<KeccakF1600>::new()
+            .with(
+                "exchange_key",
+                Value::Bytes(exchange_key.public_key().to_bytes().to_vec()),
+            )
+            .with("session_key", Value::Bytes(vec![3; 32]))
+            .with("payload", Value::Bytes(b"signed".to_vec()))
+            .with("signature_key", Value::Bytes(signing_key.to_bytes().to_vec()));
+
+        let mut ctx = sizeof::Context::new();
+        schema.sizeof(&mut ctx, &record)?;
+        let mut buf = vec![0; ctx.finalize()];
+        let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+        schema.wrap(&mut ctx, &mut record)?;
+        assert!(ctx.stream().is_empty());
+
+        // The signature follows the bytes of the key exchange, which are random
+        let signed = buf.len() - ed25519::SIGNATURE_LENGTH;
+        let mut hash = NBytes::new([0; 64]);
+        let mut expected = vec![0; buf.len()];
+        expected[..signed].copy_from_slice(&buf[..signed]);
+        let mut ctx = unwrap::Context::<_, KeccakF1600>::new(&buf[..signed]);
+        ctx.x25519(&exchange_key, NBytes::new([0; 32]))?
+            .mask(Bytes::new(&mut Vec::new()))?
+            .commit()?
+            .squeeze(External::new(&mut hash))?;
+        let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut expected[signed..]);
+        ctx.ed25519(&signing_key, External::new(&hash))?;
+        assert_eq!(buf, expected);
+        assert_eq!(record.get("hash"), Some(&Value::Bytes(hash.as_slice().to_vec())));
+
+        let unwrapping = |public_key: &ed25519::PublicKey| {
+            Record::<KeccakF1600>::new()
+                .with("exchange_key", Value::Bytes(exchange_key.to_bytes().to_vec()))
+                .with("signature_key", Value::Bytes(public_key.to_bytes().to_vec()))
+        };
+        let mut unwrapped = unwrapping(&signing_key.public_key());
+        schema.unwrap(&mut unwrap::Context::new(&buf[..]), &mut unwrapped)?;
+        assert_eq!(unwrapped.get("session_key"), record.get("session_key"));
+        assert_eq!(unwrapped.get("payload"), record.get("payload"));
+        assert_eq!(unwrapped.get("hash"), record.get("hash"));
+
+        let forger = ed25519::SecretKey::from_bytes([8; ed25519::SECRET_KEY_LENGTH]);
+        let mut forged = unwrapping(&forger.public_key());
+        let error = schema
+            .unwrap(&mut unwrap::Context::new(&buf[..]), &mut forged)
+            .unwrap_err();
+        assert!(matches!(error.unlocated(), Error::SignatureMismatch));
+        Ok(())
+    }
+
+    #[test]
+    fn schemas_report_what_they_cannot_process() {
+        let invalid = |body: &str| Schema::parse(&format!("message Invalid {{\n{}\n}}", body));
+        assert!(matches!(
+            invalid("    ed25519(hash) u8 signature[32];"),
+            Err(Error::Schema(2, _))
+        ));
+        assert!(matches!(
+            invalid("    x25519(pub/priv_key) u8 key[32];"),
+            Err(Error::Schema(2, _))
+        ));
+        assert!(matches!(
+            invalid("    mask external u8 key[32];"),
+            Err(Error::Schema(2, _))
+        ));
+        assert!(matches!(
+            invalid("    absorb external bytes payload;"),
+            Err(Error::Schema(2, _))
+        ));
+        assert!(matches!(invalid("    absorb u8 identifier"), Err(Error::Schema(2, _))));
+        assert!(matches!(
+            invalid("    repeated(n):\n    commit;"),
+            Err(Error::Schema(2, _))
+        ));
+        assert!(matches!(Schema::parse("message Open {"), Err(Error::Schema(1, _))));
+
+        let schema = Schema::parse("message Sized {\n  absorb u8 payload[size];\n}").unwrap();
+        let record = Record::<KeccakF1600>::new().with("payload", Value::Bytes(vec![0; 4]));
+        assert!(matches!(
+            schema.sizeof(&mut sizeof::Context::new(), &record),
+            Err(Error::SchemaField(field, _)) if field == "size"
+        ));
+        let record = record.with("size", Value::Uint8(5));
+        assert!(matches!(
+            schema.sizeof(&mut sizeof::Context::new(), &record),
+            Err(Error::LengthMismatch(5, 4))
+        ));
+
+        let schema = Schema::parse("message Unsigned {\n  ed25519(hash) u8 signature[64];\n}").unwrap();
+        let record = Record::<KeccakF1600>::new().with("signature_key", Value::Bytes(vec![7; 32]));
+        assert!(matches!(
+            schema.sizeof(&mut sizeof::Context::new(), &record),
+            Err(Error::SchemaField(field, _)) if field == "hash"
+        ));
+    }
+}
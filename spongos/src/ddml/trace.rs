@@ -0,0 +1,261 @@
+// Rust
+use alloc::{rc::Rc, string::String, vec::Vec};
+use core::{
+    cell::RefCell,
+    fmt::{self, Write},
+    hash::{Hash, Hasher},
+};
+
+// Local
+use crate::core::{prp::PRP, spongos::Spongos};
+
+/// Size of the digests of the spongos state recorded by a [`Trace`]
+pub const DIGEST_SIZE: usize = 16;
+
+/// Digest of the spongos state of a context
+pub type Digest = [u8; DIGEST_SIZE];
+
+/// Computes the [`Digest`] of a spongos state
+pub(crate) fn digest<F: PRP>(spongos: &Spongos<F>) -> Digest {
+    spongos.state_digest()
+}
+
+/// Kind of a `DDML` command recorded in a [`Trace`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Command {
+    /// A field absorbed into the stream
+    Absorb,
+    /// An external field absorbed into the spongos state only
+    AbsorbExternal,
+    /// A field masked into the stream
+    Mask,
+    /// A field encoded into the stream without spongos operations
+    Skip,
+    /// A hash squeezed into the stream
+    Squeeze,
+    /// A hash squeezed from the spongos state only
+    SqueezeExternal,
+    /// A commit of the spongos state
+    Commit,
+    /// A spongos state joined into the context
+    Join,
+    /// A fork of the context
+    Fork,
+    /// An ed25519 signature encoded into the stream
+    Ed25519,
+    /// Bytes of the stream advanced without decoding
+    Drop,
+}
+
+impl Command {
+    /// Returns the name of the command, as used in `DDML`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Absorb => "absorb",
+            Self::AbsorbExternal => "absorb_external",
+            Self::Mask => "mask",
+            Self::Skip => "skip",
+            Self::Squeeze => "squeeze",
+            Self::SqueezeExternal => "squeeze_external",
+            Self::Commit => "commit",
+            Self::Join => "join",
+            Self::Fork => "fork",
+            Self::Ed25519 => "ed25519",
+            Self::Drop => "drop",
+        }
+    }
+
+    /// Whether the command reads or writes its field in the stream
+    pub(crate) fn is_encoded(&self) -> bool {
+        matches!(
+            self,
+            Self::Absorb | Self::Mask | Self::Skip | Self::Squeeze | Self::Ed25519 | Self::Drop
+        )
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A command processed by a context, as recorded in a [`Trace`]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Event {
+    /// The kind of command
+    command: Command,
+    /// Length of the field processed, in bytes
+    length: usize,
+    /// Offset of the field in the stream
+    offset: usize,
+    /// Digest of the spongos state before the command
+    before: Option<Digest>,
+    /// Digest of the spongos state after the command
+    after: Option<Digest>,
+    /// Whether the command failed before completing
+    failed: bool,
+}
+
+impl Event {
+    /// Returns the kind of command
+    pub fn command(&self) -> Command {
+        self.command
+    }
+
+    /// Returns the length of the field processed, in bytes
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns the offset of the field in the stream. Fields not encoded in the stream are located
+    /// at the offset of the next encoded field.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the digest of the spongos state before the command, if the context keeps one
+    pub fn before(&self) -> Option<&Digest> {
+        self.before.as_ref()
+    }
+
+    /// Returns the digest of the spongos state after the command, if the context keeps one
+    pub fn after(&self) -> Option<&Digest> {
+        self.after.as_ref()
+    }
+
+    /// Returns whether the command failed before completing. The length of a failed command is the
+    /// number of bytes it processed before failing, and it carries no digest after it.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+/// The events recorded by a [`Trace`], and the stream offset they have reached
+#[derive(Default)]
+struct Recording {
+    events: Vec<Event>,
+    offset: usize,
+}
+
+/// Structured trace of the `DDML` commands processed by a `wrap` or `unwrap` context.
+///
+/// Each command is recorded as an [`Event`], with the length and stream offset of its field and the
+/// digests of the spongos state around it. Traces of the same message wrapped and unwrapped by
+/// different implementations can be exported with [`Trace::to_json`] and compared command by
+/// command. The commands that leave the spongos state untouched, like `skip` and `ed25519`, carry
+/// no digests. A command that fails, like a `squeeze` of a bad MAC, is recorded as failed.
+///
+/// A [`Trace`] is a handle: clones of it record into the same trace, which is how the forks of a
+/// context are traced along with it. A trace should be attached to a single context.
+#[derive(Clone, Default)]
+pub struct Trace(Rc<RefCell<Recording>>);
+
+impl Trace {
+    /// Creates a new, empty [`Trace`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the events recorded so far
+    pub fn events(&self) -> Vec<Event> {
+        self.0.borrow().events.clone()
+    }
+
+    /// Returns the number of events recorded so far
+    pub fn len(&self) -> usize {
+        self.0.borrow().events.len()
+    }
+
+    /// Returns whether no event has been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().events.is_empty()
+    }
+
+    /// Exports the recorded events as a JSON array of objects, with the digests in hexadecimal and
+    /// `null` when missing.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (i, event) in self.0.borrow().events.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            // Writing to a `String` cannot fail
+            let _ = write!(
+                json,
+                r#"{{"command":"{}","length":{},"offset":{},"before":{},"after":{},"failed":{}}}"#,
+                event.command,
+                event.length,
+                event.offset,
+                JsonDigest(event.before.as_ref()),
+                JsonDigest(event.after.as_ref()),
+                event.failed
+            );
+        }
+        json.push(']');
+        json
+    }
+
+    /// Records a command processed by a context
+    ///
+    /// # Arguments
+    /// * `command`: The kind of command
+    /// * `length`: The length of the field processed, in bytes
+    /// * `before`: Digest of the spongos state before the command
+    /// * `after`: Digest of the spongos state after the command
+    /// * `failed`: Whether the command failed before completing
+    pub(crate) fn record(
+        &self,
+        command: Command,
+        length: usize,
+        before: Option<Digest>,
+        after: Option<Digest>,
+        failed: bool,
+    ) {
+        let mut recording = self.0.borrow_mut();
+        let offset = recording.offset;
+        if command.is_encoded() {
+            recording.offset += length;
+        }
+        recording.events.push(Event {
+            command,
+            length,
+            offset,
+            before,
+            after,
+            failed,
+        });
+    }
+}
+
+impl PartialEq for Trace {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || self.0.borrow().events == other.0.borrow().events
+    }
+}
+
+impl Eq for Trace {}
+
+impl Hash for Trace {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.borrow().events.hash(state);
+    }
+}
+
+impl fmt::Debug for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.borrow().events.iter()).finish()
+    }
+}
+
+/// JSON representation of an optional [`Digest`]
+struct JsonDigest<'a>(Option<&'a Digest>);
+
+impl fmt::Display for JsonDigest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(digest) => write!(f, "\"{}\"", hex::encode(digest)),
+            None => write!(f, "null"),
+        }
+    }
+}
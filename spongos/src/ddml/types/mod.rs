@@ -4,6 +4,7 @@ mod maybe;
 mod nbytes;
 mod size;
 mod uint;
+mod varint;
 
 pub use bytes::Bytes;
 pub use mac::Mac;
@@ -12,3 +13,4 @@ pub use nbytes::NBytes;
 pub use size::Size;
 pub(crate) use uint::{Uint16, Uint32};
 pub use uint::{Uint64, Uint8};
+pub use varint::Varint;
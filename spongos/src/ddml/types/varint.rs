@@ -0,0 +1,100 @@
+use core::fmt;
+
+use crate::error::{Error::MalformedVarint, Result};
+
+/// Maximum number of bytes needed to encode a `u64` as a [`Varint`]
+const MAX_VARINT_BYTES: usize = 10;
+
+/// Variable-length integer wrapper for `DDML` operations, encoded LEB128-style: 7 bits per byte,
+/// least significant group first, with the high bit of each byte set when more bytes follow.
+///
+/// Unlike [`Size`](`crate::ddml::types::Size`), no length byte is encoded, so values below 128 take
+/// a single byte. Only the shortest encoding of a value is accepted when decoding, so that each
+/// value has a single representation absorbed into the spongos state.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash, Debug, Default)]
+pub struct Varint(u64);
+
+impl Varint {
+    /// Create a [`Varint`] wrapper for `DDML` operations
+    ///
+    /// # Arguments
+    /// * `n`: the `u64` to be wrapped.
+    pub fn new(n: u64) -> Self {
+        Self(n)
+    }
+
+    /// Returns the inner `u64`.
+    pub fn inner(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of bytes needed to encode a value of [`Varint`] type.
+    pub(crate) fn num_bytes(self) -> usize {
+        let mut d = 1;
+        let mut n = self.0 >> 7;
+        while n > 0 {
+            n >>= 7;
+            d += 1;
+        }
+        d
+    }
+
+    /// Encodes inner `u64` into a byte array.
+    ///
+    /// # Arguments
+    /// * `codec`: a function for encoding bytes to a [`Spongos`] stream
+    pub(crate) fn encode(&self, mut codec: impl FnMut(u8) -> Result<()>) -> Result<()> {
+        let mut n = self.0;
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                return codec(byte);
+            }
+            codec(byte | 0x80)?;
+        }
+    }
+
+    /// Decodes inner `u64` from a byte array, rejecting encodings that overflow a `u64` or that
+    /// are longer than needed.
+    ///
+    /// # Arguments
+    /// * `codec`: a function for decoding bytes from a [`Spongos`] stream
+    pub(crate) fn decode(mut codec: impl FnMut(&mut u8) -> Result<()>) -> Result<Self> {
+        let mut result = 0u64;
+        for i in 0..MAX_VARINT_BYTES {
+            let mut byte = 0;
+            codec(&mut byte)?;
+            // The last byte can only hold the most significant bit of a `u64`
+            if i == MAX_VARINT_BYTES - 1 && byte > 1 {
+                return Err(MalformedVarint("the value overflows a u64"));
+            }
+            result |= u64::from(byte & 0x7f) << (7 * i);
+            if byte & 0x80 == 0 {
+                if byte == 0 && i > 0 {
+                    return Err(MalformedVarint("the encoding is not the shortest"));
+                }
+                return Ok(Varint(result));
+            }
+        }
+        unreachable!("the last byte of a varint has no continuation bit")
+    }
+}
+
+impl fmt::Display for Varint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Varint({})", self.0)
+    }
+}
+
+impl From<u64> for Varint {
+    fn from(n: u64) -> Self {
+        Self(n)
+    }
+}
+
+impl From<Varint> for u64 {
+    fn from(n: Varint) -> Self {
+        n.inner()
+    }
+}
@@ -1,6 +1,6 @@
 use core::{array::TryFromSliceError, fmt::Debug};
 
-use alloc::string::String;
+use alloc::{boxed::Box, string::String};
 use thiserror_no_std::Error;
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -38,6 +38,23 @@ pub enum Error {
     #[error("Reserved area was not empty: {0}")]
     Reserved(&'static str),
 
+    #[error("Malformed varint: {0}")]
+    MalformedVarint(&'static str),
+
+    #[error("Unwrap limit on the {0} exceeded (limit: {1}, requested: {2})")]
+    LimitExceeded(&'static str, usize, usize),
+
+    #[error("{2} (at `{0}`, byte offset {1})")]
+    Located(String, usize, Box<Error>),
+
+    //////////
+    // DDML Schema
+    //////////
+    #[error("Invalid DDML schema at line {0}: {1}")]
+    Schema(usize, String),
+    #[error("Field '{0}' of the DDML schema cannot be processed: {1}")]
+    SchemaField(String, String),
+
     //////////
     // DDML IO
     //////////
@@ -53,6 +70,25 @@ pub enum Error {
     External(anyhow::Error),
 }
 
+impl Error {
+    /// Returns the breadcrumb path and the byte offset in the stream where unwrapping failed, if
+    /// they were recorded.
+    pub fn location(&self) -> Option<(&str, usize)> {
+        match self {
+            Self::Located(path, offset, _) => Some((path, *offset)),
+            _ => None,
+        }
+    }
+
+    /// Returns the error that caused the failure, without the location it occurred at.
+    pub fn unlocated(&self) -> &Self {
+        match self {
+            Self::Located(_, _, error) => error,
+            error => error,
+        }
+    }
+}
+
 impl From<TryFromSliceError> for Error {
     fn from(error: TryFromSliceError) -> Self {
         Self::SliceMismatch(error)
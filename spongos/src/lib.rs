@@ -12,7 +12,7 @@
 #[cfg_attr(all(test, not(feature = "std")), macro_use)]
 extern crate alloc;
 
-// Spongos requires the feature "std" for the Dump DDML command
+// Spongos requires the feature "std" for the deprecated Dump command and the streams over `std::io` readers and writers
 #[cfg(feature = "std")]
 #[macro_use]
 extern crate std;
@@ -27,6 +27,10 @@ pub mod ddml;
 
 pub use crate::core::{
     prng::SpongosRng,
-    prp::{keccak::KeccakF1600, PRP},
+    prp::{
+        keccak::{KeccakF1600, KeccakP1600R12},
+        xoodoo::Xoodoo,
+        PRP,
+    },
     spongos::Spongos,
 };
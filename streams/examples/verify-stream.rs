@@ -0,0 +1,62 @@
+//! Verifies the integrity of a stream published on the Tangle.
+//!
+//! ```text
+//! cargo run --example verify-stream -- <announcement address> [<reader seed>]
+//! ```
+//!
+//! The node is read from the `URL` environment variable (or `.env` file), and defaults to
+//! <https://chrysalis-nodes.iota.org>. Without a reader seed, only the public part of the stream
+//! is verified. The pre shared key of the reader can be provided as a seed with the `PSK_SEED`
+//! environment variable.
+
+// Rust
+use std::{env, process};
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use streams::{
+    id::{Ed25519, Psk},
+    transport::utangle,
+    Address, Result, User, Verifier,
+};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Load or .env file, carry on if we failed
+    let _ = dotenv::dotenv();
+
+    let mut args = env::args().skip(1);
+    let announcement: Address = match args.next().map(|arg| arg.parse()) {
+        Some(Ok(address)) => address,
+        Some(Err(e)) => {
+            eprintln!("Invalid announcement address: {}", e);
+            process::exit(2);
+        }
+        None => {
+            eprintln!("Usage: verify-stream <announcement address> [<reader seed>]");
+            process::exit(2);
+        }
+    };
+    let node_url = env::var("URL").unwrap_or_else(|_| "https://chrysalis-nodes.iota.org".to_string());
+
+    let transport: utangle::Client = utangle::Client::new(&node_url);
+
+    let mut builder = User::builder().with_transport(transport);
+    if let Some(seed) = args.next() {
+        builder = builder.with_identity(Ed25519::from_seed(seed));
+    }
+    if let Ok(psk_seed) = env::var("PSK_SEED") {
+        let psk = Psk::from_seed(psk_seed);
+        builder = builder.with_psk(psk.to_pskid(), psk);
+    }
+
+    let report = Verifier::new(builder.build()).verify(announcement).await?;
+    print!("{}", report);
+    if !report.is_intact() {
+        process::exit(1);
+    }
+    Ok(())
+}
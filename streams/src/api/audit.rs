@@ -0,0 +1,371 @@
+// Rust
+use alloc::vec::Vec;
+
+// 3rd-party
+use hashbrown::HashMap;
+
+// IOTA
+
+// Streams
+use lets::{
+    address::Address,
+    id::{Identifier, Permissioned, PskId},
+    message::Topic,
+};
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Mask},
+        io,
+        types::{Size, Uint64},
+    },
+    error::Result as SpongosResult,
+    PRP,
+};
+
+// Local
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// Returns the current `Unix` timestamp in seconds, as used by default to timestamp the entries of
+/// the [`AuditLog`]. Without the `std` feature there is no system clock and `None` is returned, so
+/// a clock must be provided through [`UserBuilder::with_clock`](crate::UserBuilder::with_clock).
+pub(crate) fn system_time() -> Option<u64> {
+    #[cfg(feature = "std")]
+    {
+        Some(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+        )
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+/// A keyload seen by a [`User`](crate::User), with the membership changes it applied to its branch
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct KeyloadRecord {
+    /// Time at which the keyload was sent or processed, as given by the clock of the
+    /// [`User`](crate::User). Keyloads carry no publication time, so a keyload fetched long after
+    /// it was published is stamped with the time it was fetched.
+    pub timestamp: u64,
+    /// The [`Address`] of the keyload
+    pub address: Address,
+    /// The administrator that issued the keyload
+    pub admin: Identifier,
+    /// The [permissions](`Permissioned`) granted by the keyload
+    pub subscribers: Vec<Permissioned<Identifier>>,
+    /// The identifiers of the pre shared keys included in the keyload. Received keyloads only list
+    /// the pre shared keys known by the [`User`](crate::User).
+    pub psks: Vec<PskId>,
+    /// The publishers that lost write access to the branch with the keyload
+    pub demoted: Vec<Identifier>,
+}
+
+impl KeyloadRecord {
+    /// Returns true if the [`Identifier`] was granted access to the branch by the keyload
+    ///
+    /// # Arguments
+    /// * `id`: The [`Identifier`] to look for
+    pub fn grants(&self, id: &Identifier) -> bool {
+        self.subscribers.iter().any(|permission| permission.identifier() == id)
+    }
+}
+
+/// Log of the keyloads seen by a [`User`](crate::User), grouped by branch in the order they were
+/// seen. Keyloads are handled in the order they are linked in their branch, so that order is the
+/// order they were published in, whatever the times they were seen at.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct AuditLog(HashMap<Topic, Vec<KeyloadRecord>>);
+
+impl AuditLog {
+    /// Creates an empty [`AuditLog`]
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends a [`KeyloadRecord`] to the log of a branch
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch of the keyload
+    /// * `record`: The [`KeyloadRecord`] to append
+    pub(crate) fn record(&mut self, topic: Topic, record: KeyloadRecord) {
+        self.0.entry(topic).or_default().push(record);
+    }
+
+    /// Returns the keyloads seen in a branch, oldest first
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    pub fn records(&self, topic: &Topic) -> &[KeyloadRecord] {
+        self.0.get(topic).map_or(&[], |records| records.as_slice())
+    }
+
+    /// Returns an iterator over the branches with at least one keyload in the log
+    pub fn topics(&self) -> impl Iterator<Item = &Topic> + ExactSizeIterator {
+        self.0.keys()
+    }
+
+    /// Returns the keyload in force in a branch at a given time, that is the latest keyload seen
+    /// at or before `timestamp`. Its [subscribers](`KeyloadRecord::subscribers`) and
+    /// [pre shared keys](`KeyloadRecord::psks`) are the ones that could read the branch at that
+    /// time, along with the author of the stream.
+    ///
+    /// The times are the ones the keyloads were seen at by the [`User`](crate::User), not the ones
+    /// they were published at: history synchronized late is stamped with the time it was
+    /// processed, and keyloads published before then are not found in force at earlier times.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `timestamp`: The time to look at, in the units of the clock of the
+    ///   [`User`](crate::User)
+    pub fn in_force_at(&self, topic: &Topic, timestamp: u64) -> Option<&KeyloadRecord> {
+        self.records(topic)
+            .iter()
+            .rev()
+            .find(|record| record.timestamp <= timestamp)
+    }
+}
+
+impl Mask<&KeyloadRecord> for sizeof::Context {
+    fn mask(&mut self, record: &KeyloadRecord) -> SpongosResult<&mut Self> {
+        self.mask(Uint64::new(record.timestamp))?
+            .mask(&record.address)?
+            .mask(&record.admin)?
+            .mask(Size::new(record.subscribers.len()))?;
+        for subscriber in &record.subscribers {
+            self.mask(subscriber)?;
+        }
+        self.mask(Size::new(record.psks.len()))?;
+        for pskid in &record.psks {
+            self.mask(pskid)?;
+        }
+        self.mask(Size::new(record.demoted.len()))?;
+        for demoted in &record.demoted {
+            self.mask(demoted)?;
+        }
+        Ok(self)
+    }
+}
+
+impl<OS, F> Mask<&KeyloadRecord> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+{
+    fn mask(&mut self, record: &KeyloadRecord) -> SpongosResult<&mut Self> {
+        self.mask(Uint64::new(record.timestamp))?
+            .mask(&record.address)?
+            .mask(&record.admin)?
+            .mask(Size::new(record.subscribers.len()))?;
+        for subscriber in &record.subscribers {
+            self.mask(subscriber)?;
+        }
+        self.mask(Size::new(record.psks.len()))?;
+        for pskid in &record.psks {
+            self.mask(pskid)?;
+        }
+        self.mask(Size::new(record.demoted.len()))?;
+        for demoted in &record.demoted {
+            self.mask(demoted)?;
+        }
+        Ok(self)
+    }
+}
+
+impl<IS, F> Mask<&mut KeyloadRecord> for unwrap::Context<IS, F>
+where
+    F: PRP,
+    IS: io::IStream,
+{
+    fn mask(&mut self, record: &mut KeyloadRecord) -> SpongosResult<&mut Self> {
+        let mut timestamp = Uint64::default();
+        let mut amount_subscribers = Size::default();
+        self.mask(&mut timestamp)?
+            .mask(&mut record.address)?
+            .mask(&mut record.admin)?
+            .mask(&mut amount_subscribers)?;
+        record.timestamp = timestamp.inner();
+        for _ in 0..amount_subscribers.inner() {
+            let mut subscriber = Permissioned::default();
+            self.mask(&mut subscriber)?;
+            record.subscribers.push(subscriber);
+        }
+        let mut amount_psks = Size::default();
+        self.mask(&mut amount_psks)?;
+        for _ in 0..amount_psks.inner() {
+            let mut pskid = PskId::default();
+            self.mask(&mut pskid)?;
+            record.psks.push(pskid);
+        }
+        let mut amount_demoted = Size::default();
+        self.mask(&mut amount_demoted)?;
+        for _ in 0..amount_demoted.inner() {
+            let mut demoted = Identifier::default();
+            self.mask(&mut demoted)?;
+            record.demoted.push(demoted);
+        }
+        Ok(self)
+    }
+}
+
+impl Mask<&AuditLog> for sizeof::Context {
+    fn mask(&mut self, log: &AuditLog) -> SpongosResult<&mut Self> {
+        self.mask(Size::new(log.0.len()))?;
+        for (topic, records) in &log.0 {
+            self.mask(topic)?.mask(Size::new(records.len()))?;
+            for record in records {
+                self.mask(record)?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl<OS, F> Mask<&AuditLog> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+{
+    fn mask(&mut self, log: &AuditLog) -> SpongosResult<&mut Self> {
+        self.mask(Size::new(log.0.len()))?;
+        for (topic, records) in &log.0 {
+            self.mask(topic)?.mask(Size::new(records.len()))?;
+            for record in records {
+                self.mask(record)?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl<IS, F> Mask<&mut AuditLog> for unwrap::Context<IS, F>
+where
+    F: PRP,
+    IS: io::IStream,
+{
+    fn mask(&mut self, log: &mut AuditLog) -> SpongosResult<&mut Self> {
+        let mut amount_topics = Size::default();
+        self.mask(&mut amount_topics)?;
+        for _ in 0..amount_topics.inner() {
+            let mut topic = Topic::default();
+            let mut amount_records = Size::default();
+            self.mask(&mut topic)?.mask(&mut amount_records)?;
+            for _ in 0..amount_records.inner() {
+                let mut record = KeyloadRecord::default();
+                self.mask(&mut record)?;
+                log.record(topic.clone(), record);
+            }
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec, vec::Vec};
+    use core::{
+        cell::RefCell,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use lets::{
+        address::Address,
+        id::{Ed25519, Identity, Permissioned},
+        message::Topic,
+        transport::bucket,
+    };
+
+    use super::{AuditLog, KeyloadRecord};
+    use crate::{api::messages::tests::Transport, Result, User};
+
+    #[test]
+    fn audit_log_finds_the_keyload_in_force_at_a_given_time() {
+        let admin = Identity::from(Ed25519::from_seed("admin")).identifier().clone();
+        let reader = Identity::from(Ed25519::from_seed("reader")).identifier().clone();
+        let topic = Topic::from("BASE_BRANCH");
+
+        let mut log = AuditLog::new();
+        log.record(
+            topic.clone(),
+            KeyloadRecord {
+                timestamp: 10,
+                admin: admin.clone(),
+                subscribers: vec![Permissioned::Read(reader.clone())],
+                ..Default::default()
+            },
+        );
+        log.record(
+            topic.clone(),
+            KeyloadRecord {
+                timestamp: 20,
+                admin,
+                ..Default::default()
+            },
+        );
+
+        assert!(log.in_force_at(&topic, 5).is_none());
+        assert!(log.in_force_at(&topic, 15).unwrap().grants(&reader));
+        assert!(!log.in_force_at(&topic, 20).unwrap().grants(&reader));
+        assert!(log.in_force_at(&Topic::from("OTHER"), 15).is_none());
+    }
+
+    #[tokio::test]
+    async fn audit_logs_stamp_keyloads_with_the_time_they_are_seen_at() -> Result<()> {
+        // Clocks are plain functions, so the time they read is shared through a static
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn clock() -> u64 {
+            NOW.load(Ordering::SeqCst)
+        }
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_audit_log()
+            .with_clock(clock)
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_audit_log()
+            .with_clock(clock)
+            .with_transport(transport)
+            .build();
+        subscriber.receive_message(announcement.address()).await?;
+        let subscription = subscriber.subscribe().await?;
+        author.receive_message(subscription.address()).await?;
+
+        NOW.store(10, Ordering::SeqCst);
+        let keyload_1 = author.send_keyload_for_all("BASE_BRANCH").await?;
+        NOW.store(20, Ordering::SeqCst);
+        let keyload_2 = author.send_keyload_for_all("BASE_BRANCH").await?;
+        NOW.store(30, Ordering::SeqCst);
+        subscriber.sync().await?;
+
+        let topic = Topic::from("BASE_BRANCH");
+        let stamps = |user: &User<Transport>| -> Vec<(u64, Address)> {
+            let log = user.audit_log().unwrap();
+            log.records(&topic)
+                .iter()
+                .map(|record| (record.timestamp, record.address))
+                .collect()
+        };
+        assert_eq!(
+            stamps(&author),
+            vec![(10, keyload_1.address()), (20, keyload_2.address())]
+        );
+        // History synchronized late is stamped with the time it is processed, in publication order
+        assert_eq!(
+            stamps(&subscriber),
+            vec![(30, keyload_1.address()), (30, keyload_2.address())]
+        );
+        let log = subscriber.audit_log().unwrap();
+        assert!(log.in_force_at(&topic, 15).is_none());
+        assert_eq!(log.in_force_at(&topic, 30).unwrap().address, keyload_2.address());
+
+        Ok(())
+    }
+}
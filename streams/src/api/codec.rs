@@ -0,0 +1,245 @@
+// Rust
+use alloc::{string::ToString, vec::Vec};
+use core::fmt;
+
+// 3rd-party
+use serde::{de::DeserializeOwned, Serialize};
+
+// IOTA
+
+// Streams
+
+// Local
+use crate::{Error, Result};
+
+/// Content type of the payloads of a message, tagged in the encoding field of its header so that
+/// readers can pick the right decoder
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ContentType {
+    /// Raw bytes, the content type of the payloads sent without a [`Codec`]
+    Raw,
+    /// JSON encoded payloads
+    Json,
+    /// CBOR encoded payloads
+    Cbor,
+    /// Bincode encoded payloads
+    Bincode,
+    /// A content type this version of the library does not know about
+    Unknown(u8),
+}
+
+impl From<u8> for ContentType {
+    fn from(tag: u8) -> Self {
+        match tag {
+            0 => ContentType::Raw,
+            1 => ContentType::Json,
+            2 => ContentType::Cbor,
+            3 => ContentType::Bincode,
+            tag => ContentType::Unknown(tag),
+        }
+    }
+}
+
+impl From<ContentType> for u8 {
+    fn from(content_type: ContentType) -> Self {
+        match content_type {
+            ContentType::Raw => 0,
+            ContentType::Json => 1,
+            ContentType::Cbor => 2,
+            ContentType::Bincode => 3,
+            ContentType::Unknown(tag) => tag,
+        }
+    }
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentType::Raw => write!(f, "raw bytes"),
+            ContentType::Json => write!(f, "JSON"),
+            ContentType::Cbor => write!(f, "CBOR"),
+            ContentType::Bincode => write!(f, "bincode"),
+            ContentType::Unknown(tag) => write!(f, "unknown content type {}", tag),
+        }
+    }
+}
+
+/// Serialization format of typed payloads
+///
+/// Payloads encoded with a [`Codec`] are tagged with its [`ContentType`], and decoded by
+/// [`Message::decode_public()`](crate::Message::decode_public) and
+/// [`Message::decode_masked()`](crate::Message::decode_masked) with the codec of their tag.
+pub trait Codec {
+    /// The [`ContentType`] tagging the payloads encoded with the codec
+    const CONTENT_TYPE: ContentType;
+
+    /// Encodes a value into a payload
+    ///
+    /// # Arguments
+    /// * `value`: The value to encode
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>>;
+
+    /// Decodes a value from a payload
+    ///
+    /// # Arguments
+    /// * `payload`: The encoded payload
+    fn decode<T: DeserializeOwned>(payload: &[u8]) -> Result<T>;
+}
+
+/// [`Codec`] encoding payloads as JSON
+#[cfg(feature = "json")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl Codec for Json {
+    const CONTENT_TYPE: ContentType = ContentType::Json;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|e| Error::PayloadEncoding(Self::CONTENT_TYPE, e.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(payload: &[u8]) -> Result<T> {
+        serde_json::from_slice(payload).map_err(|e| Error::PayloadDecoding(Self::CONTENT_TYPE, e.to_string()))
+    }
+}
+
+/// [`Codec`] encoding payloads as CBOR
+#[cfg(feature = "cbor")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Codec for Cbor {
+    const CONTENT_TYPE: ContentType = ContentType::Cbor;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        let mut payload = Vec::new();
+        ciborium::ser::into_writer(value, &mut payload)
+            .map_err(|e| Error::PayloadEncoding(Self::CONTENT_TYPE, e.to_string()))?;
+        Ok(payload)
+    }
+
+    fn decode<T: DeserializeOwned>(payload: &[u8]) -> Result<T> {
+        ciborium::de::from_reader(payload).map_err(|e| Error::PayloadDecoding(Self::CONTENT_TYPE, e.to_string()))
+    }
+}
+
+/// [`Codec`] encoding payloads with bincode
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl Codec for Bincode {
+    const CONTENT_TYPE: ContentType = ContentType::Bincode;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        bincode::serialize(value).map_err(|e| Error::PayloadEncoding(Self::CONTENT_TYPE, e.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(payload: &[u8]) -> Result<T> {
+        bincode::deserialize(payload).map_err(|e| Error::PayloadDecoding(Self::CONTENT_TYPE, e.to_string()))
+    }
+}
+
+/// Decodes a payload with the [`Codec`] of its [`ContentType`]
+///
+/// # Arguments
+/// * `content_type`: The [`ContentType`] the payload is tagged with
+/// * `payload`: The encoded payload
+pub(crate) fn decode<T: DeserializeOwned>(content_type: ContentType, payload: &[u8]) -> Result<T> {
+    match content_type {
+        #[cfg(feature = "json")]
+        ContentType::Json => Json::decode(payload),
+        #[cfg(feature = "cbor")]
+        ContentType::Cbor => Cbor::decode(payload),
+        #[cfg(feature = "bincode")]
+        ContentType::Bincode => Bincode::decode(payload),
+        content_type => Err(Error::UnsupportedContentType(content_type)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    #[cfg(any(feature = "json", feature = "cbor", feature = "bincode"))]
+    use alloc::vec::Vec;
+
+    #[cfg(any(feature = "json", feature = "cbor", feature = "bincode"))]
+    use serde::{Deserialize, Serialize};
+
+    #[cfg(feature = "bincode")]
+    use super::Bincode;
+    #[cfg(feature = "cbor")]
+    use super::Cbor;
+    #[cfg(any(feature = "json", feature = "cbor", feature = "bincode"))]
+    use super::Codec;
+    #[cfg(feature = "json")]
+    use super::Json;
+    use super::{decode, ContentType};
+    use crate::Error;
+
+    #[cfg(any(feature = "json", feature = "cbor", feature = "bincode"))]
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+    }
+
+    #[cfg(any(feature = "json", feature = "cbor"))]
+    #[derive(Deserialize, Debug)]
+    struct Alert {
+        #[allow(dead_code)]
+        level: u8,
+    }
+
+    #[cfg(any(feature = "json", feature = "cbor", feature = "bincode"))]
+    fn roundtrip<C: Codec>() -> Vec<u8> {
+        let reading = Reading {
+            sensor: "thermometer".into(),
+            value: 21.5,
+        };
+        let payload = C::encode(&reading).unwrap();
+        assert_eq!(decode::<Reading>(C::CONTENT_TYPE, &payload).unwrap(), reading);
+        assert!(matches!(
+            decode::<Reading>(C::CONTENT_TYPE, &payload[..payload.len() - 1]),
+            Err(Error::PayloadDecoding(content_type, _)) if content_type == C::CONTENT_TYPE
+        ));
+        payload
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_payloads_are_decoded_and_checked_against_the_expected_schema() {
+        let json = roundtrip::<Json>();
+        assert!(matches!(
+            decode::<Alert>(ContentType::Json, &json),
+            Err(Error::PayloadDecoding(ContentType::Json, _))
+        ));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_payloads_are_decoded_and_checked_against_the_expected_schema() {
+        let cbor = roundtrip::<Cbor>();
+        assert!(matches!(
+            decode::<Alert>(ContentType::Cbor, &cbor),
+            Err(Error::PayloadDecoding(ContentType::Cbor, _))
+        ));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn bincode_payloads_are_decoded() {
+        roundtrip::<Bincode>();
+    }
+
+    #[test]
+    fn payloads_without_a_codec_are_not_decoded() {
+        assert!(matches!(
+            decode::<String>(ContentType::Raw, b"thermometer"),
+            Err(Error::UnsupportedContentType(ContentType::Raw))
+        ));
+    }
+}
@@ -170,6 +170,64 @@ impl CursorStore {
         }
     }
 
+    /// Records where a branch originates from: the branch it was generated from, if any, and the
+    /// message that announced it.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    /// * `parent`: The [`Topic`] of the branch it was generated from, `None` for the base branch.
+    /// * `announcement`: The [`MsgId`] of the (branch) announcement message.
+    pub(crate) fn set_branch_origin(&mut self, topic: &Topic, parent: Option<Topic>, announcement: MsgId) {
+        if let Some(branch) = self.0.get_mut(topic) {
+            branch.parent = parent;
+            branch.announcement = Some(announcement);
+        }
+    }
+
+    /// Get the [`Topic`] of the branch a branch was generated from, if any.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    pub(crate) fn get_parent(&self, topic: &Topic) -> Option<&Topic> {
+        self.0.get(topic).and_then(|branch| branch.parent.as_ref())
+    }
+
+    /// Returns true if the branch is known to the store
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    pub(crate) fn contains_branch(&self, topic: &Topic) -> bool {
+        self.0.contains_key(topic)
+    }
+
+    /// Returns true if a branch was generated from another one, directly or through other
+    /// branches, following the recorded parent of each branch.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    /// * `ancestor`: The [`Topic`] of the branch it might have been generated from.
+    pub(crate) fn is_descendant(&self, topic: &Topic, ancestor: &Topic) -> bool {
+        let mut parent = self.get_parent(topic);
+        // A branch cannot have more ancestors than there are branches, which bounds the walk should
+        // the recorded parents form a cycle
+        for _ in 0..self.0.len() {
+            match parent {
+                Some(parent) if parent == ancestor => return true,
+                Some(grandparent) => parent = self.get_parent(grandparent),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Get the [`MsgId`] of the message that announced a branch, if known.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch.
+    pub(crate) fn get_announcement(&self, topic: &Topic) -> Option<MsgId> {
+        self.0.get(topic).and_then(|branch| branch.announcement)
+    }
+
     /// Get the latest link for a topic, if it exists.
     ///
     /// # Arguments
@@ -190,10 +248,17 @@ pub(crate) struct InnerCursorStore {
     cursors: HashMap<Permissioned<Identifier>, usize>,
     /// Latest message link processed in the branch
     latest_link: MsgId,
+    /// The branch this branch was generated from, `None` for the base branch
+    parent: Option<Topic>,
+    /// The message that announced the branch
+    announcement: Option<MsgId>,
 }
 
 impl fmt::Debug for InnerCursorStore {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(parent) = &self.parent {
+            writeln!(f, "\t* parent: {}", parent)?;
+        }
         writeln!(f, "\t* latest link: {}", self.latest_link)?;
         writeln!(f, "\t* cursors:")?;
         for (id, cursor) in self.cursors.iter() {
@@ -0,0 +1,192 @@
+// Rust
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use lets::{
+    address::Address,
+    id::{Identifier, Permissioned, PskId},
+    message::Topic,
+};
+
+// Local
+
+/// A change applied to the state of a [`User`](crate::User), whether it comes from a message sent
+/// or received by the [`User`], or from a direct call to its `API`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Event {
+    /// A subscriber has been added to the stream
+    SubscriberAdded(Identifier),
+    /// A subscriber has been removed from the stream
+    SubscriberRemoved(Identifier),
+    /// A branch has been created, or announced to the [`User`]
+    BranchCreated {
+        /// The [`Topic`] of the branch
+        topic: Topic,
+        /// The [`Topic`] of the branch it was generated from, `None` for the base branch
+        parent: Option<Topic>,
+        /// The [`Address`] of the message that announced the branch
+        announcement: Address,
+    },
+    /// The permission of an [`Identifier`] in a branch has changed
+    PermissionChanged {
+        /// The [`Topic`] of the branch
+        topic: Topic,
+        /// The [`Identifier`] whose permission has changed
+        identifier: Identifier,
+        /// The previous [permission](`Permissioned`), `None` if it had none
+        previous: Option<Permissioned<Identifier>>,
+        /// The new [permission](`Permissioned`), `None` if it has been revoked
+        current: Option<Permissioned<Identifier>>,
+    },
+    /// A [pre shared key](`lets::id::Psk`) has been added
+    PskAdded(PskId),
+    /// A [pre shared key](`lets::id::Psk`) has been removed
+    PskRemoved(PskId),
+}
+
+/// Observer of the [events](`Event`) of a [`User`](crate::User). Observers are notified of each
+/// event right after it has been applied to the state of the [`User`].
+///
+/// Any `FnMut(&Event)` closure is an [`Observer`]:
+/// ```
+/// use std::sync::mpsc;
+///
+/// use streams::{
+///     id::{Ed25519, Psk},
+///     transport::bucket,
+///     Event, User,
+/// };
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let mut user = User::builder()
+///     .with_identity(Ed25519::from_seed("user"))
+///     .with_transport(bucket::Client::new())
+///     .build();
+/// user.add_observer(move |event: &Event| {
+///     let _ = sender.send(event.clone());
+/// });
+///
+/// let psk = Psk::from_seed("pre shared key");
+/// user.add_psk(psk);
+/// assert_eq!(receiver.try_recv(), Ok(Event::PskAdded(psk.to_pskid())));
+/// ```
+pub trait Observer {
+    /// Called with each [`Event`] applied to the state of the [`User`](crate::User)
+    ///
+    /// # Arguments
+    /// * `event`: The [`Event`] that has been applied
+    fn notify(&mut self, event: &Event);
+}
+
+impl<F> Observer for F
+where
+    F: FnMut(&Event),
+{
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use alloc::{rc::Rc, vec::Vec};
+    use core::cell::RefCell;
+    use std::sync::mpsc;
+
+    use lets::{
+        id::{Ed25519, PermissionDuration, Permissioned, Psk},
+        message::Topic,
+        transport::bucket,
+    };
+
+    use super::Event;
+    use crate::{Result, User};
+
+    /// Registers an observer on a [`User`], returning the receiving end of the events it observes
+    fn observe<T>(user: &mut User<T>) -> mpsc::Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        user.add_observer(move |event: &Event| {
+            let _ = sender.send(event.clone());
+        });
+        receiver
+    }
+
+    #[tokio::test]
+    async fn users_emit_the_events_of_the_messages_they_send_and_receive() -> Result<()> {
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_transport(transport)
+            .build();
+        let author_events = observe(&mut author);
+        let subscriber_events = observe(&mut subscriber);
+        let author_id = author.identifier().unwrap().clone();
+        let subscriber_id = subscriber.identifier().unwrap().clone();
+
+        let announcement = author.create_stream("BASE_BRANCH").await?.address();
+        subscriber.receive_message(announcement).await?;
+        let subscription = subscriber.subscribe().await?.address();
+        author.receive_message(subscription).await?;
+        author.send_keyload_for_all_rw("BASE_BRANCH").await?;
+        let branch_announcement = author.new_branch("BASE_BRANCH", "CHILD_BRANCH").await?.address();
+        subscriber.sync().await?;
+        let psk = Psk::from_seed("pre shared key");
+        author.add_psk(psk);
+        author.remove_subscriber(&subscriber_id);
+
+        let base_branch = Topic::from("BASE_BRANCH");
+        let child_branch = Topic::from("CHILD_BRANCH");
+        let admin = Permissioned::Admin(author_id.clone());
+        let read_write = Permissioned::ReadWrite(subscriber_id.clone(), PermissionDuration::Perpetual);
+        let granted = |topic: &Topic, permission: &Permissioned<_>| Event::PermissionChanged {
+            topic: topic.clone(),
+            identifier: permission.identifier().clone(),
+            previous: None,
+            current: Some(permission.clone()),
+        };
+        let branch_created = |topic: &Topic, parent: Option<&Topic>, announcement| Event::BranchCreated {
+            topic: topic.clone(),
+            parent: parent.cloned(),
+            announcement,
+        };
+
+        let events: Vec<Event> = author_events.try_iter().collect();
+        assert_eq!(
+            events[..3],
+            [
+                branch_created(&base_branch, None, announcement),
+                Event::SubscriberAdded(subscriber_id.clone()),
+                granted(&base_branch, &read_write),
+            ]
+        );
+        assert_eq!(events[3], branch_created(&child_branch, Some(&base_branch), branch_announcement));
+        // The permissions of the new branch are copied from its parent, in no particular order
+        assert_eq!(events[4..6].len(), 2);
+        assert!(events[4..6].contains(&granted(&child_branch, &admin)));
+        assert!(events[4..6].contains(&granted(&child_branch, &read_write)));
+        assert_eq!(
+            events[6..],
+            [Event::PskAdded(psk.to_pskid()), Event::SubscriberRemoved(subscriber_id)]
+        );
+
+        let events: Vec<Event> = subscriber_events.try_iter().collect();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], branch_created(&base_branch, None, announcement));
+        assert_eq!(events[1], granted(&base_branch, &read_write));
+        assert_eq!(events[2], branch_created(&child_branch, Some(&base_branch), branch_announcement));
+        assert!(events[3..].contains(&granted(&child_branch, &admin)));
+        assert!(events[3..].contains(&granted(&child_branch, &read_write)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+// Rust
+use alloc::{boxed::Box, vec::Vec};
+use core::{future::Future, pin::Pin};
+
+// 3rd-party
+use futures::future;
+
+// IOTA
+
+// Streams
+use lets::{
+    address::Address,
+    error::Result as LetsResult,
+    message::{Topic, TransportMessage},
+    transport::Transport,
+};
+
+// Local
+use crate::{Error, SendResponse};
+
+/// A function waiting for the given amount of milliseconds, such as
+/// `|millis| Box::pin(tokio::time::sleep(Duration::from_millis(millis)))`
+pub type Sleep = fn(u64) -> Pin<Box<dyn Future<Output = ()>>>;
+
+/// How many times the message of a branch is sent again when sending it fails while publishing a
+/// payload to several branches
+#[derive(Clone, Copy, Debug, Default)]
+pub enum RetryPolicy {
+    /// Failed messages are not sent again
+    #[default]
+    Never,
+    /// Failed messages are sent again right away, up to the given amount of times
+    Retries(usize),
+    /// Failed messages are sent again up to `retries` times, waiting `delay` milliseconds before the
+    /// first retry and twice as long before each of the next ones
+    Backoff {
+        /// The maximum amount of times a message is sent again
+        retries: usize,
+        /// The milliseconds waited before the first retry
+        delay: u64,
+        /// The function waiting between the retries
+        sleep: Sleep,
+    },
+}
+
+impl RetryPolicy {
+    /// Sends a message, sending it again as allowed by the policy while it fails
+    ///
+    /// # Arguments
+    /// * `transport`: The [`Transport`] to send the message through
+    /// * `address`: The [`Address`] of the message
+    /// * `message`: The message to send
+    pub(crate) async fn send<T, TSR>(
+        self,
+        transport: &mut T,
+        address: Address,
+        message: TransportMessage,
+    ) -> LetsResult<TSR>
+    where
+        T: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR>,
+    {
+        let (mut retries, mut delay) = match self {
+            RetryPolicy::Never => (0, 0),
+            RetryPolicy::Retries(retries) => (retries, 0),
+            RetryPolicy::Backoff { retries, delay, .. } => (retries, delay),
+        };
+        loop {
+            match transport.send_message(address, message.clone()).await {
+                Err(_) if retries > 0 => retries -= 1,
+                result => return result,
+            }
+            if let RetryPolicy::Backoff { sleep, .. } = self {
+                sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+        }
+    }
+
+    /// Sends messages concurrently, each through its own clone of the transport and sent again as
+    /// allowed by the policy while it fails. The outcomes are returned in the order of the messages.
+    ///
+    /// # Arguments
+    /// * `transport`: The [`Transport`] cloned to send each message through
+    /// * `messages`: The [`Address`] of each message, along with the message to send
+    pub(crate) async fn send_concurrently<T, TSR>(
+        self,
+        transport: &T,
+        messages: Vec<(Address, TransportMessage)>,
+    ) -> Vec<LetsResult<TSR>>
+    where
+        T: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR> + Clone,
+    {
+        future::join_all(messages.into_iter().map(|(address, message)| {
+            let mut transport = transport.clone();
+            async move { self.send(&mut transport, address, message).await }
+        }))
+        .await
+    }
+}
+
+/// The outcome of publishing a payload to several branches, one per branch
+///
+/// The messages of the branches are all wrapped before any of them is sent, so a branch the user
+/// cannot publish in aborts the whole publication. They are then sent concurrently, each through its
+/// own clone of the transport of the user. Messages that could not be sent, even after retrying as
+/// allowed by the [`RetryPolicy`], leave the state of their branch untouched, whereas the branches
+/// whose message was sent are updated as usual. The state of the user thus matches what has
+/// actually been published, and the failed branches can be published to again.
+///
+/// Messages that have been sent are not rolled back when others fail: a transport does not allow
+/// removing a message once it is published, so retrying the failed branches is the only way to
+/// make the branches consistent again.
+///
+/// Keyloads sent to several branches with
+/// [`User::send_keyload_matching()`](crate::User::send_keyload_matching) are wrapped, sent and
+/// reported the same way.
+#[derive(Debug)]
+pub struct FanOutResponse<TSR> {
+    /// The [`Topic`] of each branch, along with the outcome of sending its message
+    outcomes: Vec<(Topic, Result<SendResponse<TSR>, Error>)>,
+}
+
+impl<TSR> FanOutResponse<TSR> {
+    /// Creates a new [`FanOutResponse`]
+    ///
+    /// # Arguments
+    /// * `outcomes`: The [`Topic`] of each branch, along with the outcome of sending its message
+    pub(crate) fn new(outcomes: Vec<(Topic, Result<SendResponse<TSR>, Error>)>) -> Self {
+        Self { outcomes }
+    }
+
+    /// Returns true if the message of every branch was sent
+    pub fn is_complete(&self) -> bool {
+        self.outcomes.iter().all(|(_, outcome)| outcome.is_ok())
+    }
+
+    /// Returns the outcome of sending the message of a branch, if it was targeted
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    pub fn outcome(&self, topic: &Topic) -> Option<&Result<SendResponse<TSR>, Error>> {
+        self.outcomes.iter().find(|(t, _)| t == topic).map(|(_, outcome)| outcome)
+    }
+
+    /// Returns an iterator over the branches whose message was sent, along with its
+    /// [`SendResponse`]
+    pub fn sent(&self) -> impl Iterator<Item = (&Topic, &SendResponse<TSR>)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(topic, outcome)| outcome.as_ref().ok().map(|response| (topic, response)))
+    }
+
+    /// Returns an iterator over the branches whose message could not be sent, along with the
+    /// [`Error`] that prevented it
+    pub fn failed(&self) -> impl Iterator<Item = (&Topic, &Error)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(topic, outcome)| outcome.as_ref().err().map(|error| (topic, error)))
+    }
+
+    /// Consumes the [`FanOutResponse`], returning the outcome of every branch in the order the
+    /// branches were targeted
+    pub fn into_outcomes(self) -> Vec<(Topic, Result<SendResponse<TSR>, Error>)> {
+        self.outcomes
+    }
+}
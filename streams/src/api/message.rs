@@ -2,6 +2,7 @@
 use alloc::vec::Vec;
 
 // 3rd-party
+use serde::de::DeserializeOwned;
 
 // IOTA
 
@@ -9,64 +10,103 @@ use alloc::vec::Vec;
 use lets::{
     address::Address,
     id::{Identifier, Permissioned, PskId},
-    message::{Message as LetsMessage, PreparsedMessage, Topic, TopicHash, TransportMessage, HDF},
+    message::{BytesView, Message as LetsMessage, PreparsedMessage, Topic, TopicHash, TransportMessage, HDF},
 };
+use spongos::KeccakF1600;
 
 // Local
-use crate::message::{
-    announcement, branch_announcement, keyload, signed_packet, subscription, tagged_packet, unsubscription,
+use crate::{
+    api::codec::{self, ContentType},
+    message::{
+        announcement, branch_announcement,
+        checkpoint::{self, CheckpointHistory},
+        keyload, signed_packet, subscription, tagged_packet, unsubscription,
+    },
+    Error, Result,
 };
 
 /// A processed Streams message
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Message {
+pub struct Message<F = KeccakF1600> {
     /// The [`Address`] of the message
     pub address: Address,
     /// The message [header](`HDF`)
     pub header: HDF,
+    /// The [`Topic`] of the branch the message was published in
+    pub topic: Topic,
     /// The message payload
-    pub content: MessageContent,
+    pub content: MessageContent<F>,
 }
 
-impl Message {
+impl<F> Message<F> {
     /// Creates a [`Message`] from a [`LetsMessage`] and the message [`Address`]
     ///
     /// # Arguments
     /// * `address`: The [`Address`] of the message.
+    /// * `topic`: The [`Topic`] of the branch the message was published in
     /// * `lets_message`: The raw message from transport
     ///
     /// Returns:
     /// A [`Message`] struct
-    pub(crate) fn from_lets_message<Unwrap>(address: Address, lets_message: LetsMessage<Unwrap>) -> Self
+    pub(crate) fn from_lets_message<Unwrap>(address: Address, topic: Topic, lets_message: LetsMessage<Unwrap>) -> Self
     where
-        Unwrap: Into<MessageContent>,
+        Unwrap: Into<MessageContent<F>>,
     {
         let parts = lets_message.into_parts();
         Message {
             address,
             header: parts.0,
+            topic,
             content: parts.1.into_content().into(),
         }
     }
 
+    /// Creates a [`Message`] from a [`LetsMessage`] that was unwrapped in place, and the message
+    /// [`Address`]. The payloads of the message are views into the body it was unwrapped in.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message.
+    /// * `topic`: The [`Topic`] of the branch the message was published in
+    /// * `lets_message`: The message unwrapped in place
+    /// * `transport_msg`: The raw message the content was decrypted in
+    ///
+    /// Returns:
+    /// A [`Message`] struct
+    pub(crate) fn from_lets_message_in_place<Unwrap>(
+        address: Address,
+        topic: Topic,
+        lets_message: LetsMessage<Unwrap>,
+        transport_msg: TransportMessage,
+    ) -> Self
+    where
+        for<'a> (Unwrap, &'a BytesView): Into<MessageContent<F>>,
+    {
+        let (header, pcf) = lets_message.into_parts();
+        let body = BytesView::from(transport_msg);
+        Message {
+            address,
+            header,
+            topic,
+            content: (pcf.into_content(), &body).into(),
+        }
+    }
+
     /// Create a generic `Orphan` message, meaning that the previous link address does not match any
     /// spongos in store, and the message cannot be processed.
     ///
     /// # Arguments
     /// * `address`: The [`Address`] of the message
+    /// * `topic`: The [`Topic`] of the branch the message was published in
     /// * `preparsed`: The [`PreparsedMessage`] that could not be processed
     ///
     /// Returns:
     /// An `Orphan` [`Message`]
-    pub(crate) fn orphan(address: Address, preparsed: PreparsedMessage) -> Self {
-        let parts = preparsed.into_parts();
+    pub(crate) fn orphan(address: Address, topic: Topic, preparsed: PreparsedMessage<F>) -> Self {
         Self {
             address,
-            header: parts.0,
-            content: MessageContent::Orphan(Orphan {
-                cursor: parts.3,
-                message: parts.1,
-            }),
+            header: preparsed.header().clone(),
+            topic,
+            content: MessageContent::Orphan(Orphan { preparsed }),
         }
     }
 
@@ -81,7 +121,7 @@ impl Message {
     }
 
     /// Returns a reference to the [payload](`MessageContent`) of the message
-    pub fn content(&self) -> &MessageContent {
+    pub fn content(&self) -> &MessageContent<F> {
         &self.content
     }
 
@@ -90,6 +130,11 @@ impl Message {
         self.header.topic_hash()
     }
 
+    /// Returns a reference to the [`Topic`] of the branch the message was published in
+    pub fn topic(&self) -> &Topic {
+        &self.topic
+    }
+
     /// Returns true if the message is a [`MessageContent`]`::Announcement`
     pub fn is_announcement(&self) -> bool {
         matches!(self.content, MessageContent::Announcement { .. })
@@ -125,6 +170,11 @@ impl Message {
         matches!(self.content, MessageContent::Unsubscription { .. })
     }
 
+    /// Returns true if the message is a [`MessageContent`]`::Checkpoint`
+    pub fn is_checkpoint(&self) -> bool {
+        matches!(self.content, MessageContent::Checkpoint { .. })
+    }
+
     /// Returns true if the message is a [`MessageContent`]`::Orphan`
     pub fn is_orphan(&self) -> bool {
         matches!(self.content, MessageContent::Orphan { .. })
@@ -193,8 +243,17 @@ impl Message {
         }
     }
 
+    /// If the message is a `Checkpoint` return it as one
+    pub fn as_checkpoint(&self) -> Option<&Checkpoint> {
+        if let MessageContent::Checkpoint(checkpoint) = &self.content {
+            Some(checkpoint)
+        } else {
+            None
+        }
+    }
+
     /// If the message is an `Orphan` return it as one
-    pub fn as_orphan(&self) -> Option<&Orphan> {
+    pub fn as_orphan(&self) -> Option<&Orphan<F>> {
         if let MessageContent::Orphan(orphan) = &self.content {
             Some(orphan)
         } else {
@@ -225,10 +284,37 @@ impl Message {
             _ => None,
         }
     }
+
+    /// Returns the [`ContentType`] the payloads of the message are tagged with
+    pub fn content_type(&self) -> ContentType {
+        self.header.encoding().into()
+    }
+
+    /// Decodes the public payload of the message with the [`Codec`](crate::Codec) of its
+    /// [`ContentType`]
+    ///
+    /// # Errors
+    /// Fails if the message is not a packet, if no codec is available for its [`ContentType`], or
+    /// if the payload does not match the schema of `T`
+    pub fn decode_public<T: DeserializeOwned>(&self) -> Result<T> {
+        let payload = self.public_payload().ok_or(Error::NoPayload(self.address))?;
+        codec::decode(self.content_type(), payload)
+    }
+
+    /// Decodes the masked payload of the message with the [`Codec`](crate::Codec) of its
+    /// [`ContentType`]
+    ///
+    /// # Errors
+    /// Fails if the message is not a packet, if no codec is available for its [`ContentType`], or
+    /// if the payload does not match the schema of `T`
+    pub fn decode_masked<T: DeserializeOwned>(&self) -> Result<T> {
+        let payload = self.masked_payload().ok_or(Error::NoPayload(self.address))?;
+        codec::decode(self.content_type(), payload)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub enum MessageContent {
+pub enum MessageContent<F = KeccakF1600> {
     Announcement(Announcement),
     BranchAnnouncement(BranchAnnouncement),
     Keyload(Keyload),
@@ -236,7 +322,8 @@ pub enum MessageContent {
     TaggedPacket(TaggedPacket),
     Subscription(Subscription),
     Unsubscription(Unsubscription),
-    Orphan(Orphan),
+    Checkpoint(Checkpoint),
+    Orphan(Orphan<F>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -273,18 +360,18 @@ pub struct SignedPacket {
     /// The [`Identifier`] of the publisher
     pub publisher_identifier: Identifier,
     /// A payload that was encrypted
-    pub masked_payload: Vec<u8>,
+    pub masked_payload: BytesView,
     /// A payload that was not encrypted
-    pub public_payload: Vec<u8>,
+    pub public_payload: BytesView,
 }
 
 /// Tagged Packet [`Message`].
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TaggedPacket {
     /// A payload that was encrypted
-    pub masked_payload: Vec<u8>,
+    pub masked_payload: BytesView,
     /// A payload that was not encrypted
-    pub public_payload: Vec<u8>,
+    pub public_payload: BytesView,
 }
 
 /// Subscription [`Message`].
@@ -315,16 +402,58 @@ impl Unsubscription {
     }
 }
 
+/// Checkpoint [`Message`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    /// The members of the branch at the time of the checkpoint, with their cursors. Empty if the
+    /// reader is not a recipient of the checkpoint.
+    pub cursors: Vec<(Permissioned<Identifier>, usize)>,
+    /// The pre shared keys of the reader included in the checkpoint
+    pub psks: Vec<PskId>,
+    /// Which messages of the branch published before the checkpoint can still be read by the
+    /// readers starting from it
+    pub history: CheckpointHistory,
+}
+
+impl Checkpoint {
+    /// Returns true if the reader is a recipient of the checkpoint, and could read its snapshot
+    pub fn is_readable(&self) -> bool {
+        !self.cursors.is_empty()
+    }
+}
+
 /// Orphan [`Message`].
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Orphan {
-    /// Raw message that could not be processed
-    pub message: TransportMessage,
-    /// Publisher cursor
-    pub cursor: usize,
+pub struct Orphan<F = KeccakF1600> {
+    /// Message that could not be processed, preparsed up to the end of its header. Unwrapping
+    /// resumes from this state once the message it is linked to is known, without parsing the
+    /// header again.
+    pub preparsed: PreparsedMessage<F>,
 }
 
-impl From<announcement::Unwrap> for MessageContent {
+impl<F> Orphan<F> {
+    /// Returns a reference to the raw [`TransportMessage`]
+    pub fn message(&self) -> &TransportMessage {
+        self.preparsed.transport_msg()
+    }
+
+    /// Returns the read position within the raw message, marking the end of its header
+    pub fn cursor(&self) -> usize {
+        self.preparsed.cursor()
+    }
+
+    /// Consumes the [`Orphan`], returning the raw [`TransportMessage`]
+    pub fn into_message(self) -> TransportMessage {
+        self.preparsed.into_parts().1
+    }
+
+    /// Consumes the [`Orphan`], returning its [`PreparsedMessage`]
+    pub fn into_preparsed(self) -> PreparsedMessage<F> {
+        self.preparsed
+    }
+}
+
+impl<F> From<announcement::Unwrap> for MessageContent<F> {
     fn from(announce: announcement::Unwrap) -> Self {
         Self::Announcement(Announcement {
             author_identifier: announce.into_author_id(),
@@ -332,24 +461,24 @@ impl From<announcement::Unwrap> for MessageContent {
     }
 }
 
-impl<'a> From<branch_announcement::Unwrap<'a>> for MessageContent {
-    fn from(branch_announcement: branch_announcement::Unwrap<'a>) -> Self {
+impl<'a, F> From<branch_announcement::Unwrap<'a, F>> for MessageContent<F> {
+    fn from(branch_announcement: branch_announcement::Unwrap<'a, F>) -> Self {
         Self::BranchAnnouncement(BranchAnnouncement {
             topic: branch_announcement.into_new_topic(),
         })
     }
 }
 
-impl<'a> From<subscription::Unwrap<'a>> for MessageContent {
-    fn from(subscription: subscription::Unwrap<'a>) -> Self {
+impl<'a, F> From<subscription::Unwrap<'a, F>> for MessageContent<F> {
+    fn from(subscription: subscription::Unwrap<'a, F>) -> Self {
         Self::Subscription(Subscription {
             subscriber_identifier: subscription.into_subscriber_identifier(),
         })
     }
 }
 
-impl<'a> From<keyload::Unwrap<'a>> for MessageContent {
-    fn from(keyload: keyload::Unwrap<'a>) -> Self {
+impl<'a, F> From<keyload::Unwrap<'a, F>> for MessageContent<F> {
+    fn from(keyload: keyload::Unwrap<'a, F>) -> Self {
         Self::Keyload(Keyload {
             psks: keyload.psks,
             subscribers: keyload.subscribers,
@@ -357,10 +486,21 @@ impl<'a> From<keyload::Unwrap<'a>> for MessageContent {
     }
 }
 
-impl<'a> From<signed_packet::Unwrap<'a>> for MessageContent {
-    fn from(mut signed_packet: signed_packet::Unwrap<'a>) -> Self {
-        let masked_payload = signed_packet.take_masked_payload();
-        let public_payload = signed_packet.take_public_payload();
+impl<'a, F> From<checkpoint::Unwrap<'a, F>> for MessageContent<F> {
+    fn from(checkpoint: checkpoint::Unwrap<'a, F>) -> Self {
+        let (cursors, psks, history) = checkpoint.into_parts();
+        Self::Checkpoint(Checkpoint {
+            cursors,
+            psks,
+            history,
+        })
+    }
+}
+
+impl<'a, F> From<(signed_packet::Unwrap<'a, F>, &BytesView)> for MessageContent<F> {
+    fn from((signed_packet, body): (signed_packet::Unwrap<'a, F>, &BytesView)) -> Self {
+        let masked_payload = signed_packet.masked_payload(body);
+        let public_payload = signed_packet.public_payload(body);
         Self::SignedPacket(SignedPacket {
             publisher_identifier: signed_packet.into_publisher_identifier(),
             masked_payload,
@@ -369,17 +509,17 @@ impl<'a> From<signed_packet::Unwrap<'a>> for MessageContent {
     }
 }
 
-impl<'a> From<tagged_packet::Unwrap<'a>> for MessageContent {
-    fn from(mut tagged_packet: tagged_packet::Unwrap<'a>) -> Self {
+impl<'a, F> From<(tagged_packet::Unwrap<'a, F>, &BytesView)> for MessageContent<F> {
+    fn from((tagged_packet, body): (tagged_packet::Unwrap<'a, F>, &BytesView)) -> Self {
         Self::TaggedPacket(TaggedPacket {
-            masked_payload: tagged_packet.take_masked_payload(),
-            public_payload: tagged_packet.take_public_payload(),
+            masked_payload: tagged_packet.masked_payload(body),
+            public_payload: tagged_packet.public_payload(body),
         })
     }
 }
 
-impl<'a> From<unsubscription::Unwrap<'a>> for MessageContent {
-    fn from(unsubscription: unsubscription::Unwrap<'a>) -> Self {
+impl<'a, F> From<unsubscription::Unwrap<'a, F>> for MessageContent<F> {
+    fn from(unsubscription: unsubscription::Unwrap<'a, F>) -> Self {
         Self::Unsubscription(Unsubscription {
             subscriber_identifier: unsubscription.into_subscriber_identifier(),
         })
@@ -1,29 +1,48 @@
-use crate::{Error, Result, SendResponse, User};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::{
+    message::cipher_suite::StreamPRP, Codec, ContentType, Error, FanOutResponse, Result, RetryPolicy, SendResponse,
+    User,
+};
 use lets::{
     message::{Topic, TransportMessage},
     transport::Transport,
 };
+use spongos::KeccakF1600;
 
 /// A builder for creating messages for transport
-pub struct MessageBuilder<'a, P, Trans> {
+pub struct MessageBuilder<'a, P, Trans, F = KeccakF1600> {
     /// A User Client to send the message from
-    user: &'a mut User<Trans>,
+    user: &'a mut User<Trans, F>,
     /// Whether or not the message payload will be masked in transit (defaults to masked)
     private: bool,
     /// Whether or not the message will be signed by the user sending (defaults to unsigned)
     signed: bool,
     /// The Topic of the branch the message will be sent to (defaults to the base branch)
     topic: Topic,
+    /// The Topics of the branches the message will be published to by [`MessageBuilder::send_all`]
+    /// (defaults to the branch of `topic`)
+    topics: Vec<Topic>,
+    /// The retry policy applied to the messages that fail to be sent by
+    /// [`MessageBuilder::send_all`] (defaults to no retries)
+    retry_policy: RetryPolicy,
     /// A payload to be sent to the channel
     payload: P,
+    /// The content type the payload is encoded with (defaults to raw bytes)
+    content_type: ContentType,
 }
 
-impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
+impl<'a, P, Trans, F> MessageBuilder<'a, P, Trans, F>
+where
+    F: StreamPRP,
+{
     /// Creates a new MessageBuilder from an existing User Client
     ///
     /// # Arguments
     /// * user - User Client that will send the message
-    pub fn new(user: &'a mut User<Trans>) -> Self
+    pub fn new(user: &'a mut User<Trans, F>) -> Self
     where
         P: Default,
     {
@@ -33,7 +52,10 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
             private: true,
             signed: false,
             topic,
+            topics: Vec::new(),
+            retry_policy: RetryPolicy::Never,
             payload: P::default(),
+            content_type: ContentType::Raw,
         }
     }
 
@@ -72,6 +94,30 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
         self
     }
 
+    /// Inject the Topics of several branches into the builder, to publish the payload to all of them
+    /// with [`MessageBuilder::send_all`].
+    ///
+    /// # Arguments
+    /// * topics - The topics of the branches the message will be published to
+    pub fn with_topics<I, Top>(mut self, topics: I) -> Self
+    where
+        I: IntoIterator<Item = Top>,
+        Top: Into<Topic>,
+    {
+        self.topics = topics.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Inject the [`RetryPolicy`] applied by [`MessageBuilder::send_all`] to the messages that fail
+    /// to be sent.
+    ///
+    /// # Arguments
+    /// * retry_policy - How many times a failed message is sent again
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Sends the message payload to the specified branch using the User Client. If the message is
     /// signed, the message will be sent as a Signed Packet, and if not, it will be sent as a
     /// Tagged Packet.
@@ -124,10 +170,140 @@ impl<'a, P, Trans> MessageBuilder<'a, P, Trans> {
         }
 
         if self.signed {
-            self.user.send_signed_packet(self.topic, public, private).await
+            self.user
+                .send_encoded_signed_packet(self.topic, public, private, self.content_type)
+                .await
+        } else {
+            self.user
+                .send_encoded_tagged_packet(self.topic, public, private, self.content_type)
+                .await
+        }
+    }
+
+    /// Publishes the message payload to every branch specified with [`MessageBuilder::with_topics`]
+    /// (or to the branch specified with [`MessageBuilder::with_topic`] if none was) using the User
+    /// Client, sending a Signed or a Tagged Packet to each of them.
+    ///
+    /// The messages are all wrapped before any of them is sent, so nothing is published if the
+    /// User cannot publish in one of the branches. They are then sent concurrently, each through its
+    /// own clone of the transport of the User Client, and the outcome of each branch is reported in
+    /// the [`FanOutResponse`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use streams::{id::Ed25519, transport::bucket, RetryPolicy, User, Result};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let mut user = User::builder()
+    /// #    .with_identity(Ed25519::from_seed("cryptographically-secure-random-user-seed"))
+    /// #    .with_transport(Rc::new(RefCell::new(bucket::Client::new())))
+    /// #    .build();
+    /// # user.create_stream("Branch 1").await?;
+    /// # user.new_branch("Branch 1", "Branch 2").await?;
+    /// let response = user
+    ///     .message()
+    ///     .with_topics(["Branch 1", "Branch 2"])
+    ///     .with_payload("A Data Payload")
+    ///     .with_retry_policy(RetryPolicy::Retries(3))
+    ///     .signed()
+    ///     .send_all()
+    ///     .await?;
+    /// assert!(response.is_complete());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_all<TSR>(self) -> Result<FanOutResponse<TSR>>
+    where
+        P: AsRef<[u8]>,
+        Trans: for<'b> Transport<'b, Msg = TransportMessage, SendResponse = TSR> + Clone,
+    {
+        if self.payload.as_ref().is_empty() {
+            return Err(Error::PayloadEmpty);
+        }
+
+        let topics = if self.topics.is_empty() {
+            vec![self.topic]
+        } else {
+            self.topics
+        };
+        let mut public: &[u8] = &[];
+        let mut private: &[u8] = &[];
+
+        if self.private {
+            private = self.payload.as_ref()
         } else {
-            self.user.send_tagged_packet(self.topic, public, private).await
+            public = self.payload.as_ref()
         }
+
+        self.user
+            .send_encoded_packets(
+                topics,
+                public,
+                private,
+                self.content_type,
+                self.signed,
+                self.retry_policy,
+            )
+            .await
+    }
+}
+
+impl<'a, Trans, F> MessageBuilder<'a, Vec<u8>, Trans, F> {
+    /// Inject a typed data payload into the builder, encoded with a [`Codec`]. The message is tagged
+    /// with the [`ContentType`] of the codec, so readers can decode it with
+    /// [`Message::decode_masked()`](crate::Message::decode_masked) or
+    /// [`Message::decode_public()`](crate::Message::decode_public).
+    ///
+    /// # Arguments
+    /// * value - The value that will be encoded and sent
+    ///
+    /// # Examples
+    /// ```
+    /// # use serde::{Deserialize, Serialize};
+    /// # use streams::{id::Ed25519, transport::bucket, User, Result};
+    /// use streams::Json;
+    ///
+    /// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    /// struct Reading {
+    ///     sensor: String,
+    ///     value: f64,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let mut user = User::builder()
+    /// #    .with_identity(Ed25519::from_seed("cryptographically-secure-random-user-seed"))
+    /// #    .with_transport(bucket::Client::new())
+    /// #    .build();
+    /// # user.create_stream("Branch 1").await?;
+    /// let reading = Reading {
+    ///     sensor: "thermometer".to_string(),
+    ///     value: 21.5,
+    /// };
+    /// let response = user
+    ///     .message()
+    ///     .with_typed_payload::<Json, _>(&reading)?
+    ///     .send()
+    ///     .await?;
+    ///
+    /// let message = user.receive_message(response.address()).await?;
+    /// assert_eq!(message.decode_masked::<Reading>()?, reading);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Fails if the value cannot be encoded with the [`Codec`]
+    pub fn with_typed_payload<C, T>(mut self, value: &T) -> Result<Self>
+    where
+        C: Codec,
+        T: Serialize,
+    {
+        self.payload = C::encode(value)?;
+        self.content_type = C::CONTENT_TYPE;
+        Ok(self)
     }
 }
 
@@ -285,4 +461,309 @@ mod message_builder_tests {
                 .is_empty()
         );
     }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn send_typed_messages() {
+        use alloc::string::String;
+
+        use serde::{Deserialize, Serialize};
+
+        use crate::{ContentType, Error, Json};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Reading {
+            sensor: String,
+            value: f64,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Alert {
+            #[allow(dead_code)]
+            level: u8,
+        }
+
+        let mut user = make_user().await;
+        let reading = Reading {
+            sensor: "thermometer".into(),
+            value: 21.5,
+        };
+
+        let typed_msg = MessageBuilder::new(&mut user)
+            .with_typed_payload::<Json, _>(&reading)
+            .unwrap()
+            .public()
+            .send()
+            .await
+            .unwrap();
+        let raw_msg = MessageBuilder::new(&mut user)
+            .with_payload("A Raw Payload")
+            .send()
+            .await
+            .unwrap();
+
+        let received_typed_msg = user.receive_message(typed_msg.address()).await.unwrap();
+        let received_raw_msg = user.receive_message(raw_msg.address()).await.unwrap();
+
+        assert_eq!(received_typed_msg.content_type(), ContentType::Json);
+        assert_eq!(received_typed_msg.decode_public::<Reading>().unwrap(), reading);
+        assert!(matches!(
+            received_typed_msg.decode_public::<Alert>(),
+            Err(Error::PayloadDecoding(ContentType::Json, _))
+        ));
+        assert_eq!(received_raw_msg.content_type(), ContentType::Raw);
+        assert!(matches!(
+            received_raw_msg.decode_masked::<Reading>(),
+            Err(Error::UnsupportedContentType(ContentType::Raw))
+        ));
+    }
+
+    #[tokio::test]
+    async fn send_messages_to_several_branches() {
+        use alloc::{rc::Rc, vec::Vec};
+        use core::cell::RefCell;
+
+        use crate::RetryPolicy;
+
+        let topic = "A message topic";
+        let payload = "A Fan-out Payload";
+        let mut user = User::builder()
+            .with_transport(Rc::new(RefCell::new(bucket::Client::new())))
+            .with_identity(Ed25519::from_seed("user seed"))
+            .build();
+        user.create_stream(BASE_BRANCH).await.unwrap();
+        user.new_branch(BASE_BRANCH, topic).await.unwrap();
+
+        let response = MessageBuilder::new(&mut user)
+            .with_topics([BASE_BRANCH, topic])
+            .with_payload(payload)
+            .with_retry_policy(RetryPolicy::Retries(1))
+            .signed()
+            .send_all()
+            .await
+            .unwrap();
+        assert!(response.is_complete());
+        assert_eq!(response.failed().count(), 0);
+
+        for (sent_topic, send_response) in response.sent() {
+            let received_msg = user.receive_message(send_response.address()).await.unwrap();
+            assert!(received_msg.is_signed_packet());
+            assert_eq!(received_msg.topic(), sent_topic);
+            assert_eq!(received_msg.masked_payload().unwrap(), payload.as_bytes());
+        }
+
+        // Nothing is published when one of the branches cannot be published to
+        let base_cursors: Vec<usize> = user
+            .cursors()
+            .filter(|(t, _, _)| **t == Topic::from(BASE_BRANCH))
+            .map(|(_, _, cursor)| cursor)
+            .collect();
+        let result = MessageBuilder::new(&mut user)
+            .with_topics([BASE_BRANCH, "An unknown topic"])
+            .with_payload(payload)
+            .send_all()
+            .await;
+        assert!(result.is_err());
+        assert!(user
+            .cursors()
+            .filter(|(t, _, _)| **t == Topic::from(BASE_BRANCH))
+            .map(|(_, _, cursor)| cursor)
+            .eq(base_cursors));
+    }
+
+    #[tokio::test]
+    async fn send_messages_to_several_branches_reports_the_failed_ones() {
+        use alloc::{boxed::Box, rc::Rc, vec::Vec};
+        use core::{
+            cell::{Cell, RefCell},
+            sync::atomic::{AtomicU64, Ordering},
+        };
+
+        use async_trait::async_trait;
+        use lets::{
+            address::Address,
+            error::{Error as LetsError, Result as LetsResult},
+            message::TransportMessage,
+            transport::Transport,
+        };
+
+        use crate::RetryPolicy;
+
+        /// A transport failing its first sends
+        #[derive(Clone)]
+        struct FlakyTransport {
+            client: Rc<RefCell<bucket::Client>>,
+            failures: Rc<Cell<usize>>,
+        }
+
+        #[async_trait(?Send)]
+        impl Transport<'_> for FlakyTransport {
+            type Msg = TransportMessage;
+            type SendResponse = TransportMessage;
+
+            async fn send_message(&mut self, address: Address, msg: TransportMessage) -> LetsResult<TransportMessage>
+            where
+                Self::Msg: 'async_trait,
+            {
+                if self.failures.get() > 0 {
+                    self.failures.set(self.failures.get() - 1);
+                    return Err(LetsError::AddressError("flaky transport failed to send", address));
+                }
+                self.client.send_message(address, msg).await
+            }
+
+            async fn recv_messages(&mut self, address: Address) -> LetsResult<Vec<TransportMessage>> {
+                self.client.recv_messages(address).await
+            }
+        }
+
+        let topic = "A message topic";
+        let payload = "A Fan-out Payload";
+        let mut user = User::builder()
+            .with_transport(FlakyTransport {
+                client: Rc::new(RefCell::new(bucket::Client::new())),
+                failures: Rc::new(Cell::new(0)),
+            })
+            .with_identity(Ed25519::from_seed("user seed"))
+            .build();
+        user.create_stream(BASE_BRANCH).await.unwrap();
+        user.new_branch(BASE_BRANCH, topic).await.unwrap();
+        let base_cursors: Vec<usize> = user
+            .cursors()
+            .filter(|(t, _, _)| **t == Topic::from(BASE_BRANCH))
+            .map(|(_, _, cursor)| cursor)
+            .collect();
+
+        // The message of the base branch fails to be sent even after being retried, the other one
+        // is sent on the first attempt
+        user.transport_mut().failures.set(2);
+        let response = MessageBuilder::new(&mut user)
+            .with_topics([BASE_BRANCH, topic])
+            .with_payload(payload)
+            .with_retry_policy(RetryPolicy::Retries(1))
+            .send_all()
+            .await
+            .unwrap();
+        assert!(!response.is_complete());
+        assert!(response.outcome(&Topic::from(BASE_BRANCH)).unwrap().is_err());
+        assert!(response.outcome(&Topic::from(topic)).unwrap().is_ok());
+        assert_eq!(
+            response.failed().map(|(t, _)| t.clone()).collect::<Vec<_>>(),
+            [Topic::from(BASE_BRANCH)]
+        );
+        assert!(user
+            .cursors()
+            .filter(|(t, _, _)| **t == Topic::from(BASE_BRANCH))
+            .map(|(_, _, cursor)| cursor)
+            .eq(base_cursors));
+
+        // The failed branch can be published to again
+        let response = MessageBuilder::new(&mut user)
+            .with_topics([BASE_BRANCH])
+            .with_payload(payload)
+            .send_all()
+            .await
+            .unwrap();
+        assert!(response.is_complete());
+
+        // Retries can back off, waiting twice as long before each of them
+        static SLEPT: AtomicU64 = AtomicU64::new(0);
+        user.transport_mut().failures.set(2);
+        let response = MessageBuilder::new(&mut user)
+            .with_topics([BASE_BRANCH])
+            .with_payload(payload)
+            .with_retry_policy(RetryPolicy::Backoff {
+                retries: 2,
+                delay: 10,
+                sleep: |millis| {
+                    SLEPT.fetch_add(millis, Ordering::SeqCst);
+                    Box::pin(async {})
+                },
+            })
+            .send_all()
+            .await
+            .unwrap();
+        assert!(response.is_complete());
+        assert_eq!(SLEPT.load(Ordering::SeqCst), 10 + 20);
+    }
+
+    #[tokio::test]
+    async fn send_messages_to_several_branches_concurrently() {
+        use alloc::{boxed::Box, rc::Rc, vec::Vec};
+        use core::{
+            cell::{Cell, RefCell},
+            task::Poll,
+        };
+
+        use async_trait::async_trait;
+        use futures::future;
+        use lets::{
+            address::Address,
+            error::Result as LetsResult,
+            message::TransportMessage,
+            transport::Transport,
+        };
+
+        /// A transport yielding before each send, and keeping track of the sends in flight
+        #[derive(Clone)]
+        struct SlowTransport {
+            client: Rc<RefCell<bucket::Client>>,
+            in_flight: Rc<Cell<usize>>,
+            max_in_flight: Rc<Cell<usize>>,
+        }
+
+        #[async_trait(?Send)]
+        impl Transport<'_> for SlowTransport {
+            type Msg = TransportMessage;
+            type SendResponse = TransportMessage;
+
+            async fn send_message(&mut self, address: Address, msg: TransportMessage) -> LetsResult<TransportMessage>
+            where
+                Self::Msg: 'async_trait,
+            {
+                self.in_flight.set(self.in_flight.get() + 1);
+                self.max_in_flight.set(self.max_in_flight.get().max(self.in_flight.get()));
+                let mut yielded = false;
+                future::poll_fn(|cx| {
+                    if yielded {
+                        return Poll::Ready(());
+                    }
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                })
+                .await;
+                self.in_flight.set(self.in_flight.get() - 1);
+                self.client.send_message(address, msg).await
+            }
+
+            async fn recv_messages(&mut self, address: Address) -> LetsResult<Vec<TransportMessage>> {
+                self.client.recv_messages(address).await
+            }
+        }
+
+        let topics = ["Branch 1", "Branch 2", "Branch 3"];
+        let mut user = User::builder()
+            .with_transport(SlowTransport {
+                client: Rc::new(RefCell::new(bucket::Client::new())),
+                in_flight: Rc::new(Cell::new(0)),
+                max_in_flight: Rc::new(Cell::new(0)),
+            })
+            .with_identity(Ed25519::from_seed("user seed"))
+            .build();
+        user.create_stream(BASE_BRANCH).await.unwrap();
+        for topic in topics {
+            user.new_branch(BASE_BRANCH, topic).await.unwrap();
+        }
+        assert_eq!(user.transport_mut().max_in_flight.get(), 1);
+
+        let response = MessageBuilder::new(&mut user)
+            .with_topics(topics)
+            .with_payload("A Fan-out Payload")
+            .send_all()
+            .await
+            .unwrap();
+        assert!(response.is_complete());
+        assert_eq!(user.transport_mut().max_in_flight.get(), topics.len());
+    }
 }
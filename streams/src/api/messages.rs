@@ -1,6 +1,6 @@
 // Rust
 use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
-use core::{future::Future, pin::Pin};
+use core::{fmt, future::Future, pin::Pin, str::FromStr};
 
 // 3rd-party
 use anyhow::Result;
@@ -18,15 +18,26 @@ use hashbrown::HashMap;
 use lets::{
     address::{Address, MsgId},
     id::{Identifier, Permissioned},
-    message::{Topic, TransportMessage, HDF},
+    message::{PreparsedMessage, Topic, TransportMessage},
     transport::Transport,
 };
+use spongos::{
+    ddml::{
+        commands::{unwrap, wrap, Commit, Mask, Squeeze},
+        types::{Mac, Size},
+    },
+    KeccakF1600,
+};
 
 // Local
-use crate::api::{
-    message::{Message, MessageContent, Orphan},
-    selector::Selector,
-    user::User,
+use crate::{
+    api::{
+        message::Message,
+        selector::Selector,
+        user::User,
+    },
+    message::cipher_suite::StreamPRP,
+    Error,
 };
 
 /// a [`Stream`] over the messages of the channel pending to be fetch from the transport
@@ -125,26 +136,236 @@ use crate::api::{
 /// suggested that, when suitable, use the methods in [`futures::TryStreamExt`] to make the
 /// error-handling much more ergonomic (with the use of `?`) and shortcircuit the
 /// [`futures::Stream`] on the first error.
-pub struct Messages<'a, T>(PinBoxFut<'a, (MessagesState<'a, T>, Option<Result<Message>>)>);
+///
+/// # Starting Point and Pagination
+/// By default the stream starts from the current cursors of the user. It can instead start from a
+/// given [sequence number of a publisher](`Messages::starting_at()`) or [message
+/// address](`Messages::starting_from()`), and be restricted to the publishers of a
+/// [branch](`Messages::in_branch()`). Messages can be fetched a [page](`Messages::next_page()`) at a
+/// time; each [`Page`] comes with a [`PageToken`] that [resumes](`Messages::resume()`) the traversal
+/// where the page ended, possibly with another user.
+///
+/// # Rejected Messages
+/// Messages that are found but cannot be handled by the user (corrupt messages, invalid
+/// signatures, messages from unknown branches...) are skipped by the stream. To be notified of
+/// them, register a handler with [`Messages::on_rejected()`].
+pub struct Messages<'a, T, F = KeccakF1600> {
+    /// The state of the stream, present while no message is being fetched
+    state: Option<MessagesState<'a, T, F>>,
+    /// The fetching of the next message, if in progress
+    pending: Option<PinBoxFut<'a, (MessagesState<'a, T, F>, Option<Result<Message<F>>>)>>,
+}
 
 type PinBoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 
-struct MessagesState<'a, T> {
-    user: &'a mut User<T>,
+/// Handler for the messages rejected by a [`Messages`] stream
+type RejectionHandler<'a> = Box<dyn FnMut(Rejected) + 'a>;
+
+/// A message skipped by a [`Messages`] stream because the user failed to handle it
+#[derive(Debug)]
+pub struct Rejected {
+    /// The [`Address`] of the message
+    pub address: Address,
+    /// The publisher of the message, `None` if its header could not be parsed
+    pub publisher: Option<Identifier>,
+    /// The type of the message, `None` if its header could not be parsed
+    pub message_type: Option<u8>,
+    /// The reason the message was rejected
+    pub error: Error,
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "message {} was rejected: {}", self.address, self.error)
+    }
+}
+
+/// A page of messages fetched by [`Messages::next_page()`]
+#[derive(Debug)]
+pub struct Page<F = KeccakF1600> {
+    /// The messages of the page, in the order they were yielded by the stream
+    pub messages: Vec<Message<F>>,
+    /// The token to fetch the next page from
+    pub token: PageToken,
+}
+
+/// Position of a [`Messages`] stream, marking the next message to fetch from every publisher
+///
+/// Tokens can be kept as [bytes](`PageToken::to_bytes()`) or as an hexadecimal string, and
+/// [resumed](`Messages::resume()`) later, even by another user of the stream.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct PageToken(Vec<(Topic, Identifier, usize)>);
+
+impl PageToken {
+    /// Returns the sequence number of the next message to fetch from a publisher in a branch
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `publisher`: The [`Identifier`] of the publisher
+    pub fn next_sequence(&self, topic: &Topic, publisher: &Identifier) -> Option<usize> {
+        self.0
+            .iter()
+            .find(|(t, p, _)| t == topic && p == publisher)
+            .map(|(_, _, sequence)| *sequence)
+    }
+
+    /// Encodes the token into bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        // Encoding a token into a growable buffer cannot fail
+        self.wrap(&mut wrap::Context::new(&mut buf)).expect("a page token can always be encoded");
+        buf
+    }
+
+    /// Decodes a token from bytes
+    ///
+    /// # Arguments
+    /// * `bytes`: The bytes produced by [`PageToken::to_bytes()`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut ctx = unwrap::Context::new(bytes);
+        let mut amount_positions = Size::default();
+        ctx.mask(&mut amount_positions).map_err(Error::Spongos)?;
+        let mut positions = Vec::new();
+        for _ in 0..amount_positions.inner() {
+            let mut topic = Topic::default();
+            let mut publisher = Identifier::default();
+            let mut sequence = Size::default();
+            ctx.mask(&mut topic)
+                .and_then(|ctx| ctx.mask(&mut publisher))
+                .and_then(|ctx| ctx.mask(&mut sequence))
+                .map_err(Error::Spongos)?;
+            positions.push((topic, publisher, sequence.inner()));
+        }
+        ctx.commit()
+            .and_then(|ctx| ctx.squeeze(Mac::new(32)))
+            .map_err(|_| Error::PageToken("the token is corrupt"))?;
+        Ok(Self(positions))
+    }
+
+    fn wrap(&self, ctx: &mut wrap::Context<&mut Vec<u8>>) -> spongos::error::Result<()> {
+        ctx.mask(Size::new(self.0.len()))?;
+        for (topic, publisher, sequence) in &self.0 {
+            ctx.mask(topic)?.mask(publisher)?.mask(Size::new(*sequence))?;
+        }
+        ctx.commit()?.squeeze(Mac::new(32))?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for PageToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for PageToken {
+    type Err = Error;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| Error::PageToken("the token is not an hexadecimal string"))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+struct MessagesState<'a, T, F> {
+    user: &'a mut User<T, F>,
     ids_stack: Vec<(Topic, Permissioned<Identifier>, usize)>,
-    msg_queue: HashMap<MsgId, VecDeque<(MsgId, TransportMessage)>>,
     stage: VecDeque<(MsgId, TransportMessage)>,
     successful_round: bool,
+    on_rejected: Option<RejectionHandler<'a>>,
+    /// Sequence of the next message to fetch from a publisher, overriding the cursor of the user.
+    /// Kept up to date as the messages of the publisher are fetched
+    starts: HashMap<(Topic, Identifier), usize>,
+    /// Messages to fetch before any other
+    start_addresses: VecDeque<Address>,
+    /// The branch the stream is restricted to, if any
+    branch: Option<Topic>,
+    /// The [`Selector`] the stream is filtered by, if any, to skip the branches it cannot match
+    selector: Option<Selector>,
 }
 
-impl<'a, T> MessagesState<'a, T> {
-    fn new(user: &'a mut User<T>) -> Self {
+impl<'a, T, F> MessagesState<'a, T, F>
+where
+    F: StreamPRP,
+{
+    fn new(user: &'a mut User<T, F>) -> Self {
         Self {
             user,
             ids_stack: Vec::new(),
-            msg_queue: HashMap::new(),
             stage: VecDeque::new(),
             successful_round: false,
+            on_rejected: None,
+            starts: HashMap::new(),
+            start_addresses: VecDeque::new(),
+            branch: None,
+            selector: None,
+        }
+    }
+
+    /// Returns true if the stream visits the publishers of a branch
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    fn visits(&self, topic: &Topic) -> bool {
+        self.branch.as_ref().map_or(true, |branch| branch == topic)
+            && self
+                .selector
+                .as_ref()
+                .map_or(true, |selector| selector.may_match_branch(topic, self.user.cursor_store()))
+    }
+
+    /// Returns true if the stream yields a message, that is if it matches the selector the stream
+    /// is filtered by, if any
+    ///
+    /// # Arguments
+    /// * `message`: The handled [`Message`]
+    fn selects(&self, message: &Message<F>) -> bool {
+        self.selector
+            .as_ref()
+            .map_or(true, |selector| selector.is_in(message, self.user.cursor_store()))
+    }
+
+    /// Returns the position of the stream, that is the next message to fetch from every publisher
+    /// it visits
+    fn position(&self) -> PageToken {
+        let mut positions: Vec<(Topic, Identifier, usize)> = self
+            .user
+            .cursors()
+            .filter(|(topic, permission, _)| !permission.is_readonly() && self.visits(topic))
+            .map(|(topic, permission, cursor)| {
+                let key = (topic.clone(), permission.identifier().clone());
+                let sequence = self.starts.get(&key).copied().unwrap_or(cursor + 1);
+                (key.0, key.1, sequence)
+            })
+            .collect();
+        // Publishers the user does not know yet keep their start
+        for ((topic, publisher), sequence) in &self.starts {
+            if !positions.iter().any(|(t, p, _)| t == topic && p == publisher) {
+                positions.push((topic.clone(), publisher.clone(), *sequence));
+            }
+        }
+        PageToken(positions)
+    }
+
+    /// Reports a message that could not be handled to the rejection handler, if any
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message
+    /// * `msg`: The [`TransportMessage`] that could not be handled
+    /// * `error`: The [`Error`] raised while handling the message
+    async fn reject(&mut self, address: Address, msg: Option<TransportMessage>, error: Error) {
+        if let Some(on_rejected) = self.on_rejected.as_mut() {
+            let preparsed: Option<PreparsedMessage<F>> = match msg {
+                Some(msg) => msg.parse_header().await.ok(),
+                None => None,
+            };
+            let header = preparsed.as_ref().map(|preparsed| preparsed.header());
+            on_rejected(Rejected {
+                address,
+                publisher: header.map(|header| header.publisher().clone()),
+                message_type: header.map(|header| header.message_type()),
+                error,
+            });
         }
     }
 
@@ -152,52 +373,51 @@ impl<'a, T> MessagesState<'a, T> {
     ///
     /// See [`Messages`] documentation and examples for more details.
     #[async_recursion(?Send)]
-    async fn next(&mut self) -> Option<Result<Message>>
+    async fn next(&mut self) -> Option<Result<Message<F>>>
     where
         T: for<'b> Transport<'b, Msg = TransportMessage>,
     {
-        if let Some((relative_address, binary_msg)) = self.stage.pop_front() {
+        if let Some((address, orphan)) = self.user.pop_resolved_orphan() {
+            // Handle the orphans resolved by the last messages handled before fetching new ones, so
+            // that they come right after their predecessors
+            let rejected_msg = self.on_rejected.as_ref().map(|_| orphan.transport_msg().clone());
+            match self.user.handle_orphan(address, orphan).await {
+                Ok(message) if message.is_orphan() => self.next().await,
+                Ok(message) if !self.selects(&message) => self.next().await,
+                Ok(message) => Some(Ok(message)),
+                Err(e) => {
+                    self.reject(address, rejected_msg, e).await;
+                    self.next().await
+                }
+            }
+        } else if let Some(address) = self.stage.is_empty().then(|| self.start_addresses.pop_front()).flatten() {
+            // Fetch the messages the stream starts from before visiting the publishers
+            match self.user.transport_mut().recv_message(address).await {
+                Ok(msg) => self.stage.push_back((address.relative(), msg)),
+                Err(e) => {
+                    self.reject(address, None, Error::Transport(address, "fetch the start message", e))
+                        .await
+                }
+            }
+            self.next().await
+        } else if let Some((relative_address, binary_msg)) = self.stage.pop_front() {
             // Drain stage if not empty...
             let address = Address::new(self.user.stream_address()?.base(), relative_address);
+            // Keep a copy of the message to describe it in case it is rejected
+            let rejected_msg = self.on_rejected.as_ref().map(|_| binary_msg.clone());
             match self.user.handle_message(address, binary_msg).await {
-                Ok(Message {
-                    header:
-                        HDF {
-                            linked_msg_address: Some(linked_msg_address),
-                            ..
-                        },
-                    content:
-                        MessageContent::Orphan(Orphan {
-                            // Currently ignoring cursor, as `GenericUser::handle_message()` parses the whole binary
-                            // message again this redundancy is acceptable in favour of
-                            // avoiding carrying over the Spongos state within `Message`
-                            message: orphaned_msg,
-                            ..
-                        }),
-                    ..
-                }) => {
-                    // The message might be unreadable because it's predecessor might still be pending
-                    // to be retrieved from the Tangle. We could defensively check if the predecessor
-                    // is already present in the state, but we don't want to couple this iterator to
-                    // a memory-intensive storage. Instead, we take the optimistic approach and store
-                    // the msg for later if the handling has failed.
-                    self.msg_queue
-                        .entry(linked_msg_address)
-                        .or_default()
-                        .push_back((relative_address, orphaned_msg));
-
+                // The message might be unreadable because its predecessor might still be pending to
+                // be retrieved from the Tangle. The user keeps it in its orphan queue and retries it
+                // as soon as its predecessor is handled
+                Ok(message) if message.is_orphan() => self.next().await,
+                // Messages not matching the selector still update the state of the user
+                Ok(message) if !self.selects(&message) => self.next().await,
+                Ok(message) => Some(Ok(message)),
+                // message-Handling errors are a normal execution path, report and skip them
+                Err(e) => {
+                    self.reject(address, rejected_msg, e).await;
                     self.next().await
                 }
-                Ok(message) => {
-                    // Check if message has descendants pending to process and stage them for processing
-                    if let Some(msgs) = self.msg_queue.remove(&message.address().relative()) {
-                        self.stage.extend(msgs);
-                    }
-
-                    Some(Ok(message))
-                }
-                // message-Handling errors are a normal execution path, just skip them
-                Err(_e) => self.next().await,
             }
         } else {
             // Stage is empty, populate it with some more messages
@@ -206,12 +426,21 @@ impl<'a, T> MessagesState<'a, T> {
                 None => {
                     // new round
                     self.successful_round = false;
-                    self.ids_stack = self
+                    let ids: Vec<(Topic, Permissioned<Identifier>, usize)> = self
                         .user
                         .cursors()
-                        .filter(|(_, p, _)| !p.is_readonly())
+                        .filter(|(t, p, _)| !p.is_readonly() && self.visits(t))
                         .map(|(t, p, c)| (t.clone(), p.clone(), c))
                         .collect();
+                    // Start positions override the cursor of the user
+                    self.ids_stack = ids
+                        .into_iter()
+                        .map(|(t, p, c)| {
+                            let start = self.starts.get(&(t.clone(), p.identifier().clone()));
+                            let c = start.map_or(c, |sequence| sequence.saturating_sub(1));
+                            (t, p, c)
+                        })
+                        .collect();
                     self.ids_stack.pop()?
                 }
             };
@@ -221,6 +450,9 @@ impl<'a, T> MessagesState<'a, T> {
 
             match self.user.transport_mut().recv_message(address).await {
                 Ok(msg) => {
+                    if let Some(start) = self.starts.get_mut(&(topic, publisher.identifier().clone())) {
+                        *start = cursor + 2;
+                    }
                     self.stage.push_back((address.relative(), msg));
                     self.successful_round = true;
                     self.next().await
@@ -243,16 +475,174 @@ impl<'a, T> MessagesState<'a, T> {
     }
 }
 
-impl<'a, T> Messages<'a, T>
+impl<'a, T, F> Messages<'a, T, F>
 where
+    F: StreamPRP,
     T: for<'b> Transport<'b, Msg = TransportMessage>,
 {
-    pub(crate) fn new(user: &'a mut User<T>) -> Self {
-        let mut state = MessagesState::new(user);
-        Self(Box::pin(async move {
-            let r = state.next().await;
-            (state, r)
-        }))
+    pub(crate) fn new(user: &'a mut User<T, F>) -> Self {
+        Self {
+            state: Some(MessagesState::new(user)),
+            pending: None,
+        }
+    }
+
+    /// Registers a handler called with every message the stream skips because it could not be
+    /// handled, replacing any previous handler. The handler is not called for messages that are
+    /// yielded, nor for network failures, which are returned by the stream.
+    ///
+    /// # Arguments
+    /// * `handler`: The function to call with each [`Rejected`] message
+    ///
+    /// # Example
+    /// ```
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// # use streams::transport::bucket;
+    /// use futures::TryStreamExt;
+    /// use streams::{id::Ed25519, Result, User};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let transport = Rc::new(RefCell::new(bucket::Client::new()));
+    /// # let mut author = User::builder()
+    /// #     .with_identity(Ed25519::from_seed("author"))
+    /// #     .with_transport(transport.clone())
+    /// #     .build();
+    /// # let announcement = author.create_stream("BASE_BRANCH").await?;
+    /// # let mut subscriber = User::builder()
+    /// #     .with_identity(Ed25519::from_seed("subscriber"))
+    /// #     .with_transport(transport)
+    /// #     .build();
+    /// # subscriber.receive_message(announcement.address()).await?;
+    /// let mut rejected = Vec::new();
+    /// let _messages: Vec<_> = subscriber
+    ///     .messages()
+    ///     .on_rejected(|r| rejected.push(r.address))
+    ///     .try_collect()
+    ///     .await?;
+    /// # assert!(rejected.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched, which cannot happen through the `async`
+    /// methods of the stream.
+    pub fn on_rejected(mut self, handler: impl FnMut(Rejected) + 'a) -> Self {
+        self.state_mut().on_rejected = Some(Box::new(handler));
+        self
+    }
+
+    /// Starts fetching the messages of a publisher in a branch from a given sequence number,
+    /// instead of the cursor of the user. Messages linked to messages the user has not handled
+    /// cannot be read until those are handled.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `publisher`: The [`Identifier`] of the publisher
+    /// * `sequence`: The sequence number of the first message to fetch
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched
+    pub fn starting_at(mut self, topic: impl Into<Topic>, publisher: Identifier, sequence: usize) -> Self {
+        self.state_mut().starts.insert((topic.into(), publisher), sequence);
+        self
+    }
+
+    /// Starts the stream from a given message. The message is fetched before any other, and the
+    /// messages of its publisher are then fetched from it on.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the first message to fetch
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched
+    pub fn starting_from(mut self, address: Address) -> Self {
+        self.state_mut().start_addresses.push_back(address);
+        self
+    }
+
+    /// Restricts the stream to the publishers of a branch
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched
+    pub fn in_branch(mut self, topic: impl Into<Topic>) -> Self {
+        self.state_mut().branch = Some(topic.into());
+        self
+    }
+
+    /// Resumes the stream from the position of a [`PageToken`]. The user must have handled the
+    /// messages the following ones are linked to, for example by fetching the previous pages or by
+    /// being restored from a backup taken after fetching them.
+    ///
+    /// # Arguments
+    /// * `token`: The [`PageToken`] returned with a previous [`Page`]
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched
+    pub fn resume(mut self, token: PageToken) -> Self {
+        let state = self.state_mut();
+        for (topic, publisher, sequence) in token.0 {
+            state.starts.insert((topic, publisher), sequence);
+        }
+        self
+    }
+
+    /// Fetches up to `limit` messages, returning them along with the [`PageToken`] to fetch the
+    /// following ones. The page holds less than `limit` messages when there are no more messages
+    /// available for now.
+    ///
+    /// The position of the stream is only kept by the stream and its tokens: the cursors the user
+    /// had before the page are left where they were, so fetching a page does not mark its messages
+    /// as read. The user still keeps what it needs to read the messages linked to them, and the
+    /// publishers it learns of while fetching the page.
+    ///
+    /// # Arguments
+    /// * `limit`: The maximum amount of messages of the page
+    pub async fn next_page(&mut self, limit: usize) -> Result<Page<F>> {
+        let cursors: Vec<(Topic, Identifier, usize)> = self
+            .state_mut()
+            .user
+            .cursors()
+            .map(|(topic, permission, cursor)| (topic.clone(), permission.identifier().clone(), cursor))
+            .collect();
+        let mut messages = Vec::new();
+        let fetched = loop {
+            if messages.len() == limit {
+                break Ok(());
+            }
+            match self.try_next().await {
+                Ok(Some(message)) => messages.push(message),
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        let state = self.state_mut();
+        let token = state.position();
+        state.user.rewind_cursors(cursors);
+        fetched?;
+        // The stream carries on from the end of the page, as the cursors of the user do not
+        state.starts.extend(
+            token
+                .0
+                .iter()
+                .map(|(topic, publisher, sequence)| ((topic.clone(), publisher.clone()), *sequence)),
+        );
+        Ok(Page { messages, token })
+    }
+
+    /// Returns a mutable reference to the state of the stream
+    ///
+    /// # Panics
+    /// Panics if a message is being fetched
+    fn state_mut(&mut self) -> &mut MessagesState<'a, T, F> {
+        self.state
+            .as_mut()
+            .expect("the state of the stream cannot be accessed while a message is being fetched")
     }
 
     /// "Filter the stream of messages to only those that match the selectors, and return the result
@@ -269,16 +659,9 @@ where
     /// Returns:
     ///
     /// A vector of Messages.
-    pub async fn from(&mut self, selectors: &[Selector]) -> Vec<Message> {
+    pub async fn from(&mut self, selectors: &[Selector]) -> Vec<Message<F>> {
         StreamExt::filter(self, |x| match &x {
-            Ok(m) => {
-                for selector in selectors {
-                    if selector.is(m) {
-                        return future::ready(true);
-                    }
-                }
-                future::ready(false)
-            }
+            Ok(m) => future::ready(selectors.iter().any(|selector| selector.is(m))),
             Err(_) => future::ready(false),
         })
         .map(|x| x.unwrap())
@@ -286,12 +669,57 @@ where
         .await
     }
 
+    /// Filters the stream by a [`Selector`]. Messages are fetched and matched one at a time, so the
+    /// returned [`Stream`] can be dropped as soon as enough messages have been found, and the
+    /// publishers of the branches the selector cannot match are not fetched at all.
+    ///
+    /// ```
+    /// use futures::{StreamExt, TryStreamExt};
+    ///
+    /// use streams::{id::Ed25519, transport::bucket, MessageKind, Result, Selector, User};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut subscriber = User::builder()
+    ///     .with_identity(Ed25519::from_seed("subscriber"))
+    ///     .with_transport(bucket::Client::new())
+    ///     .build();
+    /// // The first 10 tagged packets of the `SENSORS` branch and its sub-branches
+    /// let selector = Selector::Subtree("SENSORS".into()).and(Selector::MessageKind(MessageKind::TaggedPacket));
+    /// let packets: Vec<_> = subscriber.messages().select(selector).take(10).try_collect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `selector`: The [`Selector`] the messages must match
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched
+    pub fn select(mut self, selector: Selector) -> impl Stream<Item = Result<Message<F>>> + 'a {
+        self.state_mut().selector = Some(selector);
+        self
+    }
+
+    /// Fetches the messages matching a [`Selector`] until `limit` of them are found, without
+    /// fetching any message past the last one
+    ///
+    /// # Arguments
+    /// * `selector`: The [`Selector`] the messages must match
+    /// * `limit`: The maximum amount of messages to return
+    ///
+    /// # Panics
+    /// Panics if called while a message is being fetched
+    pub async fn select_up_to(self, selector: Selector, limit: usize) -> Result<Vec<Message<F>>> {
+        self.select(selector).take(limit).try_collect().await
+    }
+
     /// `next` is an async function that returns an Option of a Result of a Message
     ///
     /// Returns:
     ///
     /// A message
-    pub async fn next(&mut self) -> Option<Result<Message>> {
+    pub async fn next(&mut self) -> Option<Result<Message<F>>> {
         StreamExt::next(self).await
     }
 
@@ -304,11 +732,11 @@ where
     /// docs](struct.Messages.html#filter-the-messages-of-a-particular-branch) for more details.
     pub fn filter_branch<Fut>(
         self,
-        predicate: impl FnMut(&Message) -> Fut + 'a,
-    ) -> impl Stream<Item = Result<Message>> + 'a
+        predicate: impl FnMut(&Message<F>) -> Fut + 'a,
+    ) -> impl Stream<Item = Result<Message<F>>> + 'a
     where
         Fut: Future<Output = Result<bool>> + 'a,
-        Self: TryStream<Ok = Message, Error = anyhow::Error>,
+        Self: TryStream<Ok = Message<F>, Error = anyhow::Error>,
     {
         self.try_skip_while(predicate)
             .scan(None, |branch_last_address, msg| {
@@ -327,28 +755,36 @@ where
     }
 }
 
-impl<'a, T> From<&'a mut User<T>> for Messages<'a, T>
+impl<'a, T, F> From<&'a mut User<T, F>> for Messages<'a, T, F>
 where
+    F: StreamPRP,
     T: for<'b> Transport<'b, Msg = TransportMessage>,
 {
-    fn from(user: &'a mut User<T>) -> Self {
+    fn from(user: &'a mut User<T, F>) -> Self {
         Self::new(user)
     }
 }
 
-impl<'a, T> Stream for Messages<'a, T>
+impl<'a, T, F> Stream for Messages<'a, T, F>
 where
+    F: StreamPRP,
     T: for<'b> Transport<'b, Msg = TransportMessage>,
 {
-    type Item = Result<Message>;
+    type Item = Result<Message<F>>;
 
     fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.0.as_mut().poll(ctx) {
-            Poll::Ready((mut state, result)) => {
-                self.set(Messages(Box::pin(async move {
-                    let r = state.next().await;
-                    (state, r)
-                })));
+        if self.pending.is_none() {
+            let mut state = self.state.take().expect("a stream not fetching a message must hold its state");
+            self.pending = Some(Box::pin(async move {
+                let r = state.next().await;
+                (state, r)
+            }));
+        }
+        let pending = self.pending.as_mut().expect("the fetching of the next message has just been set");
+        match pending.as_mut().poll(ctx) {
+            Poll::Ready((state, result)) => {
+                self.pending = None;
+                self.state = Some(state);
                 Poll::Ready(result)
             }
             Poll::Pending => Poll::Pending,
@@ -357,11 +793,12 @@ where
 }
 
 #[cfg(test)]
-mod tests {
-    use alloc::rc::Rc;
+pub(crate) mod tests {
+    use alloc::{rc::Rc, vec::Vec};
     use core::cell::RefCell;
 
-    use lets::{address::Address, id::Ed25519, transport::bucket};
+    use futures::TryStreamExt;
+    use lets::{address::Address, id::Ed25519, message::TopicKey, transport::bucket};
 
     use crate::{
         api::{
@@ -369,12 +806,14 @@ mod tests {
                 Message,
                 MessageContent::{BranchAnnouncement, Keyload, SignedPacket},
             },
+            messages::PageToken,
             user::User,
         },
-        Result,
+        message::message_types,
+        Error, Result,
     };
 
-    type Transport = Rc<RefCell<bucket::Client>>;
+    pub(crate) type Transport = Rc<RefCell<bucket::Client>>;
 
     #[tokio::test]
     async fn messages_awake_pending_messages_link_to_them_even_if_their_content_is_unreadable() -> Result<()> {
@@ -394,7 +833,7 @@ mod tests {
 
         author.sync().await?;
 
-        // This packet has to wait in the `Messages::msg_queue` until `packet` is processed
+        // This packet has to wait in the orphan queue of subscriber2 until `packet` is processed
         let keyload_2 = author.send_keyload_for_all_rw(branch_1).await?;
 
         subscriber1.sync().await?;
@@ -435,9 +874,80 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn messages_report_the_messages_they_reject() -> Result<()> {
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_topic_key(TopicKey::from_seed("topic key"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        // The subscriber holds another topic key, so it cannot map messages to their branch
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_topic_key(TopicKey::from_seed("another topic key"))
+            .with_transport(transport)
+            .build();
+        subscriber.receive_message(announcement.address()).await?;
+        let packet = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        let mut rejected = Vec::new();
+        let msgs: Vec<Message> = subscriber
+            .messages()
+            .on_rejected(|r| rejected.push(r))
+            .try_collect()
+            .await?;
+
+        assert!(msgs.is_empty());
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].address, packet.address());
+        assert_eq!(rejected[0].message_type, Some(message_types::TAGGED_PACKET));
+        assert_eq!(rejected[0].publisher.as_ref(), author.identifier());
+        assert!(matches!(rejected[0].error, Error::UnknownTopic(_)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn messages_can_be_fetched_a_page_at_a_time() -> Result<()> {
+        let (mut author, mut subscriber, _, transport) = author_subscriber_fixture().await?;
+        let mut packets = Vec::new();
+        for _ in 0..5 {
+            packets.push(author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?);
+        }
+
+        let mut pages = subscriber.messages().in_branch("BASE_BRANCH");
+        let first_page = pages.next_page(2).await?;
+        assert_eq!(first_page.messages.len(), 2);
+        assert_eq!(first_page.messages[0].address(), packets[0].address());
+        assert_eq!(first_page.messages[1].address(), packets[1].address());
+        // The stream carries on from the end of the page
+        let next_page = pages.next_page(1).await?;
+        assert_eq!(next_page.messages[0].address(), packets[2].address());
+        drop(pages);
+
+        // Fetching pages leaves the cursors of the subscriber untouched
+        let backup = subscriber.backup("password").await?;
+        let first_message = subscriber.messages().in_branch("BASE_BRANCH").next().await.transpose()?;
+        assert_eq!(first_message.map(|message| message.address()), Some(packets[0].address()));
+
+        // The token alone carries the position, for a user that can read the linked messages
+        let token: PageToken = first_page.token.to_string().parse()?;
+        assert_eq!(token, first_page.token);
+        let mut restored = User::restore(backup, "password", transport).await?;
+        let second_page = restored.messages().resume(token).next_page(10).await?;
+        assert_eq!(second_page.messages.len(), 3);
+        assert_eq!(second_page.messages[0].address(), packets[2].address());
+        assert_eq!(second_page.messages[2].address(), packets[4].address());
+        assert!(PageToken::from_bytes(&[1, 2, 3]).is_err());
+
+        Ok(())
+    }
+
     /// Prepare a simple scenario with an author, a subscriber, a channel announcement and a bucket
     /// transport
-    async fn author_subscriber_fixture() -> Result<(User<Transport>, User<Transport>, Address, Transport)> {
+    pub(crate) async fn author_subscriber_fixture() -> Result<(User<Transport>, User<Transport>, Address, Transport)> {
         let transport = Rc::new(RefCell::new(bucket::Client::new()));
         let mut author = User::builder()
             .with_identity(Ed25519::from_seed("author"))
@@ -449,7 +959,7 @@ mod tests {
         Ok((author, subscriber, announcement.address(), transport))
     }
 
-    async fn subscriber_fixture(
+    pub(crate) async fn subscriber_fixture(
         seed: &str,
         author: &mut User<Transport>,
         announcement_link: Address,
@@ -1,3 +1,11 @@
+/// Keyload history and membership audit trail
+pub(crate) mod audit;
+/// User State Events and Observers
+pub(crate) mod events;
+/// Typed payload encoding
+pub(crate) mod codec;
+/// Publication of a payload to several branches
+pub(crate) mod fan_out;
 /// Identifier Key storage. Used for keeping track of channel state
 mod cursor_store;
 
@@ -11,7 +19,11 @@ pub mod messages;
 pub(crate) mod selector;
 /// Message Wrapper for Sent Messages
 pub(crate) mod send_response;
+/// Branch tree and message DAG introspection
+pub(crate) mod topology;
 /// User Client
 pub mod user;
 /// User Client Builder
 pub mod user_builder;
+/// Stream Integrity Verification
+pub(crate) mod verifier;
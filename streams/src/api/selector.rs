@@ -1,29 +1,109 @@
 // Rust
-use core::{fmt::Formatter, ops::Range};
+use alloc::{boxed::Box, rc::Rc, vec::Vec};
+use core::{
+    fmt::{self, Formatter},
+    ops::Range,
+};
 
 // IOTA
 
 // Streams
-use lets::{address::Address, id::Identifier, message::TopicHash};
+use lets::{
+    address::Address,
+    id::Identifier,
+    message::{Topic, TopicFilter, TopicHash},
+};
 
-use crate::Message;
+use crate::{api::cursor_store::CursorStore, Message};
 
 /// An enum that is used to select messages from a stream.
-#[derive(Clone, PartialEq, Eq, Debug)]
+///
+/// Selectors can be composed with [`Selector::And`], [`Selector::Or`] and [`Selector::Not`]:
+/// ```
+/// use std::rc::Rc;
+///
+/// use streams::{MessageKind, Selector};
+///
+/// // Signed packets of the `SENSORS` branch or any of its sub-branches, with a public payload
+/// // starting with a given prefix
+/// let prefix = b"temperature".to_vec();
+/// let selector = Selector::MessageKind(MessageKind::SignedPacket)
+///     .and(Selector::Subtree("SENSORS".into()))
+///     .and(Selector::PublicPayload(Rc::new(move |payload| payload.starts_with(&prefix))));
+/// ```
+#[derive(Clone)]
 pub enum Selector {
     Address(Address),
     Topic(TopicHash),
+    TopicFilter(TopicFilter),
     Identifier(Identifier),
     Level(Range<usize>),
+    /// Messages of a branch or of any of the branches generated from it. Streams filtered with
+    /// [`Messages::select()`](crate::Messages::select) follow the branches announced in the
+    /// stream, whereas [`Selector::is()`] only knows about the branches whose topic is
+    /// hierarchically nested in the topic of the branch.
+    Subtree(Topic),
+    /// Messages of a given kind
+    MessageKind(MessageKind),
+    /// Messages with a public payload satisfying a predicate. Predicates may capture their
+    /// environment, and compare equal only to the same shared predicate.
+    PublicPayload(Rc<dyn Fn(&[u8]) -> bool>),
+    /// Messages with a masked payload satisfying a predicate. Predicates may capture their
+    /// environment, and compare equal only to the same shared predicate.
+    MaskedPayload(Rc<dyn Fn(&[u8]) -> bool>),
+    /// Messages matching all the selectors
+    And(Vec<Selector>),
+    /// Messages matching at least one of the selectors
+    Or(Vec<Selector>),
+    /// Messages not matching the selector
+    Not(Box<Selector>),
+}
+
+/// The kinds of messages of a stream, as selected by [`Selector::MessageKind`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MessageKind {
+    Announcement,
+    BranchAnnouncement,
+    Keyload,
+    SignedPacket,
+    TaggedPacket,
+    Subscription,
+    Unsubscription,
+    Checkpoint,
+}
+
+impl MessageKind {
+    /// Returns true if the message is of this kind
+    ///
+    /// # Arguments
+    /// * `message`: The message to check
+    pub fn matches<F>(&self, message: &Message<F>) -> bool {
+        match self {
+            MessageKind::Announcement => message.is_announcement(),
+            MessageKind::BranchAnnouncement => message.is_branch_announcement(),
+            MessageKind::Keyload => message.is_keyload(),
+            MessageKind::SignedPacket => message.is_signed_packet(),
+            MessageKind::TaggedPacket => message.is_tagged_packet(),
+            MessageKind::Subscription => message.is_subscription(),
+            MessageKind::Unsubscription => message.is_unsubscription(),
+            MessageKind::Checkpoint => message.is_checkpoint(),
+        }
+    }
 }
 
 impl Selector {
     /// > If the selector is an address, check if the message address is equal to the selector
     /// > address.
     /// If the selector is a topic, check if the message topic is equal to the selector topic. If
+    /// the selector is a topic filter, check if the message topic matches the filter. If
     /// the selector is an identifier, check if the message publisher is equal to the selector
     /// identifier. If the selector is a level, check if the message sequence is contained in
-    /// the selector level
+    /// the selector level. Composed selectors are evaluated lazily, stopping at the first
+    /// selector that decides the result.
+    ///
+    /// Without the branches announced in the stream, a [`Selector::Subtree`] only matches the
+    /// messages of its branch and of the branches whose topic is hierarchically nested in its
+    /// topic.
     ///
     /// # Arguments
     ///
@@ -32,18 +112,191 @@ impl Selector {
     /// Returns:
     ///
     /// A boolean value.
-    pub fn is(&self, message: &Message) -> bool {
+    pub fn is<F>(&self, message: &Message<F>) -> bool {
+        self.matches(message, None)
+    }
+
+    /// Returns true if the message matches the selector, following the branches announced in the
+    /// stream, as recorded in a [`CursorStore`], to tell the sub-branches of a
+    /// [`Selector::Subtree`].
+    ///
+    /// # Arguments
+    /// * `message`: The message to check against the selector.
+    /// * `branches`: The [`CursorStore`] recording the branches of the stream
+    pub(crate) fn is_in<F>(&self, message: &Message<F>, branches: &CursorStore) -> bool {
+        self.matches(message, Some(branches))
+    }
+
+    /// Returns true if the message matches the selector, following the branches recorded in the
+    /// [`CursorStore`], if any.
+    ///
+    /// # Arguments
+    /// * `message`: The message to check against the selector.
+    /// * `branches`: The [`CursorStore`] recording the branches of the stream, if known
+    fn matches<F>(&self, message: &Message<F>, branches: Option<&CursorStore>) -> bool {
         match self {
             Selector::Address(address) => &message.address == address,
             Selector::Topic(topic) => message.header().topic_hash() == topic,
+            Selector::TopicFilter(filter) => filter.matches(message.topic()),
             Selector::Identifier(identifier) => message.header().publisher() == identifier,
             Selector::Level(range) => range.contains(&message.header().sequence()),
+            Selector::Subtree(root) => {
+                root == message.topic()
+                    || branches.map_or_else(
+                        || root.is_ancestor_of(message.topic()),
+                        |branches| branches.is_descendant(message.topic(), root),
+                    )
+            }
+            Selector::MessageKind(kind) => kind.matches(message),
+            Selector::PublicPayload(predicate) => message.public_payload().map_or(false, |payload| predicate(payload)),
+            Selector::MaskedPayload(predicate) => message.masked_payload().map_or(false, |payload| predicate(payload)),
+            Selector::And(selectors) => selectors.iter().all(|selector| selector.matches(message, branches)),
+            Selector::Or(selectors) => selectors.iter().any(|selector| selector.matches(message, branches)),
+            Selector::Not(selector) => !selector.matches(message, branches),
         }
     }
+
+    /// Returns true if messages of a branch might match the selector, or the branch might lead to
+    /// branches whose messages might match it. Branches for which it returns false can be skipped
+    /// altogether when fetching messages.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `branches`: The [`CursorStore`] recording the branches of the stream
+    pub(crate) fn may_match_branch(&self, topic: &Topic, branches: &CursorStore) -> bool {
+        // A branch is announced in the branch it is generated from, which is not known until the
+        // announcement is fetched. Until then, any branch might lead to it
+        let in_subtree_of = |root: &Topic| {
+            !branches.contains_branch(root) || root == topic || branches.is_descendant(topic, root)
+        };
+        match self {
+            Selector::Subtree(root) => in_subtree_of(root),
+            Selector::TopicFilter(filter) if filter.is_exact() => in_subtree_of(&Topic::from(filter.str())),
+            Selector::And(selectors) => selectors
+                .iter()
+                .all(|selector| selector.may_match_branch(topic, branches)),
+            Selector::Or(selectors) => selectors
+                .iter()
+                .any(|selector| selector.may_match_branch(topic, branches)),
+            // Any other selector, including negations, may match messages of any branch
+            _ => true,
+        }
+    }
+
+    /// Combines the selector with another one, selecting the messages matching both
+    ///
+    /// # Arguments
+    /// * `other`: The other [`Selector`]
+    pub fn and(self, other: Selector) -> Selector {
+        match self {
+            Selector::And(mut selectors) => {
+                selectors.push(other);
+                Selector::And(selectors)
+            }
+            selector => Selector::And(vec![selector, other]),
+        }
+    }
+
+    /// Combines the selector with another one, selecting the messages matching any of them
+    ///
+    /// # Arguments
+    /// * `other`: The other [`Selector`]
+    pub fn or(self, other: Selector) -> Selector {
+        match self {
+            Selector::Or(mut selectors) => {
+                selectors.push(other);
+                Selector::Or(selectors)
+            }
+            selector => Selector::Or(vec![selector, other]),
+        }
+    }
+
+    /// Negates the selector, selecting the messages it does not match
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Selector {
+        Selector::Not(Box::new(self))
+    }
 }
 
-impl core::fmt::Display for Selector {
-    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+impl PartialEq for Selector {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Selector::Address(a), Selector::Address(b)) => a == b,
+            (Selector::Topic(a), Selector::Topic(b)) => a == b,
+            (Selector::TopicFilter(a), Selector::TopicFilter(b)) => a == b,
+            (Selector::Identifier(a), Selector::Identifier(b)) => a == b,
+            (Selector::Level(a), Selector::Level(b)) => a == b,
+            (Selector::Subtree(a), Selector::Subtree(b)) => a == b,
+            (Selector::MessageKind(a), Selector::MessageKind(b)) => a == b,
+            (Selector::PublicPayload(a), Selector::PublicPayload(b))
+            | (Selector::MaskedPayload(a), Selector::MaskedPayload(b)) => Rc::ptr_eq(a, b),
+            (Selector::And(a), Selector::And(b)) | (Selector::Or(a), Selector::Or(b)) => a == b,
+            (Selector::Not(a), Selector::Not(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Selector {}
+
+impl fmt::Debug for Selector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Address(address) => f.debug_tuple("Address").field(address).finish(),
+            Selector::Topic(topic) => f.debug_tuple("Topic").field(topic).finish(),
+            Selector::TopicFilter(filter) => f.debug_tuple("TopicFilter").field(filter).finish(),
+            Selector::Identifier(identifier) => f.debug_tuple("Identifier").field(identifier).finish(),
+            Selector::Level(range) => f.debug_tuple("Level").field(range).finish(),
+            Selector::Subtree(root) => f.debug_tuple("Subtree").field(root).finish(),
+            Selector::MessageKind(kind) => f.debug_tuple("MessageKind").field(kind).finish(),
+            // Predicates cannot be displayed
+            Selector::PublicPayload(_) => f.debug_tuple("PublicPayload").field(&format_args!("..")).finish(),
+            Selector::MaskedPayload(_) => f.debug_tuple("MaskedPayload").field(&format_args!("..")).finish(),
+            Selector::And(selectors) => f.debug_tuple("And").field(selectors).finish(),
+            Selector::Or(selectors) => f.debug_tuple("Or").field(selectors).finish(),
+            Selector::Not(selector) => f.debug_tuple("Not").field(selector).finish(),
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", &self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec::Vec};
+
+    use super::{MessageKind, Selector};
+    use crate::{api::messages::tests::author_subscriber_fixture, Result};
+
+    #[tokio::test]
+    async fn selecting_messages_stops_fetching_once_the_limit_is_reached() -> Result<()> {
+        let (mut author, mut subscriber, _, _) = author_subscriber_fixture().await?;
+        // Outside of the subtree, although it matches the other selectors
+        author.send_tagged_packet("BASE_BRANCH", b"selected", b"masked").await?;
+        // The topic of the branch is not nested in the topic of the base branch, the subtree is
+        // followed through its announcement
+        author.new_branch("BASE_BRANCH", "SENSORS").await?;
+        let mut packets = Vec::new();
+        for public in [&b"skipped"[..], b"selected", b"selected later"] {
+            packets.push(author.send_tagged_packet("SENSORS", public, b"masked").await?);
+        }
+
+        let selector = Selector::MessageKind(MessageKind::TaggedPacket)
+            .and(Selector::PublicPayload(Rc::new(|payload| payload.starts_with(b"selected"))))
+            .and(Selector::Subtree("SENSORS".into()));
+        let selected = subscriber.messages().select_up_to(selector, 1).await?;
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].address(), packets[1].address());
+
+        // The message past the limit was not fetched
+        let remaining = subscriber.messages().next_page(10).await?;
+        assert_eq!(remaining.messages.len(), 1);
+        assert_eq!(remaining.messages[0].address(), packets[2].address());
+
+        Ok(())
+    }
+}
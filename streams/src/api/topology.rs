@@ -0,0 +1,458 @@
+// Rust
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+// 3rd-party
+#[cfg(feature = "json")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+// IOTA
+
+// Streams
+use lets::{
+    address::{Address, MsgId},
+    id::{Identifier, PermissionDuration, Permissioned},
+    message::Topic,
+};
+
+// Local
+use crate::message::message_types;
+
+/// Description of a branch of the stream, as known by a [`User`](crate::User)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BranchInfo {
+    /// The [`Topic`] of the branch
+    pub topic: Topic,
+    /// The [`Topic`] of the branch it was generated from, `None` for the base branch
+    pub parent: Option<Topic>,
+    /// The [`Address`] of the message that announced the branch, if known
+    pub announcement: Option<Address>,
+    /// The administrator of the branch, if known
+    pub admin: Option<Identifier>,
+    /// The current [permissions](`Permissioned`) of the branch, with the cursor of each publisher
+    pub permissions: Vec<(Permissioned<Identifier>, usize)>,
+    /// The [`Address`] of the latest message linked in the branch
+    pub latest_link: Address,
+}
+
+/// A message of the stream, as seen by a [`User`](crate::User)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MessageNode {
+    /// The [`MsgId`] of the message
+    pub msgid: MsgId,
+    /// The type of the message
+    pub message_type: u8,
+    /// The sequence number of the message for its publisher
+    pub sequence: usize,
+    /// The [`MsgId`] of the message it is linked to, if any
+    pub linked_msg: Option<MsgId>,
+}
+
+/// The [permission](`Permissioned`) held by an [`Identifier`] in a branch of the stream
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Membership {
+    /// The [`Topic`] of the branch
+    pub topic: Topic,
+    /// The [permission](`Permissioned`) held in the branch
+    pub permission: Permissioned<Identifier>,
+    /// The cursor of the publisher in the branch
+    pub cursor: usize,
+}
+
+impl Membership {
+    /// Returns a reference to the [`Identifier`] of the member
+    pub fn identifier(&self) -> &Identifier {
+        self.permission.identifier()
+    }
+
+    /// Returns the [`PermissionDuration`] after which the write access of the member expires, if
+    /// any. Read and admin permissions, as well as perpetual write access, never expire.
+    pub fn expiry(&self) -> Option<PermissionDuration> {
+        self.permission
+            .duration()
+            .filter(|duration| duration != &PermissionDuration::Perpetual)
+    }
+}
+
+/// The branch tree and the message DAG of a stream, as seen by a [`User`](crate::User)
+///
+/// The topology can be exported to [DOT](`Topology::to_dot`) or, with the `json` feature, to JSON
+/// with [`Topology::to_json`] or any other format through its `Serialize` implementation, to
+/// visualise it with external tools.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Topology {
+    /// The branches of the stream
+    pub branches: Vec<BranchInfo>,
+    /// The messages of the stream seen so far
+    pub messages: Vec<MessageNode>,
+}
+
+impl Topology {
+    /// Renders the topology as a DOT graph. Branches are drawn as boxes linked to the branch they
+    /// were generated from and to their latest message, and messages as ellipses linked to the
+    /// message they are linked to.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        // Writing into a String cannot fail
+        let _ = self.write_dot(&mut dot);
+        dot
+    }
+
+    /// Renders the topology as a JSON document
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a topology can always be serialized")
+    }
+
+    fn write_dot(&self, out: &mut String) -> core::fmt::Result {
+        writeln!(out, "digraph stream {{")?;
+        for branch in &self.branches {
+            write!(out, "  \"branch:")?;
+            write_escaped(out, branch.topic.str())?;
+            write!(out, "\" [shape=box, label=\"")?;
+            write_escaped(out, branch.topic.str())?;
+            writeln!(out, "\"];")?;
+            if let Some(parent) = &branch.parent {
+                write!(out, "  \"branch:")?;
+                write_escaped(out, parent.str())?;
+                write!(out, "\" -> \"branch:")?;
+                write_escaped(out, branch.topic.str())?;
+                writeln!(out, "\";")?;
+            }
+            write!(out, "  \"branch:")?;
+            write_escaped(out, branch.topic.str())?;
+            writeln!(
+                out,
+                "\" -> \"{}\" [style=dashed, label=\"latest\"];",
+                branch.latest_link.relative()
+            )?;
+        }
+        for message in &self.messages {
+            writeln!(
+                out,
+                "  \"{}\" [label=\"{} #{}\"];",
+                message.msgid,
+                message_type_name(message.message_type),
+                message.sequence
+            )?;
+            if let Some(linked_msg) = message.linked_msg {
+                writeln!(out, "  \"{}\" -> \"{}\";", message.msgid, linked_msg)?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for Topology {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut topology = serializer.serialize_struct("Topology", 2)?;
+        topology.serialize_field("branches", &self.branches)?;
+        topology.serialize_field("messages", &self.messages)?;
+        topology.end()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for BranchInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let permissions: Vec<Member> = self
+            .permissions
+            .iter()
+            .map(|(permission, cursor)| Member(permission, *cursor))
+            .collect();
+        let mut branch = serializer.serialize_struct("BranchInfo", 6)?;
+        branch.serialize_field("topic", &Displayed(&self.topic))?;
+        branch.serialize_field("parent", &self.parent.as_ref().map(Displayed))?;
+        branch.serialize_field("announcement", &self.announcement.as_ref().map(Displayed))?;
+        branch.serialize_field("admin", &self.admin.as_ref().map(Displayed))?;
+        branch.serialize_field("permissions", &permissions)?;
+        branch.serialize_field("latest_link", &Displayed(&self.latest_link))?;
+        branch.end()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for MessageNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut message = serializer.serialize_struct("MessageNode", 4)?;
+        message.serialize_field("msgid", &Displayed(&self.msgid))?;
+        message.serialize_field("type", message_type_name(self.message_type))?;
+        message.serialize_field("sequence", &self.sequence)?;
+        message.serialize_field("linked_msg", &self.linked_msg.as_ref().map(Displayed))?;
+        message.end()
+    }
+}
+
+/// A [permission](`Permissioned`) of a branch with the cursor of its publisher, serialized with
+/// the name of its role
+#[cfg(feature = "json")]
+struct Member<'a>(&'a Permissioned<Identifier>, usize);
+
+#[cfg(feature = "json")]
+impl Serialize for Member<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut member = serializer.serialize_struct("Member", 3)?;
+        member.serialize_field("identifier", &Displayed(self.0.identifier()))?;
+        member.serialize_field("role", role_name(self.0))?;
+        member.serialize_field("cursor", &self.1)?;
+        member.end()
+    }
+}
+
+/// A value serialized as the string it is displayed as
+#[cfg(feature = "json")]
+struct Displayed<T>(T);
+
+#[cfg(feature = "json")]
+impl<T: core::fmt::Display> Serialize for Displayed<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+/// Returns a human readable name for a message type
+fn message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        message_types::ANNOUNCEMENT => "announcement",
+        message_types::BRANCH_ANNOUNCEMENT => "branch_announcement",
+        message_types::KEYLOAD => "keyload",
+        message_types::SIGNED_PACKET => "signed_packet",
+        message_types::TAGGED_PACKET => "tagged_packet",
+        message_types::SUBSCRIPTION => "subscription",
+        message_types::UNSUBSCRIPTION => "unsubscription",
+        message_types::CHECKPOINT => "checkpoint",
+        _ => "unknown",
+    }
+}
+
+/// Returns a human readable name for the role of a [`Permissioned`]
+#[cfg(feature = "json")]
+fn role_name(permission: &Permissioned<Identifier>) -> &'static str {
+    match permission {
+        Permissioned::Read(_) => "read",
+        Permissioned::ReadWrite(..) => "read_write",
+        Permissioned::Admin(_) => "admin",
+    }
+}
+
+/// Writes a string escaping the characters that are not allowed within DOT quoted strings
+fn write_escaped(out: &mut String, s: &str) -> core::fmt::Result {
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.push(c),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec, vec::Vec};
+    use core::cell::RefCell;
+
+    use lets::{
+        address::{Address, AppAddr, MsgId},
+        id::{Ed25519, Identifier, Identity, PermissionDuration, Permissioned},
+        message::Topic,
+        transport::bucket,
+    };
+
+    use super::{BranchInfo, MessageNode, Topology};
+    use crate::{
+        api::messages::tests::{author_subscriber_fixture, subscriber_fixture},
+        message::message_types,
+        Result, User,
+    };
+
+    #[test]
+    fn topology_exports_branches_and_messages() {
+        let author = Identity::from(Ed25519::from_seed("author")).identifier().clone();
+        let base_topic = Topic::from("BASE_BRANCH");
+        let appaddr = AppAddr::gen(&author, &base_topic);
+        let announcement = MsgId::gen(appaddr, &author, &base_topic, 1);
+        let branch_announcement = MsgId::gen(appaddr, &author, &base_topic, 2);
+        let child_topic = Topic::from("BASE_BRANCH/\"quoted\"");
+
+        let topology = Topology {
+            branches: vec![
+                BranchInfo {
+                    topic: base_topic.clone(),
+                    parent: None,
+                    announcement: Some(Address::new(appaddr, announcement)),
+                    admin: Some(author.clone()),
+                    permissions: vec![(Permissioned::Admin(author.clone()), 2)],
+                    latest_link: Address::new(appaddr, branch_announcement),
+                },
+                BranchInfo {
+                    topic: child_topic,
+                    parent: Some(base_topic),
+                    announcement: Some(Address::new(appaddr, branch_announcement)),
+                    admin: Some(author.clone()),
+                    permissions: vec![(Permissioned::Admin(author), 1)],
+                    latest_link: Address::new(appaddr, branch_announcement),
+                },
+            ],
+            messages: vec![
+                MessageNode {
+                    msgid: announcement,
+                    message_type: message_types::ANNOUNCEMENT,
+                    sequence: 0,
+                    linked_msg: None,
+                },
+                MessageNode {
+                    msgid: branch_announcement,
+                    message_type: message_types::BRANCH_ANNOUNCEMENT,
+                    sequence: 2,
+                    linked_msg: Some(announcement),
+                },
+            ],
+        };
+
+        let dot = topology.to_dot();
+        assert!(dot.starts_with("digraph stream {"));
+        assert!(dot.contains("\"branch:BASE_BRANCH\" -> \"branch:BASE_BRANCH/\\\"quoted\\\"\";"));
+        assert!(dot.contains(&alloc::format!("\"{}\" -> \"{}\";", branch_announcement, announcement)));
+
+        #[cfg(feature = "json")]
+        {
+            let json = topology.to_json();
+            assert!(json.starts_with("{\"branches\":[{\"topic\":\"BASE_BRANCH\",\"parent\":null"));
+            assert!(json.contains("\"topic\":\"BASE_BRANCH/\\\"quoted\\\"\",\"parent\":\"BASE_BRANCH\""));
+            assert!(json.contains("\"type\":\"branch_announcement\",\"sequence\":2"));
+            assert!(json.ends_with("]}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn users_tracking_the_message_dag_export_the_topology_of_the_stream() -> Result<()> {
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_message_dag()
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let branch_announcement = author.new_branch("BASE_BRANCH", "CHILD").await?;
+        let packet = author.send_tagged_packet("CHILD", b"public", b"masked").await?;
+
+        let topology = author.topology();
+        assert_eq!(topology.branches.len(), 2);
+        let child = topology
+            .branches
+            .iter()
+            .find(|branch| branch.topic == Topic::from("CHILD"))
+            .unwrap();
+        assert_eq!(child.parent, Some(Topic::from("BASE_BRANCH")));
+        assert_eq!(child.announcement, Some(branch_announcement.address()));
+        let mut messages: Vec<(MsgId, u8, Option<MsgId>)> = topology
+            .messages
+            .iter()
+            .map(|message| (message.msgid, message.message_type, message.linked_msg))
+            .collect();
+        messages.sort_by_key(|(_, message_type, _)| *message_type);
+        let mut expected = vec![
+            (announcement.address().relative(), message_types::ANNOUNCEMENT, None),
+            (
+                branch_announcement.address().relative(),
+                message_types::BRANCH_ANNOUNCEMENT,
+                Some(announcement.address().relative()),
+            ),
+            (
+                packet.address().relative(),
+                message_types::TAGGED_PACKET,
+                Some(branch_announcement.address().relative()),
+            ),
+        ];
+        expected.sort_by_key(|(_, message_type, _)| *message_type);
+        assert_eq!(messages, expected);
+        assert!(topology.to_dot().contains("\"branch:BASE_BRANCH\" -> \"branch:CHILD\";"));
+        #[cfg(feature = "json")]
+        assert!(topology.to_json().contains("\"type\":\"tagged_packet\""));
+
+        // The message DAG is not backed up, restored users track it again once asked to
+        let backup = author.backup("password").await?;
+        let mut restored = User::restore(backup, "password", transport).await?;
+        assert_eq!(restored.message_dag().count(), 0);
+        restored.track_message_dag();
+        let next_packet = restored.send_tagged_packet("CHILD", b"public", b"masked").await?;
+        let tracked: Vec<MsgId> = restored.message_dag().map(|message| message.msgid).collect();
+        assert_eq!(tracked, vec![next_packet.address().relative()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn members_and_memberships_report_the_permissions_of_each_branch() -> Result<()> {
+        let (mut author, mut subscriber_a, announcement_link, transport) = author_subscriber_fixture().await?;
+        let subscriber_b = subscriber_fixture("subscriber_b", &mut author, announcement_link, transport).await?;
+        let author_id = author.identifier().unwrap().clone();
+        let id_a = subscriber_a.identifier().unwrap().clone();
+        let id_b = subscriber_b.identifier().unwrap().clone();
+        let expiry = PermissionDuration::Unix(1_000);
+
+        author
+            .send_keyload(
+                "BASE_BRANCH",
+                vec![
+                    Permissioned::ReadWrite(&id_a, expiry),
+                    Permissioned::ReadWrite(&id_b, PermissionDuration::Perpetual),
+                ],
+                vec![],
+            )
+            .await?;
+        author.new_branch("BASE_BRANCH", "CHILD_BRANCH").await?;
+        // Subscriber B is left out of the child branch, so it can only read it
+        author
+            .send_keyload("CHILD_BRANCH", vec![Permissioned::ReadWrite(&id_a, expiry)], vec![])
+            .await?;
+        subscriber_a.sync().await?;
+
+        let base_branch = Topic::from("BASE_BRANCH");
+        let child_branch = Topic::from("CHILD_BRANCH");
+        let members = subscriber_a.members(&base_branch)?;
+        let permissions: Vec<_> = members.iter().map(|member| member.permission.clone()).collect();
+        assert_eq!(permissions.len(), 3);
+        assert!(permissions.contains(&Permissioned::Admin(author_id.clone())));
+        assert!(permissions.contains(&Permissioned::ReadWrite(id_a.clone(), expiry)));
+        assert!(permissions.contains(&Permissioned::ReadWrite(id_b.clone(), PermissionDuration::Perpetual)));
+        assert!(permissions.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(members.iter().all(|member| member.topic == base_branch));
+        assert!(subscriber_a.members(&Topic::from("UNKNOWN_BRANCH")).is_err());
+
+        // Only timed write permissions expire
+        let member_expiry = |id: &Identifier| members.iter().find(|member| member.identifier() == id).unwrap().expiry();
+        assert_eq!(member_expiry(&id_a), Some(expiry));
+        assert_eq!(member_expiry(&id_b), None);
+        assert_eq!(member_expiry(&author_id), None);
+
+        let memberships_a = subscriber_a.memberships(&id_a);
+        assert_eq!(memberships_a.len(), 2);
+        assert_eq!(memberships_a[0].topic, base_branch);
+        assert_eq!(memberships_a[1].topic, child_branch);
+        assert!(memberships_a.iter().all(|membership| membership.expiry() == Some(expiry)));
+
+        let memberships_b = subscriber_a.memberships(&id_b);
+        assert_eq!(memberships_b.len(), 2);
+        assert_eq!(
+            memberships_b[0].permission,
+            Permissioned::ReadWrite(id_b.clone(), PermissionDuration::Perpetual)
+        );
+        assert_eq!(memberships_b[1].topic, child_branch);
+        assert_eq!(memberships_b[1].permission, Permissioned::Read(id_b));
+        assert_eq!(memberships_b[1].expiry(), None);
+
+        let memberships_author = subscriber_a.memberships(&author_id);
+        assert!(memberships_author
+            .iter()
+            .all(|membership| membership.permission.is_admin() && membership.expiry().is_none()));
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 use alloc::{
     borrow::ToOwned,
     boxed::Box,
+    collections::VecDeque,
     format,
     string::{String, ToString},
     vec::Vec,
@@ -21,16 +22,20 @@ use lets::{
     address::{Address, AppAddr, MsgId},
     id::{Identifier, Identity, PermissionDuration, Permissioned, Psk, PskId},
     message::{
-        ContentSizeof, ContentUnwrap, ContentWrap, Message as LetsMessage, PreparsedMessage, Topic, TopicHash,
+        ContentUnwrap, ContentWrap, Message as LetsMessage, PreparsedMessage, Topic, TopicFilter, TopicHash, TopicKey,
         TransportMessage, HDF, PCF,
     },
     transport::Transport,
 };
 use spongos::{
     ddml::{
-        commands::{sizeof, unwrap, wrap, Absorb, Commit, Mask, Squeeze},
+        commands::{
+            unwrap::{self, Limits},
+            wrap, Absorb, Commit, Mask, Squeeze,
+        },
+        io,
         modifiers::External,
-        types::{Mac, Maybe, NBytes, Size, Uint8},
+        types::{Bytes, Mac, Maybe, NBytes, Size, Uint8},
     },
     error::{Error as SpongosError, Result as SpongosResult},
     KeccakF1600, Spongos, SpongosRng,
@@ -39,23 +44,41 @@ use spongos::{
 // Local
 use crate::{
     api::{
-        cursor_store::CursorStore, message::Message, message_builder::MessageBuilder, messages::Messages,
-        send_response::SendResponse, user_builder::UserBuilder,
+        audit::{self, AuditLog, KeyloadRecord},
+        codec::ContentType,
+        cursor_store::CursorStore,
+        events::{Event, Observer},
+        fan_out::{FanOutResponse, RetryPolicy},
+        message::Message,
+        message_builder::MessageBuilder,
+        messages::Messages,
+        send_response::SendResponse,
+        topology::{BranchInfo, Membership, MessageNode, Topology},
+        user_builder::UserBuilder,
     },
     message::{
-        announcement, branch_announcement, keyload, message_types, signed_packet, subscription, tagged_packet,
-        unsubscription,
+        announcement, branch_announcement,
+        checkpoint::{self, CheckpointHistory},
+        cipher_suite::{CipherSuite, StreamPRP},
+        keyload, message_types, signed_packet, subscription, tagged_packet, unsubscription,
     },
     Error, Result,
 };
 
+#[cfg(feature = "std")]
+extern crate std;
+
 const ANN_MESSAGE_NUM: usize = 0; // Announcement is always the first message of authors
 const SUB_MESSAGE_NUM: usize = 0; // Subscription is always the first message of subscribers
 const INIT_MESSAGE_NUM: usize = 1; // First non-reserved message number
+/// Version of the format of backups, to be increased whenever the backed up `State` changes. Backups
+/// of other versions are refused instead of being misread. Version 1 is the unversioned format,
+/// before topic keys, audit logs and orphans were backed up.
+const BACKUP_VERSION: u8 = 2;
 
 /// The state of a user, mapping publisher cursors and link states for message processing.
-#[derive(PartialEq, Eq, Default)]
-struct State {
+#[derive(Default)]
+struct State<F> {
     /// Users' [`Identity`] information, contains keys and logic for signing and verification.
     ///
     /// None if the user is not created with an identity
@@ -84,7 +107,7 @@ struct State {
     /// Mapping of message links ([`MsgId`]) and [`Spongos`] states. Messages are built from the
     /// [`Spongos`] state of a previous message. If the state for a link is not stored, then a
     /// message cannot be formed or processed.
-    spongos_store: HashMap<MsgId, Spongos>,
+    spongos_store: HashMap<MsgId, Spongos<F>>,
 
     base_branch: Topic,
 
@@ -96,15 +119,95 @@ struct State {
 
     /// List of known branch topics.
     topics: HashSet<Topic>,
+
+    /// Secret shared among stream members used to key the [topic hashes](`TopicHash`) of message
+    /// headers and to mask branch names in branch announcements.
+    ///
+    /// None if the stream uses plain topic hashes.
+    topic_key: Option<TopicKey>,
+
+    /// Messages of the stream seen so far, mapped by their [`MsgId`]. Used to introspect the message
+    /// DAG of the stream. Not backed up.
+    ///
+    /// None if the user does not track the message DAG.
+    message_dag: Option<HashMap<MsgId, MessageNode>>,
+
+    /// Log of the keyloads seen in each branch, with the membership changes they applied.
+    ///
+    /// None if the user does not keep an audit log.
+    audit_log: Option<AuditLog>,
+
+    /// Messages that could not be handled because the message they are linked to has not been
+    /// handled yet, mapped by the [`MsgId`] of that message. They are retried as soon as it is
+    /// handled.
+    orphans: HashMap<MsgId, Vec<(MsgId, PreparsedMessage<F>)>>,
+
+    /// Orphans whose linked message has been handled, mapped by their [`MsgId`], pending to be
+    /// handled and yielded by [`Messages`].
+    resolved_orphans: VecDeque<(MsgId, PreparsedMessage<F>)>,
+
+    /// Clock used to timestamp the entries of the audit log. Not backed up.
+    ///
+    /// None to use the system clock, which only exists with the `std` feature.
+    clock: Option<fn() -> u64>,
+
+    /// Limits on the resources spent unwrapping a received message. Not backed up.
+    ///
+    /// None to use the [default limits](User::default_unwrap_limits).
+    unwrap_limits: Option<Limits>,
+}
+
+/// Two states are equal if the parts that are backed up are equal. The message DAG, the clock and
+/// the unwrap limits are left out so that a restored [`User`] equals the original one.
+impl<F: PartialEq> PartialEq for State<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.user_id == other.user_id
+            && self.stream_address == other.stream_address
+            && self.author_identifier == other.author_identifier
+            && self.cursor_store == other.cursor_store
+            && self.psk_store == other.psk_store
+            && self.subscribers == other.subscribers
+            && self.spongos_store == other.spongos_store
+            && self.base_branch == other.base_branch
+            && self.lean == other.lean
+            && self.topics == other.topics
+            && self.topic_key == other.topic_key
+            && self.audit_log == other.audit_log
+            && self.orphans == other.orphans
+            && self.resolved_orphans == other.resolved_orphans
+    }
 }
 
+impl<F: Eq> Eq for State<F> {}
+
 /// Public `API` Client for participation in a `Streams` channel.
-pub struct User<T> {
+///
+/// Messages are processed with the permutation `F` of the [cipher suite](`crate::CipherSuite`) of
+/// the stream, [`KeccakF1600`] unless another one is picked with
+/// [`UserBuilder::with_cipher_suite`].
+pub struct User<T, F = KeccakF1600> {
     /// A transport client for sending and receiving messages.
     transport: T,
     /// The internal [state](`State`) of the user, containing message state mappings and publisher
     /// cursors for message processing.
-    state: State,
+    state: State<F>,
+    /// The [observers](`Observer`) notified of the [events](`Event`) applied to the state.
+    observers: Vec<Box<dyn Observer + Send>>,
+}
+
+/// The optional features of a [`User`], set through the [`UserBuilder`]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct UserOptions {
+    /// The secret used to key topic hashes, if the stream uses keyed topics
+    pub(crate) topic_key: Option<TopicKey>,
+    /// If true, the [`User`] tracks the message DAG of the stream
+    pub(crate) message_dag: bool,
+    /// If true, the [`User`] keeps an audit log of the keyloads it sees
+    pub(crate) audit: bool,
+    /// The clock used to timestamp the audit log, `None` to use the system clock
+    pub(crate) clock: Option<fn() -> u64>,
+    /// The limits on the resources spent unwrapping a message, `None` to use the default limits
+    pub(crate) unwrap_limits: Option<Limits>,
 }
 
 impl User<()> {
@@ -114,7 +217,10 @@ impl User<()> {
     }
 }
 
-impl<T> User<T> {
+impl<T, F> User<T, F>
+where
+    F: StreamPRP,
+{
     /// Creates a new [`User`] with the provided configurations.
     ///
     /// # Arguments
@@ -122,7 +228,14 @@ impl<T> User<T> {
     /// * `psks`: A list of trusted pre shared keys.
     /// * `transport`: The transport to use for sending and receiving messages.
     /// * `lean`: If true, the client will store only required message states.
-    pub(crate) fn new<Psks>(user_id: Option<Identity>, psks: Psks, transport: T, lean: bool) -> Self
+    /// * `options`: The optional features of the client.
+    pub(crate) fn new<Psks>(
+        user_id: Option<Identity>,
+        psks: Psks,
+        transport: T,
+        lean: bool,
+        options: UserOptions,
+    ) -> Self
     where
         Psks: IntoIterator<Item = (PskId, Psk)>,
     {
@@ -133,6 +246,13 @@ impl<T> User<T> {
         psks.into_iter().for_each(|(pskid, psk)| {
             psk_store.insert(pskid, psk);
         });
+        let UserOptions {
+            topic_key,
+            message_dag,
+            audit,
+            clock,
+            unwrap_limits,
+        } = options;
 
         Self {
             transport,
@@ -147,7 +267,15 @@ impl<T> User<T> {
                 base_branch: Default::default(),
                 lean,
                 topics: Default::default(),
+                topic_key,
+                message_dag: message_dag.then(HashMap::new),
+                audit_log: audit.then(AuditLog::new),
+                orphans: Default::default(),
+                resolved_orphans: VecDeque::new(),
+                clock,
+                unwrap_limits,
             },
+            observers: Vec::new(),
         }
     }
 
@@ -214,13 +342,34 @@ impl<T> User<T> {
         self.state.topics.iter()
     }
 
+    /// Returns an iterator over the known branch [topics](`Topic`) matching a [`TopicFilter`]
+    ///
+    /// # Arguments
+    /// * `filter`: The [`TopicFilter`] to match the topics against
+    pub fn topics_matching<'a>(&'a self, filter: &'a TopicFilter) -> impl Iterator<Item = &'a Topic> + 'a {
+        self.topics().filter(move |topic| filter.matches(topic))
+    }
+
     /// Iterates through known topics, returning the [`Topic`] that matches the [`TopicHash`]
     /// provided if any
     ///
     /// # Arguments
     /// * `hash`: The [`TopicHash`] from a message header
     pub(crate) fn topic_by_hash(&self, hash: &TopicHash) -> Option<Topic> {
-        self.topics().find(|t| &TopicHash::from(*t) == hash).cloned()
+        self.topics().find(|t| &self.topic_hash(t) == hash).cloned()
+    }
+
+    /// Returns the [`TopicHash`] used in message headers for the provided [`Topic`]. If the
+    /// [`User`] holds a [`TopicKey`], the hash is keyed with it. Use this method to build
+    /// [`Selector::Topic`](crate::Selector::Topic) selectors on streams with keyed topics.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    pub fn topic_hash(&self, topic: &Topic) -> TopicHash {
+        match &self.state.topic_key {
+            Some(topic_key) => TopicHash::keyed(topic, topic_key),
+            None => topic.into(),
+        }
     }
 
     /// Returns true if [`User`] lean state configuration is true
@@ -228,12 +377,32 @@ impl<T> User<T> {
         self.state.lean
     }
 
+    /// Returns the [`CursorStore`] recording the branches known to the [`User`]. Used by
+    /// [`Messages`] streams to follow the branches of the stream.
+    pub(crate) fn cursor_store(&self) -> &CursorStore {
+        &self.state.cursor_store
+    }
+
     /// Returns an iterator over [`CursorStore`], producing tuples of [`Topic`], [`Permissioned`]
     /// [`Identifier`], and the cursor. Used by [`Messages`] streams to find next messages.
     pub(crate) fn cursors(&self) -> impl Iterator<Item = (&Topic, &Permissioned<Identifier>, usize)> + '_ {
         self.state.cursor_store.cursors()
     }
 
+    /// Moves the cursors of some publishers back to previous positions, keeping their permissions.
+    /// Used by [`Messages`] streams to fetch pages of messages without moving the cursors of the
+    /// [`User`].
+    ///
+    /// # Arguments
+    /// * `cursors`: The [`Topic`], [`Identifier`] and cursor of every publisher to rewind
+    pub(crate) fn rewind_cursors(&mut self, cursors: Vec<(Topic, Identifier, usize)>) {
+        for (topic, publisher, cursor) in cursors {
+            if let Some(permission) = self.state.cursor_store.get_permission(&topic, &publisher).cloned() {
+                self.state.cursor_store.insert_cursor(&topic, permission, cursor);
+            }
+        }
+    }
+
     /// Returns an iterator over a [`Topic`] mapped branch in [`CursorStore`], producing tuples of
     /// [`Permissioned`][`Identifier`] and a cursor. Used to carry permissions forward through
     /// branch declarations. Returns an error if the [`Topic`] is not found in store.
@@ -273,7 +442,7 @@ impl<T> User<T> {
     /// * `msg_address`: The [`Address`] of the message that we're storing the [`Spongos`] for.
     /// * `spongos`: The [`Spongos`] state to be stored.
     /// * `linked_msg_address`: The address of the message that the spongos is linked to.
-    fn store_spongos(&mut self, msg_address: MsgId, spongos: Spongos, linked_msg_address: MsgId) {
+    fn store_spongos(&mut self, msg_address: MsgId, spongos: Spongos<F>, linked_msg_address: MsgId) {
         let is_stream_address = self
             .stream_address()
             .map_or(false, |stream_address| stream_address.relative() == linked_msg_address);
@@ -285,25 +454,372 @@ impl<T> User<T> {
         self.state.spongos_store.insert(msg_address, spongos);
     }
 
+    /// Records a message in the message DAG of the stream, if the [`User`] tracks it.
+    ///
+    /// # Arguments:
+    /// * `msgid`: The [`MsgId`] of the message
+    /// * `message_type`: The type of the message
+    /// * `sequence`: The sequence number of the message for its publisher
+    /// * `linked_msg`: The [`MsgId`] of the message it is linked to, if any
+    fn record_message(&mut self, msgid: MsgId, message_type: u8, sequence: usize, linked_msg: Option<MsgId>) {
+        if let Some(message_dag) = self.state.message_dag.as_mut() {
+            message_dag.insert(
+                msgid,
+                MessageNode {
+                    msgid,
+                    message_type,
+                    sequence,
+                    linked_msg,
+                },
+            );
+        }
+    }
+
+    /// Returns the description of a known branch, including where it was generated from, its
+    /// administrator and its current permissions. Returns `None` if the branch is not known.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    pub fn branch(&self, topic: &Topic) -> Option<BranchInfo> {
+        let base_address = self.stream_address()?.base();
+        let cursor_store = &self.state.cursor_store;
+        let permissions: Vec<(Permissioned<Identifier>, usize)> = cursor_store
+            .cursors_by_topic(topic)?
+            .map(|(permission, cursor)| (permission.clone(), *cursor))
+            .collect();
+        let admin = permissions
+            .iter()
+            .find(|(permission, _)| permission.is_admin())
+            .map(|(permission, _)| permission.identifier().clone());
+        Some(BranchInfo {
+            topic: topic.clone(),
+            parent: cursor_store.get_parent(topic).cloned(),
+            announcement: cursor_store
+                .get_announcement(topic)
+                .map(|msgid| Address::new(base_address, msgid)),
+            admin,
+            permissions,
+            latest_link: Address::new(base_address, cursor_store.get_latest_link(topic)?),
+        })
+    }
+
+    /// Returns the description of every known branch. See [`User::branch`].
+    pub fn branches(&self) -> Vec<BranchInfo> {
+        self.topics().filter_map(|topic| self.branch(topic)).collect()
+    }
+
+    /// Returns an iterator over the messages of the stream seen so far by the [`User`]. Messages
+    /// are only tracked once enabled with [`UserBuilder::with_message_dag`] or
+    /// [`User::track_message_dag`].
+    pub fn message_dag(&self) -> impl Iterator<Item = &MessageNode> {
+        self.state.message_dag.iter().flat_map(|message_dag| message_dag.values())
+    }
+
+    /// Starts tracking the message DAG of the stream, from the next message sent or handled. The
+    /// DAG is not backed up, so tracking must be started again on restored users.
+    pub fn track_message_dag(&mut self) {
+        self.state.message_dag.get_or_insert_with(HashMap::new);
+    }
+
+    /// Returns the branch tree and the message DAG of the stream as seen by the [`User`], ready to
+    /// be exported to DOT or JSON.
+    pub fn topology(&self) -> Topology {
+        Topology {
+            branches: self.branches(),
+            messages: self.message_dag().copied().collect(),
+        }
+    }
+
+    /// Returns the members of a branch: every [permission](`Permissioned`) held in the branch, with
+    /// the cursor of each publisher. Members are sorted by permission. Errors if the branch is not
+    /// known.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    pub fn members(&self, topic: &Topic) -> Result<Vec<Membership>> {
+        let mut members: Vec<Membership> = self
+            .cursors_by_topic(topic)?
+            .map(|(permission, cursor)| Membership {
+                topic: topic.clone(),
+                permission: permission.clone(),
+                cursor: *cursor,
+            })
+            .collect();
+        members.sort_by(|a, b| a.permission.cmp(&b.permission));
+        Ok(members)
+    }
+
+    /// Returns the branches an [`Identifier`] is a member of, with the [permission](`Permissioned`)
+    /// it holds and its cursor in each of them. Memberships are sorted by [`Topic`].
+    ///
+    /// # Arguments
+    /// * `id`: The [`Identifier`] of the member
+    pub fn memberships(&self, id: &Identifier) -> Vec<Membership> {
+        let mut memberships: Vec<Membership> = self
+            .cursors()
+            .filter(|(_, permission, _)| permission.identifier() == id)
+            .map(|(topic, permission, cursor)| Membership {
+                topic: topic.clone(),
+                permission: permission.clone(),
+                cursor,
+            })
+            .collect();
+        memberships.sort_by(|a, b| a.topic.str().cmp(b.topic.str()));
+        memberships
+    }
+
+    /// Returns the [`AuditLog`] of the keyloads seen by the [`User`], if it keeps one.
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.state.audit_log.as_ref()
+    }
+
+    /// Sets the clock used to timestamp the entries of the [`AuditLog`]. The clock is not backed
+    /// up, so it must be set again on restored users that do not use the system clock.
+    ///
+    /// # Arguments
+    /// * `clock`: A function returning the current time
+    pub fn set_clock(&mut self, clock: fn() -> u64) {
+        self.state.clock = Some(clock);
+    }
+
+    /// The default [`Limits`] on the resources spent unwrapping a message. They are generous for the
+    /// messages of a stream, and keep a malicious message from exhausting the memory of the [`User`]
+    /// before it is authenticated.
+    pub fn default_unwrap_limits() -> Limits {
+        Limits::new()
+            .with_max_allocation(8 * 1024 * 1024)
+            .with_max_bytes_len(4 * 1024 * 1024)
+            .with_max_repeated(65_536)
+            .with_max_depth(16)
+    }
+
+    /// Returns the [`Limits`] applied to every message the [`User`] unwraps
+    pub fn unwrap_limits(&self) -> Limits {
+        self.state.unwrap_limits.unwrap_or_else(Self::default_unwrap_limits)
+    }
+
+    /// Sets the [`Limits`] applied to every message the [`User`] unwraps. The limits are not backed
+    /// up, so they must be set again on restored users that do not use the default limits.
+    ///
+    /// # Arguments
+    /// * `limits`: The limits on the resources spent unwrapping a message
+    pub fn set_unwrap_limits(&mut self, limits: Limits) {
+        self.state.unwrap_limits = Some(limits);
+    }
+
+    /// Returns an iterator over the orphans kept by the [`User`], as pairs of the [`Address`] of
+    /// the orphan and the [`MsgId`] of the missing message it is linked to. Orphans are handled
+    /// again by [`Messages`] as soon as the message they are linked to is handled.
+    pub fn orphans(&self) -> impl Iterator<Item = (Address, MsgId)> + '_ {
+        let base_address = self.stream_address().map(|address| address.base()).unwrap_or_default();
+        self.state.orphans.iter().flat_map(move |(linked_msg, orphans)| {
+            orphans
+                .iter()
+                .map(move |(msgid, _)| (Address::new(base_address, *msgid), *linked_msg))
+        })
+    }
+
+    /// Discards an orphan kept by the [`User`]. Returns true if the orphan was found.
+    ///
+    /// # Arguments
+    /// * `msgid`: The [`MsgId`] of the orphan to discard
+    pub fn remove_orphan(&mut self, msgid: MsgId) -> bool {
+        let mut found = false;
+        self.state.orphans.retain(|_, orphans| {
+            let amount = orphans.len();
+            orphans.retain(|(orphan_msgid, _)| orphan_msgid != &msgid);
+            found |= orphans.len() != amount;
+            !orphans.is_empty()
+        });
+        let amount = self.state.resolved_orphans.len();
+        self.state
+            .resolved_orphans
+            .retain(|(orphan_msgid, _)| orphan_msgid != &msgid);
+        found || self.state.resolved_orphans.len() != amount
+    }
+
+    /// Retries all the orphans kept by the [`User`], regardless of whether the message they are
+    /// linked to has been handled, and returns the ones that could be handled, along with the
+    /// orphans they resolved in turn. Orphans that fail to be handled are kept: the ones whose
+    /// linked message is missing wait for it, the others are reported by [`Messages`] through its
+    /// [rejection handler](`Messages::on_rejected`).
+    pub async fn retry_orphans(&mut self) -> Vec<Message<F>> {
+        let base_address = match self.stream_address() {
+            Some(stream_address) => stream_address.base(),
+            None => return Vec::new(),
+        };
+        let orphans = core::mem::take(&mut self.state.orphans);
+        let mut handled = Vec::new();
+        for (linked_msg, orphans) in orphans {
+            for (orphan_msgid, orphan) in orphans {
+                let address = Address::new(base_address, orphan_msgid);
+                match self.handle_orphan(address, orphan.clone()).await {
+                    Ok(message) if message.is_orphan() => (),
+                    Ok(message) => handled.push(message),
+                    Err(_) => self
+                        .state
+                        .orphans
+                        .entry(linked_msg)
+                        .or_default()
+                        .push((orphan_msgid, orphan)),
+                }
+            }
+        }
+        // Orphans resolved along the way are returned here rather than through `Messages`
+        let mut failed = Vec::new();
+        while let Some((address, orphan)) = self.pop_resolved_orphan() {
+            match self.handle_orphan(address, orphan.clone()).await {
+                Ok(message) if message.is_orphan() => (),
+                Ok(message) => handled.push(message),
+                Err(_) => failed.push((address.relative(), orphan)),
+            }
+        }
+        self.state.resolved_orphans.extend(failed);
+        handled
+    }
+
+    /// Returns the time to timestamp the keyload being processed with in the [`AuditLog`], or
+    /// `None` if the user does not keep one. Fails if there is no clock to read the time from.
+    fn audit_timestamp(&self) -> Result<Option<u64>> {
+        if self.state.audit_log.is_none() {
+            return Ok(None);
+        }
+        self.state
+            .clock
+            .map_or_else(audit::system_time, |clock| Some(clock()))
+            .map(Some)
+            .ok_or(Error::NoClock)
+    }
+
+    /// Appends a keyload to the [`AuditLog`], if the [`User`] keeps one. Must be called once the
+    /// permissions of the branch have been updated with the keyload: the publishers that held write
+    /// access to the branch before the keyload and do not anymore are recorded as demoted.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch of the keyload
+    /// * `previous`: The [permissions](`Permissioned`) held in the branch before the keyload
+    /// * `record`: The [`KeyloadRecord`] of the keyload, timestamped with [`User::audit_timestamp`]
+    fn audit_keyload(&mut self, topic: &Topic, previous: Vec<Permissioned<Identifier>>, mut record: KeyloadRecord) {
+        let cursor_store = &self.state.cursor_store;
+        record.demoted = previous
+            .into_iter()
+            .filter(|permission| !permission.is_readonly())
+            .filter(|permission| {
+                cursor_store
+                    .get_permission(topic, permission.identifier())
+                    .map_or(true, |current| current.is_readonly())
+            })
+            .map(|permission| permission.identifier().clone())
+            .collect();
+        if let Some(audit_log) = self.state.audit_log.as_mut() {
+            audit_log.record(topic.clone(), record);
+        }
+    }
+
     /// Store a new subscriber [`Identifier`] in state. Returns true if subscriber was not present.
     pub fn add_subscriber(&mut self, subscriber: Identifier) -> bool {
-        self.state.subscribers.insert(subscriber)
+        let added = self.state.subscribers.insert(subscriber.clone());
+        if added {
+            self.emit(Event::SubscriberAdded(subscriber));
+        }
+        added
     }
 
     /// Remove a subscriber [`Identifier`] from state. Returns true if the subscriber was present.
     pub fn remove_subscriber(&mut self, id: &Identifier) -> bool {
-        self.state.subscribers.remove(id)
+        let removed = self.state.subscribers.remove(id);
+        if removed {
+            self.emit(Event::SubscriberRemoved(id.clone()));
+        }
+        removed
     }
 
     /// Store a new [Pre-Shared Key](`Psk`) in state. Returns true if [`Psk`] was not present.
     pub fn add_psk(&mut self, psk: Psk) -> bool {
-        self.state.psk_store.insert(psk.to_pskid(), psk).is_none()
+        let pskid = psk.to_pskid();
+        let added = self.state.psk_store.insert(pskid, psk).is_none();
+        if added {
+            self.emit(Event::PskAdded(pskid));
+        }
+        added
     }
 
     /// Remove a [`Psk`] from state by its [identifier](`PskId`). Returns true if the [`Psk`] was
     /// present.
     pub fn remove_psk(&mut self, pskid: PskId) -> bool {
-        self.state.psk_store.remove(&pskid).is_some()
+        let removed = self.state.psk_store.remove(&pskid).is_some();
+        if removed {
+            self.emit(Event::PskRemoved(pskid));
+        }
+        removed
+    }
+
+    /// Registers an [`Observer`] to be notified of the [events](`Event`) applied to the state of
+    /// the [`User`], whether they come from sent or received messages, or from direct calls to the
+    /// [`User`] `API`. Observers are not backed up.
+    ///
+    /// # Arguments
+    /// * `observer`: The [`Observer`] to register
+    pub fn add_observer<O>(&mut self, observer: O)
+    where
+        O: Observer + Send + 'static,
+    {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Notifies the registered [observers](`Observer`) of an [`Event`]
+    ///
+    /// # Arguments
+    /// * `event`: The [`Event`] that has been applied
+    fn emit(&mut self, event: Event) {
+        for observer in &mut self.observers {
+            observer.notify(&event);
+        }
+    }
+
+    /// Notifies the registered [observers](`Observer`) of the permissions of a branch that differ
+    /// from the provided previous permissions
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `previous`: The [permissions](`Permissioned`) of the branch before the change
+    fn emit_permission_changes(&mut self, topic: &Topic, previous: &[Permissioned<Identifier>]) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let current: Vec<Permissioned<Identifier>> = self
+            .state
+            .cursor_store
+            .cursors_by_topic(topic)
+            .into_iter()
+            .flatten()
+            .map(|(permission, _)| permission.clone())
+            .collect();
+        let mut events = Vec::new();
+        for permission in &current {
+            let old = previous.iter().find(|p| p.identifier() == permission.identifier());
+            if old != Some(permission) {
+                events.push(Event::PermissionChanged {
+                    topic: topic.clone(),
+                    identifier: permission.identifier().clone(),
+                    previous: old.cloned(),
+                    current: Some(permission.clone()),
+                });
+            }
+        }
+        for permission in previous {
+            if !current.iter().any(|p| p.identifier() == permission.identifier()) {
+                events.push(Event::PermissionChanged {
+                    topic: topic.clone(),
+                    identifier: permission.identifier().clone(),
+                    previous: Some(permission.clone()),
+                    current: None,
+                });
+            }
+        }
+        for event in events {
+            self.emit(event);
+        }
     }
 
     /// Sets the latest message link for a specified branch. If the branch does not exist, it is
@@ -324,18 +840,99 @@ impl<T> User<T> {
         self.state.cursor_store.get_latest_link(topic)
     }
 
-    /// Parse and process a [`TransportMessage`] dependent on its type.
+    /// Parse and process a [`TransportMessage`] dependent on its type. If the message is an
+    /// orphan, it is kept in the orphan queue; otherwise, the orphans waiting for it are queued to be
+    /// handled again.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message to process
+    /// * `msg`: The raw [`TransportMessage`]
+    pub(crate) async fn handle_message(&mut self, address: Address, msg: TransportMessage) -> Result<Message<F>> {
+        let message = self.handle_single_message(address, msg).await?;
+        self.update_orphans(&message);
+        Ok(message)
+    }
+
+    /// Process an orphan again, resuming its unwrapping right after its header. If it is still an
+    /// orphan, it is kept in the orphan queue; otherwise, the orphans waiting for it are queued to
+    /// be handled again.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the orphan
+    /// * `orphan`: The [`PreparsedMessage`] of the orphan
+    pub(crate) async fn handle_orphan(&mut self, address: Address, orphan: PreparsedMessage<F>) -> Result<Message<F>> {
+        let message = self.handle_preparsed_message(address, orphan).await?;
+        self.update_orphans(&message);
+        Ok(message)
+    }
+
+    /// Keeps an orphan [`Message`] in the orphan queue until the message it is linked to is
+    /// handled, or queues the orphans waiting for a handled [`Message`] to be handled again.
+    ///
+    /// # Arguments
+    /// * `message`: The handled [`Message`]
+    fn update_orphans(&mut self, message: &Message<F>) {
+        match (message.header().linked_msg_address(), message.as_orphan()) {
+            (Some(linked_msg), Some(orphan)) => {
+                let msgid = message.address().relative();
+                let orphans = self.state.orphans.entry(linked_msg).or_default();
+                if !orphans.iter().any(|(orphan_msgid, _)| orphan_msgid == &msgid) {
+                    orphans.push((msgid, orphan.preparsed.clone()));
+                }
+            }
+            (_, None) => {
+                if let Some(orphans) = self.state.orphans.remove(&message.address().relative()) {
+                    self.state.resolved_orphans.extend(orphans);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns the next orphan whose linked message has been handled, if any, along with its
+    /// [`Address`]. It is to be handled again with [`User::handle_orphan`].
+    pub(crate) fn pop_resolved_orphan(&mut self) -> Option<(Address, PreparsedMessage<F>)> {
+        let base_address = self.stream_address()?.base();
+        self.state
+            .resolved_orphans
+            .pop_front()
+            .map(|(msgid, orphan)| (Address::new(base_address, msgid), orphan))
+    }
+
+    /// Parse and process a [`TransportMessage`] dependent on its type, regardless of the orphan
+    /// queue.
     ///
     /// # Arguments
     /// * `address`: The [`Address`] of the message to process
     /// * `msg`: The raw [`TransportMessage`]
-    pub(crate) async fn handle_message(&mut self, address: Address, msg: TransportMessage) -> Result<Message> {
+    async fn handle_single_message(&mut self, address: Address, msg: TransportMessage) -> Result<Message<F>> {
+        // The rest of the header can only be unwrapped with the cipher suite of the message
+        let cipher_suite = CipherSuite::of(address, &msg)?;
+        if cipher_suite != F::CIPHER_SUITE {
+            return Err(Error::CipherSuite(address, cipher_suite, F::CIPHER_SUITE));
+        }
         let preparsed = msg
-            .parse_header()
+            .parse_header_with_limits(self.unwrap_limits())
             .await
             .map_err(|e| Error::Unwrapping("header", address, e))?;
+        self.handle_preparsed_message(address, preparsed).await
+    }
 
-        match preparsed.header().message_type() {
+    /// Process a [`PreparsedMessage`] dependent on its type, resuming the unwrapping right after
+    /// its header. Orphans are handled again through this path once the message they are linked to
+    /// is known.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message to process
+    /// * `preparsed`: The [`PreparsedMessage`] to process
+    async fn handle_preparsed_message(
+        &mut self,
+        address: Address,
+        preparsed: PreparsedMessage<F>,
+    ) -> Result<Message<F>> {
+        // Orphans may have been preparsed before the current limits were set, or restored from a backup
+        let preparsed = preparsed.with_limits(self.unwrap_limits());
+        let message = match preparsed.header().message_type() {
             message_types::ANNOUNCEMENT => self.handle_announcement(address, preparsed).await,
             message_types::BRANCH_ANNOUNCEMENT => self.handle_branch_announcement(address, preparsed).await,
             message_types::SUBSCRIPTION => self.handle_subscription(address, preparsed).await,
@@ -343,8 +940,20 @@ impl<T> User<T> {
             message_types::KEYLOAD => self.handle_keyload(address, preparsed).await,
             message_types::SIGNED_PACKET => self.handle_signed_packet(address, preparsed).await,
             message_types::TAGGED_PACKET => self.handle_tagged_packet(address, preparsed).await,
+            message_types::CHECKPOINT => self.handle_checkpoint(address, preparsed).await,
             unknown => Err(Error::MessageTypeUnknown(unknown)),
+        }?;
+
+        if !message.is_orphan() {
+            let header = message.header();
+            self.record_message(
+                address.relative(),
+                header.message_type(),
+                header.sequence(),
+                header.linked_msg_address(),
+            );
         }
+        Ok(message)
     }
 
     /// Processes an announcement message, binding a [`User`] to the stream announced in the
@@ -353,7 +962,7 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_announcement(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_announcement(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
         // Check Topic
         let publisher = preparsed.header().publisher().clone();
 
@@ -367,7 +976,13 @@ impl<T> User<T> {
         let topic = message.payload().content().topic();
         // Insert new branch into store
         self.state.cursor_store.new_branch(topic.clone());
+        self.state.cursor_store.set_branch_origin(topic, None, address.relative());
         self.state.topics.insert(topic.clone());
+        self.emit(Event::BranchCreated {
+            topic: topic.clone(),
+            parent: None,
+            announcement: address,
+        });
 
         // When handling an announcement it means that no cursors have been stored, as no topics are
         // known yet. The message must be unwrapped to retrieve the initial topic before storing cursors
@@ -387,7 +1002,7 @@ impl<T> User<T> {
         self.state.base_branch = topic.clone();
         self.state.stream_address = Some(address);
 
-        Ok(Message::from_lets_message(address, message))
+        Ok(Message::from_lets_message(address, self.base_branch().clone(), message))
     }
 
     /// Processes a branch announcement message, creating a new branch in [`CursorStore`], carrying
@@ -396,7 +1011,11 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_branch_announcement(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_branch_announcement(
+        &mut self,
+        address: Address,
+        preparsed: PreparsedMessage<F>,
+    ) -> Result<Message<F>> {
         // Retrieve header values
         let prev_topic = self
             .topic_by_hash(preparsed.header().topic_hash())
@@ -426,10 +1045,12 @@ impl<T> User<T> {
                 // Spongos must be copied because wrapping mutates it
                 spongos
             } else {
-                return Ok(Message::orphan(address, preparsed));
+                return Ok(Message::orphan(address, prev_topic, preparsed));
             }
         };
-        let branch_announcement = branch_announcement::Unwrap::new(&mut linked_msg_spongos);
+        // Key must be copied to release the borrow on state while the message is alive
+        let topic_key = self.state.topic_key;
+        let branch_announcement = branch_announcement::Unwrap::new(&mut linked_msg_spongos, topic_key.as_ref());
         let (message, spongos) = preparsed
             .unwrap(branch_announcement)
             .await
@@ -440,7 +1061,15 @@ impl<T> User<T> {
         self.store_spongos(address.relative(), spongos, linked_msg_address);
         // Insert new branch into store
         self.state.cursor_store.new_branch(new_topic.clone());
+        self.state
+            .cursor_store
+            .set_branch_origin(new_topic, Some(prev_topic.clone()), address.relative());
         self.state.topics.insert(new_topic.clone());
+        self.emit(Event::BranchCreated {
+            topic: new_topic.clone(),
+            parent: Some(prev_topic.clone()),
+            announcement: address,
+        });
         // Collect permissions from previous branch and clone them into new branch
         let prev_permissions = self
             .cursors_by_topic(&prev_topic)?
@@ -449,11 +1078,12 @@ impl<T> User<T> {
         for id in prev_permissions {
             self.state.cursor_store.insert_cursor(new_topic, id, INIT_MESSAGE_NUM);
         }
+        self.emit_permission_changes(new_topic, &[]);
 
         // Update branch links
         self.set_latest_link(new_topic.clone(), address.relative());
 
-        Ok(Message::from_lets_message(address, message))
+        Ok(Message::from_lets_message(address, prev_topic, message))
     }
 
     /// Processes a [`User`] subscription message, storing the subscriber [`Identifier`].
@@ -461,7 +1091,7 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_subscription(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_subscription(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
         // Cursor is not stored, as cursor is only tracked for subscribers with write permissions
 
         // Unwrap message
@@ -474,7 +1104,7 @@ impl<T> User<T> {
                 // Spongos must be copied because wrapping mutates it
                 spongos
             } else {
-                return Ok(Message::orphan(address, preparsed));
+                return Ok(Message::orphan(address, self.base_branch().clone(), preparsed));
             }
         };
         let user_ke_sk = &self
@@ -497,7 +1127,7 @@ impl<T> User<T> {
         let subscriber_identifier = message.payload().content().subscriber_identifier();
         self.add_subscriber(subscriber_identifier.clone());
 
-        Ok(Message::from_lets_message(address, message))
+        Ok(Message::from_lets_message(address, self.base_branch().clone(), message))
     }
 
     /// Processes a [`User`] unsubscription message, removing the subscriber [`Identifier`] from
@@ -506,7 +1136,7 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_unsubscription(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_unsubscription(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
         // Cursor is not stored, as user is unsubscribing
 
         // Unwrap message
@@ -519,7 +1149,7 @@ impl<T> User<T> {
                 // Spongos must be cloned because wrapping mutates it
                 *spongos
             } else {
-                return Ok(Message::orphan(address, preparsed));
+                return Ok(Message::orphan(address, self.base_branch().clone(), preparsed));
             }
         };
         let unsubscription = unsubscription::Unwrap::new(&mut linked_msg_spongos);
@@ -534,7 +1164,7 @@ impl<T> User<T> {
         // Store message content into stores
         self.remove_subscriber(message.payload().content().subscriber_identifier());
 
-        Ok(Message::from_lets_message(address, message))
+        Ok(Message::from_lets_message(address, self.base_branch().clone(), message))
     }
 
     /// Processes a keyload message, updating store to include the contained list of
@@ -544,8 +1174,9 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_keyload(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_keyload(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
         let stream_address = self.stream_address().ok_or(Error::NoStream("handling a keyload"))?;
+        let timestamp = self.audit_timestamp()?;
 
         let topic = self
             .topic_by_hash(preparsed.header().topic_hash())
@@ -566,7 +1197,7 @@ impl<T> User<T> {
         // handling the message
         self.state
             .cursor_store
-            .insert_cursor(&topic, Permissioned::Admin(publisher), preparsed.header().sequence());
+            .insert_cursor(&topic, Permissioned::Admin(publisher.clone()), preparsed.header().sequence());
 
         // Unwrap message
         // Ok to unwrap since an author identifier is set at the same time as the stream address
@@ -601,6 +1232,8 @@ impl<T> User<T> {
             .cursors_by_topic(&topic)?
             .map(|(perm, cursor)| (perm.clone(), *cursor))
             .collect();
+        let previous_permissions: Vec<Permissioned<Identifier>> =
+            stored_subscribers.iter().map(|(perm, _)| perm.clone()).collect();
 
         for (perm, cursor) in stored_subscribers {
             if !(perm.identifier() == author_identifier
@@ -621,8 +1254,128 @@ impl<T> User<T> {
             }
         }
 
+        let granted = subscribers.to_vec();
+        let psks = message.payload().content().psks.clone();
         // Have to make message before setting branch links due to immutable borrow in keyload::unwrap
-        let final_message = Message::from_lets_message(address, message);
+        let final_message = Message::from_lets_message(address, topic.clone(), message);
+        self.emit_permission_changes(&topic, &previous_permissions);
+        if let Some(timestamp) = timestamp {
+            let record = KeyloadRecord {
+                timestamp,
+                address,
+                admin: publisher,
+                subscribers: granted,
+                psks,
+                demoted: Vec::new(),
+            };
+            self.audit_keyload(&topic, previous_permissions, record);
+        }
+        // Update branch links
+        self.set_latest_link(topic, address.relative());
+        Ok(final_message)
+    }
+
+    /// Processes a checkpoint message. If the [`User`] is a recipient of the checkpoint, the
+    /// members of the branch and their cursors are taken from its snapshot, so that the messages
+    /// published before it do not need to be fetched. Cursors already known are never moved back.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message to be processed
+    /// * `preparsed`: The [`PreparsedMessage`] to be processed
+    async fn handle_checkpoint(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
+        let stream_address = self.stream_address().ok_or(Error::NoStream("handling a checkpoint"))?;
+
+        let topic = self
+            .topic_by_hash(preparsed.header().topic_hash())
+            .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
+        let publisher = preparsed.header().publisher().clone();
+        // Confirm checkpoint came from administrator
+        if !self
+            .state
+            .cursor_store
+            .get_permission(&topic, &publisher)
+            .ok_or(Error::NoCursor(topic.clone()))?
+            .is_admin()
+        {
+            return Err(Error::WrongRole("admin", publisher, "receive checkpoint"));
+        }
+        // From the point of view of cursor tracking, the message exists, regardless of the validity or
+        // accessibility to its content. Therefore we must update the cursor of the publisher before
+        // handling the message
+        self.state
+            .cursor_store
+            .insert_cursor(&topic, Permissioned::Admin(publisher.clone()), preparsed.header().sequence());
+        let linked_msg_address = preparsed
+            .header()
+            .linked_msg_address()
+            .ok_or(Error::NotLinked("checkpoint", address))?;
+
+        // Unwrap message
+        // Like keyloads, checkpoints attach to the stream announcement spongos
+        let mut announcement_spongos = self
+            .state
+            .spongos_store
+            .get(&stream_address.relative())
+            .copied()
+            .expect("a subscriber that has received an stream announcement must keep its spongos in store");
+        let checkpoint = checkpoint::Unwrap::new(
+            &mut announcement_spongos,
+            self.state.user_id.as_ref(),
+            &publisher,
+            &self.state.psk_store,
+        );
+        let (message, spongos) = preparsed
+            .unwrap(checkpoint)
+            .await
+            .map_err(|e| Error::Unwrapping("checkpoint", address, e))?;
+
+        // Store spongos
+        self.state.spongos_store.insert(address.relative(), spongos);
+
+        let content = message.payload().content();
+        let history_spongos = content.history_spongos().to_vec();
+        let readable = content.is_readable();
+        let members = content.cursors().to_vec();
+        let previous_permissions: Vec<Permissioned<Identifier>> =
+            self.cursors_by_topic(&topic)?.map(|(perm, _)| perm.clone()).collect();
+        if readable {
+            // Members of the branch left out of the checkpoint lose their write access, as with a keyload
+            let author_identifier = self.state.author_identifier.clone();
+            for permission in &previous_permissions {
+                if Some(permission.identifier()) != author_identifier.as_ref()
+                    && !members
+                        .iter()
+                        .any(|(member, _)| member.identifier() == permission.identifier())
+                {
+                    let cursor = self
+                        .state
+                        .cursor_store
+                        .get_cursor(&topic, permission.identifier())
+                        .unwrap_or(INIT_MESSAGE_NUM);
+                    self.state.cursor_store.insert_cursor(
+                        &topic,
+                        Permissioned::Read(permission.identifier().clone()),
+                        cursor,
+                    );
+                }
+            }
+            for (member, cursor) in members {
+                let known = self.state.cursor_store.get_cursor(&topic, member.identifier());
+                if member.is_readonly() && known.is_none() {
+                    continue;
+                }
+                let cursor = known.map_or(cursor, |known| known.max(cursor));
+                self.state.cursor_store.insert_cursor(&topic, member, cursor);
+            }
+            // Keep the messages published before the checkpoint that stay readable, so that the
+            // messages linked to them can still be read
+            for (msgid, spongos) in history_spongos {
+                self.state.spongos_store.entry(msgid).or_insert(spongos);
+            }
+        }
+
+        let final_message = Message::from_lets_message(address, topic.clone(), message);
+        self.emit_permission_changes(&topic, &previous_permissions);
         // Update branch links
         self.set_latest_link(topic, address.relative());
         Ok(final_message)
@@ -634,7 +1387,7 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_signed_packet(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_signed_packet(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
         let topic = self
             .topic_by_hash(preparsed.header().topic_hash())
             .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
@@ -662,12 +1415,12 @@ impl<T> User<T> {
                 // Spongos must be copied because wrapping mutates it
                 spongos
             } else {
-                return Ok(Message::orphan(address, preparsed));
+                return Ok(Message::orphan(address, topic, preparsed));
             }
         };
         let signed_packet = signed_packet::Unwrap::new(&mut linked_msg_spongos);
-        let (message, spongos) = preparsed
-            .unwrap(signed_packet)
+        let (message, spongos, transport_msg) = preparsed
+            .unwrap_in_place(signed_packet)
             .await
             .map_err(|e| Error::Unwrapping("signed packet", address, e))?;
 
@@ -675,8 +1428,8 @@ impl<T> User<T> {
         self.store_spongos(address.relative(), spongos, linked_msg_address);
 
         // Store message content into stores
-        self.set_latest_link(topic, address.relative());
-        Ok(Message::from_lets_message(address, message))
+        self.set_latest_link(topic.clone(), address.relative());
+        Ok(Message::from_lets_message_in_place(address, topic, message, transport_msg))
     }
 
     /// Processes a tagged packet message, retrieving the public and masked payloads.
@@ -684,7 +1437,7 @@ impl<T> User<T> {
     /// # Arguments:
     /// * `address`: The [`Address`] of the message to be processed
     /// * `preparsed`: The [`PreparsedMessage`] to be processed
-    async fn handle_tagged_packet(&mut self, address: Address, preparsed: PreparsedMessage) -> Result<Message> {
+    async fn handle_tagged_packet(&mut self, address: Address, preparsed: PreparsedMessage<F>) -> Result<Message<F>> {
         let topic = self
             .topic_by_hash(preparsed.header().topic_hash())
             .ok_or(Error::UnknownTopic(*preparsed.header().topic_hash()))?;
@@ -712,12 +1465,12 @@ impl<T> User<T> {
                 // Spongos must be copied because wrapping mutates it
                 spongos
             } else {
-                return Ok(Message::orphan(address, preparsed));
+                return Ok(Message::orphan(address, topic, preparsed));
             }
         };
         let tagged_packet = tagged_packet::Unwrap::new(&mut linked_msg_spongos);
-        let (message, spongos) = preparsed
-            .unwrap(tagged_packet)
+        let (message, spongos, transport_msg) = preparsed
+            .unwrap_in_place(tagged_packet)
             .await
             .map_err(|e| Error::Unwrapping("tagged packet", address, e))?;
 
@@ -725,9 +1478,9 @@ impl<T> User<T> {
         self.store_spongos(address.relative(), spongos, linked_msg_address);
 
         // Store message content into stores
-        self.set_latest_link(topic, address.relative());
+        self.set_latest_link(topic.clone(), address.relative());
 
-        Ok(Message::from_lets_message(address, message))
+        Ok(Message::from_lets_message_in_place(address, topic, message, transport_msg))
     }
 
     /// Creates an encrypted, serialised representation of a [`User`] `State` for backup and
@@ -739,32 +1492,53 @@ impl<T> User<T> {
     where
         P: AsRef<[u8]>,
     {
-        let mut ctx = sizeof::Context::new();
-        ctx.sizeof(&self.state).await.map_err(Error::Spongos)?;
-        let buf_size = ctx.finalize() + 32; // State + Mac Size
-
-        let mut buf = vec![0; buf_size];
-
-        let mut ctx = wrap::Context::new(&mut buf[..]);
-        let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
-        ctx.absorb(External::new(&NBytes::new(key)))
-            .map_err(Error::Spongos)?
-            .commit()
-            .map_err(Error::Spongos)?
-            .squeeze(&Mac::new(32))
-            .map_err(Error::Spongos)?;
-        ctx.wrap(&mut self.state).await.map_err(Error::Spongos)?;
-        assert!(
-            ctx.stream().is_empty(),
-            "Missmatch between buffer size expected by SizeOf ({buf_size}) and actual size of Wrap ({})",
-            ctx.stream().len()
-        );
-
+        let mut buf = Vec::new();
+        self.wrap_backup(&mut wrap::Context::new(&mut buf), pwd).await?;
         Ok(buf)
     }
 
-    /// Restore a [`User`] from an encrypted binary stream using the provided password and transport
-    /// client.
+    /// Writes an encrypted, serialised representation of a [`User`] `State` for backup and recovery
+    /// to a writer, as it is produced, instead of holding the whole backup in memory.
+    ///
+    /// # Arguments
+    /// * `pwd`: The password to encrypt the `State` with
+    /// * `writer`: The writer the backup is written to, such as a file or a socket
+    #[cfg(feature = "std")]
+    pub async fn backup_to<P, W>(&mut self, pwd: P, writer: W) -> Result<()>
+    where
+        P: AsRef<[u8]>,
+        W: std::io::Write,
+    {
+        let mut ctx = wrap::Context::new(io::Writer::new(writer));
+        self.wrap_backup(&mut ctx, pwd).await?;
+        ctx.stream_mut().finish().map_err(Error::Spongos)
+    }
+
+    /// Encrypts and serialises the `State` into the provided [`wrap::Context`]
+    ///
+    /// # Arguments
+    /// * `ctx`: The context to wrap the `State` into
+    /// * `pwd`: The password to encrypt the `State` with
+    async fn wrap_backup<OS, P>(&mut self, ctx: &mut wrap::Context<OS, F>, pwd: P) -> Result<()>
+    where
+        OS: io::OStream,
+        P: AsRef<[u8]>,
+    {
+        let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
+        ctx.absorb(Uint8::new(BACKUP_VERSION))
+            .map_err(Error::Spongos)?
+            .absorb(External::new(&NBytes::new(key)))
+            .map_err(Error::Spongos)?
+            .commit()
+            .map_err(Error::Spongos)?
+            .squeeze(&Mac::new(32))
+            .map_err(Error::Spongos)?;
+        ctx.wrap(&mut self.state).await.map_err(Error::Spongos)?;
+        Ok(())
+    }
+
+    /// Restore a [`User`] from an encrypted binary stream using the provided password and transport
+    /// client.
     ///
     /// # Arguments
     /// * `backup`: Encrypted binary stream of backed up `State`.
@@ -775,7 +1549,52 @@ impl<T> User<T> {
         P: AsRef<[u8]>,
         B: AsRef<[u8]>,
     {
-        let mut ctx = unwrap::Context::new(backup.as_ref());
+        let state = Self::unwrap_backup(&mut unwrap::Context::new(backup.as_ref()), pwd).await?;
+        Ok(User {
+            transport,
+            state,
+            observers: Vec::new(),
+        })
+    }
+
+    /// Restore a [`User`] from an encrypted backup read from a reader, as it is consumed, using the
+    /// provided password and transport client. The length of the backup is not known in advance, so
+    /// it is unwrapped within the [default unwrap limits](User::default_unwrap_limits).
+    ///
+    /// # Arguments
+    /// * `reader`: The reader the backup is read from, such as a file or a socket
+    /// * `pwd`: The decryption password.
+    /// * `transport`: The transport client for sending and receiving messages.
+    #[cfg(feature = "std")]
+    pub async fn restore_from<R, P>(reader: R, pwd: P, transport: T) -> Result<Self>
+    where
+        P: AsRef<[u8]>,
+        R: std::io::Read,
+    {
+        let mut ctx = unwrap::Context::new(io::Reader::new(reader)).with_limits(Self::default_unwrap_limits());
+        let state = Self::unwrap_backup(&mut ctx, pwd).await?;
+        Ok(User {
+            transport,
+            state,
+            observers: Vec::new(),
+        })
+    }
+
+    /// Decrypts and deserialises a `State` from the provided [`unwrap::Context`]
+    ///
+    /// # Arguments
+    /// * `ctx`: The context to unwrap the `State` from
+    /// * `pwd`: The decryption password
+    async fn unwrap_backup<IS, P>(ctx: &mut unwrap::Context<IS, F>, pwd: P) -> Result<State<F>>
+    where
+        IS: io::IStream,
+        P: AsRef<[u8]>,
+    {
+        let mut version = Uint8::new(0);
+        ctx.absorb(&mut version).map_err(Error::Spongos)?;
+        if version.inner() != BACKUP_VERSION {
+            return Err(Error::BackupVersion(version.inner(), BACKUP_VERSION));
+        }
         let key: [u8; 32] = SpongosRng::<KeccakF1600>::new(pwd).gen();
         ctx.absorb(External::new(&NBytes::new(key)))
             .map_err(Error::Spongos)?
@@ -785,19 +1604,20 @@ impl<T> User<T> {
             .map_err(Error::Spongos)?;
         let mut state = State::default();
         ctx.unwrap(&mut state).await.map_err(Error::Spongos)?;
-        Ok(User { transport, state })
+        Ok(state)
     }
 }
 
-impl<T> User<T>
+impl<T, F> User<T, F>
 where
+    F: StreamPRP,
     T: for<'a> Transport<'a, Msg = TransportMessage>,
 {
     /// Receive a raw message packet using the internal [`Transport`] client
     ///
     /// # Arguments
     /// * `address`: The [`Address`] of the message to be retrieved.
-    pub async fn receive_message(&mut self, address: Address) -> Result<Message>
+    pub async fn receive_message(&mut self, address: Address) -> Result<Message<F>>
     where
         T: for<'a> Transport<'a, Msg = TransportMessage>,
     {
@@ -812,7 +1632,7 @@ where
     /// Start a [`Messages`] stream to traverse the channel messages
     ///
     /// See the documentation in [`Messages`] for more details and examples.
-    pub fn messages(&mut self) -> Messages<T> {
+    pub fn messages(&mut self) -> Messages<T, F> {
         Messages::new(self)
     }
 
@@ -832,13 +1652,14 @@ where
     /// Return a vector with all the messages collected. This is a convenience
     /// method around the [`Messages`] stream. Check out its docs for more
     /// advanced usages.
-    pub async fn fetch_next_messages(&mut self) -> Result<Vec<Message>> {
+    pub async fn fetch_next_messages(&mut self) -> Result<Vec<Message<F>>> {
         self.messages().try_collect().await.map_err(Error::Messages)
     }
 }
 
-impl<T, TSR> User<T>
+impl<T, F, TSR> User<T, F>
 where
+    F: StreamPRP,
     T: for<'a> Transport<'a, Msg = TransportMessage, SendResponse = TSR>,
 {
     /// Create and send a stream Announcement message, anchoring the stream for others to attach to.
@@ -864,7 +1685,9 @@ where
         let stream_address = Address::new(stream_base_address, stream_rel_address);
 
         // Prepare HDF and PCF
-        let header = HDF::new(message_types::ANNOUNCEMENT, ANN_MESSAGE_NUM, identifier.clone(), &topic);
+        let header = HDF::new(message_types::ANNOUNCEMENT, ANN_MESSAGE_NUM, identifier.clone(), &topic)
+            .with_topic_hash(self.topic_hash(&topic))
+            .with_cipher_suite(F::CIPHER_SUITE.into());
         let content = PCF::new_final_frame().with_content(announcement::Wrap::new(self.identity().unwrap(), &topic));
 
         // Wrap message
@@ -886,12 +1709,26 @@ where
 
         // If a message has been sent successfully, insert the base branch into store
         self.state.cursor_store.new_branch(topic.clone());
+        self.state
+            .cursor_store
+            .set_branch_origin(&topic, None, stream_address.relative());
         self.state.topics.insert(topic.clone());
+        self.emit(Event::BranchCreated {
+            topic: topic.clone(),
+            parent: None,
+            announcement: stream_address,
+        });
         // Commit message to stores
         self.state
             .cursor_store
             .insert_cursor(&topic, Permissioned::Admin(identifier.clone()), INIT_MESSAGE_NUM);
         self.state.spongos_store.insert(stream_address.relative(), spongos);
+        self.record_message(
+            stream_address.relative(),
+            message_types::ANNOUNCEMENT,
+            ANN_MESSAGE_NUM,
+            None,
+        );
 
         // Update branch links
         self.set_latest_link(topic.clone(), stream_address.relative());
@@ -958,11 +1795,14 @@ where
             identifier.clone(),
             &prev_topic,
         )
+        .with_topic_hash(self.topic_hash(&prev_topic))
+        .with_cipher_suite(F::CIPHER_SUITE.into())
         .with_linked_msg_address(link_to);
         let content = PCF::new_final_frame().with_content(branch_announcement::Wrap::new(
             &mut linked_msg_spongos,
             self.identity().unwrap(),
             &topic,
+            self.state.topic_key.as_ref(),
         ));
 
         // Wrap message
@@ -983,7 +1823,15 @@ where
 
         // If message has been sent successfully, create the new branch in store
         self.state.cursor_store.new_branch(topic.clone());
+        self.state
+            .cursor_store
+            .set_branch_origin(&topic, Some(prev_topic.clone()), address.relative());
         self.state.topics.insert(topic.clone());
+        self.emit(Event::BranchCreated {
+            topic: topic.clone(),
+            parent: Some(prev_topic.clone()),
+            announcement: address,
+        });
         // Commit message to stores and update cursors
         self.state.cursor_store.insert_cursor(
             &prev_topic,
@@ -991,6 +1839,12 @@ where
             self.next_cursor(&prev_topic)?,
         );
         self.state.spongos_store.insert(address.relative(), spongos);
+        self.record_message(
+            address.relative(),
+            message_types::BRANCH_ANNOUNCEMENT,
+            user_cursor,
+            Some(link_to),
+        );
         // Collect permissions from previous branch and clone them into new branch
         let prev_permissions = self
             .cursors_by_topic(&prev_topic)?
@@ -999,12 +1853,25 @@ where
         for id in prev_permissions {
             self.state.cursor_store.insert_cursor(&topic, id, INIT_MESSAGE_NUM);
         }
+        self.emit_permission_changes(&topic, &[]);
 
         // Update branch links
         self.state.cursor_store.set_latest_link(topic, address.relative());
         Ok(SendResponse::new(address, send_response))
     }
 
+    /// Create and send a new Branch Announcement message for a hierarchical [`Topic`], generating
+    /// the branch from its parent branch so that it inherits the parent permissions. For instance,
+    /// the branch "plant1/line3" is generated from the branch "plant1".
+    ///
+    /// # Arguments
+    /// * `topic`: The hierarchical [`Topic`] of the new branch.
+    pub async fn new_child_branch(&mut self, topic: impl Into<Topic>) -> Result<SendResponse<TSR>> {
+        let topic: Topic = topic.into();
+        let parent = topic.parent().ok_or_else(|| Error::NoParentTopic(topic.clone()))?;
+        self.new_branch(parent, topic).await
+    }
+
     /// Create and send a new Subscription message, awaiting the stream author's acceptance into the
     /// stream.
     pub async fn subscribe(&mut self) -> Result<SendResponse<TSR>> {
@@ -1051,6 +1918,8 @@ where
             identifier.clone(),
             base_branch,
         )
+        .with_topic_hash(self.topic_hash(base_branch))
+        .with_cipher_suite(F::CIPHER_SUITE.into())
         .with_linked_msg_address(link_to);
 
         // Wrap message
@@ -1078,6 +1947,12 @@ where
         // - Subscription messages are not stored in the cursor store
         // - Subscription messages are never stored in spongos to maintain consistency about the view of the
         // set of messages of the stream between all the subscribers and across stateless recovers
+        self.record_message(
+            rel_address,
+            message_types::SUBSCRIPTION,
+            SUB_MESSAGE_NUM,
+            Some(link_to),
+        );
         Ok(SendResponse::new(message_address, send_response))
     }
 
@@ -1117,6 +1992,8 @@ where
             identifier.clone(),
             base_branch,
         )
+        .with_topic_hash(self.topic_hash(base_branch))
+        .with_cipher_suite(F::CIPHER_SUITE.into())
         .with_linked_msg_address(link_to);
 
         // Wrap message
@@ -1143,6 +2020,7 @@ where
             .cursor_store
             .insert_cursor(base_branch, permission, new_cursor);
         self.store_spongos(rel_address, spongos, link_to);
+        self.record_message(rel_address, message_types::UNSUBSCRIPTION, new_cursor, Some(link_to));
         Ok(SendResponse::new(message_address, send_response))
     }
 
@@ -1165,6 +2043,43 @@ where
         Subscribers::IntoIter: ExactSizeIterator,
         Top: Into<Topic>,
         Psks: IntoIterator<Item = PskId>,
+    {
+        let (keyload, transport_msg) = self.prepare_keyload(topic.into(), subscribers, psk_ids).await?;
+
+        // Attempt to send message
+        let message_address = keyload.address;
+        if !self.transport.recv_message(message_address).await.is_err() {
+            return Err(Error::AddressUsed("keyload", message_address));
+        }
+
+        let send_response = self
+            .transport
+            .send_message(message_address, transport_msg)
+            .await
+            .map_err(|e| Error::Transport(keyload.stream_address, "send keyload message", e))?;
+
+        // If message has been sent successfully, commit message to stores
+        self.commit_keyload(keyload)?;
+        Ok(SendResponse::new(message_address, send_response))
+    }
+
+    /// Wraps a Keyload message for a branch, checking the [`User`] administers it. Neither the
+    /// transport nor the state of the [`User`] are modified.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch the permissions will be updated for.
+    /// * `subscribers`: The updated [`Permissioned`] list for the branch.
+    /// * `psk_ids`: A list of [Psk Id's](`PskId`) with read access for the branch.
+    async fn prepare_keyload<'a, Subscribers, Psks>(
+        &self,
+        topic: Topic,
+        subscribers: Subscribers,
+        psk_ids: Psks,
+    ) -> Result<(PreparedKeyload<F>, TransportMessage)>
+    where
+        Subscribers: IntoIterator<Item = Permissioned<&'a Identifier>> + Clone,
+        Subscribers::IntoIter: ExactSizeIterator,
+        Psks: IntoIterator<Item = PskId>,
     {
         // Check conditions
         let stream_address = self
@@ -1173,8 +2088,7 @@ where
         // Confirm user has identity
         let user_id = self.identity().ok_or(Error::NoIdentity("send keyload"))?;
         let identifier = user_id.identifier().clone();
-        // Check Topic
-        let topic = topic.into();
+        let timestamp = self.audit_timestamp()?;
         // Check Permission
         let permission = self.permission(&topic).ok_or(Error::NoCursor(topic.clone()))?;
         if !permission.is_admin() {
@@ -1213,8 +2127,10 @@ where
             nonce,
             user_id,
         ));
-        let header =
-            HDF::new(message_types::KEYLOAD, new_cursor, identifier.clone(), &topic).with_linked_msg_address(link_to);
+        let header = HDF::new(message_types::KEYLOAD, new_cursor, identifier.clone(), &topic)
+            .with_topic_hash(self.topic_hash(&topic))
+            .with_cipher_suite(F::CIPHER_SUITE.into())
+            .with_linked_msg_address(link_to);
 
         // Wrap message
         let (transport_msg, spongos) = LetsMessage::new(header, content)
@@ -1222,35 +2138,247 @@ where
             .await
             .map_err(|e| Error::Wrapped("send keyload", e))?;
 
+        let keyload = PreparedKeyload {
+            topic,
+            identifier,
+            cursor: new_cursor,
+            stream_address,
+            address: Address::new(stream_address.base(), rel_address),
+            link_to,
+            spongos,
+            subscribers: subscribers.into_iter().map(Permissioned::from).collect(),
+            psks: psk_ids_with_psks.iter().map(|(pskid, _)| *pskid).collect(),
+            timestamp,
+        };
+        Ok((keyload, transport_msg))
+    }
+
+    /// Commits a Keyload message that has been sent to the stores of the [`User`], updating the
+    /// permissions of its branch
+    ///
+    /// # Arguments
+    /// * `keyload`: The [`PreparedKeyload`] that has been sent
+    fn commit_keyload(&mut self, keyload: PreparedKeyload<F>) -> Result<()> {
+        let topic = keyload.topic;
+        let rel_address = keyload.address.relative();
+        let previous_permissions: Vec<Permissioned<Identifier>> =
+            self.cursors_by_topic(&topic)?.map(|(perm, _)| perm.clone()).collect();
+        for subscriber in &keyload.subscribers {
+            if self.should_store_cursor(&topic, subscriber.as_ref()) {
+                self.state
+                    .cursor_store
+                    .insert_cursor(&topic, subscriber.clone(), INIT_MESSAGE_NUM);
+            }
+        }
+        self.state
+            .cursor_store
+            .insert_cursor(&topic, Permissioned::Admin(keyload.identifier.clone()), keyload.cursor);
+        self.store_spongos(rel_address, keyload.spongos, keyload.link_to);
+        self.record_message(rel_address, message_types::KEYLOAD, keyload.cursor, Some(keyload.link_to));
+        self.emit_permission_changes(&topic, &previous_permissions);
+        if let Some(timestamp) = keyload.timestamp {
+            let record = KeyloadRecord {
+                timestamp,
+                address: keyload.address,
+                admin: keyload.identifier,
+                subscribers: keyload.subscribers,
+                psks: keyload.psks,
+                demoted: Vec::new(),
+            };
+            self.audit_keyload(&topic, previous_permissions, record);
+        }
+        // Update Branch Links
+        self.set_latest_link(topic, rel_address);
+        Ok(())
+    }
+
+    /// Create and send a new Checkpoint message for a branch. The checkpoint snapshots the members of
+    /// the branch with their cursors and renews the key of the branch for them and for every pre
+    /// shared key in store. Users joining the stream later can start reading the branch from the
+    /// checkpoint, without fetching the messages published before it. Whether those messages can
+    /// still be read by them is set by the [`CheckpointHistory`].
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to checkpoint.
+    /// * `history`: Which messages of the branch published before the checkpoint stay readable by
+    ///   the members of the branch that start from it.
+    pub async fn send_checkpoint<Top>(&mut self, topic: Top, history: CheckpointHistory) -> Result<SendResponse<TSR>>
+    where
+        Top: Into<Topic>,
+    {
+        // Check conditions
+        let stream_address = self
+            .stream_address()
+            .ok_or(Error::Setup("before sending a checkpoint, the stream must be created"))?;
+        // Confirm user has identity
+        let user_id = self.identity().ok_or(Error::NoIdentity("send checkpoint"))?;
+        let identifier = user_id.identifier().clone();
+        // Check Topic
+        let topic = topic.into();
+        // Check Permission
+        let permission = self.permission(&topic).ok_or(Error::NoCursor(topic.clone()))?;
+        if !permission.is_admin() {
+            return Err(Error::WrongRole("Admin", identifier, "send a checkpoint"));
+        }
+
+        // Link message to edge of branch
+        let link_to = self
+            .get_latest_link(&topic)
+            .ok_or_else(|| Error::TopicNotFound(topic.clone()))?;
+        let mut history_spongos = Vec::new();
+        if history != CheckpointHistory::Dropped {
+            let edge_spongos = self
+                .state
+                .spongos_store
+                .get(&link_to)
+                .copied()
+                .ok_or(Error::Setup("a checkpoint keeping the edge of the branch requires its spongos"))?;
+            history_spongos.push((link_to, edge_spongos));
+        }
+        if history == CheckpointHistory::Readable {
+            // Every message of the branch is addressed by its publisher and its sequence number
+            for (permission, cursor) in self.cursors_by_topic(&topic)? {
+                for sequence in INIT_MESSAGE_NUM..=*cursor {
+                    let msgid = MsgId::gen(stream_address.base(), permission.identifier(), &topic, sequence);
+                    if msgid == link_to {
+                        continue;
+                    }
+                    if let Some(spongos) = self.state.spongos_store.get(&msgid) {
+                        history_spongos.push((msgid, *spongos));
+                    }
+                }
+            }
+        }
+        // Update own's cursor
+        let new_cursor = self.next_cursor(&topic)?;
+        let rel_address = MsgId::gen(stream_address.base(), &identifier, &topic, new_cursor);
+
+        // Prepare HDF and PCF
+        // Like keyloads, checkpoints attach to stream Announcement message spongos
+        let mut announcement_msg_spongos = self
+            .state
+            .spongos_store
+            .get(&stream_address.relative())
+            .copied()
+            .ok_or(Error::Setup("a user must keep a stream announcement spongos in store"))?;
+
+        let mut rng = StdRng::from_entropy();
+        let encryption_key = rng.gen();
+        let nonce = rng.gen();
+        let members: Vec<(Permissioned<Identifier>, usize)> = self
+            .cursors_by_topic(&topic)?
+            .map(|(perm, cursor)| (perm.clone(), *cursor))
+            .collect();
+        let psk_ids_with_psks: Vec<(PskId, &Psk)> =
+            self.state.psk_store.iter().map(|(pskid, psk)| (*pskid, psk)).collect();
+        let keyload = keyload::Wrap::new(
+            &mut announcement_msg_spongos,
+            members.iter().map(|(perm, _)| perm.as_ref()).collect::<Vec<_>>(),
+            &psk_ids_with_psks,
+            encryption_key,
+            nonce,
+            user_id,
+        );
+        let content = PCF::new_final_frame().with_content(checkpoint::Wrap::new(
+            keyload,
+            &members,
+            history,
+            &history_spongos,
+            user_id,
+        ));
+        let header = HDF::new(message_types::CHECKPOINT, new_cursor, identifier.clone(), &topic)
+            .with_topic_hash(self.topic_hash(&topic))
+            .with_cipher_suite(F::CIPHER_SUITE.into())
+            .with_linked_msg_address(link_to);
+
+        // Wrap message
+        let (transport_msg, spongos) = LetsMessage::new(header, content)
+            .wrap()
+            .await
+            .map_err(|e| Error::Wrapped("send checkpoint", e))?;
+
         // Attempt to send message
         let message_address = Address::new(stream_address.base(), rel_address);
         if !self.transport.recv_message(message_address).await.is_err() {
-            return Err(Error::AddressUsed("keyload", message_address));
+            return Err(Error::AddressUsed("checkpoint", message_address));
         }
 
         let send_response = self
             .transport
             .send_message(message_address, transport_msg)
             .await
-            .map_err(|e| Error::Transport(stream_address, "send keyload message", e))?;
+            .map_err(|e| Error::Transport(stream_address, "send checkpoint message", e))?;
 
         // If message has been sent successfully, commit message to stores
-        for subscriber in subscribers {
-            if self.should_store_cursor(&topic, subscriber) {
-                self.state
-                    .cursor_store
-                    .insert_cursor(&topic, subscriber.into(), INIT_MESSAGE_NUM);
-            }
-        }
         self.state
             .cursor_store
             .insert_cursor(&topic, Permissioned::Admin(identifier), new_cursor);
         self.store_spongos(rel_address, spongos, link_to);
+        self.record_message(rel_address, message_types::CHECKPOINT, new_cursor, Some(link_to));
         // Update Branch Links
         self.set_latest_link(topic, message_address.relative());
         Ok(SendResponse::new(message_address, send_response))
     }
 
+    /// Create and send a new Keyload message to every known branch matching a [`TopicFilter`] and
+    /// administered by this [`User`], updating their read/write permissions. The keyloads are all
+    /// wrapped before any of them is sent, and they are then sent concurrently: see
+    /// [`FanOutResponse`] for how failures are reported.
+    ///
+    /// # Arguments
+    /// * `filter`: The [`TopicFilter`] the branches must match.
+    /// * `subscribers`: The updated [`Permissioned`] list for the branches.
+    /// * `psk_ids`: A list of [Psk Id's](`PskId`) with read access for the branches.
+    /// * `retry_policy`: The [`RetryPolicy`] applied to the keyloads that fail to be sent.
+    pub async fn send_keyload_matching<'a, Subscribers, Psks>(
+        &mut self,
+        filter: &TopicFilter,
+        subscribers: Subscribers,
+        psk_ids: Psks,
+        retry_policy: RetryPolicy,
+    ) -> Result<FanOutResponse<TSR>>
+    where
+        T: Clone,
+        Subscribers: IntoIterator<Item = Permissioned<&'a Identifier>> + Clone,
+        Subscribers::IntoIter: ExactSizeIterator,
+        Psks: IntoIterator<Item = PskId> + Clone,
+    {
+        // Alas, must collect to release the &self immutable borrow
+        let topics: Vec<Topic> = self
+            .topics_matching(filter)
+            .filter(|topic| self.permission(topic).map_or(false, |p| p.is_admin()))
+            .cloned()
+            .collect();
+        let mut keyloads = Vec::with_capacity(topics.len());
+        let mut transport_msgs = Vec::with_capacity(topics.len());
+        for topic in topics {
+            let (keyload, transport_msg) = self
+                .prepare_keyload(topic, subscribers.clone(), psk_ids.clone())
+                .await?;
+            if self.transport.recv_message(keyload.address).await.is_ok() {
+                return Err(Error::AddressUsed("keyload", keyload.address));
+            }
+            transport_msgs.push((keyload.address, transport_msg));
+            keyloads.push(keyload);
+        }
+        let results = retry_policy.send_concurrently(&self.transport, transport_msgs).await;
+
+        // Only the keyloads that have been sent are committed to the stores
+        let mut outcomes = Vec::with_capacity(keyloads.len());
+        for (keyload, result) in keyloads.into_iter().zip(results) {
+            let topic = keyload.topic.clone();
+            let address = keyload.address;
+            let outcome = match result {
+                Ok(send_response) => self
+                    .commit_keyload(keyload)
+                    .map(|_| SendResponse::new(address, send_response)),
+                Err(e) => Err(Error::Transport(keyload.stream_address, "send keyload message", e)),
+            };
+            outcomes.push((topic, outcome));
+        }
+        Ok(FanOutResponse::new(outcomes))
+    }
+
     /// Create and send a new Keyload message for all participants, updating the specified branch to
     /// grant all known subscribers read permissions.
     ///
@@ -1328,7 +2456,7 @@ where
     }
 
     /// Create a new [`MessageBuilder`] instance.
-    pub fn message<P: Default>(&mut self) -> MessageBuilder<P, T> {
+    pub fn message<P: Default>(&mut self) -> MessageBuilder<P, T, F> {
         MessageBuilder::new(self)
     }
 
@@ -1351,78 +2479,40 @@ where
         P: AsRef<[u8]>,
         Top: Into<Topic>,
     {
-        // Check conditions
-        let stream_address = self.stream_address().ok_or(Error::Setup(
-            "before sending a signed packet, the stream must be created",
-        ))?;
-        let user_id = self.identity().ok_or(Error::NoIdentity("send signed packet"))?;
-        let identifier = user_id.identifier().clone();
-        // Check Topic
-        let topic = topic.into();
-        // Check Permission
-        let permission = self
-            .state
-            .cursor_store
-            .get_permission(&topic, &identifier)
-            .ok_or(Error::NoCursor(topic.clone()))?;
-        if permission.is_readonly() {
-            return Err(Error::WrongRole(
-                "ReadWrite",
-                permission.identifier().clone(),
-                "send a signed packet",
-            ));
-        }
-        // Link message to latest message in branch
-        let link_to = self
-            .get_latest_link(&topic)
-            .ok_or_else(|| Error::TopicNotFound(topic.clone()))?;
-        // Update own's cursor
-        let new_cursor = self.next_cursor(&topic)?;
-        let rel_address = MsgId::gen(stream_address.base(), &identifier, &topic, new_cursor);
-
-        // Prepare HDF and PCF
-        // Spongos must be copied because wrapping mutates it
-        let mut linked_msg_spongos = self
-            .state
-            .spongos_store
-            .get(&link_to)
-            .copied()
-            .ok_or(Error::MessageMissing(link_to, "spongos store"))?;
-
-        let content = PCF::new_final_frame().with_content(signed_packet::Wrap::new(
-            &mut linked_msg_spongos,
-            &(*user_id),
-            public_payload.as_ref(),
-            masked_payload.as_ref(),
-        ));
-        let header = HDF::new(message_types::SIGNED_PACKET, new_cursor, identifier.clone(), &topic)
-            .with_linked_msg_address(link_to);
-
-        // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
-            .await
-            .map_err(|e| Error::Wrapped("send signed packet", e))?;
-
-        // Attempt to send message
-        let message_address = Address::new(stream_address.base(), rel_address);
-        if !self.transport.recv_message(message_address).await.is_err() {
-            return Err(Error::AddressUsed("signed packet", message_address));
-        }
-        let send_response = self
-            .transport
-            .send_message(message_address, transport_msg)
+        self.send_encoded_signed_packet(topic, public_payload, masked_payload, ContentType::Raw)
             .await
-            .map_err(|e| Error::Transport(stream_address, "send signed packet", e))?;
+    }
 
-        // If message has been sent successfully, commit message to stores
-        self.state
-            .cursor_store
-            .insert_cursor(&topic, permission.clone(), new_cursor);
-        self.store_spongos(rel_address, spongos, link_to);
-        // Update Branch Links
-        self.set_latest_link(topic, message_address.relative());
-        Ok(SendResponse::new(message_address, send_response))
+    /// Create and send a new Signed Packet message to the specified branch, tagging its payloads
+    /// with the [`ContentType`] they are encoded with.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to send the message to.
+    /// * `public_payload`: The unmasked payload of the message.
+    /// * `masked_payload`: The masked payload of the message.
+    /// * `content_type`: The [`ContentType`] of the payloads.
+    pub(crate) async fn send_encoded_signed_packet<P, M, Top>(
+        &mut self,
+        topic: Top,
+        public_payload: P,
+        masked_payload: M,
+        content_type: ContentType,
+    ) -> Result<SendResponse<TSR>>
+    where
+        M: AsRef<[u8]>,
+        P: AsRef<[u8]>,
+        Top: Into<Topic>,
+    {
+        let (packet, transport_msg) = self
+            .prepare_packet(
+                topic.into(),
+                public_payload.as_ref(),
+                masked_payload.as_ref(),
+                content_type,
+                true,
+            )
+            .await?;
+        self.send_packet(packet, transport_msg).await
     }
 
     /// Create and send a new Tagged Packet message to the specified branch. The message will
@@ -1443,14 +2533,147 @@ where
         P: AsRef<[u8]>,
         Top: Into<Topic>,
     {
+        self.send_encoded_tagged_packet(topic, public_payload, masked_payload, ContentType::Raw)
+            .await
+    }
+
+    /// Create and send a new Tagged Packet message to the specified branch, tagging its payloads
+    /// with the [`ContentType`] they are encoded with.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to send the message to.
+    /// * `public_payload`: The unmasked payload of the message.
+    /// * `masked_payload`: The masked payload of the message.
+    /// * `content_type`: The [`ContentType`] of the payloads.
+    pub(crate) async fn send_encoded_tagged_packet<P, M, Top>(
+        &mut self,
+        topic: Top,
+        public_payload: P,
+        masked_payload: M,
+        content_type: ContentType,
+    ) -> Result<SendResponse<TSR>>
+    where
+        M: AsRef<[u8]>,
+        P: AsRef<[u8]>,
+        Top: Into<Topic>,
+    {
+        let (packet, transport_msg) = self
+            .prepare_packet(
+                topic.into(),
+                public_payload.as_ref(),
+                masked_payload.as_ref(),
+                content_type,
+                false,
+            )
+            .await?;
+        self.send_packet(packet, transport_msg).await
+    }
+
+    /// Create and send a new Signed or Tagged Packet message to each of the specified branches,
+    /// tagging their payloads with the [`ContentType`] they are encoded with.
+    ///
+    /// All the messages are wrapped before any of them is sent, and they are then sent concurrently,
+    /// each through its own clone of the transport of the [`User`]. See [`FanOutResponse`] for how
+    /// failures are reported.
+    ///
+    /// # Arguments
+    /// * `topics`: The [`Topic`] of each branch to send a message to.
+    /// * `public_payload`: The unmasked payload of the messages.
+    /// * `masked_payload`: The masked payload of the messages.
+    /// * `content_type`: The [`ContentType`] of the payloads.
+    /// * `signed`: Whether Signed Packets are sent rather than Tagged Packets.
+    /// * `retry_policy`: The [`RetryPolicy`] applied to the messages that fail to be sent.
+    pub(crate) async fn send_encoded_packets(
+        &mut self,
+        topics: Vec<Topic>,
+        public_payload: &[u8],
+        masked_payload: &[u8],
+        content_type: ContentType,
+        signed: bool,
+        retry_policy: RetryPolicy,
+    ) -> Result<FanOutResponse<TSR>>
+    where
+        T: Clone,
+    {
+        let mut packets: Vec<(PreparedPacket<F>, TransportMessage)> = Vec::with_capacity(topics.len());
+        for topic in topics {
+            if packets.iter().any(|(packet, _)| packet.topic == topic) {
+                continue;
+            }
+            let (packet, transport_msg) = self
+                .prepare_packet(topic, public_payload, masked_payload, content_type, signed)
+                .await?;
+            if self.transport.recv_message(packet.address).await.is_ok() {
+                return Err(Error::AddressUsed(packet.name, packet.address));
+            }
+            packets.push((packet, transport_msg));
+        }
+
+        let (packets, transport_msgs): (Vec<_>, Vec<_>) = packets
+            .into_iter()
+            .map(|(packet, transport_msg)| {
+                let address = packet.address;
+                (packet, (address, transport_msg))
+            })
+            .unzip();
+        let results = retry_policy.send_concurrently(&self.transport, transport_msgs).await;
+
+        // Only the messages that have been sent are committed to the stores
+        let mut outcomes = Vec::with_capacity(packets.len());
+        for (packet, result) in packets.into_iter().zip(results) {
+            let topic = packet.topic.clone();
+            let address = packet.address;
+            let outcome = result
+                .map_err(|e| Error::Transport(address, packet.action, e))
+                .map(|send_response| {
+                    self.commit_packet(packet);
+                    SendResponse::new(address, send_response)
+                });
+            outcomes.push((topic, outcome));
+        }
+        Ok(FanOutResponse::new(outcomes))
+    }
+
+    /// Wraps a Signed or Tagged Packet message for a branch, checking the [`User`] can publish in
+    /// it. Neither the transport nor the state of the [`User`] are modified.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch to send the message to.
+    /// * `public_payload`: The unmasked payload of the message.
+    /// * `masked_payload`: The masked payload of the message.
+    /// * `content_type`: The [`ContentType`] of the payloads.
+    /// * `signed`: Whether a Signed Packet is wrapped rather than a Tagged Packet.
+    async fn prepare_packet(
+        &self,
+        topic: Topic,
+        public_payload: &[u8],
+        masked_payload: &[u8],
+        content_type: ContentType,
+        signed: bool,
+    ) -> Result<(PreparedPacket<F>, TransportMessage)> {
+        let (message_type, name, action, role_action) = if signed {
+            (
+                message_types::SIGNED_PACKET,
+                "signed packet",
+                "send signed packet",
+                "send a signed packet",
+            )
+        } else {
+            (
+                message_types::TAGGED_PACKET,
+                "tagged packet",
+                "send tagged packet",
+                "send a tagged packet",
+            )
+        };
         // Check conditions
-        let stream_address = self.stream_address().ok_or(Error::Setup(
-            "before sending a tagged packet, the stream must be created",
-        ))?;
-        let user_id = self.identity().ok_or(Error::NoIdentity("send tagged packet"))?;
+        let stream_address = self.stream_address().ok_or(Error::Setup(if signed {
+            "before sending a signed packet, the stream must be created"
+        } else {
+            "before sending a tagged packet, the stream must be created"
+        }))?;
+        let user_id = self.identity().ok_or(Error::NoIdentity(action))?;
         let identifier = user_id.identifier().clone();
-        // Check Topic
-        let topic = topic.into();
         // Check Permission
         let permission = self
             .state
@@ -1461,14 +2684,13 @@ where
             return Err(Error::WrongRole(
                 "ReadWrite",
                 permission.identifier().clone(),
-                "send a tagged packet",
+                role_action,
             ));
         }
         // Link message to latest message in branch
         let link_to = self
             .get_latest_link(&topic)
             .ok_or_else(|| Error::TopicNotFound(topic.clone()))?;
-
         // Update own's cursor
         let new_cursor = self.next_cursor(&topic)?;
         let rel_address = MsgId::gen(stream_address.base(), &identifier, &topic, new_cursor);
@@ -1481,116 +2703,143 @@ where
             .get(&link_to)
             .copied()
             .ok_or(Error::MessageMissing(link_to, "spongos store"))?;
-        let content = PCF::new_final_frame().with_content(tagged_packet::Wrap::new(
-            &mut linked_msg_spongos,
-            public_payload.as_ref(),
-            masked_payload.as_ref(),
-        ));
-        let header = HDF::new(message_types::TAGGED_PACKET, new_cursor, identifier.clone(), &topic)
-            .with_linked_msg_address(link_to);
+        let header = HDF::new(message_type, new_cursor, identifier.clone(), &topic)
+            .with_topic_hash(self.topic_hash(&topic))
+            .with_cipher_suite(F::CIPHER_SUITE.into())
+            .with_linked_msg_address(link_to)
+            .with_encoding(content_type.into());
 
         // Wrap message
-        let (transport_msg, spongos) = LetsMessage::new(header, content)
-            .wrap()
-            .await
-            .map_err(|e| Error::Wrapped("send tagged packet", e))?;
+        let (transport_msg, spongos) = if signed {
+            let content = PCF::new_final_frame().with_content(signed_packet::Wrap::new(
+                &mut linked_msg_spongos,
+                user_id,
+                public_payload,
+                masked_payload,
+            ));
+            LetsMessage::new(header, content).wrap().await
+        } else {
+            let content = PCF::new_final_frame().with_content(tagged_packet::Wrap::new(
+                &mut linked_msg_spongos,
+                public_payload,
+                masked_payload,
+            ));
+            LetsMessage::new(header, content).wrap().await
+        }
+        .map_err(|e| Error::Wrapped(action, e))?;
 
+        let packet = PreparedPacket {
+            topic,
+            permission: permission.clone(),
+            cursor: new_cursor,
+            address: Address::new(stream_address.base(), rel_address),
+            link_to,
+            message_type,
+            spongos,
+            name,
+            action,
+        };
+        Ok((packet, transport_msg))
+    }
+
+    /// Sends a prepared Signed or Tagged Packet message, committing it to the state of the
+    /// [`User`] once it has been sent
+    ///
+    /// # Arguments
+    /// * `packet`: The [`PreparedPacket`] to commit once sent
+    /// * `transport_msg`: The wrapped message
+    async fn send_packet(
+        &mut self,
+        packet: PreparedPacket<F>,
+        transport_msg: TransportMessage,
+    ) -> Result<SendResponse<TSR>> {
         // Attempt to send message
-        let message_address = Address::new(stream_address.base(), rel_address);
+        let message_address = packet.address;
         if !self.transport.recv_message(message_address).await.is_err() {
-            return Err(Error::AddressUsed("tagged packet", message_address));
+            return Err(Error::AddressUsed(packet.name, message_address));
         }
         let send_response = self
             .transport
             .send_message(message_address, transport_msg)
             .await
-            .map_err(|e| Error::Transport(stream_address, "send tagged packet", e))?;
+            .map_err(|e| Error::Transport(message_address, packet.action, e))?;
 
         // If message has been sent successfully, commit message to stores
+        self.commit_packet(packet);
+        Ok(SendResponse::new(message_address, send_response))
+    }
+
+    /// Commits a Signed or Tagged Packet message that has been sent to the stores of the [`User`]
+    ///
+    /// # Arguments
+    /// * `packet`: The [`PreparedPacket`] that has been sent
+    fn commit_packet(&mut self, packet: PreparedPacket<F>) {
+        let rel_address = packet.address.relative();
         self.state
             .cursor_store
-            .insert_cursor(&topic, permission.clone(), new_cursor);
-        self.store_spongos(rel_address, spongos, link_to);
+            .insert_cursor(&packet.topic, packet.permission, packet.cursor);
+        self.store_spongos(rel_address, packet.spongos, packet.link_to);
+        self.record_message(rel_address, packet.message_type, packet.cursor, Some(packet.link_to));
         // Update Branch Links
-        self.set_latest_link(topic, rel_address);
-        Ok(SendResponse::new(message_address, send_response))
+        self.set_latest_link(packet.topic, rel_address);
     }
 }
 
-#[async_trait(?Send)]
-impl ContentSizeof<State> for sizeof::Context {
-    async fn sizeof(&mut self, user_state: &State) -> SpongosResult<&mut Self> {
-        self.mask(Maybe::new(user_state.user_id.as_ref()))?
-            .mask(Maybe::new(user_state.stream_address.as_ref()))?
-            .mask(Maybe::new(user_state.author_identifier.as_ref()))?
-            .mask(&user_state.base_branch)?;
-
-        let amount_spongos = user_state.spongos_store.len();
-        self.mask(Size::new(amount_spongos))?;
-        for (address, spongos) in &user_state.spongos_store {
-            self.mask(address)?.mask(spongos)?;
-        }
-
-        // Only keep topics that exist in cursor store, any others serve no purpose
-        let topics = user_state
-            .topics
-            .iter()
-            .filter(|t| user_state.cursor_store.get_latest_link(t).is_some());
-        let amount_topics = topics.clone().count();
-        self.mask(Size::new(amount_topics))?;
-
-        for topic in topics {
-            self.mask(topic)?;
-            let latest_link = user_state
-                .cursor_store
-                .get_latest_link(topic)
-                .ok_or(SpongosError::InvalidAction(
-                    "calculate sizeof for topic latest link",
-                    topic.to_string(),
-                    "No Cursor".to_owned(),
-                ))?;
-            self.mask(&latest_link)?;
-
-            let cursors: Vec<(&Permissioned<Identifier>, &usize)> = user_state
-                .cursor_store
-                .cursors_by_topic(topic)
-                .ok_or(SpongosError::InvalidAction(
-                    "get cursor for topic",
-                    topic.to_string(),
-                    "No Cursor".to_owned(),
-                ))?
-                .collect();
-            let amount_cursors = cursors.len();
-            self.mask(Size::new(amount_cursors))?;
-            for (subscriber, cursor) in cursors {
-                self.mask(subscriber)?.mask(Size::new(*cursor))?;
-            }
-        }
-
-        let subs = &user_state.subscribers;
-        let amount_subs = subs.len();
-        self.mask(Size::new(amount_subs))?;
-        for subscriber in subs {
-            self.mask(subscriber)?;
-        }
-
-        let psks = user_state.psk_store.iter();
-        let amount_psks = psks.len();
-        self.mask(Size::new(amount_psks))?;
-        for (pskid, psk) in psks {
-            self.mask(pskid)?.mask(psk)?;
-        }
-
-        let lean = if user_state.lean { 1 } else { 0 };
-        self.mask(Uint8::new(lean))?;
+/// A Keyload message wrapped for a branch, whose effects on the state of the [`User`] are only
+/// committed once it has been sent
+struct PreparedKeyload<F> {
+    /// The [`Topic`] of the branch
+    topic: Topic,
+    /// The [`Identifier`] of the administrator of the branch
+    identifier: Identifier,
+    /// The cursor of the administrator in the branch once the message is sent
+    cursor: usize,
+    /// The [`Address`] of the stream
+    stream_address: Address,
+    /// The [`Address`] of the message
+    address: Address,
+    /// The message the keyload is linked to
+    link_to: MsgId,
+    /// The [`Spongos`] state of the message once wrapped
+    spongos: Spongos<F>,
+    /// The [permissions](`Permissioned`) granted by the keyload
+    subscribers: Vec<Permissioned<Identifier>>,
+    /// The identifiers of the pre shared keys included in the keyload
+    psks: Vec<PskId>,
+    /// The time the keyload is audited at, if the [`User`] keeps an [`AuditLog`]
+    timestamp: Option<u64>,
+}
 
-        self.commit()?.squeeze(Mac::new(32))
-    }
+/// A Signed or Tagged Packet message wrapped for a branch, whose effects on the state of the
+/// [`User`] are only committed once it has been sent
+struct PreparedPacket<F> {
+    /// The [`Topic`] of the branch
+    topic: Topic,
+    /// The permission of the [`User`] in the branch
+    permission: Permissioned<Identifier>,
+    /// The cursor of the [`User`] in the branch once the message is sent
+    cursor: usize,
+    /// The [`Address`] of the message
+    address: Address,
+    /// The message the packet is linked to
+    link_to: MsgId,
+    /// The type of the message
+    message_type: u8,
+    /// The [`Spongos`] state of the message once wrapped
+    spongos: Spongos<F>,
+    /// The name of the message in errors
+    name: &'static str,
+    /// The action of sending the message in errors
+    action: &'static str,
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
-    async fn wrap(&mut self, user_state: &mut State) -> SpongosResult<&mut Self> {
+impl<OS, F> ContentWrap<State<F>> for wrap::Context<OS, F>
+where
+    F: StreamPRP,
+    OS: io::OStream,
+{
+    async fn wrap(&mut self, user_state: &mut State<F>) -> SpongosResult<&mut Self> {
         self.mask(Maybe::new(user_state.user_id.as_ref()))?
             .mask(Maybe::new(user_state.stream_address.as_ref()))?
             .mask(Maybe::new(user_state.author_identifier.as_ref()))?
@@ -1620,7 +2869,9 @@ impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
                     topic.to_string(),
                     "No latest link".to_owned(),
                 ))?;
-            self.mask(&latest_link)?;
+            self.mask(&latest_link)?
+                .mask(Maybe::new(user_state.cursor_store.get_parent(topic)))?
+                .mask(Maybe::new(user_state.cursor_store.get_announcement(topic).as_ref()))?;
 
             let cursors: Vec<(&Permissioned<Identifier>, &usize)> = user_state
                 .cursor_store
@@ -1654,14 +2905,32 @@ impl<'a> ContentWrap<State> for wrap::Context<&'a mut [u8]> {
 
         let lean = if user_state.lean { 1 } else { 0 };
         self.mask(Uint8::new(lean))?;
+        self.mask(Maybe::new(user_state.topic_key.as_ref()))?;
+        self.mask(Maybe::new(user_state.audit_log.as_ref()))?;
+
+        self.mask(Size::new(user_state.orphans.len()))?;
+        for (linked_msg, orphans) in &user_state.orphans {
+            self.mask(linked_msg)?.mask(Size::new(orphans.len()))?;
+            for (msgid, orphan) in orphans {
+                self.mask(msgid)?.mask(Bytes::new(orphan.transport_msg()))?;
+            }
+        }
+        self.mask(Size::new(user_state.resolved_orphans.len()))?;
+        for (msgid, orphan) in &user_state.resolved_orphans {
+            self.mask(msgid)?.mask(Bytes::new(orphan.transport_msg()))?;
+        }
 
         self.commit()?.squeeze(Mac::new(32))
     }
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentUnwrap<State> for unwrap::Context<&'a [u8]> {
-    async fn unwrap(&mut self, user_state: &mut State) -> SpongosResult<&mut Self> {
+impl<IS, F> ContentUnwrap<State<F>> for unwrap::Context<IS, F>
+where
+    F: StreamPRP,
+    IS: io::IStream,
+{
+    async fn unwrap(&mut self, user_state: &mut State<F>) -> SpongosResult<&mut Self> {
         self.mask(Maybe::new(&mut user_state.user_id))?
             .mask(Maybe::new(&mut user_state.stream_address))?
             .mask(Maybe::new(&mut user_state.author_identifier))?
@@ -1683,10 +2952,19 @@ impl<'a> ContentUnwrap<State> for unwrap::Context<&'a [u8]> {
             let mut topic = Topic::default();
             self.mask(&mut topic)?;
             let mut latest_link = MsgId::default();
-            self.mask(&mut latest_link)?;
+            let mut parent: Option<Topic> = None;
+            let mut announcement: Option<MsgId> = None;
+            self.mask(&mut latest_link)?
+                .mask(Maybe::new(&mut parent))?
+                .mask(Maybe::new(&mut announcement))?;
 
             user_state.topics.insert(topic.clone());
             user_state.cursor_store.set_latest_link(topic.clone(), latest_link);
+            if let Some(announcement) = announcement {
+                user_state
+                    .cursor_store
+                    .set_branch_origin(&topic, parent, announcement);
+            }
 
             let mut amount_cursors = Size::default();
             self.mask(&mut amount_cursors)?;
@@ -1720,12 +2998,50 @@ impl<'a> ContentUnwrap<State> for unwrap::Context<&'a [u8]> {
         let mut lean = Uint8::new(0);
         self.mask(&mut lean)?;
         user_state.lean = lean.inner() == 1;
+        self.mask(Maybe::new(&mut user_state.topic_key))?;
+        self.mask(Maybe::new(&mut user_state.audit_log))?;
+
+        let mut amount_linked_msgs = Size::default();
+        self.mask(&mut amount_linked_msgs)?;
+        for _ in 0..amount_linked_msgs.inner() {
+            let mut linked_msg = MsgId::default();
+            let mut amount_orphans = Size::default();
+            self.mask(&mut linked_msg)?.mask(&mut amount_orphans)?;
+            let mut orphans = Vec::new();
+            for _ in 0..amount_orphans.inner() {
+                let mut msgid = MsgId::default();
+                let mut body = Vec::new();
+                self.mask(&mut msgid)?.mask(Bytes::new(&mut body))?;
+                // Orphans are backed up in their raw form, their header is parsed again on restore
+                let preparsed: PreparsedMessage<F> = TransportMessage::new(body)
+                    .parse_header()
+                    .await
+                    .map_err(|e| SpongosError::Context("ContentUnwrap orphan", e.to_string()))?;
+                orphans.push((msgid, preparsed));
+            }
+            user_state.orphans.insert(linked_msg, orphans);
+        }
+        let mut amount_resolved_orphans = Size::default();
+        self.mask(&mut amount_resolved_orphans)?;
+        for _ in 0..amount_resolved_orphans.inner() {
+            let mut msgid = MsgId::default();
+            let mut body = Vec::new();
+            self.mask(&mut msgid)?.mask(Bytes::new(&mut body))?;
+            let preparsed: PreparsedMessage<F> = TransportMessage::new(body)
+                .parse_header()
+                .await
+                .map_err(|e| SpongosError::Context("ContentUnwrap orphan", e.to_string()))?;
+            user_state.resolved_orphans.push_back((msgid, preparsed));
+        }
 
         self.commit()?.squeeze(Mac::new(32))
     }
 }
 
-impl<T> Debug for User<T> {
+impl<T, F> Debug for User<T, F>
+where
+    F: StreamPRP,
+{
     fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
         write!(
             f,
@@ -1751,7 +3067,7 @@ impl<T> Debug for User<T> {
 /// An streams user equality is determined by the equality of its state. The major consequence of
 /// this fact is that two users with the same identity but different transport configurations are
 /// considered equal
-impl<T> PartialEq for User<T> {
+impl<T, F: PartialEq> PartialEq for User<T, F> {
     fn eq(&self, other: &Self) -> bool {
         self.state == other.state
     }
@@ -1760,4 +3076,236 @@ impl<T> PartialEq for User<T> {
 /// An streams user equality is determined by the equality of its state. The major consequence of
 /// this fact is that two users with the same identity but different transport configurations are
 /// considered equal
-impl<T> Eq for User<T> {}
+impl<T, F: Eq> Eq for User<T, F> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec, vec::Vec};
+    use core::cell::RefCell;
+
+    use futures::TryStreamExt;
+    use lets::{
+        address::{Address, MsgId},
+        id::{Ed25519, PermissionDuration, Permissioned},
+        message::{Topic, TopicFilter, TopicHash, TopicKey, TransportMessage},
+        transport::{bucket, Transport as _},
+    };
+
+    use super::User;
+    use crate::{
+        api::{
+            fan_out::RetryPolicy,
+            message::Message,
+            messages::tests::{author_subscriber_fixture, Transport},
+        },
+        message::message_types,
+        Error, Result,
+    };
+
+    #[tokio::test]
+    async fn keyed_topics_are_only_resolved_by_users_holding_the_key() -> Result<()> {
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let topic_key = TopicKey::from_seed("topic key");
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_topic_key(topic_key)
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let branch_announcement = author.new_branch("BASE_BRANCH", "SECRET_BRANCH").await?;
+
+        let secret_branch = Topic::from("SECRET_BRANCH");
+        let keyed_hash = TopicHash::keyed(&secret_branch, &topic_key);
+        assert_eq!(author.topic_hash(&secret_branch), keyed_hash);
+        assert_eq!(author.topic_by_hash(&keyed_hash), Some(secret_branch.clone()));
+        assert_eq!(author.topic_by_hash(&TopicHash::from(&secret_branch)), None);
+
+        let mut member = User::builder()
+            .with_identity(Ed25519::from_seed("member"))
+            .with_topic_key(topic_key)
+            .with_transport(transport.clone())
+            .build();
+        member.receive_message(announcement.address()).await?;
+        member.receive_message(branch_announcement.address()).await?;
+        assert_eq!(member.topic_by_hash(&keyed_hash), Some(secret_branch.clone()));
+
+        let mut outsider = User::builder()
+            .with_identity(Ed25519::from_seed("outsider"))
+            .with_transport(transport)
+            .build();
+        outsider.receive_message(announcement.address()).await?;
+        assert!(outsider.receive_message(branch_announcement.address()).await.is_err());
+        assert_eq!(outsider.topic_by_hash(&keyed_hash), None);
+        assert!(!outsider.topics().any(|topic| topic == &secret_branch));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn backups_of_other_versions_are_refused() -> Result<()> {
+        let (_, mut subscriber, _, transport) = author_subscriber_fixture().await?;
+        let mut backup = subscriber.backup("password").await?;
+        // The version of the format is the first byte of the backup
+        backup[0] += 1;
+        let restored = User::restore(backup, "password", transport).await;
+        assert!(matches!(restored, Err(Error::BackupVersion(3, 2))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn orphans_survive_backups_and_are_retried_when_their_predecessor_arrives() -> Result<()> {
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let packet_1 = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+        let packet_2 = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        let mut reader = User::builder()
+            .with_identity(Ed25519::from_seed("reader"))
+            .with_transport(transport.clone())
+            .build();
+        reader.receive_message(announcement.address()).await?;
+        assert!(reader.receive_message(packet_2.address()).await?.is_orphan());
+
+        let backup = reader.backup("password").await?;
+        let mut reader = User::restore(backup, "password", transport.clone()).await?;
+        assert_eq!(
+            reader.orphans().collect::<Vec<_>>(),
+            [(packet_2.address(), packet_1.address().relative())]
+        );
+
+        // Receiving the predecessor directly resolves the orphan, which is then yielded by `Messages`,
+        // even after another backup
+        assert!(reader.receive_message(packet_1.address()).await?.is_tagged_packet());
+        assert_eq!(reader.orphans().count(), 0);
+        let backup = reader.backup("password").await?;
+        let mut reader = User::restore(backup, "password", transport).await?;
+        let msgs: Vec<Message> = reader.messages().try_collect().await?;
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].address(), packet_2.address());
+        assert!(msgs[0].is_tagged_packet());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn backups_written_to_a_writer_can_be_restored_from_a_reader() -> Result<()> {
+        let (mut author, mut subscriber, _, transport) = author_subscriber_fixture().await?;
+        author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+        subscriber.sync().await?;
+
+        let mut written = Vec::new();
+        subscriber.backup_to("password", &mut written).await?;
+        assert_eq!(written, subscriber.backup("password").await?);
+        assert!(User::restore_from(&written[..], "wrong password", transport.clone())
+            .await
+            .is_err());
+
+        // The restored subscriber only reads the messages published since the backup
+        let mut subscriber = User::restore_from(&written[..], "password", transport).await?;
+        let packet = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+        let msgs = subscriber.fetch_next_messages().await?;
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].address(), packet.address());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn orphans_failing_once_their_predecessor_arrives_are_reported() -> Result<()> {
+        let mut transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let packet_1 = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+        let packet_2 = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        // The reader gets a copy of the stream where the MAC of the second packet is corrupted
+        let mut tampered_transport = bucket::Client::new();
+        for address in [announcement.address(), packet_1.address()] {
+            let msg = transport.recv_message(address).await.unwrap();
+            tampered_transport.send_message(address, msg).await.unwrap();
+        }
+        let mut body: Vec<u8> = transport.recv_message(packet_2.address()).await.unwrap().into();
+        *body.last_mut().unwrap() ^= 1;
+        tampered_transport
+            .send_message(packet_2.address(), TransportMessage::new(body))
+            .await
+            .unwrap();
+
+        let mut reader = User::builder()
+            .with_identity(Ed25519::from_seed("reader"))
+            .with_transport(tampered_transport)
+            .build();
+        reader.receive_message(announcement.address()).await?;
+        assert!(reader.receive_message(packet_2.address()).await?.is_orphan());
+        assert!(reader.receive_message(packet_1.address()).await?.is_tagged_packet());
+
+        let mut rejected = Vec::new();
+        let msgs: Vec<Message> = reader
+            .messages()
+            .on_rejected(|r| rejected.push(r))
+            .try_collect()
+            .await?;
+        assert!(msgs.is_empty());
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].address, packet_2.address());
+        assert_eq!(rejected[0].message_type, Some(message_types::TAGGED_PACKET));
+        assert_eq!(reader.orphans().count(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keyloads_sent_to_matching_branches_report_the_outcome_of_each_branch() -> Result<()> {
+        let (mut author, subscriber, announcement_link, mut transport) = author_subscriber_fixture().await?;
+        author.new_branch("BASE_BRANCH", "plant1/line1").await?;
+        author.new_branch("BASE_BRANCH", "plant1/line2").await?;
+        author.new_branch("BASE_BRANCH", "plant2/line1").await?;
+
+        // The address of the next keyload of one of the branches is already taken
+        let taken_branch = Topic::from("plant1/line2");
+        let author_id = author.identifier().unwrap().clone();
+        let base = announcement_link.base();
+        let taken = Address::new(base, MsgId::gen(base, &author_id, &taken_branch, 2));
+        transport
+            .send_message(taken, TransportMessage::new(b"spam".to_vec()))
+            .await
+            .unwrap();
+
+        let filter = TopicFilter::new("plant1/#")?;
+        let subscriber_id = subscriber.identifier().unwrap().clone();
+        let subscribers = vec![Permissioned::ReadWrite(&subscriber_id, PermissionDuration::Perpetual)];
+        let is_member = |author: &User<Transport>, topic: &str| {
+            author
+                .members(&Topic::from(topic))
+                .map(|members| members.iter().any(|member| member.identifier() == &subscriber_id))
+        };
+        // The keyloads are all wrapped before any of them is sent, so none is sent
+        let result = author
+            .send_keyload_matching(&filter, subscribers.clone(), vec![], RetryPolicy::Never)
+            .await;
+        assert!(matches!(result, Err(Error::AddressUsed("keyload", address)) if address == taken));
+        assert!(!is_member(&author, "plant1/line1")?);
+        assert!(!is_member(&author, "plant1/line2")?);
+
+        let filter = TopicFilter::new("plant2/#")?;
+        let response = author
+            .send_keyload_matching(&filter, subscribers, vec![], RetryPolicy::Retries(1))
+            .await?;
+        assert!(response.is_complete());
+        let sent: Vec<&Topic> = response.sent().map(|(topic, _)| topic).collect();
+        assert_eq!(sent, vec![&Topic::from("plant2/line1")]);
+        assert!(response.outcome(&Topic::from("plant1/line1")).is_none());
+        assert!(is_member(&author, "plant2/line1")?);
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 // Rust
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 
 // IOTA
 
@@ -7,18 +8,23 @@ use alloc::vec::Vec;
 use lets::{
     address::Address,
     id::{Identity, Psk, PskId},
-    message::TransportMessage,
+    message::{TopicKey, TransportMessage},
     transport::Transport,
 };
+use spongos::{ddml::commands::unwrap::Limits, KeccakF1600};
 
 #[cfg(feature = "utangle-client")]
 use lets::transport::utangle;
 
 // Local
-use crate::{api::user::User, Result};
+use crate::{
+    api::user::{User, UserOptions},
+    message::cipher_suite::StreamPRP,
+    Result,
+};
 
 /// Builder instance for a Streams [`User`].
-pub struct UserBuilder<T> {
+pub struct UserBuilder<T, F = KeccakF1600> {
     /// Base [`Identity`] that will be used to identify a Streams [`User`]
     id: Option<Identity>,
     /// [`Transport`] Client instance.
@@ -27,6 +33,10 @@ pub struct UserBuilder<T> {
     psks: Vec<(PskId, Psk)>,
     /// Spongos Storage Type.
     lean: bool,
+    /// Optional features of the [`User`].
+    options: UserOptions,
+    /// Permutation of the cipher suite of the stream.
+    cipher_suite: PhantomData<F>,
 }
 
 impl Default for UserBuilder<()> {
@@ -36,6 +46,8 @@ impl Default for UserBuilder<()> {
             transport: (),
             psks: Default::default(),
             lean: false,
+            options: UserOptions::default(),
+            cipher_suite: PhantomData,
         }
     }
 }
@@ -47,7 +59,7 @@ impl UserBuilder<()> {
     }
 }
 
-impl<T> UserBuilder<T> {
+impl<T, F> UserBuilder<T, F> {
     /// Inject Base [`Identity`] into the [`User`] Builder.
     ///
     /// # Arguments
@@ -70,7 +82,7 @@ impl<T> UserBuilder<T> {
     ///
     /// # Arguments
     /// * `transport` - Transport Client to be used by the Streams User
-    pub fn with_transport<NewTransport>(self, transport: NewTransport) -> UserBuilder<NewTransport>
+    pub fn with_transport<NewTransport>(self, transport: NewTransport) -> UserBuilder<NewTransport, F>
     where
         NewTransport: for<'a> Transport<'a>,
     {
@@ -79,6 +91,41 @@ impl<T> UserBuilder<T> {
             id: self.id,
             psks: self.psks,
             lean: self.lean,
+            options: self.options,
+            cipher_suite: self.cipher_suite,
+        }
+    }
+
+    /// Set the [cipher suite](`crate::CipherSuite`) of the User Builder, that is the permutation its
+    /// messages are processed with. Defaults to [`KeccakF1600`].
+    ///
+    /// The cipher suite is declared in the header of every message the [`User`] sends, and a
+    /// [`User`] can only read messages declaring the same cipher suite: reading a message of another
+    /// one fails with [`Error::CipherSuite`](`crate::Error::CipherSuite`), naming the cipher suite of
+    /// the message. [`CipherSuite::of_message`](`crate::CipherSuite::of_message`) tells the cipher
+    /// suite of a stream before building the [`User`] reading it.
+    ///
+    /// # Examples
+    /// ```
+    /// use streams::{id::Ed25519, transport::bucket, User, Xoodoo};
+    ///
+    /// let user = User::builder()
+    ///     .with_identity(Ed25519::from_seed("constrained device"))
+    ///     .with_transport(bucket::Client::new())
+    ///     .with_cipher_suite::<Xoodoo>()
+    ///     .build();
+    /// ```
+    pub fn with_cipher_suite<NewF>(self) -> UserBuilder<T, NewF>
+    where
+        NewF: StreamPRP,
+    {
+        UserBuilder {
+            transport: self.transport,
+            id: self.id,
+            psks: self.psks,
+            lean: self.lean,
+            options: self.options,
+            cipher_suite: PhantomData,
         }
     }
 
@@ -109,9 +156,69 @@ impl<T> UserBuilder<T> {
         self.psks.push((pskid, psk));
         self
     }
+
+    /// Inject a [`TopicKey`] into the User Builder, enabling keyed topics.
+    ///
+    /// With a [`TopicKey`], the topic hashes in message headers are keyed with it and the branch
+    /// names carried in branch announcements can only be read with it, so that only members can
+    /// map branches to their names. All the members of a stream must use the same [`TopicKey`],
+    /// shared outside of Streams scope.
+    ///
+    /// # Arguments
+    /// * `topic_key` - Secret shared among the members of the stream
+    pub fn with_topic_key(mut self, topic_key: TopicKey) -> Self {
+        self.options.topic_key = Some(topic_key);
+        self
+    }
+
+    /// Enable the tracking of the message DAG of the stream by the User Builder.
+    ///
+    /// The [`User`] will record every message it sends or handles, so that the DAG of the stream can
+    /// be introspected with [`User::message_dag`] and exported with [`User::topology`]. The DAG
+    /// grows with every message of the stream and is not backed up: restored users must enable it
+    /// again with [`User::track_message_dag`].
+    pub fn with_message_dag(mut self) -> Self {
+        self.options.message_dag = true;
+        self
+    }
+
+    /// Enable the keyload audit log of the User Builder.
+    ///
+    /// The [`User`] will record every keyload it sends or receives, with the permissions it grants,
+    /// the pre shared keys it includes and the publishers it demotes. The log is included in
+    /// backups and can be queried with [`User::audit_log`]. Without the `std` feature there is no
+    /// system clock: a clock must be set with [`UserBuilder::with_clock`], or keyloads fail with
+    /// [`Error::NoClock`](crate::Error::NoClock).
+    pub fn with_audit_log(mut self) -> Self {
+        self.options.audit = true;
+        self
+    }
+
+    /// Inject the clock used to timestamp the keyload audit log into the User Builder. Defaults to
+    /// the system clock (`Unix` timestamp in seconds) when the `std` feature is enabled.
+    ///
+    /// # Arguments
+    /// * `clock` - Function returning the current time
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.options.clock = Some(clock);
+        self
+    }
+
+    /// Inject the [`Limits`] on the resources the [`User`] may spend unwrapping a message into the
+    /// User Builder. Defaults to [`User::default_unwrap_limits`].
+    ///
+    /// # Arguments
+    /// * `limits` - Limits applied to every message received
+    pub fn with_unwrap_limits(mut self, limits: Limits) -> Self {
+        self.options.unwrap_limits = Some(limits);
+        self
+    }
 }
 
-impl<T> UserBuilder<T> {
+impl<T, F> UserBuilder<T, F>
+where
+    F: StreamPRP,
+{
     /// Build a [`User`] instance using the Builder parameters.
     ///
     /// If a [`Transport`] is not provided, the builder will use a default client.
@@ -135,12 +242,12 @@ impl<T> UserBuilder<T> {
     /// # }
     /// ```
 
-    pub fn build<Trans>(self) -> User<Trans>
+    pub fn build<Trans>(self) -> User<Trans, F>
     where
         T: IntoTransport<Trans>,
         Trans: for<'a> Transport<'a>,
     {
-        User::new(self.id, self.psks, self.transport.into(), self.lean)
+        User::new(self.id, self.psks, self.transport.into(), self.lean, self.options)
     }
 
     /// Recover a user instance from the builder parameters.
@@ -155,7 +262,9 @@ impl<T> UserBuilder<T> {
     /// of execution, keep in mind that only the information present as messages
     /// in the stream will be recovered; OOB actions, particularly manually
     /// added or removed subscribers and PSK, will not be recovered and will
-    /// need to be reapplied manually.
+    /// need to be reapplied manually. Branches with a checkpoint the [`User`]
+    /// is a recipient of are only reread from the checkpoint on, as the
+    /// cursors of their publishers are taken from it.
     ///
     /// # Errors
     /// This function will produce errors if the [`User`] tries to recover their
@@ -191,7 +300,7 @@ impl<T> UserBuilder<T> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn recover<Trans>(self, announcement: Address) -> Result<User<Trans>>
+    pub async fn recover<Trans>(self, announcement: Address) -> Result<User<Trans, F>>
     where
         T: IntoTransport<Trans>,
         Trans: for<'a> Transport<'a, Msg = TransportMessage>,
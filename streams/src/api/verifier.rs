@@ -0,0 +1,659 @@
+// Rust
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+// 3rd-party
+use hashbrown::HashMap;
+
+// IOTA
+
+// Streams
+use lets::{
+    address::{Address, AppAddr, MsgId},
+    error::Error as LetsError,
+    id::Identifier,
+    message::{PreparsedMessage, Topic, TopicHash, TransportMessage},
+    transport::Transport,
+};
+use spongos::{error::Error as SpongosError, KeccakF1600};
+
+// Local
+use crate::{api::user::User, message::cipher_suite::StreamPRP, Error, Result};
+
+/// Number of sequence numbers probed past a missing message before assuming the publisher has not
+/// published any further
+const DEFAULT_LOOKAHEAD: usize = 3;
+
+/// An integrity issue found while verifying a stream
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Finding {
+    /// The address of the stream announcement is not derived from the author and the base branch
+    AuthorBinding {
+        /// The [`Address`] of the stream announcement
+        announcement: Address,
+        /// The author declared in the announcement
+        author: Identifier,
+    },
+    /// A publisher skipped a sequence number: the message is missing while later ones exist
+    MissingSequence {
+        /// The [`Topic`] of the branch
+        topic: Topic,
+        /// The publisher of the missing message
+        publisher: Identifier,
+        /// The missing sequence number
+        sequence: usize,
+    },
+    /// The header of a message does not match the address it was found at
+    AddressMismatch {
+        /// The [`Address`] of the message
+        address: Address,
+        /// The publisher the address was derived from
+        expected_publisher: Identifier,
+        /// The sequence number the address was derived from
+        expected_sequence: usize,
+        /// The publisher declared in the header
+        publisher: Identifier,
+        /// The sequence number declared in the header
+        sequence: usize,
+    },
+    /// The header of a message declares another branch than the one its address was derived from
+    TopicMismatch {
+        /// The [`Address`] of the message
+        address: Address,
+        /// The [`Topic`] of the branch the address was derived from
+        topic: Topic,
+        /// The [`TopicHash`] declared in the header
+        topic_hash: TopicHash,
+    },
+    /// The signature of a message does not match its publisher
+    InvalidSignature {
+        /// The [`Address`] of the message
+        address: Address,
+        /// The publisher declared in the header
+        publisher: Identifier,
+    },
+    /// A message is linked to a message that was not found in the stream
+    Orphan {
+        /// The [`Address`] of the message
+        address: Address,
+        /// The [`MsgId`] of the message it is linked to
+        linked_msg: Option<MsgId>,
+    },
+    /// A message could not be processed, either because it is malformed or because the verifier
+    /// lacks the credentials to read it
+    Unreadable {
+        /// The [`Address`] of the message
+        address: Address,
+        /// The reason the message could not be processed
+        reason: String,
+    },
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::AuthorBinding { announcement, author } => write!(
+                f,
+                "announcement {} is not bound to its author {}",
+                announcement, author
+            ),
+            Finding::MissingSequence {
+                topic,
+                publisher,
+                sequence,
+            } => write!(
+                f,
+                "message #{} of publisher {} is missing in branch '{}'",
+                sequence, publisher, topic
+            ),
+            Finding::AddressMismatch {
+                address,
+                expected_publisher,
+                expected_sequence,
+                publisher,
+                sequence,
+            } => write!(
+                f,
+                "message {} was expected from {} with sequence #{}, but its header declares {} with sequence #{}",
+                address, expected_publisher, expected_sequence, publisher, sequence
+            ),
+            Finding::TopicMismatch {
+                address,
+                topic,
+                topic_hash,
+            } => write!(
+                f,
+                "message {} was expected in branch '{}', but its header declares the branch hashed as {}",
+                address, topic, topic_hash
+            ),
+            Finding::InvalidSignature { address, publisher } => {
+                write!(f, "message {} has an invalid signature for {}", address, publisher)
+            }
+            Finding::Orphan { address, linked_msg } => match linked_msg {
+                Some(linked_msg) => write!(f, "message {} is linked to unknown message {}", address, linked_msg),
+                None => write!(f, "message {} is not linked to any message", address),
+            },
+            Finding::Unreadable { address, reason } => write!(f, "message {} is unreadable: {}", address, reason),
+        }
+    }
+}
+
+/// The outcome of the verification of a stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// The [`Address`] of the stream announcement
+    pub announcement: Address,
+    /// The author declared in the stream announcement
+    pub author: Identifier,
+    /// Number of messages successfully verified, including the announcement
+    pub verified: usize,
+    /// The integrity issues found, in the order they were found
+    pub findings: Vec<Finding>,
+}
+
+impl VerificationReport {
+    /// Returns true if no integrity issue was found
+    pub fn is_intact(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "stream: {}", self.announcement)?;
+        writeln!(f, "author: {}", self.author)?;
+        writeln!(f, "verified messages: {}", self.verified)?;
+        writeln!(f, "findings: {}", self.findings.len())?;
+        for finding in &self.findings {
+            writeln!(f, "\t* {}", finding)?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks an entire stream, message by message, and reports its integrity issues.
+///
+/// The verifier reads the stream through a [`User`], so it can only check the messages that user
+/// can read: build the [`User`] with the identity, pre shared keys and topic key of a member to
+/// verify private branches, or with none of them to verify the public part of the stream.
+///
+/// For every publisher of every branch the verifier follows its sequence numbers, checking that:
+///  - the header of each message matches the address it was found at, including its branch
+///  - each message is linked to a message of the stream
+///  - the signature of each signed message matches its publisher
+///  - the stream announcement is bound to its author
+///
+/// Missing sequence numbers are detected by probing a few sequence numbers past a message that
+/// cannot be found (see [`Verifier::with_lookahead`]).
+///
+/// # Example
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use streams::transport::bucket;
+/// use streams::{id::Ed25519, Result, User, Verifier};
+/// #
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// # let transport = Rc::new(RefCell::new(bucket::Client::new()));
+/// # let mut author = User::builder()
+/// #     .with_identity(Ed25519::from_seed("author"))
+/// #     .with_transport(transport.clone())
+/// #     .build();
+/// # let announcement = author.create_stream("BASE_BRANCH").await?.address();
+/// let reader = User::builder().with_transport(transport).build();
+/// let report = Verifier::new(reader).verify(announcement).await?;
+/// assert!(report.is_intact());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Verifier<T, F = KeccakF1600> {
+    /// The [`User`] reading the stream
+    user: User<T, F>,
+    /// Number of sequence numbers probed past a missing message
+    lookahead: usize,
+}
+
+impl<T, F> Verifier<T, F>
+where
+    F: StreamPRP,
+    T: for<'a> Transport<'a, Msg = TransportMessage>,
+{
+    /// Creates a new [`Verifier`] reading the stream through a [`User`]. The [`User`] should not be
+    /// connected to any stream yet.
+    ///
+    /// # Arguments
+    /// * `user`: The [`User`], with the read credentials to verify the stream with
+    pub fn new(user: User<T, F>) -> Self {
+        Self {
+            user,
+            lookahead: DEFAULT_LOOKAHEAD,
+        }
+    }
+
+    /// Sets the number of sequence numbers probed past a message that cannot be found. A higher
+    /// lookahead detects longer gaps, at the cost of more requests to the transport.
+    ///
+    /// # Arguments
+    /// * `lookahead`: Number of sequence numbers to probe
+    pub fn with_lookahead(mut self, lookahead: usize) -> Self {
+        self.lookahead = lookahead;
+        self
+    }
+
+    /// Consumes the [`Verifier`], returning the [`User`], synchronised with the verified stream.
+    pub fn into_user(self) -> User<T, F> {
+        self.user
+    }
+
+    /// Verifies the stream announced at the provided [`Address`]. Errors if the announcement
+    /// cannot be read; every other issue is reported in the [`VerificationReport`].
+    ///
+    /// # Arguments
+    /// * `announcement`: The [`Address`] of the stream announcement
+    pub async fn verify(&mut self, announcement: Address) -> Result<VerificationReport> {
+        let announcement_message = self.user.receive_message(announcement).await?;
+        let author = announcement_message
+            .as_announcement()
+            .map(|announcement| announcement.author_identifier.clone())
+            .ok_or(Error::Setup("the message to verify the stream from must be a stream announcement"))?;
+
+        let mut report = VerificationReport {
+            announcement,
+            author: author.clone(),
+            verified: 1,
+            findings: Vec::new(),
+        };
+
+        let base_topic = self.user.base_branch().clone();
+        let appaddr = AppAddr::gen(&author, &base_topic);
+        if announcement.base() != appaddr
+            || announcement.relative() != MsgId::gen(appaddr, &author, &base_topic, 1)
+        {
+            report.findings.push(Finding::AuthorBinding {
+                announcement,
+                author: author.clone(),
+            });
+        }
+
+        // Next sequence number to probe for each publisher of each branch, in case the user does
+        // not advance its cursor on a message it cannot process
+        let mut next_sequences: HashMap<(Topic, Identifier), usize> = HashMap::new();
+        let mut orphans: Vec<(Address, Identifier, TransportMessage)> = Vec::new();
+
+        loop {
+            let publishers: Vec<(Topic, Identifier, usize)> = self
+                .user
+                .cursors()
+                .filter(|(_, permission, _)| !permission.is_readonly())
+                .map(|(topic, permission, cursor)| (topic.clone(), permission.identifier().clone(), cursor + 1))
+                .collect();
+
+            let mut progress = false;
+            for (topic, publisher, sequence) in publishers {
+                let key = (topic, publisher);
+                let sequence = next_sequences.get(&key).map_or(sequence, |next| sequence.max(*next));
+                let (topic, publisher) = &key;
+                if let Some((address, sequence, msg)) = self.find_next(topic, publisher, sequence, &mut report).await
+                {
+                    next_sequences.insert(key.clone(), sequence + 1);
+                    progress = true;
+                    if let Some(orphan) = self.check(address, topic, publisher, sequence, msg, &mut report).await {
+                        orphans.push(orphan);
+                    }
+                }
+            }
+
+            // Messages linked to messages found later in the walk are no longer orphans
+            if progress && !orphans.is_empty() {
+                let pending = core::mem::take(&mut orphans);
+                for (address, publisher, msg) in pending {
+                    match self.user.handle_message(address, msg.clone()).await {
+                        Ok(message) if !message.is_orphan() => report.verified += 1,
+                        Ok(_) => orphans.push((address, publisher, msg)),
+                        Err(e) => report.findings.push(finding_from_error(address, publisher, e)),
+                    }
+                }
+            }
+
+            if !progress {
+                break;
+            }
+        }
+
+        for (address, _, msg) in orphans {
+            let preparsed: Option<PreparsedMessage<F>> = msg.parse_header().await.ok();
+            let linked_msg = preparsed.and_then(|preparsed| preparsed.header().linked_msg_address());
+            report.findings.push(Finding::Orphan { address, linked_msg });
+        }
+
+        Ok(report)
+    }
+
+    /// Finds the next message of a publisher in a branch, starting at the provided sequence number
+    /// and probing up to the lookahead past it. The sequence numbers skipped before the message
+    /// found are reported as missing.
+    ///
+    /// # Arguments
+    /// * `topic`: The [`Topic`] of the branch
+    /// * `publisher`: The [`Identifier`] of the publisher
+    /// * `sequence`: The expected sequence number of the next message
+    /// * `report`: The [`VerificationReport`] to report missing messages to
+    async fn find_next(
+        &mut self,
+        topic: &Topic,
+        publisher: &Identifier,
+        sequence: usize,
+        report: &mut VerificationReport,
+    ) -> Option<(Address, usize, TransportMessage)> {
+        let base_address = self.user.stream_address()?.base();
+        for probed in sequence..=sequence + self.lookahead {
+            let address = Address::new(base_address, MsgId::gen(base_address, publisher, topic, probed));
+            if let Ok(msg) = self.user.transport_mut().recv_message(address).await {
+                report
+                    .findings
+                    .extend((sequence..probed).map(|missing| Finding::MissingSequence {
+                        topic: topic.clone(),
+                        publisher: publisher.clone(),
+                        sequence: missing,
+                    }));
+                return Some((address, probed, msg));
+            }
+        }
+        None
+    }
+
+    /// Checks a message found at the address derived from a publisher and a sequence number, and
+    /// processes it. Returns the message and its publisher if it is an orphan, so it can be retried
+    /// later on.
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] the message was found at
+    /// * `topic`: The [`Topic`] of the branch the address was derived from
+    /// * `publisher`: The [`Identifier`] the address was derived from
+    /// * `sequence`: The sequence number the address was derived from
+    /// * `msg`: The [`TransportMessage`] found
+    /// * `report`: The [`VerificationReport`] to report issues to
+    async fn check(
+        &mut self,
+        address: Address,
+        topic: &Topic,
+        publisher: &Identifier,
+        sequence: usize,
+        msg: TransportMessage,
+        report: &mut VerificationReport,
+    ) -> Option<(Address, Identifier, TransportMessage)> {
+        let preparsed: PreparsedMessage<F> = match msg.clone().parse_header().await {
+            Ok(preparsed) => preparsed,
+            Err(e) => {
+                report.findings.push(Finding::Unreadable {
+                    address,
+                    reason: e.to_string(),
+                });
+                return None;
+            }
+        };
+        let header = preparsed.header();
+        if header.publisher() != publisher || header.sequence() != sequence {
+            report.findings.push(Finding::AddressMismatch {
+                address,
+                expected_publisher: publisher.clone(),
+                expected_sequence: sequence,
+                publisher: header.publisher().clone(),
+                sequence: header.sequence(),
+            });
+            return None;
+        }
+        if header.topic_hash() != &self.user.topic_hash(topic) {
+            report.findings.push(Finding::TopicMismatch {
+                address,
+                topic: topic.clone(),
+                topic_hash: *header.topic_hash(),
+            });
+            return None;
+        }
+
+        match self.user.handle_message(address, msg.clone()).await {
+            Ok(message) if message.is_orphan() => Some((address, publisher.clone(), msg)),
+            Ok(_) => {
+                report.verified += 1;
+                None
+            }
+            Err(e) => {
+                report.findings.push(finding_from_error(address, publisher.clone(), e));
+                None
+            }
+        }
+    }
+}
+
+/// Classifies an error raised while processing a message
+///
+/// # Arguments
+/// * `address`: The [`Address`] of the message
+/// * `publisher`: The [`Identifier`] of the publisher of the message
+/// * `error`: The [`Error`] raised
+fn finding_from_error(address: Address, publisher: Identifier, error: Error) -> Finding {
+    match error {
+        Error::Unwrapping(_, _, LetsError::Spongos(e)) if matches!(e.unlocated(), SpongosError::SignatureMismatch) => {
+            Finding::InvalidSignature { address, publisher }
+        }
+        e => Finding::Unreadable {
+            address,
+            reason: e.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec, vec::Vec};
+    use core::cell::RefCell;
+
+    use lets::{
+        address::{Address, MsgId},
+        id::Ed25519,
+        message::{PreparsedMessage, Topic, TransportMessage},
+        transport::{bucket, Transport as _},
+    };
+
+    use super::{Finding, Verifier};
+    use crate::{Result, User};
+
+    type Transport = Rc<RefCell<bucket::Client>>;
+
+    /// Copies the messages found at the provided addresses to a new transport, letting a function
+    /// tamper with each of them, or leave it out by returning `None`
+    ///
+    /// # Arguments
+    /// * `transport`: The transport the messages are read from
+    /// * `addresses`: The [`Address`]es of the messages to copy
+    /// * `tamper`: The function deciding what to publish at each [`Address`]
+    async fn copy_stream(
+        transport: &mut Transport,
+        addresses: &[Address],
+        mut tamper: impl FnMut(Address, TransportMessage) -> Option<(Address, TransportMessage)>,
+    ) -> Transport {
+        let mut copy = Rc::new(RefCell::new(bucket::Client::new()));
+        for address in addresses {
+            let msg = transport.recv_message(*address).await.unwrap();
+            if let Some((address, msg)) = tamper(*address, msg) {
+                copy.send_message(address, msg).await.unwrap();
+            }
+        }
+        copy
+    }
+
+    #[tokio::test]
+    async fn verifiers_report_sequence_gaps_and_the_orphans_they_leave() -> Result<()> {
+        let mut transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?.address();
+        let mut addresses = vec![announcement];
+        for _ in 0..3 {
+            addresses.push(author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?.address());
+        }
+
+        // The second packet goes missing, leaving the third one linked to nothing
+        let missing = addresses[2];
+        let copy = copy_stream(&mut transport, &addresses, |address, msg| {
+            (address != missing).then(|| (address, msg))
+        })
+        .await;
+        let report = Verifier::new(User::builder().with_transport(copy).build())
+            .verify(announcement)
+            .await?;
+
+        assert_eq!(report.verified, 2);
+        assert_eq!(
+            report.findings,
+            vec![
+                Finding::MissingSequence {
+                    topic: "BASE_BRANCH".into(),
+                    publisher: author.identifier().unwrap().clone(),
+                    sequence: 3,
+                },
+                Finding::Orphan {
+                    address: addresses[3],
+                    linked_msg: Some(missing.relative()),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verifiers_report_forged_signatures() -> Result<()> {
+        let mut transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?.address();
+        let packet = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?.address();
+        let signed_packet = author.send_signed_packet("BASE_BRANCH", b"public", b"masked").await?.address();
+
+        // The signature is the last field of a signed packet
+        let copy = copy_stream(&mut transport, &[announcement, packet, signed_packet], |address, msg| {
+            if address != signed_packet {
+                return Some((address, msg));
+            }
+            let mut body: Vec<u8> = msg.into();
+            *body.last_mut().unwrap() ^= 1;
+            Some((address, TransportMessage::new(body)))
+        })
+        .await;
+        let report = Verifier::new(User::builder().with_transport(copy).build())
+            .verify(announcement)
+            .await?;
+
+        assert_eq!(report.verified, 2);
+        assert_eq!(
+            report.findings,
+            vec![Finding::InvalidSignature {
+                address: signed_packet,
+                publisher: author.identifier().unwrap().clone(),
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verifiers_report_messages_linked_to_messages_missing_from_the_stream() -> Result<()> {
+        let mut transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let mut subscriber = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?.address();
+        subscriber.receive_message(announcement).await?;
+        let subscription = subscriber.subscribe().await?.address();
+        author.receive_message(subscription).await?;
+        let keyload = author.send_keyload_for_all_rw("BASE_BRANCH").await?.address();
+        subscriber.sync().await?;
+        // The last message of the author goes missing, so no gap reveals it
+        let missing = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?.address();
+        subscriber.sync().await?;
+        let orphan = subscriber.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?.address();
+
+        let copy = copy_stream(&mut transport, &[announcement, subscription, keyload, orphan], |address, msg| {
+            Some((address, msg))
+        })
+        .await;
+        // Only the recipients of the keyload learn that the subscriber can publish
+        let reader = User::builder()
+            .with_identity(Ed25519::from_seed("subscriber"))
+            .with_transport(copy)
+            .build();
+        let report = Verifier::new(reader).verify(announcement).await?;
+
+        assert_eq!(report.verified, 2);
+        assert_eq!(
+            report.findings,
+            vec![Finding::Orphan {
+                address: orphan,
+                linked_msg: Some(missing.relative()),
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verifiers_report_messages_declaring_another_branch() -> Result<()> {
+        let mut transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?.address();
+        let branch_announcement = author.new_branch("BASE_BRANCH", "OTHER_BRANCH").await?.address();
+        let first_packet = author.send_tagged_packet("OTHER_BRANCH", b"public", b"masked").await?.address();
+        let second_packet = author.send_tagged_packet("OTHER_BRANCH", b"public", b"masked").await?.address();
+
+        // The second packet of the other branch is also published where the next message of the
+        // author in the base branch is expected, with the same sequence number
+        let msg = transport.recv_message(second_packet).await.unwrap();
+        let preparsed: PreparsedMessage = msg.clone().parse_header().await.unwrap();
+        let sequence = preparsed.header().sequence();
+        let base_topic = Topic::from("BASE_BRANCH");
+        let misplaced = Address::new(
+            announcement.base(),
+            MsgId::gen(announcement.base(), author.identifier().unwrap(), &base_topic, sequence),
+        );
+        let mut copy = copy_stream(
+            &mut transport,
+            &[announcement, branch_announcement, first_packet, second_packet],
+            |address, msg| Some((address, msg)),
+        )
+        .await;
+        copy.send_message(misplaced, msg).await.unwrap();
+        let report = Verifier::new(User::builder().with_transport(copy).build())
+            .verify(announcement)
+            .await?;
+
+        assert_eq!(report.verified, 4);
+        assert_eq!(
+            report.findings,
+            vec![Finding::TopicMismatch {
+                address: misplaced,
+                topic: base_topic,
+                topic_hash: *preparsed.header().topic_hash(),
+            }]
+        );
+
+        Ok(())
+    }
+}
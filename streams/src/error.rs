@@ -1,6 +1,7 @@
 //! Stream Errors
 
 // Rust
+use alloc::string::String;
 use core::{array::TryFromSliceError, fmt::Debug};
 
 // 3rd-party
@@ -17,6 +18,9 @@ use lets::{
 
 use spongos::error::Error as SpongosError;
 
+// Local
+use crate::{api::codec::ContentType, message::cipher_suite::CipherSuite};
+
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Debug, Error)]
@@ -31,6 +35,12 @@ pub enum Error {
     )]
     AddressUsed(&'static str, Address),
 
+    #[error("Backup is in version {0} of the backup format, but this version of the library only restores version {1}")]
+    BackupVersion(u8, u8),
+
+    #[error("Message '{0}' is wrapped with the {1} cipher suite, but the user processes messages with {2}")]
+    CipherSuite(Address, CipherSuite, CipherSuite),
+
     #[error("Unexpected message type {0}")]
     MessageTypeUnknown(u8),
 
@@ -40,6 +50,11 @@ pub enum Error {
     #[error("Failed to get messages. Error: {0}")]
     Messages(anyhow::Error),
 
+    #[error(
+        "The audit log needs a clock to timestamp keyloads. Without the `std` feature there is no system clock, and one must be set"
+    )]
+    NoClock,
+
     #[error(
         "User does not have a cursor stored in branch '{0}'. This probably means the user does not have write permission within that branch"
     )]
@@ -48,6 +63,12 @@ pub enum Error {
     #[error("User does not have an identity, but needs one to {0}")]
     NoIdentity(&'static str),
 
+    #[error("Topic {0} has a single level, so it has no parent branch")]
+    NoParentTopic(Topic),
+
+    #[error("Message '{0}' carries no payload")]
+    NoPayload(Address),
+
     #[error("User identity contains no secret key")]
     NoSecretKey,
 
@@ -62,9 +83,18 @@ Any {0} message must be linked to a previous message by including the address of
     )]
     NotLinked(&'static str, Address),
 
+    #[error("Invalid page token: {0}")]
+    PageToken(&'static str),
+
+    #[error("Payload does not match the expected schema of its {0} content: {1}")]
+    PayloadDecoding(ContentType, String),
+
     #[error("A payload must be specified in order to send a message")]
     PayloadEmpty,
 
+    #[error("Failed to encode payload as {0}: {1}")]
+    PayloadEncoding(ContentType, String),
+
     #[error("Setup error: {0}")]
     Setup(&'static str),
 
@@ -80,6 +110,9 @@ Any {0} message must be linked to a previous message by including the address of
     #[error("Topic by hash {0} is not known")]
     UnknownTopic(TopicHash),
 
+    #[error("No codec available to decode {0} payloads, the feature enabling it may be missing")]
+    UnsupportedContentType(ContentType),
+
     #[error("Error unwrapping the message {0}. The message at address '{1:#?}' could not be unwrapped: {2}")]
     Unwrapping(&'static str, Address, LetsError),
 
@@ -44,17 +44,35 @@ mod message;
 mod api;
 
 pub use api::{
+    audit::{AuditLog, KeyloadRecord},
+    codec::{Codec, ContentType},
+    events::{Event, Observer},
+    fan_out::{FanOutResponse, RetryPolicy},
     message::{Message, MessageContent},
     message_builder::MessageBuilder,
-    messages::Messages,
-    selector::Selector,
+    messages::{Messages, Page, PageToken, Rejected},
+    selector::{MessageKind, Selector},
     send_response::SendResponse,
+    topology::{BranchInfo, Membership, MessageNode, Topology},
     user::User,
     user_builder::UserBuilder,
+    verifier::{Finding, VerificationReport, Verifier},
 };
+pub use message::{
+    checkpoint::CheckpointHistory,
+    cipher_suite::{CipherSuite, StreamPRP},
+};
+
+#[cfg(feature = "bincode")]
+pub use api::codec::Bincode;
+#[cfg(feature = "cbor")]
+pub use api::codec::Cbor;
+#[cfg(feature = "json")]
+pub use api::codec::Json;
 
 /// Errors for Streams
 mod error;
 pub use error::{Error, Result};
 
 pub use lets::{address::Address, id, message::TransportMessage, transport};
+pub use spongos::{ddml::commands::unwrap::Limits, KeccakF1600, KeccakP1600R12, Xoodoo};
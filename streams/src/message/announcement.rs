@@ -7,11 +7,14 @@
 //!
 //! ```ddml
 //! message Announcement {
-//!     mask             u8     identifier;
-//!     mask             u8     topic;
+//!     mask                u8      identifier_type;    // 0 for ed25519
+//!     mask                u8      identifier[32];
+//!     mask                bytes   topic;
+//!     absorb              u8      signature_type;     // 0 for ed25519
+//!     commit;
+//!     squeeze external    u8      hash[64];
+//!     ed25519(hash)       u8      signature[64];
 //!     commit;
-//!     squeeze          u8     hash[64];
-//!     ed25519(hash)           sig;
 //! }
 //! ```
 
@@ -71,8 +74,9 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
 }
 
 #[async_trait(?Send)]
-impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
+impl<'a, OS, F> ContentWrap<Wrap<'a>> for wrap::Context<OS, F>
 where
+    F: PRP,
     OS: io::OStream,
 {
     async fn wrap(&mut self, announcement: &mut Wrap<'a>) -> Result<&mut Self> {
@@ -124,11 +128,41 @@ where
     IS: io::IStream,
 {
     async fn unwrap(&mut self, announcement: &mut Unwrap) -> Result<&mut Self> {
-        self.mask(&mut announcement.author_id)?
+        self.push_breadcrumb("announcement")
+            .mask(&mut announcement.author_id)?
             .mask(&mut announcement.topic)?
             .verify(&announcement.author_id)
             .await?
-            .commit()?;
+            .commit()?
+            .pop_breadcrumb();
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lets::{
+        id::{Ed25519, Identity},
+        message::Topic,
+    };
+    use spongos::ddml::schema::{Record, Value};
+
+    use super::Wrap;
+    use crate::message::assert_documented_schema;
+
+    #[tokio::test]
+    async fn announcements_match_their_documented_schema() {
+        let author = Identity::from(Ed25519::from_seed("author"));
+        let topic = Topic::from("base branch");
+
+        let mut announcement = Wrap::new(&author, &topic);
+        let record = Record::new()
+            .with("identifier_type", Value::Uint8(0))
+            .with("identifier", Value::Bytes(author.identifier().as_ref().to_vec()))
+            .with("topic", Value::Bytes(topic.as_ref().to_vec()))
+            .with("signature_type", Value::Uint8(0))
+            .with("signature_key", Value::Bytes(Ed25519::from_seed("author").as_ref().to_vec()));
+        let keys = Record::new().with("signature_key", Value::Bytes(author.identifier().as_ref().to_vec()));
+        assert_documented_schema(include_str!("announcement.rs"), &mut announcement, record, keys, false).await;
+    }
+}
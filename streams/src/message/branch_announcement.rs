@@ -3,17 +3,22 @@
 //! The `BranchAnnounce` message creates a new branch in a Stream.
 //!
 //! It announces the [`Topic`] for the new branch, as well as informs of the previous branch topic
-//! the new branch is being generated from.
+//! the new branch is being generated from. On streams that use a [`TopicKey`], the key is absorbed
+//! before the topics are masked, so that only members holding the key can learn the new branch name.
 //!
 //! ```ddml
 //! message BranchAnnounce {
 //!     join(spongos);
-//!     mask             u8     identifier;
-//!     mask             u8     new_topic;
-//!     mask             u8     previous_topic;
+//!     absorb external  u8     topic_key[32];  // only for streams using keyed topics
+//!     commit;                                 // only for streams using keyed topics
+//!     mask             u8     identifier_type;    // 0 for ed25519
+//!     mask             u8     identifier[32];
+//!     mask             bytes  new_topic;
+//!     absorb           u8     signature_type;     // 0 for ed25519
+//!     commit;
+//!     squeeze external u8     hash[64];
+//!     ed25519(hash)    u8     signature[64];
 //!     commit;
-//!     squeeze          u8     hash[64];
-//!     ed25519(hash)           sig;
 //! }
 //! ```
 
@@ -28,48 +33,61 @@ use async_trait::async_trait;
 // Streams
 use lets::{
     id::{Identifier, Identity},
-    message::{ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, Topic},
+    message::{
+        ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap, Topic, TopicKey,
+    },
 };
 use spongos::{
     ddml::{
-        commands::{sizeof, unwrap, wrap, Commit, Join, Mask},
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Join, Mask},
         io,
+        modifiers::External,
+        types::NBytes,
     },
     error::Result,
-    Spongos,
+    Spongos, PRP,
 };
 
 // Local
 
 /// A struct that holds references needed for branch announcement message encoding
-pub(crate) struct Wrap<'a> {
+pub(crate) struct Wrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// The [`Identity`] of the publisher
     user_id: &'a Identity,
     /// The new branch [`Topic`]
     new_topic: &'a Topic,
+    /// The [`TopicKey`] of the stream, if topics are keyed
+    topic_key: Option<&'a TopicKey>,
 }
 
-impl<'a> Wrap<'a> {
+impl<'a, F> Wrap<'a, F> {
     /// Creates a new [`Wrap`] struct for a branch announcement message
     ///
     /// # Arguments
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
     /// * `user_id`: The [`Identity`] of the publisher
     /// * `new_topic`: the new branch [`Topic`]
-    pub(crate) fn new(initial_state: &'a mut Spongos, user_id: &'a Identity, new_topic: &'a Topic) -> Self {
+    /// * `topic_key`: The [`TopicKey`] of the stream, if topics are keyed
+    pub(crate) fn new(
+        initial_state: &'a mut Spongos<F>,
+        user_id: &'a Identity,
+        new_topic: &'a Topic,
+        topic_key: Option<&'a TopicKey>,
+    ) -> Self {
         Self {
             initial_state,
             user_id,
             new_topic,
+            topic_key,
         }
     }
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
-    async fn sizeof(&mut self, announcement: &Wrap<'a>) -> Result<&mut Self> {
+impl<'a, F> ContentSizeof<Wrap<'a, F>> for sizeof::Context {
+    async fn sizeof(&mut self, announcement: &Wrap<'a, F>) -> Result<&mut Self> {
         self.mask(announcement.user_id.identifier())?
             .mask(announcement.new_topic)?
             .sign_sizeof(announcement.user_id)
@@ -80,13 +98,17 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
 }
 
 #[async_trait(?Send)]
-impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
+impl<'a, OS, F> ContentWrap<Wrap<'a, F>> for wrap::Context<OS, F>
 where
+    F: PRP,
     OS: io::OStream,
 {
-    async fn wrap(&mut self, announcement: &mut Wrap<'a>) -> Result<&mut Self> {
-        self.join(announcement.initial_state)?
-            .mask(announcement.user_id.identifier())?
+    async fn wrap(&mut self, announcement: &mut Wrap<'a, F>) -> Result<&mut Self> {
+        self.join(announcement.initial_state)?;
+        if let Some(topic_key) = announcement.topic_key {
+            self.absorb(External::new(&NBytes::new(topic_key)))?.commit()?;
+        }
+        self.mask(announcement.user_id.identifier())?
             .mask(announcement.new_topic)?
             .sign(announcement.user_id)
             .await?
@@ -96,22 +118,26 @@ where
 }
 
 /// A struct that holds the placeholders needed for branch announcement message decoding
-pub(crate) struct Unwrap<'a> {
+pub(crate) struct Unwrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// The new branch [`Topic`]
     new_topic: Topic,
+    /// The [`TopicKey`] of the stream, if topics are keyed
+    topic_key: Option<&'a TopicKey>,
 }
 
-impl<'a> Unwrap<'a> {
+impl<'a, F> Unwrap<'a, F> {
     /// Cretes a new [`Unwrap`] struct for a branch announcement message
     ///
     /// # Arguments
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    /// * `topic_key`: The [`TopicKey`] of the stream, if topics are keyed
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>, topic_key: Option<&'a TopicKey>) -> Self {
         Self {
             initial_state,
             new_topic: Topic::default(),
+            topic_key,
         }
     }
 
@@ -127,18 +153,76 @@ impl<'a> Unwrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
+impl<'a, IS, F> ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
 where
+    F: PRP,
     IS: io::IStream,
 {
-    async fn unwrap(&mut self, announcement: &mut Unwrap) -> Result<&mut Self> {
+    async fn unwrap(&mut self, announcement: &mut Unwrap<'a, F>) -> Result<&mut Self> {
         let mut author_id = Identifier::default();
-        self.join(announcement.initial_state)?
-            .mask(&mut author_id)?
+        self.push_breadcrumb("branch_announcement")
+            .join(announcement.initial_state)?;
+        if let Some(topic_key) = announcement.topic_key {
+            self.absorb(External::new(&NBytes::new(topic_key)))?.commit()?;
+        }
+        self.mask(&mut author_id)?
             .mask(&mut announcement.new_topic)?
             .verify(&author_id)
             .await?
-            .commit()?;
+            .commit()?
+            .pop_breadcrumb();
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use lets::{
+        id::{Ed25519, Identity},
+        message::{ContentSizeof, ContentUnwrap, ContentWrap, Topic, TopicKey},
+    };
+    use spongos::{
+        ddml::commands::{sizeof, unwrap, wrap},
+        KeccakF1600, Spongos,
+    };
+
+    use super::{Unwrap, Wrap};
+
+    async fn wrap_branch_announcement(linked: Spongos<KeccakF1600>, topic_key: Option<&TopicKey>) -> Vec<u8> {
+        let author = Identity::from(Ed25519::from_seed("author"));
+        let new_topic = Topic::from("SECRET_BRANCH");
+        let mut initial_state = linked;
+        let mut announcement = Wrap::new(&mut initial_state, &author, &new_topic, topic_key);
+        let mut ctx = sizeof::Context::new();
+        ctx.sizeof(&announcement).await.unwrap();
+        let mut buf = vec![0; ctx.finalize()];
+        let mut ctx = wrap::Context::new(&mut buf[..]);
+        ctx.wrap(&mut announcement).await.unwrap();
+        buf
+    }
+
+    #[tokio::test]
+    async fn branch_announcements_of_keyed_streams_are_unreadable_without_the_key() {
+        let mut linked = Spongos::<KeccakF1600>::init();
+        linked.absorb(b"announcement");
+        let topic_key = TopicKey::from_seed("topic key");
+        let buf = wrap_branch_announcement(linked, Some(&topic_key)).await;
+
+        let mut initial_state = linked;
+        let mut announcement = Unwrap::new(&mut initial_state, Some(&topic_key));
+        let mut ctx = unwrap::Context::new(&buf[..]);
+        ctx.unwrap(&mut announcement).await.unwrap();
+        assert_eq!(announcement.new_topic(), &Topic::from("SECRET_BRANCH"));
+
+        let another_key = TopicKey::from_seed("another topic key");
+        for topic_key in [None, Some(&another_key)] {
+            let mut initial_state = linked;
+            let mut announcement = Unwrap::new(&mut initial_state, topic_key);
+            let mut ctx = unwrap::Context::new(&buf[..]);
+            assert!(ctx.unwrap(&mut announcement).await.is_err());
+            assert_ne!(announcement.new_topic(), &Topic::from("SECRET_BRANCH"));
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! `Checkpoint` message _wrapping_ and _unwrapping_.
+//!
+//! The `Checkpoint` message snapshots the state of a branch, so that users joining the stream can
+//! start reading the branch from it instead of replaying its whole history.
+//!
+//! It renews the key of the branch exactly like a [`Keyload`](super::keyload) does, for the
+//! members of the branch and a set of pre shared keys, and then masks the cursor of every member
+//! of the branch. Messages published afterwards are linked to the checkpoint. The key exchange and
+//! the snapshot are sealed apart, and the message absorbs the sealed bytes before being signed, so
+//! that every reader can verify the signature of the admin, whether it is a recipient or not. The
+//! [`Spongos`] of the sealed snapshot, which holds the new key of the branch, is then joined, so
+//! that only the recipients of the checkpoint can read the messages linked to it.
+//!
+//! Whether the messages published before the checkpoint stay readable by the users starting from it
+//! is set by its [`CheckpointHistory`]: the snapshot then also masks the [`Spongos`] of the
+//! messages of the branch they are linked to.
+//!
+//! ```ddml
+//! message Checkpoint {
+//!     absorb                      u8  sealed[];
+//!     commit;
+//!     squeeze external            u8  hash[64];
+//!     ed25519(hash)               u8  signature[64];
+//!     commit;
+//!     join(sealed);
+//! }
+//!
+//! message Sealed {
+//!     keyload;
+//!     mask                        u8  size(n_members);
+//!     repeated(n_members):
+//!       mask                      u8  permissioned;
+//!       mask                      u8  size(cursor);
+//!     mask                        u8  history;
+//!     mask                        u8  size(n_spongos);
+//!     repeated(n_spongos):
+//!       mask                      u8  msgid[12];
+//!       mask                      u8  spongos[200];   // 48 bytes with Xoodoo
+//!     commit;
+//! }
+//! ```
+
+// Rust
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{convert::TryFrom, mem};
+
+// 3rd-party
+use async_trait::async_trait;
+use hashbrown::HashMap;
+
+// IOTA
+
+// Streams
+use lets::{
+    address::MsgId,
+    id::{Identifier, Identity, Permissioned, Psk, PskId},
+    message::{ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap},
+};
+use spongos::{
+    ddml::{
+        commands::{sizeof, unwrap, wrap, Absorb, Commit, Join, Mask},
+        io,
+        types::{Bytes, Size, Uint8},
+    },
+    error::{Error as SpongosError, Result},
+    Spongos, PRP,
+};
+
+// Local
+use crate::message::keyload;
+
+/// Whether the messages of a branch published before a checkpoint stay readable by the members of
+/// the branch that start reading it from the checkpoint
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CheckpointHistory {
+    /// None of the messages published before the checkpoint can be read from it
+    Dropped,
+    /// Only the messages linked to the edge of the branch the checkpoint is published against, by
+    /// publishers that had not seen the checkpoint yet, can still be read
+    Edge,
+    /// Every message of the branch whose linked message is known to the admin can still be read,
+    /// by [receiving it](`crate::User::receive_message`) from its address
+    Readable,
+}
+
+impl Default for CheckpointHistory {
+    fn default() -> Self {
+        CheckpointHistory::Dropped
+    }
+}
+
+impl From<CheckpointHistory> for u8 {
+    fn from(history: CheckpointHistory) -> Self {
+        match history {
+            CheckpointHistory::Dropped => 0,
+            CheckpointHistory::Edge => 1,
+            CheckpointHistory::Readable => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for CheckpointHistory {
+    type Error = SpongosError;
+
+    fn try_from(history: u8) -> Result<Self> {
+        match history {
+            0 => Ok(CheckpointHistory::Dropped),
+            1 => Ok(CheckpointHistory::Edge),
+            2 => Ok(CheckpointHistory::Readable),
+            o => Err(SpongosError::InvalidOption("checkpoint history", o)),
+        }
+    }
+}
+
+/// A struct that holds references needed for checkpoint message encoding
+pub(crate) struct Wrap<'a, 'b, F, Subscribers, Psks> {
+    /// The key exchange renewing the key of the branch
+    keyload: keyload::Wrap<'a, 'b, F, Subscribers, Psks>,
+    /// The members of the branch, with their cursors
+    cursors: &'a [(Permissioned<Identifier>, usize)],
+    /// Which messages published before the checkpoint stay readable
+    history: CheckpointHistory,
+    /// The [`Spongos`] of the messages published before the checkpoint that stay readable
+    history_spongos: &'a [(MsgId, Spongos<F>)],
+    /// The [`Identity`] of the admin publishing the checkpoint
+    user_id: &'a Identity,
+}
+
+impl<'a, 'b, F, Subscribers, Psks> Wrap<'a, 'b, F, Subscribers, Psks> {
+    /// Creates a new [`Wrap`] struct for a checkpoint message
+    ///
+    /// # Arguments
+    /// * `keyload`: The key exchange renewing the key of the branch
+    /// * `cursors`: The members of the branch, with their cursors
+    /// * `history`: Which messages published before the checkpoint stay readable
+    /// * `history_spongos`: The [`Spongos`] of the messages published before the checkpoint that
+    ///   stay readable, by address
+    /// * `user_id`: The [`Identity`] of the admin publishing the checkpoint
+    pub(crate) fn new(
+        keyload: keyload::Wrap<'a, 'b, F, Subscribers, Psks>,
+        cursors: &'a [(Permissioned<Identifier>, usize)],
+        history: CheckpointHistory,
+        history_spongos: &'a [(MsgId, Spongos<F>)],
+        user_id: &'a Identity,
+    ) -> Self {
+        Self {
+            keyload,
+            cursors,
+            history,
+            history_spongos,
+            user_id,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, 'b, F, Subscribers, Psks> ContentSizeof<Wrap<'a, 'b, F, Subscribers, Psks>> for sizeof::Context
+where
+    F: PRP,
+    sizeof::Context: ContentSizeof<keyload::Wrap<'a, 'b, F, Subscribers, Psks>>,
+{
+    async fn sizeof(&mut self, checkpoint: &Wrap<'a, 'b, F, Subscribers, Psks>) -> Result<&mut sizeof::Context> {
+        let mut sealed = sizeof::Context::new();
+        sealed
+            .sizeof(&checkpoint.keyload)
+            .await?
+            .mask(Size::new(checkpoint.cursors.len()))?;
+        for (permission, cursor) in checkpoint.cursors {
+            sealed.mask(permission)?.mask(Size::new(*cursor))?;
+        }
+        sealed
+            .mask(Uint8::new(checkpoint.history.into()))?
+            .mask(Size::new(checkpoint.history_spongos.len()))?;
+        for (address, spongos) in checkpoint.history_spongos {
+            sealed.mask(address)?.mask(spongos)?;
+        }
+        sealed.commit()?;
+        // Joining the sealed spongos takes no space in the message
+        self.absorb(Bytes::new(vec![0; sealed.finalize()]))?
+            .sign_sizeof(checkpoint.user_id)
+            .await?
+            .commit()?;
+        Ok(self)
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, 'b, OS, F, Subscribers, Psks> ContentWrap<Wrap<'a, 'b, F, Subscribers, Psks>> for wrap::Context<OS, F>
+where
+    F: PRP,
+    OS: io::OStream,
+    wrap::Context<Vec<u8>, F>: ContentWrap<keyload::Wrap<'a, 'b, F, Subscribers, Psks>>,
+{
+    async fn wrap(&mut self, checkpoint: &mut Wrap<'a, 'b, F, Subscribers, Psks>) -> Result<&mut Self> {
+        // Only the recipients of the key exchange can read the sealed snapshot
+        let mut sealed = wrap::Context::<_, F>::new(Vec::new());
+        sealed
+            .wrap(&mut checkpoint.keyload)
+            .await?
+            .mask(Size::new(checkpoint.cursors.len()))?;
+        for (permission, cursor) in checkpoint.cursors {
+            sealed.mask(permission)?.mask(Size::new(*cursor))?;
+        }
+        sealed
+            .mask(Uint8::new(checkpoint.history.into()))?
+            .mask(Size::new(checkpoint.history_spongos.len()))?;
+        for (address, spongos) in checkpoint.history_spongos {
+            sealed.mask(address)?.mask(spongos)?;
+        }
+        sealed.commit()?;
+        let sealed_bytes = mem::take(sealed.stream_mut());
+        // The sealed spongos holds the new key of the branch: joining it keeps the messages linked
+        // to the checkpoint away from the readers that are not recipients of it
+        let mut sealed_spongos = sealed.finalize();
+        self.absorb(Bytes::new(sealed_bytes))?
+            .sign(checkpoint.user_id)
+            .await?
+            .commit()?
+            .join(&mut sealed_spongos)?;
+        Ok(self)
+    }
+}
+
+/// A struct that holds the placeholders needed for checkpoint message decoding
+pub(crate) struct Unwrap<'a, F> {
+    /// The key exchange renewing the key of the branch
+    keyload: keyload::Unwrap<'a, F>,
+    /// The members of the branch, with their cursors
+    cursors: Vec<(Permissioned<Identifier>, usize)>,
+    /// Which messages published before the checkpoint stay readable
+    history: CheckpointHistory,
+    /// The [`Spongos`] of the messages published before the checkpoint that stay readable
+    history_spongos: Vec<(MsgId, Spongos<F>)>,
+    /// The [`Identifier`] of the admin that published the checkpoint
+    publisher: &'a Identifier,
+    /// Whether the reader is a recipient of the checkpoint, and therefore could read the snapshot
+    readable: bool,
+}
+
+impl<'a, F> Unwrap<'a, F> {
+    /// Creates a new [`Unwrap`] struct for a checkpoint message
+    ///
+    /// # Arguments
+    /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
+    /// * `user_id`: The optional [`Identity`] of the reading user
+    /// * `publisher`: The [`Identifier`] of the admin that published the checkpoint
+    /// * `psk_store`: A reference to the pre shared keys of the reading user
+    pub(crate) fn new(
+        initial_state: &'a mut Spongos<F>,
+        user_id: Option<&'a Identity>,
+        publisher: &'a Identifier,
+        psk_store: &'a HashMap<PskId, Psk>,
+    ) -> Self {
+        Self {
+            keyload: keyload::Unwrap::new(initial_state, user_id, publisher, psk_store),
+            cursors: Vec::new(),
+            history: CheckpointHistory::default(),
+            history_spongos: Vec::new(),
+            publisher,
+            readable: false,
+        }
+    }
+
+    /// Returns true if the reader is a recipient of the checkpoint
+    pub(crate) fn is_readable(&self) -> bool {
+        self.readable
+    }
+
+    /// Returns the members of the branch, with their cursors
+    pub(crate) fn cursors(&self) -> &[(Permissioned<Identifier>, usize)] {
+        &self.cursors
+    }
+
+    /// Returns the [`Spongos`] of the messages published before the checkpoint that stay readable,
+    /// by address
+    pub(crate) fn history_spongos(&self) -> &[(MsgId, Spongos<F>)] {
+        &self.history_spongos
+    }
+
+    /// Consumes the [`Unwrap`], returning the members of the branch with their cursors, the pre
+    /// shared keys of the reader that were included in the key exchange and which messages
+    /// published before the checkpoint stay readable
+    pub(crate) fn into_parts(self) -> (Vec<(Permissioned<Identifier>, usize)>, Vec<PskId>, CheckpointHistory) {
+        (self.cursors, self.keyload.psks, self.history)
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, IS, F> ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
+where
+    F: PRP + Clone + Default,
+    IS: io::IStream,
+{
+    async fn unwrap(&mut self, checkpoint: &mut Unwrap<'a, F>) -> Result<&mut Self> {
+        let mut sealed = Vec::new();
+        // The signature covers the sealed snapshot, so every reader verifies it before opening it
+        self.push_breadcrumb("checkpoint")
+            .absorb(Bytes::new(&mut sealed))?
+            .verify(checkpoint.publisher)
+            .await?
+            .commit()?;
+
+        // Errors in the sealed snapshot are located within it
+        let mut sealed = unwrap::Context::<_, F>::new(&sealed[..]).with_limits(*self.limits());
+        sealed
+            .push_breadcrumb("checkpoint")
+            .push_breadcrumb("sealed")
+            .unwrap(&mut checkpoint.keyload)
+            .await?;
+        // Without the key of the branch the snapshot cannot be read, like the branch itself, and the
+        // joined spongos does not hold the key either
+        if checkpoint.keyload.is_recipient() {
+            checkpoint.open(&mut sealed)?;
+            checkpoint.readable = true;
+        }
+        let (mut sealed_spongos, _) = sealed.finalize();
+        self.join(&mut sealed_spongos)?.pop_breadcrumb();
+        Ok(self)
+    }
+}
+
+impl<'a, F> Unwrap<'a, F>
+where
+    F: PRP + Default,
+{
+    /// Unmasks the snapshot sealed with the key of the branch
+    ///
+    /// # Arguments
+    /// * `sealed`: The [`unwrap::Context`] of the sealed snapshot, past its key exchange
+    fn open(&mut self, sealed: &mut unwrap::Context<&[u8], F>) -> Result<()> {
+        let mut amount_cursors = Size::default();
+        sealed
+            .mask(&mut amount_cursors)?
+            .ensure_repeated(amount_cursors.inner())?
+            .push_breadcrumb("cursors");
+        for i in 0..amount_cursors.inner() {
+            let mut permission = Permissioned::default();
+            let mut cursor = Size::default();
+            sealed.index_breadcrumb(i).mask(&mut permission)?.mask(&mut cursor)?;
+            self.cursors.push((permission, cursor.inner()));
+        }
+        sealed.pop_breadcrumb();
+        let mut history = Uint8::default();
+        let mut amount_spongos = Size::default();
+        sealed.mask(&mut history)?;
+        self.history = CheckpointHistory::try_from(history.inner()).map_err(|e| sealed.locate(e))?;
+        sealed
+            .mask(&mut amount_spongos)?
+            .ensure_repeated(amount_spongos.inner())?
+            .push_breadcrumb("history");
+        for i in 0..amount_spongos.inner() {
+            let mut address = MsgId::default();
+            let mut spongos = Spongos::default();
+            sealed.index_breadcrumb(i).mask(&mut address)?.mask(&mut spongos)?;
+            self.history_spongos.push((address, spongos));
+        }
+        sealed.pop_breadcrumb().commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use lets::{
+        id::Psk,
+        message::TransportMessage,
+        transport::{bucket, Transport as _},
+    };
+
+    use super::CheckpointHistory;
+    use crate::{api::messages::tests::author_subscriber_fixture, Result, User};
+
+    #[tokio::test]
+    async fn readers_start_from_the_checkpoints_they_are_recipients_of() -> Result<()> {
+        let p = b"payload";
+        let (mut author, mut subscriber1, announcement_link, transport) = author_subscriber_fixture().await?;
+        author.send_keyload_for_all_rw("BASE_BRANCH").await?;
+        subscriber1.sync().await?;
+        let mut early_signed_packet = None;
+        for _ in 0..3 {
+            early_signed_packet = Some(subscriber1.send_signed_packet("BASE_BRANCH", &p, &p).await?);
+        }
+        author.sync().await?;
+
+        let psk = Psk::from_seed("checkpoint psk");
+        author.add_psk(psk);
+        let checkpoint = author
+            .send_checkpoint("BASE_BRANCH", CheckpointHistory::Dropped)
+            .await?;
+        subscriber1.sync().await?;
+        let last_signed_packet = subscriber1.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+        let author_packet = author.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+
+        // The reader only holds the pre shared key included in the checkpoint
+        let mut reader = User::builder()
+            .with_psk(psk.to_pskid(), psk)
+            .with_transport(transport.clone())
+            .build();
+        reader.receive_message(announcement_link).await?;
+        let msgs = reader.fetch_next_messages().await?;
+
+        assert!(msgs.iter().all(|msg| !msg.is_orphan()));
+        assert!(msgs.iter().any(|msg| msg.address() == checkpoint.address()
+            && msg.as_checkpoint().map_or(false, |checkpoint| checkpoint.is_readable())));
+        assert!(msgs.iter().any(|msg| msg.address() == last_signed_packet.address()
+            && msg.masked_payload() == Some(&p[..])));
+        // The history of the branch is dropped: the messages published before the checkpoint are
+        // not linked to anything the reader knows
+        let early_signed_packet = early_signed_packet.unwrap();
+        assert!(reader.receive_message(early_signed_packet.address()).await?.is_orphan());
+
+        // Readers that are not recipients of the checkpoint still verify its signature
+        let mut outsider = User::builder().with_transport(transport.clone()).build();
+        outsider.receive_message(announcement_link).await?;
+        let msg = outsider.receive_message(checkpoint.address()).await?;
+        assert!(!msg.as_checkpoint().map_or(true, |checkpoint| checkpoint.is_readable()));
+        // but cannot read the messages linked to it
+        assert!(outsider.receive_message(author_packet.address()).await.is_err());
+
+        let mut tampered_transport = bucket::Client::new();
+        let mut transport = transport;
+        let announcement = transport.recv_message(announcement_link).await.unwrap();
+        tampered_transport
+            .send_message(announcement_link, announcement)
+            .await
+            .unwrap();
+        let mut body: Vec<u8> = transport.recv_message(checkpoint.address()).await.unwrap().into();
+        *body.last_mut().unwrap() ^= 1;
+        tampered_transport
+            .send_message(checkpoint.address(), TransportMessage::new(body))
+            .await
+            .unwrap();
+        let mut outsider = User::builder().with_transport(tampered_transport).build();
+        outsider.receive_message(announcement_link).await?;
+        assert!(outsider.receive_message(checkpoint.address()).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn checkpoints_can_keep_the_history_of_their_branch_readable() -> Result<()> {
+        let p = b"payload";
+        let (mut author, mut subscriber1, announcement_link, transport) = author_subscriber_fixture().await?;
+        author.send_keyload_for_all_rw("BASE_BRANCH").await?;
+        subscriber1.sync().await?;
+        subscriber1.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+        let signed_packet = subscriber1.send_signed_packet("BASE_BRANCH", &p, &p).await?;
+        author.sync().await?;
+
+        let psk = Psk::from_seed("checkpoint psk");
+        author.add_psk(psk);
+        let checkpoint = author
+            .send_checkpoint("BASE_BRANCH", CheckpointHistory::Readable)
+            .await?;
+
+        let mut reader = User::builder()
+            .with_psk(psk.to_pskid(), psk)
+            .with_transport(transport)
+            .build();
+        reader.receive_message(announcement_link).await?;
+        let msgs = reader.fetch_next_messages().await?;
+        assert!(msgs.iter().any(|msg| msg.address() == checkpoint.address()
+            && msg.as_checkpoint().map(|checkpoint| checkpoint.history) == Some(CheckpointHistory::Readable)));
+        // The messages published before the checkpoint are skipped, but can still be read
+        assert!(msgs.iter().all(|msg| msg.address() != signed_packet.address()));
+        let msg = reader.receive_message(signed_packet.address()).await?;
+        assert_eq!(msg.masked_payload(), Some(&p[..]));
+
+        Ok(())
+    }
+}
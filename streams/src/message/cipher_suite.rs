@@ -0,0 +1,200 @@
+// Rust
+use core::{fmt, hash::Hash};
+
+// 3rd-party
+
+// IOTA
+
+// Streams
+use lets::{address::Address, message::TransportMessage, transport::Transport};
+use spongos::{KeccakF1600, KeccakP1600R12, Xoodoo, PRP};
+
+// Local
+use crate::{Error, Result};
+
+/// Cipher suite of a stream, identifying the pseudo-random permutation its messages are processed
+/// with. It is declared in the cleartext prefix of the header of every message, and every reader of
+/// the stream must use it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CipherSuite {
+    /// `Keccak-f[1600]`, the full 24 rounds permutation
+    KeccakF1600,
+    /// `Keccak-p[1600,12]`, the 12 rounds permutation trading security margin for speed
+    KeccakP1600R12,
+    /// `Xoodoo[12]`, the 384-bit permutation for constrained devices
+    Xoodoo,
+    /// A cipher suite this version of the library does not know about
+    Unknown(u8),
+}
+
+impl From<u8> for CipherSuite {
+    fn from(id: u8) -> Self {
+        match id {
+            0 => CipherSuite::KeccakF1600,
+            1 => CipherSuite::KeccakP1600R12,
+            2 => CipherSuite::Xoodoo,
+            id => CipherSuite::Unknown(id),
+        }
+    }
+}
+
+impl From<CipherSuite> for u8 {
+    fn from(cipher_suite: CipherSuite) -> Self {
+        match cipher_suite {
+            CipherSuite::KeccakF1600 => 0,
+            CipherSuite::KeccakP1600R12 => 1,
+            CipherSuite::Xoodoo => 2,
+            CipherSuite::Unknown(id) => id,
+        }
+    }
+}
+
+impl CipherSuite {
+    /// Fetches the message at the provided [`Address`] and returns the cipher suite it is wrapped
+    /// with. The cipher suite is read before any permutation is involved, so that a reader can
+    /// build a [`User`](`crate::User`) with the [cipher suite](`crate::UserBuilder::with_cipher_suite`)
+    /// of a stream it only knows the announcement of:
+    ///
+    /// ```
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// # use streams::{id::Ed25519, transport::bucket, CipherSuite, Result, User, Xoodoo};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let mut transport = Rc::new(RefCell::new(bucket::Client::new()));
+    /// # let mut author = User::builder()
+    /// #     .with_identity(Ed25519::from_seed("author"))
+    /// #     .with_transport(transport.clone())
+    /// #     .with_cipher_suite::<Xoodoo>()
+    /// #     .build();
+    /// # let announcement_address = author.create_stream("BASE_BRANCH").await?.address();
+    /// match CipherSuite::of_message(&mut transport, announcement_address).await? {
+    ///     CipherSuite::Xoodoo => {
+    ///         let mut reader = User::builder()
+    ///             .with_transport(transport)
+    ///             .with_cipher_suite::<Xoodoo>()
+    ///             .build();
+    ///         reader.receive_message(announcement_address).await?;
+    ///     }
+    ///     other => panic!("unexpected cipher suite {}", other),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `transport`: The [`Transport`] to fetch the message from
+    /// * `address`: The [`Address`] of the message
+    pub async fn of_message<T>(transport: &mut T, address: Address) -> Result<Self>
+    where
+        T: for<'a> Transport<'a, Msg = TransportMessage>,
+    {
+        let msg = transport
+            .recv_message(address)
+            .await
+            .map_err(|e| Error::Transport(address, "receive message", e))?;
+        Self::of(address, &msg)
+    }
+
+    /// Returns the cipher suite a [`TransportMessage`] is wrapped with
+    ///
+    /// # Arguments
+    /// * `address`: The [`Address`] of the message
+    /// * `msg`: The raw [`TransportMessage`]
+    pub(crate) fn of(address: Address, msg: &TransportMessage) -> Result<Self> {
+        msg.cipher_suite()
+            .map(Self::from)
+            .map_err(|e| Error::Unwrapping("header", address, e))
+    }
+}
+
+impl fmt::Display for CipherSuite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CipherSuite::KeccakF1600 => write!(f, "Keccak-f[1600]"),
+            CipherSuite::KeccakP1600R12 => write!(f, "Keccak-p[1600,12]"),
+            CipherSuite::Xoodoo => write!(f, "Xoodoo[12]"),
+            CipherSuite::Unknown(id) => write!(f, "unknown cipher suite {}", id),
+        }
+    }
+}
+
+/// A [`PRP`] a stream can be processed with, bound to the [`CipherSuite`] that declares it
+#[allow(clippy::upper_case_acronyms)]
+pub trait StreamPRP: PRP + Copy + Default + Eq + Hash + fmt::Debug + Send + Sync + 'static {
+    /// The [`CipherSuite`] declared in the headers of the messages wrapped with this permutation
+    const CIPHER_SUITE: CipherSuite;
+}
+
+impl StreamPRP for KeccakF1600 {
+    const CIPHER_SUITE: CipherSuite = CipherSuite::KeccakF1600;
+}
+
+impl StreamPRP for KeccakP1600R12 {
+    const CIPHER_SUITE: CipherSuite = CipherSuite::KeccakP1600R12;
+}
+
+impl StreamPRP for Xoodoo {
+    const CIPHER_SUITE: CipherSuite = CipherSuite::Xoodoo;
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec::Vec};
+    use core::cell::RefCell;
+
+    use futures::TryStreamExt;
+    use lets::{id::Ed25519, transport::bucket};
+    use spongos::Xoodoo;
+
+    use super::CipherSuite;
+    use crate::{api::message::Message, Error, Result, User};
+
+    #[tokio::test]
+    async fn readers_must_process_a_stream_with_its_cipher_suite() -> Result<()> {
+        let transport = Rc::new(RefCell::new(bucket::Client::new()));
+        let mut author = User::builder()
+            .with_identity(Ed25519::from_seed("author"))
+            .with_transport(transport.clone())
+            .with_cipher_suite::<Xoodoo>()
+            .build();
+        let announcement = author.create_stream("BASE_BRANCH").await?;
+        let packet = author.send_tagged_packet("BASE_BRANCH", b"public", b"masked").await?;
+
+        let mut reader = User::builder()
+            .with_transport(transport.clone())
+            .with_cipher_suite::<Xoodoo>()
+            .build();
+        reader.receive_message(announcement.address()).await?;
+        let msgs: Vec<Message<Xoodoo>> = reader.messages().try_collect().await?;
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].address(), packet.address());
+        assert!(msgs[0].is_tagged_packet());
+
+        // A reader using the default cipher suite is told which one the stream declares
+        let mut reader = User::builder().with_transport(transport.clone()).build();
+        let error = reader.receive_message(announcement.address()).await.unwrap_err();
+        assert!(matches!(
+            error,
+            Error::CipherSuite(address, CipherSuite::Xoodoo, CipherSuite::KeccakF1600) if address == announcement.address()
+        ));
+        let mut transport = transport;
+        assert_eq!(
+            CipherSuite::of_message(&mut transport, packet.address()).await?,
+            CipherSuite::Xoodoo
+        );
+
+        // Headers of the default cipher suite keep the layout of the previous version
+        let mut default_author = User::builder()
+            .with_identity(Ed25519::from_seed("default author"))
+            .with_transport(transport)
+            .build();
+        let default_announcement = default_author.create_stream("BASE_BRANCH").await?;
+        let msg = reader.receive_message(default_announcement.address()).await?;
+        assert_eq!(msg.header().version, 2);
+        assert_eq!(msgs[0].header().version, 3);
+
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@
 //!     repeated(n_subscribers):
 //!       fork;
 //!       mask                      u8  permissioned;
-//!       x25519(pub/priv_key)      u8  x25519_pubkey[32];
+//!       x25519(exchange_key)      u8  key[32];
 //!     absorb                      u8  size(n_psks);
 //!     repeated(n_psks):
 //!       fork;
@@ -52,7 +52,7 @@ use spongos::{
         types::{NBytes, Size},
     },
     error::Result,
-    Spongos,
+    Spongos, PRP,
 };
 
 // Local
@@ -61,9 +61,9 @@ const NONCE_SIZE: usize = 16;
 const KEY_SIZE: usize = 32;
 
 /// A struct that holds references needed for keyload message encoding
-pub(crate) struct Wrap<'a, 'b, Subscribers, Psks> {
+pub(crate) struct Wrap<'a, 'b, F, Subscribers, Psks> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// A unique nonce
     nonce: [u8; NONCE_SIZE],
     /// A key that will be shared with intended subscribers
@@ -80,7 +80,7 @@ pub(crate) struct Wrap<'a, 'b, Subscribers, Psks> {
     subscribers_lifetime: PhantomData<&'b Identifier>,
 }
 
-impl<'a, 'b, Subscribers, Psks> Wrap<'a, 'b, Subscribers, Psks> {
+impl<'a, 'b, F, Subscribers, Psks> Wrap<'a, 'b, F, Subscribers, Psks> {
     /// Creates a new [`Wrap`] struct for a keyload message
     ///
     /// # Arguments:
@@ -92,7 +92,7 @@ impl<'a, 'b, Subscribers, Psks> Wrap<'a, 'b, Subscribers, Psks> {
     ///   twice.
     /// * `author_id`: The [`Identity`] of the author of the message.
     pub(crate) fn new(
-        initial_state: &'a mut Spongos,
+        initial_state: &'a mut Spongos<F>,
         subscribers: Subscribers,
         psks: Psks,
         key: [u8; KEY_SIZE],
@@ -118,14 +118,14 @@ impl<'a, 'b, Subscribers, Psks> Wrap<'a, 'b, Subscribers, Psks> {
 }
 
 #[async_trait(?Send)]
-impl<'a, 'b, Subscribers, Psks> message::ContentSizeof<Wrap<'a, 'b, Subscribers, Psks>> for sizeof::Context
+impl<'a, 'b, F, Subscribers, Psks> message::ContentSizeof<Wrap<'a, 'b, F, Subscribers, Psks>> for sizeof::Context
 where
     Subscribers: IntoIterator<Item = Permissioned<&'b Identifier>> + Clone,
     Subscribers::IntoIter: ExactSizeIterator,
     Psks: IntoIterator<Item = &'a (PskId, &'a Psk)> + Clone,
     Psks::IntoIter: ExactSizeIterator,
 {
-    async fn sizeof(&mut self, keyload: &Wrap<'a, 'b, Subscribers, Psks>) -> Result<&mut sizeof::Context> {
+    async fn sizeof(&mut self, keyload: &Wrap<'a, 'b, F, Subscribers, Psks>) -> Result<&mut sizeof::Context> {
         let subscribers = keyload.subscribers.clone().into_iter();
         let psks = keyload.psks.clone().into_iter();
         let n_subscribers = Size::new(subscribers.len());
@@ -156,15 +156,16 @@ where
 }
 
 #[async_trait(?Send)]
-impl<'a, 'b, OS, Subscribers, Psks> message::ContentWrap<Wrap<'a, 'b, Subscribers, Psks>> for wrap::Context<OS>
+impl<'a, 'b, OS, F, Subscribers, Psks> message::ContentWrap<Wrap<'a, 'b, F, Subscribers, Psks>> for wrap::Context<OS, F>
 where
+    F: PRP + Clone,
     Subscribers: IntoIterator<Item = Permissioned<&'b Identifier>> + Clone,
     Subscribers::IntoIter: ExactSizeIterator,
     Psks: IntoIterator<Item = &'a (PskId, &'a Psk)> + Clone,
     Psks::IntoIter: ExactSizeIterator,
     OS: io::OStream,
 {
-    async fn wrap(&mut self, keyload: &mut Wrap<'a, 'b, Subscribers, Psks>) -> Result<&mut Self> {
+    async fn wrap(&mut self, keyload: &mut Wrap<'a, 'b, F, Subscribers, Psks>) -> Result<&mut Self> {
         let subscribers = keyload.subscribers.clone().into_iter();
         let psks = keyload.psks.clone().into_iter();
         let n_subscribers = Size::new(subscribers.len());
@@ -197,9 +198,9 @@ where
 }
 
 /// A struct that holds the placeholders needed for keyload message decoding
-pub(crate) struct Unwrap<'a> {
+pub(crate) struct Unwrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// The permissions granted by the admin
     pub(crate) subscribers: Vec<Permissioned<Identifier>>,
     /// Successfully found [`PskId`]'s in store
@@ -212,7 +213,7 @@ pub(crate) struct Unwrap<'a> {
     user_id: Option<&'a Identity>,
 }
 
-impl<'a> Unwrap<'a> {
+impl<'a, F> Unwrap<'a, F> {
     /// Creates a new [`Unwrap`] struct for a keyload message
     ///
     /// # Arguments
@@ -220,7 +221,7 @@ impl<'a> Unwrap<'a> {
     /// * `user_id`: The optional [`Identity`] of the reading user
     /// * `author_id`: The [`Identifier`] of the author of the stream
     pub(crate) fn new(
-        initial_state: &'a mut Spongos,
+        initial_state: &'a mut Spongos<F>,
         user_id: Option<&'a Identity>,
         author_id: &'a Identifier,
         psk_store: &'a HashMap<PskId, Psk>,
@@ -239,24 +240,39 @@ impl<'a> Unwrap<'a> {
     pub(crate) fn subscribers(&self) -> &[Permissioned<Identifier>] {
         &self.subscribers
     }
+
+    /// Returns true if the reader could recover the key, either as a subscriber or through one of
+    /// its pre shared keys. Must be called once the keyload has been unwrapped.
+    pub(crate) fn is_recipient(&self) -> bool {
+        !self.psks.is_empty()
+            || self.user_id.map_or(false, |user_id| {
+                self.subscribers
+                    .iter()
+                    .any(|subscriber| subscriber.identifier() == user_id.identifier())
+            })
+    }
 }
 
 #[async_trait(?Send)]
-impl<'a, IS> message::ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
+impl<'a, IS, F> message::ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
 where
+    F: PRP + Clone,
     IS: io::IStream,
 {
-    async fn unwrap(&mut self, keyload: &mut Unwrap<'a>) -> Result<&mut Self> {
+    async fn unwrap(&mut self, keyload: &mut Unwrap<'a, F>) -> Result<&mut Self> {
         let mut nonce = [0u8; NONCE_SIZE];
         let mut key: Option<[u8; KEY_SIZE]> = None;
         let mut n_subscribers = Size::default();
         let mut n_psks = Size::default();
-        self.join(keyload.initial_state)?
+        self.push_breadcrumb("keyload")
+            .join(keyload.initial_state)?
             .absorb(NBytes::new(&mut nonce))?
-            .absorb(&mut n_subscribers)?;
+            .absorb(&mut n_subscribers)?
+            .ensure_repeated(n_subscribers.inner())?;
 
-        for _ in 0..n_subscribers.inner() {
+        for i in 0..n_subscribers.inner() {
             let mut fork = self.fork();
+            fork.push_breadcrumb("subscribers").index_breadcrumb(i);
             // Loop through provided number of identifiers and subsequent keys
             let mut subscriber_id = Permissioned::<Identifier>::default();
             fork.mask(&mut subscriber_id)?;
@@ -273,10 +289,11 @@ where
             }
             keyload.subscribers.push(subscriber_id);
         }
-        self.absorb(&mut n_psks)?;
+        self.absorb(&mut n_psks)?.ensure_repeated(n_psks.inner())?;
 
-        for _ in 0..n_psks.inner() {
+        for i in 0..n_psks.inner() {
             let mut fork = self.fork();
+            fork.push_breadcrumb("psks").index_breadcrumb(i);
 
             // Loop thorugh provided psks and keys
             let mut psk_id = PskId::default();
@@ -304,7 +321,7 @@ where
                 .verify(keyload.author_id)
                 .await?;
         }
-        self.commit()?;
+        self.commit()?.pop_breadcrumb();
         Ok(self)
     }
 }
@@ -12,3 +12,5 @@ pub(crate) const TAGGED_PACKET: u8 = 4;
 pub(crate) const SUBSCRIPTION: u8 = 5;
 /// Unsubscribe Message Type
 pub(crate) const UNSUBSCRIPTION: u8 = 6;
+/// Checkpoint Message Type
+pub(crate) const CHECKPOINT: u8 = 7;
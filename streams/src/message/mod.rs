@@ -23,3 +23,82 @@ pub(crate) mod message_types;
 
 /// BranchAnnouncement message.
 pub(crate) mod branch_announcement;
+
+/// Checkpoint message.
+pub(crate) mod checkpoint;
+
+/// Cipher suites a stream can be processed with.
+pub(crate) mod cipher_suite;
+
+/// Parses the DDML schema documented at the top of the source of a message module, so that the
+/// documentation can be checked against the encoding of the message
+///
+/// # Arguments
+/// * `source`: The source of the message module
+#[cfg(test)]
+pub(crate) fn documented_schema(source: &str) -> spongos::ddml::schema::Schema {
+    let schema: alloc::string::String = source
+        .lines()
+        .skip_while(|line| *line != "//! ```ddml")
+        .skip(1)
+        .take_while(|line| *line != "//! ```")
+        .map(|line| alloc::format!("{}\n", line.trim_start_matches("//!")))
+        .collect();
+    spongos::ddml::schema::Schema::parse(&schema).unwrap()
+}
+
+/// Checks that a message is encoded as documented in the source of its module: the documented
+/// schema sizes the fields of the message as its content does, and unwraps the message it is
+/// wrapped into into these fields, leaving the same spongos state. Unless wrapping the message is
+/// randomized, the schema must also wrap the fields into the same bytes.
+///
+/// # Arguments
+/// * `source`: The source of the message module
+/// * `content`: The content of the message to wrap
+/// * `record`: The fields of the message, along with the keys of its signer and recipients
+/// * `keys`: The keys unwrapping the message, replacing those of `record` when comparing
+/// * `randomized`: Whether wrapping the message is randomized, as key exchanges are
+#[cfg(test)]
+pub(crate) async fn assert_documented_schema<C>(
+    source: &str,
+    content: &mut C,
+    mut record: spongos::ddml::schema::Record,
+    keys: spongos::ddml::schema::Record,
+    randomized: bool,
+) where
+    spongos::ddml::commands::sizeof::Context: lets::message::ContentSizeof<C>,
+    for<'a> spongos::ddml::commands::wrap::Context<&'a mut [u8], spongos::KeccakF1600>: lets::message::ContentWrap<C>,
+{
+    use lets::message::{ContentSizeof, ContentWrap};
+    use spongos::{
+        ddml::commands::{sizeof, unwrap, wrap},
+        KeccakF1600,
+    };
+
+    let schema = documented_schema(source);
+    let mut ctx = sizeof::Context::new();
+    ctx.sizeof(&*content).await.unwrap();
+    let mut buf = vec![0; ctx.finalize()];
+    let mut ctx = wrap::Context::<_, KeccakF1600>::new(&mut buf[..]);
+    ctx.wrap(content).await.unwrap();
+    let spongos = ctx.finalize();
+
+    let mut ctx = sizeof::Context::new();
+    schema.sizeof(&mut ctx, &record).unwrap();
+    assert_eq!(ctx.finalize(), buf.len());
+    if !randomized {
+        let mut expected = vec![0; buf.len()];
+        let mut ctx = wrap::Context::new(&mut expected[..]);
+        schema.wrap(&mut ctx, &mut record).unwrap();
+        assert_eq!(buf, expected);
+        assert_eq!(ctx.finalize(), spongos);
+    }
+
+    let mut unwrapped = keys.clone();
+    let mut ctx = unwrap::Context::new(&buf[..]);
+    schema.unwrap(&mut ctx, &mut unwrapped).unwrap();
+    assert_eq!(ctx.finalize().0, spongos);
+    for (name, value) in record.fields().filter(|(name, _)| keys.get(name).is_none()) {
+        assert_eq!(unwrapped.get(name), Some(value), "field `{}`", name);
+    }
+}
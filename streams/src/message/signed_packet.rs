@@ -5,18 +5,19 @@
 //! ```ddml
 //! message SignedPacket {
 //!     join(spongos);
-//!     mask                u8      identifier;
-//!     absorb              uint    public_size;
-//!     absorb              u8      public_payload[public_size];
-//!     mask                uint    masked_size;
-//!     mask                u8      masked_payload[masked_size];
+//!     mask                u8      identifier_type;    // 0 for ed25519
+//!     mask                u8      identifier[32];
+//!     absorb              bytes   public_payload;
+//!     mask                bytes   masked_payload;
+//!     absorb              u8      signature_type;     // 0 for ed25519
 //!     commit;
 //!     squeeze external    u8      hash[64];
 //!     ed25519(hash)       u8      signature[64];
 //! }
 //! ```
 // Rust
-use alloc::{boxed::Box, vec::Vec};
+use alloc::boxed::Box;
+use core::ops::Range;
 
 // 3rd-party
 use async_trait::async_trait;
@@ -26,7 +27,9 @@ use async_trait::async_trait;
 // Streams
 use lets::{
     id::{Identifier, Identity},
-    message::{ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap},
+    message::{
+        BytesView, ContentSign, ContentSignSizeof, ContentSizeof, ContentUnwrap, ContentVerify, ContentWrap,
+    },
 };
 use spongos::{
     ddml::{
@@ -35,15 +38,15 @@ use spongos::{
         types::Bytes,
     },
     error::Result,
-    Spongos,
+    Spongos, PRP,
 };
 
 // Local
 
 /// A struct that holds references needed for signed packet message encoding
-pub(crate) struct Wrap<'a> {
+pub(crate) struct Wrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// Payload slice that will not be masked
     public_payload: &'a [u8],
     /// Payload slice that will be masked
@@ -52,7 +55,7 @@ pub(crate) struct Wrap<'a> {
     user_id: &'a Identity,
 }
 
-impl<'a> Wrap<'a> {
+impl<'a, F> Wrap<'a, F> {
     /// Creates a new [`Wrap`] struct for a signed packet message
     ///
     /// # Arguments:
@@ -61,7 +64,7 @@ impl<'a> Wrap<'a> {
     /// * `public_payload`: A payload that will not be masked.
     /// * `masked_payload`: A payload taht will be masked.
     pub(crate) fn new(
-        initial_state: &'a mut Spongos,
+        initial_state: &'a mut Spongos<F>,
         user_id: &'a Identity,
         public_payload: &'a [u8],
         masked_payload: &'a [u8],
@@ -76,8 +79,8 @@ impl<'a> Wrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
-    async fn sizeof(&mut self, signed_packet: &Wrap<'a>) -> Result<&mut Self> {
+impl<'a, F> ContentSizeof<Wrap<'a, F>> for sizeof::Context {
+    async fn sizeof(&mut self, signed_packet: &Wrap<'a, F>) -> Result<&mut Self> {
         self.mask(signed_packet.user_id.identifier())?
             .absorb(Bytes::new(signed_packet.public_payload))?
             .mask(Bytes::new(signed_packet.masked_payload))?
@@ -88,11 +91,12 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
 }
 
 #[async_trait(?Send)]
-impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
+impl<'a, OS, F> ContentWrap<Wrap<'a, F>> for wrap::Context<OS, F>
 where
+    F: PRP,
     OS: io::OStream,
 {
-    async fn wrap(&mut self, signed_packet: &mut Wrap<'a>) -> Result<&mut Self> {
+    async fn wrap(&mut self, signed_packet: &mut Wrap<'a, F>) -> Result<&mut Self> {
         self.join(signed_packet.initial_state)?
             .mask(signed_packet.user_id.identifier())?
             .absorb(Bytes::new(signed_packet.public_payload))?
@@ -105,23 +109,23 @@ where
 
 /// A struct that holds the placeholders needed for signed packet message decoding
 #[derive(PartialEq, Eq, Hash)]
-pub(crate) struct Unwrap<'a> {
+pub(crate) struct Unwrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
-    /// A payload that was not masked
-    public_payload: Vec<u8>,
-    /// A payload that was masked
-    masked_payload: Vec<u8>,
+    initial_state: &'a mut Spongos<F>,
+    /// Position of the payload that was not masked within the message
+    public_payload: Range<usize>,
+    /// Position of the payload that was masked, decrypted in place within the message
+    masked_payload: Range<usize>,
     /// The [`Identifier`] of the publisher
     publisher_id: Identifier,
 }
 
-impl<'a> Unwrap<'a> {
+impl<'a, F> Unwrap<'a, F> {
     /// Creates a new [`Unwrap`] struct for a signed packet message
     ///
     /// # Arguments
     /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>) -> Self {
         Self {
             initial_state,
             public_payload: Default::default(),
@@ -135,29 +139,73 @@ impl<'a> Unwrap<'a> {
         self.publisher_id
     }
 
-    /// Takes the payload that was masked from the [`Unwrap`]
-    pub(crate) fn take_masked_payload(&mut self) -> Vec<u8> {
-        core::mem::take(&mut self.masked_payload)
+    /// Returns a view of the payload that was masked within the message body it was unwrapped in
+    ///
+    /// # Arguments
+    /// * `body`: The body of the message, after unwrapping it in place
+    pub(crate) fn masked_payload(&self, body: &BytesView) -> BytesView {
+        body.slice(self.masked_payload.clone())
     }
 
-    /// Takes the payload that was not masked from the [`Unwrap`]
-    pub(crate) fn take_public_payload(&mut self) -> Vec<u8> {
-        core::mem::take(&mut self.public_payload)
+    /// Returns a view of the payload that was not masked within the message body it was unwrapped in
+    ///
+    /// # Arguments
+    /// * `body`: The body of the message, after unwrapping it in place
+    pub(crate) fn public_payload(&self, body: &BytesView) -> BytesView {
+        body.slice(self.public_payload.clone())
     }
 }
 
 #[async_trait(?Send)]
-impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
+impl<'a, IS, F> ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
 where
-    IS: io::IStream,
+    F: PRP,
+    IS: io::IStreamMut,
 {
-    async fn unwrap(&mut self, signed_packet: &mut Unwrap) -> Result<&mut Self> {
-        self.join(signed_packet.initial_state)?
+    async fn unwrap(&mut self, signed_packet: &mut Unwrap<'a, F>) -> Result<&mut Self> {
+        self.push_breadcrumb("signed_packet")
+            .join(signed_packet.initial_state)?
             .mask(&mut signed_packet.publisher_id)?
             .absorb(Bytes::new(&mut signed_packet.public_payload))?
             .mask(Bytes::new(&mut signed_packet.masked_payload))?
             .verify(&signed_packet.publisher_id)
-            .await?;
+            .await?
+            .pop_breadcrumb();
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lets::id::{Ed25519, Identity};
+    use spongos::{
+        ddml::schema::{Record, Value},
+        KeccakF1600, Spongos,
+    };
+
+    use super::Wrap;
+    use crate::message::assert_documented_schema;
+
+    #[tokio::test]
+    async fn signed_packets_match_their_documented_schema() {
+        let publisher = Identity::from(Ed25519::from_seed("publisher"));
+        let mut linked = Spongos::<KeccakF1600>::init();
+        linked.absorb(b"linked message");
+
+        let mut initial_state = linked;
+        let mut signed_packet = Wrap::new(&mut initial_state, &publisher, b"public", b"masked");
+        let record = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("identifier_type", Value::Uint8(0))
+            .with("identifier", Value::Bytes(publisher.identifier().as_ref().to_vec()))
+            .with("public_payload", Value::Bytes(b"public".to_vec()))
+            .with("masked_payload", Value::Bytes(b"masked".to_vec()))
+            .with("signature_type", Value::Uint8(0))
+            .with("signature_key", Value::Bytes(Ed25519::from_seed("publisher").as_ref().to_vec()));
+        // The signature is verified with the public key of the publisher
+        let keys = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("signature_key", Value::Bytes(publisher.identifier().as_ref().to_vec()));
+        assert_documented_schema(include_str!("signed_packet.rs"), &mut signed_packet, record, keys, false).await;
+    }
+}
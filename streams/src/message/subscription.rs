@@ -15,9 +15,10 @@
 //! ```ddml
 //! message Subscribe {
 //!     join(spongos);
-//!     x25519(pub/priv_key)    u8      x25519_auth_pubkey[32];
-//!     commit;
-//!     mask                    u8      identifier;
+//!     x25519(exchange_key)    u8      unsubscribe_key[32];
+//!     mask                    u8      identifier_type;    // 0 for ed25519
+//!     mask                    u8      identifier[32];
+//!     absorb                  u8      signature_type;     // 0 for ed25519
 //!     commit;
 //!     squeeze external        u8      hash[64];
 //!     ed25519(hash)           u8      signature[64];
@@ -44,13 +45,13 @@ use spongos::{
         types::NBytes,
     },
     error::Result,
-    Spongos,
+    Spongos, PRP,
 };
 
 /// A struct that holds references needed for subscription message encoding
-pub(crate) struct Wrap<'a> {
+pub(crate) struct Wrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// Unique key used for unsubscription request
     unsubscribe_key: [u8; 32],
     /// The [`Identity`] of the subscriber
@@ -59,7 +60,7 @@ pub(crate) struct Wrap<'a> {
     author_ke_pk: &'a x25519::PublicKey,
 }
 
-impl<'a> Wrap<'a> {
+impl<'a, F> Wrap<'a, F> {
     /// Creates a new [`Wrap`] struct for a subscription message
     ///
     /// # Arguments:
@@ -68,7 +69,7 @@ impl<'a> Wrap<'a> {
     /// * `subscriber_id`: The [`Identity`] of the subscriber.
     /// * `author_ke_pk`: The author's public exchange key
     pub(crate) fn new(
-        initial_state: &'a mut Spongos,
+        initial_state: &'a mut Spongos<F>,
         unsubscribe_key: [u8; 32],
         subscriber_id: &'a Identity,
         author_ke_pk: &'a x25519::PublicKey,
@@ -83,8 +84,8 @@ impl<'a> Wrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
-    async fn sizeof(&mut self, subscription: &Wrap<'a>) -> Result<&mut Self> {
+impl<'a, F> ContentSizeof<Wrap<'a, F>> for sizeof::Context {
+    async fn sizeof(&mut self, subscription: &Wrap<'a, F>) -> Result<&mut Self> {
         self.x25519(subscription.author_ke_pk, NBytes::new(subscription.unsubscribe_key))?
             .mask(subscription.subscriber_id.identifier())?
             .sign_sizeof(subscription.subscriber_id)
@@ -94,11 +95,12 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
 }
 
 #[async_trait(?Send)]
-impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
+impl<'a, OS, F> ContentWrap<Wrap<'a, F>> for wrap::Context<OS, F>
 where
+    F: PRP,
     OS: io::OStream,
 {
-    async fn wrap(&mut self, subscription: &mut Wrap<'a>) -> Result<&mut Self> {
+    async fn wrap(&mut self, subscription: &mut Wrap<'a, F>) -> Result<&mut Self> {
         self.join(subscription.initial_state)?
             .x25519(subscription.author_ke_pk, NBytes::new(subscription.unsubscribe_key))?
             .mask(subscription.subscriber_id.identifier())?
@@ -109,9 +111,9 @@ where
 }
 
 /// A struct that holds the placeholders needed for subscription message decoding
-pub(crate) struct Unwrap<'a> {
+pub(crate) struct Unwrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// The subscriber's unique unsubscribe key
     unsubscribe_key: [u8; 32],
     /// The [`Identifier`] of the subscriber
@@ -120,13 +122,13 @@ pub(crate) struct Unwrap<'a> {
     author_ke_sk: &'a x25519::SecretKey,
 }
 
-impl<'a> Unwrap<'a> {
+impl<'a, F> Unwrap<'a, F> {
     /// Creates a new [`Unwrap`] struct for a subscription message
     ///
     /// # Arguments:
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
     /// * `author_ke_sk`: The author's secret exchange key
-    pub(crate) fn new(initial_state: &'a mut Spongos, author_ke_sk: &'a x25519::SecretKey) -> Self {
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>, author_ke_sk: &'a x25519::SecretKey) -> Self {
         Self {
             initial_state,
             unsubscribe_key: Default::default(),
@@ -147,19 +149,62 @@ impl<'a> Unwrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
+impl<'a, IS, F> ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
 where
+    F: PRP,
     IS: io::IStream,
 {
-    async fn unwrap(&mut self, subscription: &mut Unwrap<'a>) -> Result<&mut Self> {
-        self.join(subscription.initial_state)?
+    async fn unwrap(&mut self, subscription: &mut Unwrap<'a, F>) -> Result<&mut Self> {
+        self.push_breadcrumb("subscription")
+            .join(subscription.initial_state)?
             .x25519(
                 subscription.author_ke_sk,
                 NBytes::new(&mut subscription.unsubscribe_key),
             )?
             .mask(&mut subscription.subscriber_identifier)?
             .verify(&subscription.subscriber_identifier)
-            .await?;
+            .await?
+            .pop_breadcrumb();
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lets::id::{Ed25519, Identity};
+    use spongos::{
+        ddml::schema::{Record, Value},
+        KeccakF1600, Spongos,
+    };
+
+    use super::Wrap;
+    use crate::message::assert_documented_schema;
+
+    /// The key exchange of subscriptions is random, so the documented schema is checked by sizing
+    /// them and unwrapping them
+    #[tokio::test]
+    async fn subscriptions_match_their_documented_schema() {
+        let author = Identity::from(Ed25519::from_seed("author"));
+        let subscriber = Identity::from(Ed25519::from_seed("subscriber"));
+        let author_ke_sk = author.ke_sk().unwrap();
+        let author_ke_pk = author_ke_sk.public_key();
+        let mut linked = Spongos::<KeccakF1600>::init();
+        linked.absorb(b"announcement");
+
+        let mut initial_state = linked;
+        let mut subscription = Wrap::new(&mut initial_state, [7; 32], &subscriber, &author_ke_pk);
+        let record = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("exchange_key", Value::Bytes(author_ke_pk.to_bytes().to_vec()))
+            .with("unsubscribe_key", Value::Bytes(vec![7; 32]))
+            .with("identifier_type", Value::Uint8(0))
+            .with("identifier", Value::Bytes(subscriber.identifier().as_ref().to_vec()))
+            .with("signature_type", Value::Uint8(0))
+            .with("signature_key", Value::Bytes(Ed25519::from_seed("subscriber").as_ref().to_vec()));
+        let keys = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("exchange_key", Value::Bytes(author_ke_sk.to_bytes().to_vec()))
+            .with("signature_key", Value::Bytes(subscriber.identifier().as_ref().to_vec()));
+        assert_documented_schema(include_str!("subscription.rs"), &mut subscription, record, keys, true).await;
+    }
+}
@@ -14,7 +14,8 @@
 //! }
 //! ```
 // Rust
-use alloc::{boxed::Box, vec::Vec};
+use alloc::boxed::Box;
+use core::ops::Range;
 
 // 3rd-party
 use async_trait::async_trait;
@@ -22,7 +23,7 @@ use async_trait::async_trait;
 // IOTA
 
 // Streams
-use lets::message::{ContentSizeof, ContentUnwrap, ContentWrap};
+use lets::message::{BytesView, ContentSizeof, ContentUnwrap, ContentWrap};
 use spongos::{
     ddml::{
         commands::{sizeof, unwrap, wrap, Absorb, Commit, Join, Mask, Squeeze},
@@ -30,7 +31,7 @@ use spongos::{
         types::{Bytes, Mac},
     },
     error::Result,
-    Spongos,
+    Spongos, PRP,
 };
 
 // Local
@@ -39,23 +40,23 @@ use spongos::{
 const MAC: Mac = Mac::new(32);
 
 /// A struct that holds references needed for tagged packet message encoding
-pub(crate) struct Wrap<'a> {
+pub(crate) struct Wrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// Payload slice that will not be masked
     public_payload: &'a [u8],
     /// Payload slice that will be masked
     masked_payload: &'a [u8],
 }
 
-impl<'a> Wrap<'a> {
+impl<'a, F> Wrap<'a, F> {
     /// Creates a new [`Wrap`] struct for a tagged packet message
     ///
     /// # Arguments:
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
     /// * `public_payload`: A payload that will not be masked.
     /// * `masked_payload`: A payload taht will be masked.
-    pub(crate) fn new(initial_state: &'a mut Spongos, public_payload: &'a [u8], masked_payload: &'a [u8]) -> Self {
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>, public_payload: &'a [u8], masked_payload: &'a [u8]) -> Self {
         Self {
             initial_state,
             public_payload,
@@ -65,8 +66,8 @@ impl<'a> Wrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
-    async fn sizeof(&mut self, tagged_packet: &Wrap<'a>) -> Result<&mut Self> {
+impl<'a, F> ContentSizeof<Wrap<'a, F>> for sizeof::Context {
+    async fn sizeof(&mut self, tagged_packet: &Wrap<'a, F>) -> Result<&mut Self> {
         self.absorb(Bytes::new(tagged_packet.public_payload))?
             .mask(Bytes::new(tagged_packet.masked_payload))?
             .commit()?
@@ -76,11 +77,12 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
 }
 
 #[async_trait(?Send)]
-impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
+impl<'a, OS, F> ContentWrap<Wrap<'a, F>> for wrap::Context<OS, F>
 where
+    F: PRP,
     OS: io::OStream,
 {
-    async fn wrap(&mut self, tagged_packet: &mut Wrap<'a>) -> Result<&mut Self> {
+    async fn wrap(&mut self, tagged_packet: &mut Wrap<'a, F>) -> Result<&mut Self> {
         self.join(tagged_packet.initial_state)?
             .absorb(Bytes::new(tagged_packet.public_payload))?
             .mask(Bytes::new(tagged_packet.masked_payload))?
@@ -91,21 +93,21 @@ where
 }
 
 /// A struct that holds the placeholders needed for tagged packet message decoding
-pub(crate) struct Unwrap<'a> {
+pub(crate) struct Unwrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
-    /// A payload that was not masked
-    public_payload: Vec<u8>,
-    /// A payload that was masked
-    masked_payload: Vec<u8>,
+    initial_state: &'a mut Spongos<F>,
+    /// Position of the payload that was not masked within the message
+    public_payload: Range<usize>,
+    /// Position of the payload that was masked, decrypted in place within the message
+    masked_payload: Range<usize>,
 }
 
-impl<'a> Unwrap<'a> {
+impl<'a, F> Unwrap<'a, F> {
     /// Creates a new [`Unwrap`] struct for a tagged packet message
     ///
     /// # Arguments
     /// * `initial_state`: The base [`Spongos`] state that the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>) -> Self {
         Self {
             initial_state,
             public_payload: Default::default(),
@@ -113,28 +115,63 @@ impl<'a> Unwrap<'a> {
         }
     }
 
-    /// Takes the payload that was masked from the [`Unwrap`]
-    pub(crate) fn take_masked_payload(&mut self) -> Vec<u8> {
-        core::mem::take(&mut self.masked_payload)
+    /// Returns a view of the payload that was masked within the message body it was unwrapped in
+    ///
+    /// # Arguments
+    /// * `body`: The body of the message, after unwrapping it in place
+    pub(crate) fn masked_payload(&self, body: &BytesView) -> BytesView {
+        body.slice(self.masked_payload.clone())
     }
 
-    /// Takes the payload that was not masked from the [`Unwrap`]
-    pub(crate) fn take_public_payload(&mut self) -> Vec<u8> {
-        core::mem::take(&mut self.public_payload)
+    /// Returns a view of the payload that was not masked within the message body it was unwrapped in
+    ///
+    /// # Arguments
+    /// * `body`: The body of the message, after unwrapping it in place
+    pub(crate) fn public_payload(&self, body: &BytesView) -> BytesView {
+        body.slice(self.public_payload.clone())
     }
 }
 
 #[async_trait(?Send)]
-impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
+impl<'a, IS, F> ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
 where
-    IS: io::IStream,
+    F: PRP,
+    IS: io::IStreamMut,
 {
-    async fn unwrap(&mut self, tagged_packet: &mut Unwrap<'a>) -> Result<&mut Self> {
-        self.join(tagged_packet.initial_state)?
+    async fn unwrap(&mut self, tagged_packet: &mut Unwrap<'a, F>) -> Result<&mut Self> {
+        self.push_breadcrumb("tagged_packet")
+            .join(tagged_packet.initial_state)?
             .absorb(Bytes::new(&mut tagged_packet.public_payload))?
             .mask(Bytes::new(&mut tagged_packet.masked_payload))?
             .commit()?
-            .squeeze(&MAC)?;
+            .squeeze(&MAC)?
+            .pop_breadcrumb();
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use spongos::{
+        ddml::schema::{Record, Value},
+        KeccakF1600, Spongos,
+    };
+
+    use super::Wrap;
+    use crate::message::assert_documented_schema;
+
+    #[tokio::test]
+    async fn tagged_packets_match_their_documented_schema() {
+        let mut linked = Spongos::<KeccakF1600>::init();
+        linked.absorb(b"linked message");
+
+        let mut initial_state = linked;
+        let mut tagged_packet = Wrap::new(&mut initial_state, b"public", b"masked");
+        let record = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("public_payload", Value::Bytes(b"public".to_vec()))
+            .with("masked_payload", Value::Bytes(b"masked".to_vec()));
+        let keys = Record::new().with("spongos", Value::Spongos(linked));
+        assert_documented_schema(include_str!("tagged_packet.rs"), &mut tagged_packet, record, keys, false).await;
+    }
+}
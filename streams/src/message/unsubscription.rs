@@ -4,7 +4,10 @@
 //! ```ddml
 //! message Unsubscribe {
 //!     join(spongos);
-//!     absorb                  u8      identifier;
+//!     mask                    u8      identifier_type;    // 0 for ed25519
+//!     mask                    u8      identifier[32];
+//!     commit;
+//!     absorb                  u8      signature_type;     // 0 for ed25519
 //!     commit;
 //!     squeeze external        u8      hash[64];
 //!     ed25519(hash)           u8      signature[64];
@@ -29,26 +32,26 @@ use spongos::{
         io,
     },
     error::Result,
-    Spongos,
+    Spongos, PRP,
 };
 
 // Local
 
 /// A struct that holds references needed for unsubscription message encoding
-pub(crate) struct Wrap<'a> {
+pub(crate) struct Wrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// The [`Identity`] of the subscriber
     subscriber_id: &'a Identity,
 }
 
-impl<'a> Wrap<'a> {
+impl<'a, F> Wrap<'a, F> {
     /// Creates a new [`Wrap`] struct for an unsubscription message
     ///
     /// # Arguments:
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
     /// * `subscriber_id`: The [`Identity`] of the subscriber.
-    pub(crate) fn new(initial_state: &'a mut Spongos, subscriber_id: &'a Identity) -> Self {
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>, subscriber_id: &'a Identity) -> Self {
         Self {
             initial_state,
             subscriber_id,
@@ -57,8 +60,8 @@ impl<'a> Wrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
-    async fn sizeof(&mut self, unsubscription: &Wrap<'a>) -> Result<&mut Self> {
+impl<'a, F> ContentSizeof<Wrap<'a, F>> for sizeof::Context {
+    async fn sizeof(&mut self, unsubscription: &Wrap<'a, F>) -> Result<&mut Self> {
         self.mask(unsubscription.subscriber_id.identifier())?
             .commit()?
             .sign_sizeof(unsubscription.subscriber_id)
@@ -68,11 +71,12 @@ impl<'a> ContentSizeof<Wrap<'a>> for sizeof::Context {
 }
 
 #[async_trait(?Send)]
-impl<'a, OS> ContentWrap<Wrap<'a>> for wrap::Context<OS>
+impl<'a, OS, F> ContentWrap<Wrap<'a, F>> for wrap::Context<OS, F>
 where
+    F: PRP,
     OS: io::OStream,
 {
-    async fn wrap(&mut self, unsubscription: &mut Wrap<'a>) -> Result<&mut Self> {
+    async fn wrap(&mut self, unsubscription: &mut Wrap<'a, F>) -> Result<&mut Self> {
         self.join(unsubscription.initial_state)?
             .mask(unsubscription.subscriber_id.identifier())?
             .commit()?
@@ -83,19 +87,19 @@ where
 }
 
 /// A struct that holds the placeholders needed for unsubscription message decoding
-pub(crate) struct Unwrap<'a> {
+pub(crate) struct Unwrap<'a, F> {
     /// The base [`Spongos`] state that the message will be joined to
-    initial_state: &'a mut Spongos,
+    initial_state: &'a mut Spongos<F>,
     /// The [`Identifier`] of the subscriber
     subscriber_id: Identifier,
 }
 
-impl<'a> Unwrap<'a> {
+impl<'a, F> Unwrap<'a, F> {
     /// Creates a new [`Unwrap`] struct for an unsubscription message
     ///
     /// # Arguments:
     /// * `initial_state`: The initial [`Spongos`] state the message will be joined to
-    pub(crate) fn new(initial_state: &'a mut Spongos) -> Self {
+    pub(crate) fn new(initial_state: &'a mut Spongos<F>) -> Self {
         Self {
             initial_state,
             subscriber_id: Identifier::default(),
@@ -114,16 +118,51 @@ impl<'a> Unwrap<'a> {
 }
 
 #[async_trait(?Send)]
-impl<'a, IS> ContentUnwrap<Unwrap<'a>> for unwrap::Context<IS>
+impl<'a, IS, F> ContentUnwrap<Unwrap<'a, F>> for unwrap::Context<IS, F>
 where
+    F: PRP,
     IS: io::IStream,
 {
-    async fn unwrap(&mut self, unsubscription: &mut Unwrap<'a>) -> Result<&mut Self> {
-        self.join(unsubscription.initial_state)?
+    async fn unwrap(&mut self, unsubscription: &mut Unwrap<'a, F>) -> Result<&mut Self> {
+        self.push_breadcrumb("unsubscription")
+            .join(unsubscription.initial_state)?
             .mask(&mut unsubscription.subscriber_id)?
             .commit()?
             .verify(&unsubscription.subscriber_id)
-            .await?;
+            .await?
+            .pop_breadcrumb();
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lets::id::{Ed25519, Identity};
+    use spongos::{
+        ddml::schema::{Record, Value},
+        KeccakF1600, Spongos,
+    };
+
+    use super::Wrap;
+    use crate::message::assert_documented_schema;
+
+    #[tokio::test]
+    async fn unsubscriptions_match_their_documented_schema() {
+        let subscriber = Identity::from(Ed25519::from_seed("subscriber"));
+        let mut linked = Spongos::<KeccakF1600>::init();
+        linked.absorb(b"subscription");
+
+        let mut initial_state = linked;
+        let mut unsubscription = Wrap::new(&mut initial_state, &subscriber);
+        let record = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("identifier_type", Value::Uint8(0))
+            .with("identifier", Value::Bytes(subscriber.identifier().as_ref().to_vec()))
+            .with("signature_type", Value::Uint8(0))
+            .with("signature_key", Value::Bytes(Ed25519::from_seed("subscriber").as_ref().to_vec()));
+        let keys = Record::new()
+            .with("spongos", Value::Spongos(linked))
+            .with("signature_key", Value::Bytes(subscriber.identifier().as_ref().to_vec()));
+        assert_documented_schema(include_str!("unsubscription.rs"), &mut unsubscription, record, keys, false).await;
+    }
+}